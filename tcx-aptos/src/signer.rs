@@ -8,6 +8,9 @@ use sha3::{Digest, Sha3_256};
 use tcx_chain::{Keystore, Result, TransactionSigner};
 
 const TRANSACTION_PREFIX: &str = "APTOS::RawTransaction";
+const TRANSACTION_HASH_PREFIX: &str = "APTOS::Transaction";
+// the variant index of `Transaction::UserTransaction`
+const USER_TRANSACTION_INDEX: u8 = 0;
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct SignedTransaction {
@@ -200,7 +203,11 @@ impl TransactionSigner<AptosTxIn, AptosTxOut> for Keystore {
             },
         };
         let serialized_tx = bcs::to_bytes(&signed_tx)?;
-        Ok(AptosTxOut { tx: serialized_tx })
+        let tx_hash = tx_hash(&serialized_tx);
+        Ok(AptosTxOut {
+            tx: serialized_tx,
+            tx_hash,
+        })
     }
 }
 
@@ -210,6 +217,19 @@ fn tx_prefix_hash() -> Vec<u8> {
     hasher.finalize().to_vec()
 }
 
+// note: tx_hash = sha3_256(sha3_256(hash_prefix) | user_transaction_index | bcs_bytes_of_signed_transaction).
+fn tx_hash(signed_tx: &[u8]) -> String {
+    let mut hasher = Sha3_256::new();
+    hasher.update(TRANSACTION_HASH_PREFIX.as_bytes());
+    let prefix = hasher.finalize();
+
+    let mut hasher = Sha3_256::new();
+    hasher.update(prefix);
+    hasher.update([USER_TRANSACTION_INDEX]);
+    hasher.update(signed_tx);
+    format!("0x{}", hex::encode(hasher.finalize()))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::signer::{RawTransaction, SignedTransaction, TransactionAuthenticator};
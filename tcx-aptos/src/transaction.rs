@@ -35,4 +35,7 @@ pub struct NewTransfer {
 pub struct AptosTxOut {
     #[prost(bytes, tag = "1")]
     pub tx: std::vec::Vec<u8>,
+    /// 0x prefixed sha3-256 hash of the signed user transaction
+    #[prost(string, tag = "2")]
+    pub tx_hash: std::string::String,
 }
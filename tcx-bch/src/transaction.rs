@@ -62,6 +62,10 @@ mod tests {
             change_address: "".to_string(),
             network: "MAINNET".to_string(),
             seg_wit: "NONE".to_string(),
+            outputs: vec![],
+            op_return: "".to_string(),
+            rbf: false,
+            lock_time: 0,
         };
         let coin_info = coin_info_from_param("BITCOINCASH", "MAINNET", "NONE", "").unwrap();
         let tran = BitcoinForkSinger::<BchAddress, LegacyTransactionSignComponent<BchSignHasher>> {
@@ -1,4 +1,6 @@
+use crate::bech32m;
 use crate::signer::ScriptPubKeyComponent;
+use crate::taproot::tweak_public_key;
 use crate::Error;
 use crate::Result;
 
@@ -14,7 +16,10 @@ use core::result;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 use tcx_chain::Address;
-use tcx_constants::btc_fork_network::{network_form_hrp, network_from_coin, BtcForkNetwork};
+use tcx_constants::btc_fork_network::{
+    custom_btc_fork_networks, network_form_hrp, network_from_coin, network_from_param,
+    slip132_versions, BtcForkNetwork,
+};
 use tcx_constants::coin_info::coin_info_from_param;
 use tcx_constants::CoinInfo;
 use tcx_primitive::{Ss58Codec, TypedPrivateKey, TypedPublicKey};
@@ -47,10 +52,11 @@ impl Address for BtcForkAddress {
         tcx_ensure!(network.is_some(), Error::MissingNetwork);
         let network = network.expect("network");
 
-        let addr = if coin.seg_wit.as_str() == "P2WPKH" {
-            BtcForkAddress::p2shwpkh(&public_key.to_bytes(), &network)?.to_string()
-        } else {
-            BtcForkAddress::p2pkh(&public_key.to_bytes(), &network)?.to_string()
+        let addr = match coin.seg_wit.as_str() {
+            "P2WPKH" => BtcForkAddress::p2shwpkh(&public_key.to_bytes(), &network)?.to_string(),
+            "SEGWIT" => BtcForkAddress::p2wpkh(&public_key.to_bytes(), &network)?.to_string(),
+            "P2TR" => BtcForkAddress::p2tr(&public_key.to_bytes(), &network)?.to_string(),
+            _ => BtcForkAddress::p2pkh(&public_key.to_bytes(), &network)?.to_string(),
         };
         Ok(addr.to_string())
     }
@@ -94,6 +100,17 @@ impl BtcForkAddress {
         })
     }
 
+    /// BIP86 key path only taproot address, the witness v1 program is the tweaked x-only key
+    pub fn p2tr(pub_key: &[u8], network: &BtcForkNetwork) -> Result<BtcForkAddress> {
+        Ok(BtcForkAddress {
+            payload: Payload::WitnessProgram {
+                version: bech32::u5::try_from_u8(1)?,
+                program: tweak_public_key(pub_key)?,
+            },
+            network: network.clone(),
+        })
+    }
+
     pub fn script_pubkey(&self) -> Script {
         self.payload.script_pubkey()
     }
@@ -104,9 +121,15 @@ impl BtcForkAddress {
             Payload::PubkeyHash(_) => BtcForkAddress::p2pkh(pub_key, &target.network),
             Payload::ScriptHash(_) => BtcForkAddress::p2shwpkh(pub_key, &target.network),
             Payload::WitnessProgram {
-                version: _ver,
+                version: ver,
                 program: ref _prog,
-            } => BtcForkAddress::p2wpkh(pub_key, &target.network),
+            } => {
+                if ver.to_u8() == 1 {
+                    BtcForkAddress::p2tr(pub_key, &target.network)
+                } else {
+                    BtcForkAddress::p2wpkh(pub_key, &target.network)
+                }
+            }
         }
     }
 
@@ -127,6 +150,28 @@ impl BtcForkAddress {
         tcx_ensure!(network.is_some(), Error::UnsupportedChain);
         Ok(extended_priv_key.to_ss58check_with_version(&network.unwrap().xprv_prefix))
     }
+
+    /// Encodes the account extended public key with SLIP-132 version bytes (xpub/ypub/zpub...).
+    pub fn slip132_extended_public_key(
+        derivation_info: &impl Ss58Codec,
+        coin_info: &CoinInfo,
+    ) -> Result<String> {
+        let network = network_from_coin(&coin_info);
+        tcx_ensure!(network.is_some(), Error::UnsupportedChain);
+        let (pub_version, _) = slip132_versions(&network.unwrap());
+        Ok(derivation_info.to_ss58check_with_version(&pub_version))
+    }
+
+    /// Encodes the account extended private key with SLIP-132 version bytes (xprv/yprv/zprv...).
+    pub fn slip132_extended_private_key(
+        extended_priv_key: &impl Ss58Codec,
+        coin_info: &CoinInfo,
+    ) -> Result<String> {
+        let network = network_from_coin(&coin_info);
+        tcx_ensure!(network.is_some(), Error::UnsupportedChain);
+        let (_, prv_version) = slip132_versions(&network.unwrap());
+        Ok(extended_priv_key.to_ss58check_with_version(&prv_version))
+    }
 }
 
 /// Extract the bech32 prefix.
@@ -172,8 +217,12 @@ impl FromStr for BtcForkAddress {
         // try bech32
         let bech32_network = bech32_network(s);
         if let Some(network) = bech32_network {
-            // decode as bech32
-            let (_, payload) = bech32::decode(s)?;
+            // decode as bech32, or bech32m for the witness v1+ addresses
+            let (payload, is_bech32m) = match bech32::decode(s) {
+                Ok((_, payload)) => (payload, false),
+                Err(bech32::Error::InvalidChecksum) => (bech32m::decode(s)?.1, true),
+                Err(e) => return Err(e.into()),
+            };
             if payload.is_empty() {
                 return Err(BtcAddressError::EmptyBech32Payload);
             }
@@ -197,6 +246,21 @@ impl FromStr for BtcForkAddress {
                 return Err(BtcAddressError::InvalidSegwitV0ProgramLength(program.len()));
             }
 
+            // BIP350, the witness v0 addresses must use bech32 and the others bech32m
+            if (version.to_u8() == 0) == is_bech32m {
+                return Err(BtcAddressError::Bech32(bech32::Error::InvalidChecksum));
+            }
+
+            // the networks of a coin share the hrp, pick the one of the address type
+            let seg_wit = if version.to_u8() == 1 {
+                "P2TR"
+            } else {
+                "SEGWIT"
+            };
+            let network = network_from_param(&network.coin, &network.network, seg_wit)
+                .filter(|x| x.hrp == network.hrp)
+                .unwrap_or(network);
+
             return Ok(BtcForkAddress {
                 payload: Payload::WitnessProgram { version, program },
                 network,
@@ -294,9 +358,27 @@ impl FromStr for BtcForkAddress {
             //     )
             // }
             x => {
-                return Err(BtcAddressError::Base58(base58::Error::InvalidVersion(
-                    vec![x],
-                )));
+                // fall back to the networks registered at runtime
+                let networks = custom_btc_fork_networks();
+                if let Some(network) = networks.iter().find(|n| n.p2pkh_prefix == x) {
+                    (
+                        network.clone(),
+                        Payload::PubkeyHash(PubkeyHashType::from_slice(&data[1..]).unwrap()),
+                    )
+                } else if let Some(network) = networks
+                    .iter()
+                    .find(|n| n.p2sh_prefix == x && n.seg_wit == "P2WPKH")
+                    .or_else(|| networks.iter().find(|n| n.p2sh_prefix == x))
+                {
+                    (
+                        network.clone(),
+                        Payload::ScriptHash(ScriptHashType::from_slice(&data[1..]).unwrap()),
+                    )
+                } else {
+                    return Err(BtcAddressError::Base58(base58::Error::InvalidVersion(
+                        vec![x],
+                    )));
+                }
             }
         };
 
@@ -319,11 +401,18 @@ impl Display for BtcForkAddress {
                 prefixed[1..].copy_from_slice(&hash[..]);
                 base58::check_encode_slice_to_fmt(fmt, &prefixed[..])
             }
+            Payload::WitnessProgram {
+                version: ver,
+                program: ref prog,
+            } if ver.to_u8() > 0 => {
+                let data = [vec![ver], bech32::ToBase32::to_base32(&prog)].concat();
+                fmt.write_str(&bech32m::encode(&self.network.hrp, &data))
+            }
             Payload::WitnessProgram {
                 version: ver,
                 program: ref prog,
             } => {
-                let hrp = self.network.hrp;
+                let hrp = &self.network.hrp;
                 let mut bech32_writer = bech32::Bech32Writer::new(hrp, fmt)?;
                 bech32::WriteBase32::write_u5(&mut bech32_writer, ver)?;
                 bech32::ToBase32::write_base32(&prog, &mut bech32_writer)
@@ -362,10 +451,14 @@ mod tests {
     use tcx_constants::coin_info::coin_info_from_param;
 
     use std::str::FromStr;
-    use tcx_constants::btc_fork_network::network_from_param;
+    use tcx_constants::btc_fork_network::{
+        network_from_param, register_btc_fork_network, remove_btc_fork_network, BtcForkNetwork,
+    };
 
     use tcx_constants::{CoinInfo, CurveType};
-    use tcx_primitive::{Bip32DeterministicPrivateKey, Derive, DeterministicPrivateKey, Ss58Codec};
+    use tcx_primitive::{
+        Bip32DeterministicPrivateKey, Derive, DeterministicPrivateKey, Ss58Codec, TypedPublicKey,
+    };
 
     #[test]
     pub fn test_btc_fork_address() {
@@ -394,6 +487,19 @@ mod tests {
             .unwrap()
             .to_string();
         assert_eq!(addr, "bc1qum864wd9nwsc0u9ytkctz6wzrw6g7zdntm7f4e");
+
+        // bip-0086 m/86'/0'/0'/0/0 of "abandon ... about"
+        let pub_key =
+            hex::decode("03cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115")
+                .unwrap();
+        let network = network_from_param("BITCOIN", "MAINNET", "P2TR").unwrap();
+        let addr = BtcForkAddress::p2tr(&pub_key, &network)
+            .unwrap()
+            .to_string();
+        assert_eq!(
+            addr,
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+        );
         let pub_key_str = "0287A071C74E969A2DAAB292E5C14639EF373956DCC99BF70F8FF7848A6DA73449";
         let pub_key = hex::decode(pub_key_str).unwrap();
         let network = network_from_param("DOGECOIN", "MAINNET", "NONE").unwrap();
@@ -403,6 +509,44 @@ mod tests {
         assert_eq!(addr, "D9dr3F2U77frfkpBasCwRzmzdU7pe78nzc");
     }
 
+    #[test]
+    pub fn test_from_public_key() {
+        let pub_key = TypedPublicKey::from_slice(
+            CurveType::SECP256k1,
+            &hex::decode("02506bc1dc099358e5137292f4efdd57e400f29ba5132aa5d12b18dac1c1f6aaba")
+                .unwrap(),
+        )
+        .unwrap();
+        let cases = vec![
+            ("BITCOIN", "NONE", "1N3RC53vbaDNrziTdWmctBEeQ4fo4quNpq"),
+            ("BITCOIN", "P2WPKH", "3Js9bGaZSQCNLudeGRHL4NExVinc25RbuG"),
+            (
+                "BITCOIN",
+                "SEGWIT",
+                "bc1qum864wd9nwsc0u9ytkctz6wzrw6g7zdntm7f4e",
+            ),
+            ("LITECOIN", "P2WPKH", "MR5Hu9zXPX3o9QuYNJGft1VMpRP418QDfW"),
+            (
+                "LITECOIN",
+                "SEGWIT",
+                "ltc1qum864wd9nwsc0u9ytkctz6wzrw6g7zdn08yddf",
+            ),
+        ];
+        for (coin, seg_wit, address) in cases {
+            let coin_info = CoinInfo {
+                coin: coin.to_string(),
+                derivation_path: "".to_string(),
+                curve: CurveType::SECP256k1,
+                network: "MAINNET".to_string(),
+                seg_wit: seg_wit.to_string(),
+            };
+            assert_eq!(
+                BtcForkAddress::from_public_key(&pub_key, &coin_info).unwrap(),
+                address
+            );
+        }
+    }
+
     #[test]
     pub fn test_btc_fork_address_from_str() {
         let addr = BtcForkAddress::from_str("MR5Hu9zXPX3o9QuYNJGft1VMpRP418QDfW").unwrap();
@@ -422,6 +566,25 @@ mod tests {
         assert_eq!(addr.network.coin, "BITCOIN");
         assert_eq!(addr.network.seg_wit, "SEGWIT");
         assert_eq!(addr.network.network, "MAINNET");
+        let addr = BtcForkAddress::from_str(
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+        )
+        .unwrap();
+        assert_eq!(addr.network.coin, "BITCOIN");
+        assert_eq!(addr.network.seg_wit, "P2TR");
+        assert_eq!(addr.network.network, "MAINNET");
+        assert_eq!(
+            hex::encode(addr.script_pubkey().as_bytes()),
+            "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c"
+        );
+        // bip-0350, witness v1 encoded with the bech32 checksum
+        assert!(BtcForkAddress::from_str(
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd"
+        )
+        .is_err());
+        // bip-0350, witness v0 encoded with the bech32m checksum
+        assert!(BtcForkAddress::from_str("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh").is_err());
+
         let addr = BtcForkAddress::from_str("12z6UzsA3tjpaeuvA2Zr9jwx19Azz74D6g").unwrap();
         assert_eq!(addr.network.coin, "BITCOIN");
         assert_eq!(addr.network.seg_wit, "NONE");
@@ -487,6 +650,18 @@ mod tests {
             "bc1qum864wd9nwsc0u9ytkctz6wzrw6g7zdntm7f4e",
             liked_address.to_string()
         );
+        let pub_key =
+            hex::decode("03cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115")
+                .unwrap();
+        let liked_address = BtcForkAddress::address_like(
+            "bc1pqvrla5hul9cqdtz60lwwn35zdcx363pyxua0trqnz3wx8hvjxzdsdevceu",
+            &pub_key,
+        )
+        .unwrap();
+        assert_eq!(
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+            liked_address.to_string()
+        );
     }
 
     #[test]
@@ -523,6 +698,77 @@ mod tests {
         assert_eq!("xpub6JeaAjhtvtjCDnEo4Bjr7uEbGccaHnJtLY4aBnMaAYGjkBRB3fP9XvjcCbNjMiU1n5tt7dYKVgHPGzh3t3W6eLBxavxABTaoQ2jhbiQrfe4", ltc_xprv_str);
     }
 
+    #[test]
+    pub fn slip132_extended_key_test() {
+        // BIP84 test vector: https://github.com/bitcoin/bips/blob/master/bip-0084.mediawiki
+        let root = Bip32DeterministicPrivateKey::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        let account = root.derive("m/84'/0'/0'").unwrap();
+        let coin_info = CoinInfo {
+            coin: "BITCOIN".to_string(),
+            derivation_path: "m/84'/0'/0'/0/0".to_string(),
+            curve: CurveType::SECP256k1,
+            network: "MAINNET".to_string(),
+            seg_wit: "SEGWIT".to_string(),
+        };
+        let zprv = BtcForkAddress::slip132_extended_private_key(&account, &coin_info).unwrap();
+        assert_eq!("zprvAdG4iTXWBoARxkkzNpNh8r6Qag3irQB8PzEMkAFeTRXxHpbF9z4QgEvBRmfvqWvGp42t42nvgGpNgYSJA9iefm1yYNZKEm7z6qUWCroSQnE", zprv);
+        let zpub = BtcForkAddress::slip132_extended_public_key(
+            &account.deterministic_public_key(),
+            &coin_info,
+        )
+        .unwrap();
+        assert_eq!("zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs", zpub);
+
+        // BIP49 test vector: https://github.com/bitcoin/bips/blob/master/bip-0049.mediawiki
+        let root = Bip32DeterministicPrivateKey::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        let account = root.derive("m/49'/1'/0'").unwrap();
+        let coin_info = CoinInfo {
+            coin: "BITCOIN".to_string(),
+            derivation_path: "m/49'/1'/0'/0/0".to_string(),
+            curve: CurveType::SECP256k1,
+            network: "TESTNET".to_string(),
+            seg_wit: "P2WPKH".to_string(),
+        };
+        let uprv = BtcForkAddress::slip132_extended_private_key(&account, &coin_info).unwrap();
+        assert_eq!("uprv91G7gZkzehuMVxDJTYE6tLivdF8e4rvzSu1LFfKw3b2Qx1Aj8vpoFnHdfUZ3hmi9jsvPifmZ24RTN2KhwB8BfMLTVqaBReibyaFFcTP1s9n", uprv);
+    }
+
+    #[test]
+    pub fn custom_network_address_test() {
+        let network = BtcForkNetwork {
+            coin: "DASH".to_string(),
+            network: "MAINNET".to_string(),
+            seg_wit: "NONE".to_string(),
+            hrp: "".to_string(),
+            p2pkh_prefix: 0x4c,
+            p2sh_prefix: 0x10,
+            private_prefix: 0xcc,
+            xpub_prefix: [0x04, 0x88, 0xB2, 0x1E],
+            xprv_prefix: [0x04, 0x88, 0xAD, 0xE4],
+        };
+        register_btc_fork_network(network.clone()).unwrap();
+
+        let pub_key =
+            hex::decode("02506bc1dc099358e5137292f4efdd57e400f29ba5132aa5d12b18dac1c1f6aaba")
+                .unwrap();
+        let addr = BtcForkAddress::p2pkh(&pub_key, &network)
+            .unwrap()
+            .to_string();
+        assert!(addr.starts_with('X'));
+        let parsed = BtcForkAddress::from_str(&addr).unwrap();
+        assert_eq!(parsed.network.coin, "DASH");
+        assert_eq!(parsed.to_string(), addr);
+
+        remove_btc_fork_network("DASH", "MAINNET", "NONE").unwrap();
+        assert!(BtcForkAddress::from_str(&addr).is_err());
+    }
+
     #[test]
     pub fn script_pub_key() {
         let addr = BtcForkAddress::from_str("MR5Hu9zXPX3o9QuYNJGft1VMpRP418QDfW").unwrap();
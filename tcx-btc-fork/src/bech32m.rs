@@ -0,0 +1,132 @@
+//! BIP350 bech32m, the checksum of the witness v1+ addresses.
+//! `bech32` 0.7 only implements the BIP173 checksum, the encoding is otherwise the same.
+// ref: https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki
+
+use bech32::{u5, Error};
+
+const SEP: char = '1';
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+const GEN: [u32; 5] = [
+    0x3b6a_57b2,
+    0x2650_8e6d,
+    0x1ea1_19fa,
+    0x3d42_33dd,
+    0x2a14_62b3,
+];
+
+fn polymod(values: &[u8]) -> u32 {
+    let mut chk: u32 = 1;
+    for v in values {
+        let b = (chk >> 25) as u8;
+        chk = (chk & 0x01ff_ffff) << 5 ^ u32::from(*v);
+        for (i, g) in GEN.iter().enumerate() {
+            if (b >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut v: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    v.push(0);
+    v.extend(hrp.bytes().map(|b| b & 0x1f));
+    v
+}
+
+fn checksum(hrp: &str, data: &[u8]) -> Vec<u8> {
+    let values = [hrp_expand(hrp), data.to_vec(), vec![0; 6]].concat();
+    let plm = polymod(&values) ^ BECH32M_CONST;
+    (0..6)
+        .map(|i| ((plm >> (5 * (5 - i))) & 0x1f) as u8)
+        .collect()
+}
+
+pub fn encode(hrp: &str, data: &[u5]) -> String {
+    let hrp = hrp.to_lowercase();
+    let data: Vec<u8> = data.iter().map(|x| x.to_u8()).collect();
+    let checksum = checksum(&hrp, &data);
+    let mut ret = hrp;
+    ret.push(SEP);
+    ret.extend(
+        data.iter()
+            .chain(checksum.iter())
+            .map(|x| CHARSET[*x as usize] as char),
+    );
+    ret
+}
+
+pub fn decode(s: &str) -> Result<(String, Vec<u5>), Error> {
+    if s.len() < 8 || s.len() > 90 {
+        return Err(Error::InvalidLength);
+    }
+    if s.chars().any(|c| c.is_ascii_lowercase()) && s.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err(Error::MixedCase);
+    }
+    let s = s.to_lowercase();
+    let (hrp, data) = match s.rfind(SEP) {
+        None => return Err(Error::MissingSeparator),
+        Some(sep) => (&s[..sep], &s[sep + 1..]),
+    };
+    if hrp.is_empty() || data.len() < 6 {
+        return Err(Error::InvalidLength);
+    }
+    if let Some(c) = hrp.chars().find(|c| *c < '!' || *c > '~') {
+        return Err(Error::InvalidChar(c));
+    }
+
+    let values = data
+        .chars()
+        .map(|c| {
+            CHARSET
+                .iter()
+                .position(|x| *x as char == c)
+                .map(|x| x as u8)
+                .ok_or(Error::InvalidChar(c))
+        })
+        .collect::<Result<Vec<u8>, Error>>()?;
+    if polymod(&[hrp_expand(hrp), values.clone()].concat()) != BECH32M_CONST {
+        return Err(Error::InvalidChecksum);
+    }
+
+    let payload = values[..values.len() - 6]
+        .iter()
+        .map(|x| u5::try_from_u8(*x))
+        .collect::<Result<Vec<u5>, Error>>()?;
+    Ok((hrp.to_string(), payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode};
+    use bech32::Error;
+
+    #[test]
+    fn test_bech32m_vectors() {
+        let valid = vec![
+            "A1LQFN3A",
+            "a1lqfn3a",
+            "an83characterlonghumanreadablepartthatcontainsthetheexcludedcharactersbioandnumber11sg7hg6",
+            "abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx",
+            "11llllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllludsr8",
+            "split1checkupstagehandshakeupstreamerranterredcaperredlc445v",
+            "?1v759aa",
+        ];
+        for s in valid {
+            let (hrp, data) = decode(s).unwrap();
+            assert_eq!(encode(&hrp, &data), s.to_lowercase());
+        }
+
+        assert_eq!(decode("1xj0phk").unwrap_err(), Error::InvalidLength);
+        assert_eq!(decode("qyrz8wqd2c9m").unwrap_err(), Error::MissingSeparator);
+        assert_eq!(decode("M1VUXWEZ").unwrap_err(), Error::InvalidChecksum);
+        assert_eq!(decode("y1b0jsk6g").unwrap_err(), Error::InvalidChar('b'));
+        // a bech32 checksum is not a valid bech32m one
+        assert_eq!(
+            decode("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4").unwrap_err(),
+            Error::InvalidChecksum
+        );
+    }
+}
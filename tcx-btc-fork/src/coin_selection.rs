@@ -0,0 +1,574 @@
+//! Fee rate based coin selection: branch and bound for a changeless selection, with a knapsack
+//! fallback which adds a change output.
+//! Sizes are counted in weight units, a virtual byte being 4 of them. Fees are paid per whole
+//! virtual byte, so the budget of each input and output never falls below its share of the
+//! fee of the whole transaction.
+// ref:
+// - https://github.com/bitcoin/bips/blob/master/bip-0141.mediawiki#transaction-size-calculations
+// - https://github.com/bitcoin/bitcoin/blob/master/src/wallet/coinselection.cpp
+
+use crate::address::BtcForkAddress;
+use crate::signer::{op_return_script, DUST};
+use crate::transaction::{CoinSelectionInput, CoinSelectionOutput, Utxo};
+use crate::Result;
+
+use bitcoin::consensus::serialize;
+use bitcoin::Script;
+use std::str::FromStr;
+
+/// version, input count, output count and locktime
+const TX_OVERHEAD_WEIGHT: u64 = (4 + 1 + 1 + 4) * 4;
+/// segwit marker and flag
+const WITNESS_HEADER_WEIGHT: u64 = 2;
+/// outpoint and sequence
+const TX_IN_BASE_SIZE: u64 = 36 + 4;
+/// a DER signature with its sighash type is at most 72 bytes
+const ECDSA_SIG_SIZE: u64 = 72;
+const SCHNORR_SIG_SIZE: u64 = 64;
+const PUBLIC_KEY_SIZE: u64 = 33;
+
+const MAX_BNB_TRIES: usize = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputType {
+    P2pkh,
+    P2shP2wpkh,
+    P2wpkh,
+    P2tr,
+}
+
+impl InputType {
+    /// The P2SH scripts hide what they redeem, they are taken as P2SH-P2WPKH only in the
+    /// accounts of that `seg_wit`
+    pub fn from_script(script: &Script, seg_wit: &str) -> Result<InputType> {
+        let bytes = script.as_bytes();
+        if script.is_p2pkh() {
+            Ok(InputType::P2pkh)
+        } else if script.is_p2sh() && seg_wit == "P2WPKH" {
+            Ok(InputType::P2shP2wpkh)
+        } else if script.is_v0_p2wpkh() {
+            Ok(InputType::P2wpkh)
+        } else if bytes.len() == 34 && bytes[0] == 0x51 && bytes[1] == 0x20 {
+            Ok(InputType::P2tr)
+        } else {
+            Err(format_err!("unsupported_script_type"))
+        }
+    }
+
+    pub fn is_segwit(self) -> bool {
+        self != InputType::P2pkh
+    }
+
+    /// The weight of a signed input spending this type of output
+    pub fn weight(self) -> u64 {
+        match self {
+            // <sig> <pubkey>
+            InputType::P2pkh => {
+                (TX_IN_BASE_SIZE + 1 + 1 + ECDSA_SIG_SIZE + 1 + PUBLIC_KEY_SIZE) * 4
+            }
+            // <0 <20-byte-key-hash>>, witness [sig, pubkey]
+            InputType::P2shP2wpkh => {
+                (TX_IN_BASE_SIZE + 1 + 23) * 4 + 1 + 1 + ECDSA_SIG_SIZE + 1 + PUBLIC_KEY_SIZE
+            }
+            // witness [sig, pubkey]
+            InputType::P2wpkh => {
+                (TX_IN_BASE_SIZE + 1) * 4 + 1 + 1 + ECDSA_SIG_SIZE + 1 + PUBLIC_KEY_SIZE
+            }
+            // witness [sig]
+            InputType::P2tr => (TX_IN_BASE_SIZE + 1) * 4 + 1 + 1 + SCHNORR_SIG_SIZE,
+        }
+    }
+}
+
+/// The weight of an output paying to `script`
+pub fn output_weight(script: &Script) -> u64 {
+    (8 + serialize(script).len() as u64) * 4
+}
+
+/// The virtual size of a signed transaction spending `inputs` to `outputs`
+pub fn estimate_vsize(inputs: &[InputType], outputs: &[Script]) -> u64 {
+    let mut weight = TX_OVERHEAD_WEIGHT;
+    if inputs.iter().any(|x| x.is_segwit()) {
+        weight += WITNESS_HEADER_WEIGHT;
+    }
+    weight += inputs.iter().map(|x| x.weight()).sum::<u64>();
+    weight += outputs.iter().map(output_weight).sum::<u64>();
+    (weight + 3) / 4
+}
+
+/// Rounded up to whole virtual bytes like the fee of the whole transaction
+fn fee_for_weight(weight: u64, fee_rate: u64) -> u64 {
+    (weight + 3) / 4 * fee_rate
+}
+
+fn utxo_script(utxo: &Utxo) -> Result<Script> {
+    if utxo.script_pub_key.is_empty() {
+        Ok(BtcForkAddress::from_str(&utxo.address)?.script_pubkey())
+    } else {
+        Ok(Script::from(hex::decode(&utxo.script_pub_key)?))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoinSelection {
+    pub unspents: Vec<Utxo>,
+    pub fee: u64,
+    /// zero when the selection has no change output
+    pub change: u64,
+    pub vsize: u64,
+}
+
+struct Candidate {
+    utxo: Utxo,
+    input_type: InputType,
+    /// the amount less the fee of spending it
+    effective_value: u64,
+}
+
+/// Depth first search of the subset of `values`, sorted in descending order, whose sum is in
+/// `[target, target + cost_of_change]` with the smallest excess
+fn branch_and_bound(values: &[u64], target: u64, cost_of_change: u64) -> Option<Vec<usize>> {
+    struct Search<'a> {
+        values: &'a [u64],
+        target: u64,
+        upper_bound: u64,
+        tries: usize,
+        selected: Vec<usize>,
+        best: Option<(u64, Vec<usize>)>,
+    }
+
+    impl<'a> Search<'a> {
+        fn run(&mut self, index: usize, current: u64, remaining: u64) {
+            if self.tries == 0 || self.best.as_ref().map_or(false, |x| x.0 == 0) {
+                return;
+            }
+            self.tries -= 1;
+            if current > self.upper_bound || current + remaining < self.target {
+                return;
+            }
+            if current >= self.target {
+                let excess = current - self.target;
+                if self.best.as_ref().map_or(true, |x| excess < x.0) {
+                    self.best = Some((excess, self.selected.clone()));
+                }
+                return;
+            }
+            if index == self.values.len() {
+                return;
+            }
+
+            let value = self.values[index];
+            self.selected.push(index);
+            self.run(index + 1, current + value, remaining - value);
+            self.selected.pop();
+            self.run(index + 1, current, remaining - value);
+        }
+    }
+
+    let mut search = Search {
+        values,
+        target,
+        upper_bound: target + cost_of_change,
+        tries: MAX_BNB_TRIES,
+        selected: vec![],
+        best: None,
+    };
+    search.run(0, 0, values.iter().sum());
+    search.best.map(|x| x.1)
+}
+
+/// The smallest single value covering `target`, or the greedy accumulation of the smaller
+/// ones, whichever overshoots less
+fn knapsack(values: &[u64], target: u64) -> Option<Vec<usize>> {
+    let lowest_larger = values
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| **x >= target)
+        .min_by_key(|(_, x)| **x)
+        .map(|(i, x)| (*x, vec![i]));
+
+    let mut total = 0;
+    let mut selected = vec![];
+    for (i, value) in values.iter().enumerate().filter(|(_, x)| **x < target) {
+        total += value;
+        selected.push(i);
+        if total >= target {
+            break;
+        }
+    }
+    let smaller = if total >= target {
+        Some((total, selected))
+    } else {
+        None
+    };
+
+    match (lowest_larger, smaller) {
+        (Some(larger), Some(smaller)) if smaller.0 < larger.0 => Some(smaller.1),
+        (Some(larger), _) => Some(larger.1),
+        (None, smaller) => smaller.map(|x| x.1),
+    }
+}
+
+/// Selects the unspents paying `amount` to `output_scripts` at `fee_rate` sat/vbyte, a change
+/// output paying to `change_script` is added unless the change would be dust. `seg_wit` is the
+/// script type of the account owning the unspents
+pub fn select_coins(
+    unspents: &[Utxo],
+    amount: u64,
+    output_scripts: &[Script],
+    change_script: &Script,
+    fee_rate: u64,
+    seg_wit: &str,
+) -> Result<CoinSelection> {
+    ensure!(fee_rate > 0, "invalid_fee_rate");
+    ensure!(amount >= DUST, "amount_less_than_minimum");
+
+    let mut candidates = vec![];
+    for utxo in unspents {
+        ensure!(utxo.amount > 0, "invalid_utxo_amount");
+        let input_type = InputType::from_script(&utxo_script(utxo)?, seg_wit)?;
+        let spend_fee = fee_for_weight(input_type.weight(), fee_rate);
+        if utxo.amount as u64 > spend_fee {
+            candidates.push(Candidate {
+                utxo: utxo.clone(),
+                input_type,
+                effective_value: utxo.amount as u64 - spend_fee,
+            });
+        }
+    }
+    candidates.sort_by(|a, b| b.effective_value.cmp(&a.effective_value));
+
+    let mut base_weight = TX_OVERHEAD_WEIGHT;
+    if candidates.iter().any(|x| x.input_type.is_segwit()) {
+        base_weight += WITNESS_HEADER_WEIGHT;
+    }
+    base_weight += output_scripts.iter().map(output_weight).sum::<u64>();
+    let target = amount + fee_for_weight(base_weight, fee_rate);
+    let change_fee = fee_for_weight(output_weight(change_script), fee_rate);
+    // the change costs its output now and its input when spent later
+    let cost_of_change = change_fee
+        + InputType::from_script(change_script, seg_wit)
+            .map(|x| fee_for_weight(x.weight(), fee_rate))
+            .unwrap_or(0);
+
+    let values: Vec<u64> = candidates.iter().map(|x| x.effective_value).collect();
+    let selected = match branch_and_bound(&values, target, cost_of_change) {
+        Some(selected) => selected,
+        None => knapsack(&values, target + change_fee + DUST)
+            .or_else(|| knapsack(&values, target))
+            .ok_or_else(|| format_err!("insufficient_funds"))?,
+    };
+
+    let selected: Vec<&Candidate> = selected.iter().map(|i| &candidates[*i]).collect();
+    let total: u64 = selected.iter().map(|x| x.utxo.amount as u64).sum();
+    let input_types: Vec<InputType> = selected.iter().map(|x| x.input_type).collect();
+
+    let mut outputs = output_scripts.to_vec();
+    outputs.push(change_script.clone());
+    let vsize_with_change = estimate_vsize(&input_types, &outputs);
+    let fee_with_change = vsize_with_change * fee_rate;
+    let (fee, change, vsize) = if total >= amount + fee_with_change + DUST {
+        (
+            fee_with_change,
+            total - amount - fee_with_change,
+            vsize_with_change,
+        )
+    } else {
+        // the dust goes to the miners
+        let vsize = estimate_vsize(&input_types, output_scripts);
+        ensure!(total >= amount + vsize * fee_rate, "insufficient_funds");
+        (total - amount, 0, vsize)
+    };
+
+    Ok(CoinSelection {
+        unspents: selected.iter().map(|x| x.utxo.clone()).collect(),
+        fee,
+        change,
+        vsize,
+    })
+}
+
+/// Selects the unspents of `input` paying `amount` to `to` along with its OP_RETURN output, the
+/// change goes to the change address or, when missing, to an address of the first unspent's type
+pub fn select_unspents(input: &CoinSelectionInput) -> Result<CoinSelectionOutput> {
+    ensure!(input.amount >= 0, "amount_less_than_minimum");
+    ensure!(input.fee_rate > 0, "invalid_fee_rate");
+    let mut output_scripts = vec![BtcForkAddress::from_str(&input.to)?.script_pubkey()];
+    if !input.op_return.is_empty() {
+        output_scripts.push(op_return_script(&input.op_return)?);
+    }
+    let change_script = if input.change_address.is_empty() {
+        let first = input
+            .unspents
+            .first()
+            .ok_or_else(|| format_err!("insufficient_funds"))?;
+        utxo_script(first)?
+    } else {
+        BtcForkAddress::from_str(&input.change_address)?.script_pubkey()
+    };
+
+    let selection = select_coins(
+        &input.unspents,
+        input.amount as u64,
+        &output_scripts,
+        &change_script,
+        input.fee_rate as u64,
+        &input.seg_wit,
+    )?;
+    Ok(CoinSelectionOutput {
+        unspents: selection.unspents,
+        fee: selection.fee as i64,
+        change: selection.change as i64,
+        vsize: selection.vsize as i64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{estimate_vsize, select_coins, select_unspents, InputType};
+    use crate::transaction::{CoinSelectionInput, Utxo};
+    use bitcoin::Script;
+
+    const P2WPKH_SCRIPT: &str = "0014e6cfaab9a59ba187f0a45db0b169c21bb48f09b3";
+
+    fn utxo(index: i32, amount: i64) -> Utxo {
+        Utxo {
+            tx_hash: "a477af6b2667c29670467e4e0728b685ee07b240235771862318e29ddbe58458".to_string(),
+            vout: index,
+            amount,
+            address: "".to_string(),
+            script_pub_key: P2WPKH_SCRIPT.to_string(),
+            derived_path: "0/0".to_string(),
+            sequence: 0,
+        }
+    }
+
+    fn p2wpkh_script() -> Script {
+        Script::from(hex::decode(P2WPKH_SCRIPT).unwrap())
+    }
+
+    #[test]
+    fn test_estimate_vsize() {
+        let p2pkh = Script::from(
+            hex::decode("76a914e6cfaab9a59ba187f0a45db0b169c21bb48f09b388ac").unwrap(),
+        );
+        let p2sh =
+            Script::from(hex::decode("a91421d31b2241fe0493a2af099b73ee8643ebaf9b4b87").unwrap());
+        assert_eq!(
+            estimate_vsize(&[InputType::P2pkh], &[p2pkh.clone(), p2pkh]),
+            226
+        );
+        assert_eq!(
+            estimate_vsize(&[InputType::P2wpkh], &[p2wpkh_script(), p2wpkh_script()]),
+            141
+        );
+        assert_eq!(
+            estimate_vsize(&[InputType::P2shP2wpkh], &[p2sh.clone(), p2sh]),
+            166
+        );
+        assert_eq!(
+            estimate_vsize(&[InputType::P2tr, InputType::P2wpkh], &[p2wpkh_script()]),
+            167
+        );
+    }
+
+    #[test]
+    fn test_select_coins_without_change() {
+        let unspents = vec![utxo(0, 50_000), utxo(1, 30_000), utxo(2, 20_200)];
+        let selection = select_coins(
+            &unspents,
+            50_000,
+            &[p2wpkh_script()],
+            &p2wpkh_script(),
+            1,
+            "SEGWIT",
+        )
+        .unwrap();
+        assert_eq!(selection.unspents, vec![utxo(1, 30_000), utxo(2, 20_200)]);
+        assert_eq!(selection.change, 0);
+        assert_eq!(selection.vsize, 178);
+        // the excess below the cost of a change goes to the fee
+        assert_eq!(selection.fee, 200);
+    }
+
+    #[test]
+    fn test_select_coins_with_change() {
+        let unspents = vec![utxo(0, 10_000), utxo(1, 200_000), utxo(2, 20_000)];
+        let selection = select_coins(
+            &unspents,
+            25_000,
+            &[p2wpkh_script()],
+            &p2wpkh_script(),
+            10,
+            "SEGWIT",
+        )
+        .unwrap();
+        assert_eq!(selection.unspents, vec![utxo(2, 20_000), utxo(0, 10_000)]);
+        assert_eq!(selection.vsize, 209);
+        assert_eq!(selection.fee, 2090);
+        assert_eq!(selection.change, 2910);
+
+        let selection = select_coins(
+            &unspents,
+            100_000,
+            &[p2wpkh_script()],
+            &p2wpkh_script(),
+            10,
+            "SEGWIT",
+        )
+        .unwrap();
+        assert_eq!(selection.unspents, vec![utxo(1, 200_000)]);
+        assert_eq!(selection.vsize, 141);
+        assert_eq!(selection.fee, 1410);
+        assert_eq!(selection.change, 98_590);
+    }
+
+    #[test]
+    fn test_select_coins_dust_change() {
+        // the 490 satoshis left once the change output is paid are dust
+        let unspents = vec![utxo(0, 51_900)];
+        let selection = select_coins(
+            &unspents,
+            50_000,
+            &[p2wpkh_script()],
+            &p2wpkh_script(),
+            10,
+            "SEGWIT",
+        )
+        .unwrap();
+        assert_eq!(selection.change, 0);
+        assert_eq!(selection.vsize, 110);
+        assert_eq!(selection.fee, 1900);
+    }
+
+    #[test]
+    fn test_select_coins_exact_match() {
+        // the 68 vbytes input and 42 vbytes of the rest cost 1100 at 10 sat/vbyte
+        let selection = select_coins(
+            &[utxo(0, 51_100)],
+            50_000,
+            &[p2wpkh_script()],
+            &p2wpkh_script(),
+            10,
+            "SEGWIT",
+        )
+        .unwrap();
+        assert_eq!(selection.change, 0);
+        assert_eq!(selection.vsize, 110);
+        assert_eq!(selection.fee, 1100);
+
+        let err = select_coins(
+            &[utxo(0, 51_095)],
+            50_000,
+            &[p2wpkh_script()],
+            &p2wpkh_script(),
+            10,
+            "SEGWIT",
+        )
+        .unwrap_err();
+        assert_eq!(format!("{}", err), "insufficient_funds");
+    }
+
+    #[test]
+    fn test_select_coins_invalid() {
+        let unspents = vec![utxo(0, 50_000), utxo(1, 100)];
+        let err = select_coins(
+            &unspents,
+            50_000,
+            &[p2wpkh_script()],
+            &p2wpkh_script(),
+            1,
+            "SEGWIT",
+        )
+        .unwrap_err();
+        assert_eq!(format!("{}", err), "insufficient_funds");
+        let err = select_coins(
+            &unspents,
+            500,
+            &[p2wpkh_script()],
+            &p2wpkh_script(),
+            1,
+            "SEGWIT",
+        )
+        .unwrap_err();
+        assert_eq!(format!("{}", err), "amount_less_than_minimum");
+        let err = select_coins(
+            &unspents,
+            10_000,
+            &[p2wpkh_script()],
+            &p2wpkh_script(),
+            0,
+            "SEGWIT",
+        )
+        .unwrap_err();
+        assert_eq!(format!("{}", err), "invalid_fee_rate");
+
+        let mut unknown = utxo(0, 50_000);
+        unknown.script_pub_key = "6a00".to_string();
+        let err = select_coins(
+            &[unknown],
+            10_000,
+            &[p2wpkh_script()],
+            &p2wpkh_script(),
+            1,
+            "SEGWIT",
+        )
+        .unwrap_err();
+        assert_eq!(format!("{}", err), "unsupported_script_type");
+
+        // a P2SH unspent outside of a P2SH-P2WPKH account may redeem a multisig script
+        let mut p2sh = utxo(0, 50_000);
+        p2sh.script_pub_key = "a91421d31b2241fe0493a2af099b73ee8643ebaf9b4b87".to_string();
+        let err = select_coins(
+            &[p2sh.clone()],
+            10_000,
+            &[p2wpkh_script()],
+            &p2wpkh_script(),
+            1,
+            "SEGWIT",
+        )
+        .unwrap_err();
+        assert_eq!(format!("{}", err), "unsupported_script_type");
+        let selection = select_coins(
+            &[p2sh],
+            10_000,
+            &[p2wpkh_script()],
+            &p2wpkh_script(),
+            1,
+            "P2WPKH",
+        )
+        .unwrap();
+        assert_eq!(selection.vsize, 164);
+    }
+
+    #[test]
+    fn test_select_unspents() {
+        let input = CoinSelectionInput {
+            chain_type: "BITCOIN".to_string(),
+            network: "MAINNET".to_string(),
+            seg_wit: "SEGWIT".to_string(),
+            to: "bc1qum864wd9nwsc0u9ytkctz6wzrw6g7zdntm7f4e".to_string(),
+            amount: 100_000,
+            unspents: vec![utxo(0, 10_000), utxo(1, 200_000), utxo(2, 20_000)],
+            fee_rate: 10,
+            change_address: "".to_string(),
+            op_return: "".to_string(),
+        };
+        let output = select_unspents(&input).unwrap();
+        assert_eq!(output.unspents, vec![utxo(1, 200_000)]);
+        assert_eq!(output.fee, 1410);
+        assert_eq!(output.change, 98_590);
+        assert_eq!(output.vsize, 141);
+
+        // the 40 bytes of data make a 51 vbytes output
+        let input = CoinSelectionInput {
+            op_return: "00".repeat(40),
+            ..input
+        };
+        let output = select_unspents(&input).unwrap();
+        assert_eq!(output.unspents, vec![utxo(1, 200_000)]);
+        assert_eq!(output.fee, 1920);
+        assert_eq!(output.change, 98_080);
+        assert_eq!(output.vsize, 192);
+    }
+}
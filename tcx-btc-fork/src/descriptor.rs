@@ -0,0 +1,630 @@
+//! Output script descriptors of the BTC fork accounts: the single key pkh, sh(wpkh), wpkh and tr
+//! ones and the sortedmulti ones, over extended public keys with their key origins.
+// ref:
+// - https://github.com/bitcoin/bips/blob/master/bip-0380.mediawiki
+// - https://github.com/bitcoin/bips/blob/master/bip-0381.mediawiki
+// - https://github.com/bitcoin/bips/blob/master/bip-0382.mediawiki
+// - https://github.com/bitcoin/bips/blob/master/bip-0383.mediawiki
+// - https://github.com/bitcoin/bips/blob/master/bip-0386.mediawiki
+
+use crate::address::BtcForkAddress;
+use crate::multisig::{sorted_multisig_address, P2SH, P2SH_P2WSH, P2WSH};
+use crate::Result;
+
+use std::fmt;
+use std::str::FromStr;
+use tcx_constants::btc_fork_network::{network_from_param, slip132_versions};
+use tcx_constants::CoinInfo;
+use tcx_primitive::{
+    Bip32DeterministicPublicKey, Derive, DeriveJunction, DerivePath, DeterministicPublicKey,
+    PublicKey, Ss58Codec,
+};
+
+const INPUT_CHARSET: &str =
+    "0123456789()[],'/*abcdefgh@:$%{}IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+const CHECKSUM_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u64; 5] = [
+    0xf5dee51989,
+    0xa9fdca3312,
+    0x1bab10e32d,
+    0x3706b1677a,
+    0x644d626ffd,
+];
+
+/// The 8 characters checksum of the descriptor, appended after a `#`
+pub fn descriptor_checksum(desc: &str) -> Result<String> {
+    let mut symbols = vec![];
+    let mut groups = vec![];
+    for c in desc.chars() {
+        let pos = INPUT_CHARSET
+            .find(c)
+            .ok_or_else(|| format_err!("invalid_descriptor"))? as u64;
+        symbols.push(pos & 31);
+        groups.push(pos >> 5);
+        if groups.len() == 3 {
+            symbols.push(groups[0] * 9 + groups[1] * 3 + groups[2]);
+            groups.clear();
+        }
+    }
+    match groups.len() {
+        1 => symbols.push(groups[0]),
+        2 => symbols.push(groups[0] * 3 + groups[1]),
+        _ => {}
+    }
+    symbols.extend_from_slice(&[0; 8]);
+
+    let mut chk = 1u64;
+    for value in symbols {
+        let top = chk >> 35;
+        chk = (chk & 0x7_ffff_ffff) << 5 ^ value;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= generator;
+            }
+        }
+    }
+    chk ^= 1;
+
+    Ok((0..8)
+        .map(|i| CHECKSUM_CHARSET[((chk >> (5 * (7 - i))) & 31) as usize] as char)
+        .collect())
+}
+
+/// A `[fingerprint/origin]xpub/child/*` key expression
+#[derive(Clone, Debug, PartialEq)]
+pub struct DescriptorKey {
+    /// the hex encoded fingerprint of the master key, empty without a key origin
+    pub fingerprint: String,
+    /// the path of the extended public key from the master key, e.g. m/84'/0'/0'
+    pub origin_path: String,
+    pub xpub: String,
+    /// the unhardened path from the extended public key, e.g. 0 for the receiving addresses
+    pub child_path: String,
+    /// ends with `/*`, one key per address index
+    pub ranged: bool,
+}
+
+impl DescriptorKey {
+    /// The compressed public key at the address `index`
+    pub fn public_key(&self, index: u32) -> Result<Vec<u8>> {
+        let (xpub, _) = Bip32DeterministicPublicKey::from_ss58check_with_version(&self.xpub)?;
+        let mut path = vec![];
+        if !self.child_path.is_empty() {
+            path.push(self.child_path.clone());
+        }
+        if self.ranged {
+            path.push(index.to_string());
+        }
+
+        let key = if path.is_empty() {
+            xpub
+        } else {
+            xpub.derive(&path.join("/"))?
+        };
+        Ok(key.public_key().to_bytes())
+    }
+}
+
+/// The path with the hardened junctions marked by `'`, without the leading `m`
+fn normalize_path(path: &str) -> Result<String> {
+    if path.is_empty() || path == "m" {
+        return Ok("".to_string());
+    }
+    Ok(DerivePath::from_str(path)?
+        .as_ref()
+        .iter()
+        .map(|x| match x {
+            DeriveJunction::Soft(index) => index.to_string(),
+            DeriveJunction::Hard(index) => format!("{}'", index),
+        })
+        .collect::<Vec<String>>()
+        .join("/"))
+}
+
+/// The path from the master key of the normalized relative `path`
+fn origin_path(path: &str) -> String {
+    if path.is_empty() {
+        "m".to_string()
+    } else {
+        format!("m/{}", path)
+    }
+}
+
+impl FromStr for DescriptorKey {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (fingerprint, origin_path, key) = if s.starts_with('[') {
+            let end = s
+                .find(']')
+                .ok_or_else(|| format_err!("invalid_descriptor_key"))?;
+            let origin = &s[1..end];
+            let (fingerprint, path) = origin.split_at(origin.find('/').unwrap_or(origin.len()));
+            ensure!(
+                fingerprint.len() == 8 && hex::decode(fingerprint).is_ok(),
+                "invalid_descriptor_key"
+            );
+            let path = normalize_path(path.trim_start_matches('/'))
+                .map_err(|_| format_err!("invalid_descriptor_key"))?;
+            (
+                fingerprint.to_lowercase(),
+                origin_path(&path),
+                &s[end + 1..],
+            )
+        } else {
+            ("".to_string(), "".to_string(), s)
+        };
+
+        let mut parts = key.split('/').collect::<Vec<&str>>();
+        let xpub = parts.remove(0);
+        ensure!(
+            Bip32DeterministicPublicKey::from_ss58check_with_version(xpub).is_ok(),
+            "unsupported_descriptor_key"
+        );
+        let ranged = parts.last() == Some(&"*");
+        if ranged {
+            parts.pop();
+        }
+        let child_path =
+            normalize_path(&parts.join("/")).map_err(|_| format_err!("invalid_descriptor_key"))?;
+        ensure!(!child_path.contains('\''), "unsupported_descriptor_key");
+
+        Ok(DescriptorKey {
+            fingerprint,
+            origin_path,
+            xpub: xpub.to_string(),
+            child_path,
+            ranged,
+        })
+    }
+}
+
+impl fmt::Display for DescriptorKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.fingerprint.is_empty() {
+            write!(f, "[{}", self.fingerprint)?;
+            if let Some(path) = self.origin_path.strip_prefix("m/") {
+                write!(f, "/{}", path)?;
+            }
+            write!(f, "]")?;
+        }
+        write!(f, "{}", self.xpub)?;
+        if !self.child_path.is_empty() {
+            write!(f, "/{}", self.child_path)?;
+        }
+        if self.ranged {
+            write!(f, "/*")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Descriptor {
+    Pkh(DescriptorKey),
+    ShWpkh(DescriptorKey),
+    Wpkh(DescriptorKey),
+    /// the key path only taproot output
+    Tr(DescriptorKey),
+    /// the P2SH, P2SH_P2WSH or P2WSH multisig script type, the threshold and the cosigner keys
+    SortedMulti(String, usize, Vec<DescriptorKey>),
+}
+
+/// The arguments of the `name(..)` expression
+fn arguments<'a>(desc: &'a str, name: &str) -> Option<&'a str> {
+    desc.strip_prefix(name)?
+        .strip_prefix('(')?
+        .strip_suffix(')')
+}
+
+fn sorted_multi(script_type: &str, desc: Option<&str>) -> Result<Descriptor> {
+    let desc = desc
+        .and_then(|x| arguments(x, "sortedmulti"))
+        .ok_or_else(|| format_err!("unsupported_descriptor"))?;
+    let mut args = desc.split(',');
+    let threshold = args
+        .next()
+        .and_then(|x| x.parse::<usize>().ok())
+        .ok_or_else(|| format_err!("invalid_descriptor"))?;
+    let keys = args
+        .map(DescriptorKey::from_str)
+        .collect::<Result<Vec<DescriptorKey>>>()?;
+    ensure!(
+        (1..=keys.len()).contains(&threshold),
+        "invalid_multisig_threshold"
+    );
+    Ok(Descriptor::SortedMulti(
+        script_type.to_string(),
+        threshold,
+        keys,
+    ))
+}
+
+impl FromStr for Descriptor {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let desc = match s.rfind('#') {
+            Some(pos) => {
+                ensure!(
+                    descriptor_checksum(&s[..pos])? == s[pos + 1..],
+                    "invalid_descriptor_checksum"
+                );
+                &s[..pos]
+            }
+            None => s,
+        };
+
+        if let Some(key) = arguments(desc, "pkh") {
+            Ok(Descriptor::Pkh(key.parse()?))
+        } else if let Some(key) = arguments(desc, "wpkh") {
+            Ok(Descriptor::Wpkh(key.parse()?))
+        } else if let Some(key) = arguments(desc, "tr") {
+            // script path spending trees are not supported
+            ensure!(!key.contains(','), "unsupported_descriptor");
+            Ok(Descriptor::Tr(key.parse()?))
+        } else if let Some(sh) = arguments(desc, "sh") {
+            if let Some(key) = arguments(sh, "wpkh") {
+                Ok(Descriptor::ShWpkh(key.parse()?))
+            } else if sh.starts_with("wsh(") {
+                sorted_multi(P2SH_P2WSH, arguments(sh, "wsh"))
+            } else {
+                sorted_multi(P2SH, Some(sh))
+            }
+        } else if desc.starts_with("wsh(") {
+            sorted_multi(P2WSH, arguments(desc, "wsh"))
+        } else {
+            Err(format_err!("unsupported_descriptor"))
+        }
+    }
+}
+
+impl fmt::Display for Descriptor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Descriptor::Pkh(key) => write!(f, "pkh({})", key),
+            Descriptor::ShWpkh(key) => write!(f, "sh(wpkh({}))", key),
+            Descriptor::Wpkh(key) => write!(f, "wpkh({})", key),
+            Descriptor::Tr(key) => write!(f, "tr({})", key),
+            Descriptor::SortedMulti(script_type, threshold, keys) => {
+                let multi = format!(
+                    "sortedmulti({},{})",
+                    threshold,
+                    keys.iter()
+                        .map(|x| x.to_string())
+                        .collect::<Vec<String>>()
+                        .join(",")
+                );
+                match script_type.as_str() {
+                    P2SH => write!(f, "sh({})", multi),
+                    P2SH_P2WSH => write!(f, "sh(wsh({}))", multi),
+                    _ => write!(f, "wsh({})", multi),
+                }
+            }
+        }
+    }
+}
+
+impl Descriptor {
+    /// The single key descriptor of the account of the `coin_info` script type, over the account
+    /// extended public key at `account_path` of the master key of `fingerprint` and ranged over
+    /// the receiving (0) or change (1) addresses
+    pub fn from_account(
+        fingerprint: &[u8],
+        account_path: &str,
+        xpub: &Bip32DeterministicPublicKey,
+        coin_info: &CoinInfo,
+        change: u32,
+    ) -> Result<Descriptor> {
+        let key = DescriptorKey {
+            fingerprint: hex::encode(fingerprint),
+            origin_path: origin_path(&normalize_path(account_path)?),
+            xpub: BtcForkAddress::extended_public_key(xpub, coin_info)?,
+            child_path: change.to_string(),
+            ranged: true,
+        };
+        Ok(match coin_info.seg_wit.as_str() {
+            "P2WPKH" => Descriptor::ShWpkh(key),
+            "SEGWIT" => Descriptor::Wpkh(key),
+            "P2TR" => Descriptor::Tr(key),
+            _ => Descriptor::Pkh(key),
+        })
+    }
+
+    /// The descriptor followed by its `#checksum`
+    pub fn to_string_with_checksum(&self) -> Result<String> {
+        let desc = self.to_string();
+        let checksum = descriptor_checksum(&desc)?;
+        Ok(format!("{}#{}", desc, checksum))
+    }
+
+    pub fn keys(&self) -> Vec<&DescriptorKey> {
+        match self {
+            Descriptor::Pkh(key)
+            | Descriptor::ShWpkh(key)
+            | Descriptor::Wpkh(key)
+            | Descriptor::Tr(key) => vec![key],
+            Descriptor::SortedMulti(_, _, keys) => keys.iter().collect(),
+        }
+    }
+
+    /// The seg_wit of the network the addresses are encoded with
+    pub fn seg_wit(&self) -> &str {
+        match self {
+            Descriptor::Pkh(_) => "NONE",
+            Descriptor::ShWpkh(_) => "P2WPKH",
+            Descriptor::Wpkh(_) => "SEGWIT",
+            Descriptor::Tr(_) => "P2TR",
+            Descriptor::SortedMulti(script_type, _, _) if script_type == P2WSH => "SEGWIT",
+            Descriptor::SortedMulti(..) => "NONE",
+        }
+    }
+
+    /// Ensures the version bytes of every extended public key belong to the network, either its
+    /// BIP32 ones (xpub/tpub) or the SLIP-132 ones of the script type (ypub/zpub/upub/vpub...)
+    pub fn check_network(&self, chain_type: &str, network: &str) -> Result<()> {
+        let network = network_from_param(chain_type, network, self.seg_wit())
+            .ok_or_else(|| format_err!("unsupported_chain"))?;
+        let (slip132_version, _) = slip132_versions(&network);
+        for key in self.keys() {
+            let (_, version) = Bip32DeterministicPublicKey::from_ss58check_with_version(&key.xpub)?;
+            ensure!(
+                version == network.xpub_prefix || version == slip132_version,
+                "descriptor_network_mismatch"
+            );
+        }
+        Ok(())
+    }
+
+    /// The address at `index`, ignored by the descriptors without ranged keys
+    pub fn address(&self, chain_type: &str, network: &str, index: u32) -> Result<String> {
+        let network = network_from_param(chain_type, network, self.seg_wit())
+            .ok_or_else(|| format_err!("unsupported_chain"))?;
+        let address = match self {
+            Descriptor::Pkh(key) => BtcForkAddress::p2pkh(&key.public_key(index)?, &network)?,
+            Descriptor::ShWpkh(key) => BtcForkAddress::p2shwpkh(&key.public_key(index)?, &network)?,
+            Descriptor::Wpkh(key) => BtcForkAddress::p2wpkh(&key.public_key(index)?, &network)?,
+            Descriptor::Tr(key) => BtcForkAddress::p2tr(&key.public_key(index)?, &network)?,
+            Descriptor::SortedMulti(script_type, threshold, keys) => {
+                let pub_keys = keys
+                    .iter()
+                    .map(|x| x.public_key(index))
+                    .collect::<Result<Vec<Vec<u8>>>>()?;
+                return Ok(
+                    sorted_multisig_address(&network, script_type, *threshold, pub_keys)?.address,
+                );
+            }
+        };
+        Ok(address.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{descriptor_checksum, Descriptor, DescriptorKey};
+    use std::str::FromStr;
+    use tcx_constants::coin_info::coin_info_from_param;
+    use tcx_primitive::{Bip32DeterministicPrivateKey, Derive, DeterministicPrivateKey};
+
+    // the m/84'/0'/0' account of "abandon ... about"
+    const BIP84_XPUB: &str = "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V";
+
+    #[test]
+    fn test_descriptor_checksum() {
+        // bip-0380 test vectors
+        assert_eq!(descriptor_checksum("raw(deadbeef)").unwrap(), "89f8spxm");
+        assert_eq!(
+            descriptor_checksum("sh(multi(2,[00000000/111'/222]xprvA1RpRA33e1JQ7ifknakTFpgNXPmW2YvmhqLQYMmrj4xJXXWYpDPS3xz7iAxn8L39njGVyuoseXzU6rcxFLJ8HFsTjSyQbLYnMpCqE2VbFWc,xprv9uPDJpEQgRQfDcW7BkF7eTya6RPxXeJCqCJGHuCJ4GiRVLzkTXBAJMu2qaMWPrS7AANYqdq6vcBcBUdJCVVFceUvJFjaPdGZ2y9WACViL4L/0))").unwrap(),
+            "ggrsrxfy"
+        );
+        assert_eq!(
+            format!("{}", descriptor_checksum("raw(deadbeef\n)").unwrap_err()),
+            "invalid_descriptor"
+        );
+    }
+
+    #[test]
+    fn test_parse_descriptor() {
+        let desc = format!("wpkh([73c5da0a/84h/0h/0h]{}/0/*)#afwvtk2s", BIP84_XPUB);
+        let descriptor = Descriptor::from_str(&desc).unwrap();
+        assert_eq!(
+            descriptor,
+            Descriptor::Wpkh(DescriptorKey {
+                fingerprint: "73c5da0a".to_string(),
+                origin_path: "m/84'/0'/0'".to_string(),
+                xpub: BIP84_XPUB.to_string(),
+                child_path: "0".to_string(),
+                ranged: true,
+            })
+        );
+        assert_eq!(
+            descriptor.to_string_with_checksum().unwrap(),
+            format!("wpkh([73c5da0a/84'/0'/0']{}/0/*)#wc3n3van", BIP84_XPUB)
+        );
+
+        let descriptor = Descriptor::from_str(&format!("wpkh({})", BIP84_XPUB)).unwrap();
+        assert_eq!(descriptor.keys()[0].fingerprint, "");
+        assert_eq!(descriptor.keys()[0].child_path, "");
+        assert!(!descriptor.keys()[0].ranged);
+
+        let cases = vec![
+            (
+                format!("wpkh([73c5da0a/84'/0'/0']{}/0/*)#wc3n3vam", BIP84_XPUB),
+                "invalid_descriptor_checksum",
+            ),
+            (
+                format!("wsh(multi(1,{}/0/*))", BIP84_XPUB),
+                "unsupported_descriptor",
+            ),
+            (
+                format!("combo({}/0/*)", BIP84_XPUB),
+                "unsupported_descriptor",
+            ),
+            (
+                format!("tr({}/0/*,pk({}/1/*))", BIP84_XPUB, BIP84_XPUB),
+                "unsupported_descriptor",
+            ),
+            (
+                format!("wpkh({}/0'/*)", BIP84_XPUB),
+                "unsupported_descriptor_key",
+            ),
+            (
+                "wpkh(L4rK1yDtCWekvXuE6oXD9jCYfFNV2cWRpVuPLBcCU2z8TrisoyY1)".to_string(),
+                "unsupported_descriptor_key",
+            ),
+            (
+                format!("wpkh([73c5da0a84'/0'/0']{}/0/*)", BIP84_XPUB),
+                "invalid_descriptor_key",
+            ),
+            (
+                format!("wsh(sortedmulti(2,{}/0/*))", BIP84_XPUB),
+                "invalid_multisig_threshold",
+            ),
+        ];
+        for (desc, err) in cases {
+            assert_eq!(
+                format!("{}", Descriptor::from_str(&desc).unwrap_err()),
+                err,
+                "{}",
+                desc
+            );
+        }
+    }
+
+    #[test]
+    fn test_descriptor_network() {
+        let descriptor = Descriptor::from_str(&format!("wpkh({}/0/*)", BIP84_XPUB)).unwrap();
+        assert!(descriptor.check_network("BITCOIN", "MAINNET").is_ok());
+        assert_eq!(
+            format!(
+                "{}",
+                descriptor.check_network("BITCOIN", "TESTNET").unwrap_err()
+            ),
+            "descriptor_network_mismatch"
+        );
+
+        // the SLIP-132 version of the script type
+        let zpub = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+        let descriptor = Descriptor::from_str(&format!("wpkh({}/0/*)", zpub)).unwrap();
+        assert!(descriptor.check_network("BITCOIN", "MAINNET").is_ok());
+        let descriptor = Descriptor::from_str(&format!("pkh({}/0/*)", zpub)).unwrap();
+        assert_eq!(
+            format!(
+                "{}",
+                descriptor.check_network("BITCOIN", "MAINNET").unwrap_err()
+            ),
+            "descriptor_network_mismatch"
+        );
+
+        let tpub = "tpubDCpWeoTY6x4BR2PqoTFJnEdfYbjnC4G8VvKoDUPFjt2dvZJWkMRxLST1pbVW56P7zY3L5jq9MRSeff2xsLnvf9qBBN9AgvrhwfZgw5dJG6R";
+        let descriptor = Descriptor::from_str(&format!("pkh({}/0/*)", tpub)).unwrap();
+        assert!(descriptor.check_network("BITCOIN", "TESTNET").is_ok());
+        assert_eq!(
+            format!(
+                "{}",
+                descriptor.check_network("BITCOIN", "MAINNET").unwrap_err()
+            ),
+            "descriptor_network_mismatch"
+        );
+    }
+
+    #[test]
+    fn test_descriptor_address() {
+        let cases = vec![
+            (
+                "pkh([73c5da0a/44'/0'/0']xpub6BosfCnifzxcFwrSzQiqu2DBVTshkCXacvNsWGYJVVhhawA7d4R5WSWGFNbi8Aw6ZRc1brxMyWMzG3DSSSSoekkudhUd9yLb6qx39T9nMdj/0/*)#8w4z8fed",
+                "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA",
+            ),
+            (
+                "sh(wpkh([73c5da0a/49'/0'/0']xpub6C6nQwHaWbSrzs5tZ1q7m5R9cPK9eYpNMFesiXsYrgc1P8bvLLAet9JfHjYXKjToD8cBRswJXXbbFpXgwsswVPAZzKMa1jUp2kVkGVUaJa7/0/*))#gvfpdstz",
+                "37VucYSaXLCAsxYyAPfbSi9eh4iEcbShgf",
+            ),
+            (
+                "wpkh([73c5da0a/84'/0'/0']xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/1/*)#lv5jvedt",
+                "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el",
+            ),
+            (
+                "tr([73c5da0a/86'/0'/0']xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ/0/*)#rg247h69",
+                "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+            ),
+            (
+                "wsh(sortedmulti(2,[1468dba9/48'/0'/0']xpub6CJWFLqLfYKRssptbhvkwbqcQR2eWKJwLNbyxbMagAT75TQDcLxJ7xBkeokJhkTHC7Msh5SL5RRh7C9ekqKLh9xMBQ8dhfaH3ktX8o9MWQQ/0/*,[73c5da0a/48'/0'/0']xpub6CKZtUaK1YHpQbg6CLaGRmsMKLQB1iKzsvmxtyHD6X7gzLqCB2VNZYd1XCxrccQnE8hhDxtYbR1Sakkvisy2J4CcTxWeeGjmkasCoNS9vZm/0/*,[b8688df1/48'/0'/0']xpub6CETL9tkmWBQkYmxxxGAEzVZNHCZLx24pj58FYqe41qhXgyAcabP9iyXaVCJXcZWcVbzttVPdoJpJvYNfBnQeFcunvuxcsKKAwxMw6S5S7s/0/*))#34r4qp9d",
+                "bc1qxrr0fzuefs2tn7gfjatey3rxrc2g4uehmduzn8dmavtp3q6p3afsa02c5u",
+            ),
+        ];
+        for (desc, address) in cases {
+            let descriptor = Descriptor::from_str(desc).unwrap();
+            assert_eq!(descriptor.to_string_with_checksum().unwrap(), desc);
+            assert_eq!(
+                descriptor.address("BITCOIN", "MAINNET", 0).unwrap(),
+                address
+            );
+        }
+
+        // the multisig addresses of the other script types
+        let multi = "sortedmulti(2,[1468dba9/48'/0'/0']xpub6CJWFLqLfYKRssptbhvkwbqcQR2eWKJwLNbyxbMagAT75TQDcLxJ7xBkeokJhkTHC7Msh5SL5RRh7C9ekqKLh9xMBQ8dhfaH3ktX8o9MWQQ/0/*,[73c5da0a/48'/0'/0']xpub6CKZtUaK1YHpQbg6CLaGRmsMKLQB1iKzsvmxtyHD6X7gzLqCB2VNZYd1XCxrccQnE8hhDxtYbR1Sakkvisy2J4CcTxWeeGjmkasCoNS9vZm/0/*,[b8688df1/48'/0'/0']xpub6CETL9tkmWBQkYmxxxGAEzVZNHCZLx24pj58FYqe41qhXgyAcabP9iyXaVCJXcZWcVbzttVPdoJpJvYNfBnQeFcunvuxcsKKAwxMw6S5S7s/0/*)";
+        let descriptor = Descriptor::from_str(&format!("sh({})", multi)).unwrap();
+        assert_eq!(
+            descriptor.address("BITCOIN", "MAINNET", 0).unwrap(),
+            "3AnQQiWgzLALrDZ8bSHQdGrqUgnS5QKMBo"
+        );
+        assert_eq!(
+            descriptor.address("LITECOIN", "MAINNET", 0).unwrap(),
+            "MGzYibvewT1meiq2hKGkSv7EoPNt3TTXLq"
+        );
+        let descriptor = Descriptor::from_str(&format!("sh(wsh({}))", multi)).unwrap();
+        assert_eq!(
+            descriptor.address("BITCOIN", "MAINNET", 0).unwrap(),
+            "3LykZ3Cx5PpTJVMayrvZMuWLnGU7JtugU2"
+        );
+
+        let descriptor = Descriptor::from_str(&format!("tr({}/0/*)", BIP84_XPUB)).unwrap();
+        assert_eq!(
+            format!(
+                "{}",
+                descriptor.address("DOGECOIN", "MAINNET", 0).unwrap_err()
+            ),
+            "unsupported_chain"
+        );
+    }
+
+    #[test]
+    fn test_descriptor_from_account() {
+        let root = Bip32DeterministicPrivateKey::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        let fingerprint = root.deterministic_public_key().fingerprint();
+        let mut coin_info = coin_info_from_param("BITCOIN", "MAINNET", "NONE", "").unwrap();
+        coin_info.seg_wit = "SEGWIT".to_string();
+
+        let xpub = root
+            .derive("m/84'/0'/0'")
+            .unwrap()
+            .deterministic_public_key();
+        let descriptor =
+            Descriptor::from_account(&fingerprint, "m/84'/0'/0'", &xpub, &coin_info, 0).unwrap();
+        assert_eq!(
+            descriptor.to_string_with_checksum().unwrap(),
+            format!("wpkh([73c5da0a/84'/0'/0']{}/0/*)#wc3n3van", BIP84_XPUB)
+        );
+        let descriptor =
+            Descriptor::from_account(&fingerprint, "m/84'/0'/0'", &xpub, &coin_info, 1).unwrap();
+        assert_eq!(
+            descriptor.to_string_with_checksum().unwrap(),
+            format!("wpkh([73c5da0a/84'/0'/0']{}/1/*)#lv5jvedt", BIP84_XPUB)
+        );
+
+        coin_info.seg_wit = "NONE".to_string();
+        let xpub = root
+            .derive("m/44'/0'/0'")
+            .unwrap()
+            .deterministic_public_key();
+        let descriptor =
+            Descriptor::from_account(&fingerprint, "m/44'/0'/0'", &xpub, &coin_info, 0).unwrap();
+        assert_eq!(
+            descriptor.to_string_with_checksum().unwrap(),
+            "pkh([73c5da0a/44'/0'/0']xpub6BosfCnifzxcFwrSzQiqu2DBVTshkCXacvNsWGYJVVhhawA7d4R5WSWGFNbi8Aw6ZRc1brxMyWMzG3DSSSSoekkudhUd9yLb6qx39T9nMdj/0/*)#8w4z8fed"
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use crate::signer::RBF_SEQUENCE;
+use crate::transaction::{BtcForkFeeBumpInput, BtcForkTxInput};
+use crate::Result;
+use bitcoin::consensus::deserialize;
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::Transaction;
+
+/// the minimum fee rate (satoshi per vbyte) a replacement pays for its own relay
+const INCREMENTAL_RELAY_FEE_RATE: i64 = 1;
+
+/// Rebuilds the input of a BIP125 replaceable transaction paying the higher `fee`,
+/// spending the same unspents to the same receivers
+pub fn bump_fee(input: &BtcForkFeeBumpInput) -> Result<BtcForkTxInput> {
+    let tx_input = input
+        .tx
+        .as_ref()
+        .ok_or_else(|| format_err!("missing_tx_input"))?;
+    let signed_tx_bytes =
+        hex::decode(&input.signed_tx).map_err(|_| format_err!("invalid_signed_tx"))?;
+    let signed_tx: Transaction =
+        deserialize(&signed_tx_bytes).map_err(|_| format_err!("invalid_signed_tx"))?;
+
+    ensure!(
+        signed_tx.input.len() == tx_input.unspents.len(),
+        "fee_bump_inputs_mismatch"
+    );
+    for (tx_in, unspent) in signed_tx.input.iter().zip(tx_input.unspents.iter()) {
+        let txid = bitcoin::Txid::from_hex(&unspent.tx_hash)
+            .map_err(|_| format_err!("invalid_tx_hash"))?;
+        ensure!(
+            tx_in.previous_output.txid == txid && tx_in.previous_output.vout == unspent.vout as u32,
+            "fee_bump_inputs_mismatch"
+        );
+    }
+    ensure!(
+        signed_tx.input.iter().any(|x| x.sequence <= RBF_SEQUENCE),
+        "tx_not_replaceable"
+    );
+
+    let total_in: i64 = tx_input.unspents.iter().map(|x| x.amount).sum();
+    let total_out: i64 = signed_tx.output.iter().map(|x| x.value as i64).sum();
+    let original_fee = total_in - total_out;
+    ensure!(original_fee >= 0, "fee_bump_inputs_mismatch");
+
+    let vsize = (signed_tx.get_weight() as i64 + 3) / 4;
+    ensure!(
+        input.fee >= original_fee + vsize * INCREMENTAL_RELAY_FEE_RATE,
+        "fee_bump_too_small"
+    );
+
+    let mut bumped = tx_input.clone();
+    bumped.fee = input.fee;
+    bumped.rbf = true;
+    Ok(bumped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::Utxo;
+    use crate::{BtcForkAddress, BtcForkTransaction};
+    use std::str::FromStr;
+    use tcx_constants::coin_info::coin_info_from_param;
+    use tcx_primitive::Secp256k1PrivateKey;
+
+    const RBF_TX: &str = "01000000015884e5db9de218238671572340b207ee85b628074e7e467096c267266baf77a4000000006b483045022100b9b060dcedaa888fdb892dfa376c5c304ca790b2f30cd2a0dcd069f5890c991202203f492ea82019cf710a581066632d9da020a91ffdd56ffaca97328b8f420d04e601210223078d2942df62c45621d209fab84ea9a7a23346201b7727b9b45a29c4e76f5efdffffff0220a10700000000001976a9147821c0a3768aa9d1a37e16cf76002aef5373f1a888ac801a0600000000001976a914073b7eae2823efa349e3b9155b8a735526463a0f88ac87d61200";
+
+    fn rbf_tx_input() -> BtcForkTxInput {
+        BtcForkTxInput {
+            to: "mrU9pEmAx26HcbKVrABvgL7AwA5fjNFoDc".to_string(),
+            amount: 500000,
+            unspents: vec![Utxo {
+                tx_hash: "a477af6b2667c29670467e4e0728b685ee07b240235771862318e29ddbe58458"
+                    .to_string(),
+                vout: 0,
+                amount: 1000000,
+                address: "mszYqVnqKoQx4jcTdJXxwKAissE3Jbrrc1".to_string(),
+                script_pub_key: "76a91488d9931ea73d60eaf7e5671efc0552b912911f2a88ac".to_string(),
+                derived_path: "0/0".to_string(),
+                sequence: 0,
+            }],
+            fee: 100000,
+            change_address_index: 1u32,
+            change_address: "".to_string(),
+            network: "TESTNET".to_string(),
+            seg_wit: "NONE".to_string(),
+            outputs: vec![],
+            op_return: "".to_string(),
+            rbf: true,
+            lock_time: 1234567,
+        }
+    }
+
+    #[test]
+    fn test_bump_fee() {
+        let input = BtcForkFeeBumpInput {
+            tx: Some(rbf_tx_input()),
+            signed_tx: RBF_TX.to_string(),
+            fee: 150000,
+        };
+        let bumped = bump_fee(&input).unwrap();
+        assert_eq!(bumped.fee, 150000);
+        assert_eq!(bumped.lock_time, 1234567);
+
+        let coin_info = coin_info_from_param("LITECOIN", "TESTNET", "NONE", "").unwrap();
+        let prv_key =
+            Secp256k1PrivateKey::from_wif("cSBnVM4xvxarwGQuAfQFwqDg9k5tErHUHzgWsEfD4zdwUasvqRVY")
+                .unwrap();
+        let change_addr = BtcForkAddress::from_str("mgBCJAsvzgT2qNNeXsoECg2uPKrUsZ76up").unwrap();
+        let tran = BtcForkTransaction::new(bumped, coin_info);
+        let signed = tran
+            .sign_transaction(&vec![prv_key], change_addr.script_pubkey())
+            .unwrap();
+        assert_eq!(signed.signature, "01000000015884e5db9de218238671572340b207ee85b628074e7e467096c267266baf77a4000000006b4830450221009cac9c169cfa743c51123ce4c0193f487ffa1f0cab0fbfa10093f16bd67975ad0220464c33f289c6eb14b80243c6c8e0a9a45ad0202c5f0b258bd58bcc0f6da6254601210223078d2942df62c45621d209fab84ea9a7a23346201b7727b9b45a29c4e76f5efdffffff0220a10700000000001976a9147821c0a3768aa9d1a37e16cf76002aef5373f1a888ac30570500000000001976a914073b7eae2823efa349e3b9155b8a735526463a0f88ac87d61200");
+        assert_eq!(
+            signed.tx_hash,
+            "435404d63a916862e7a32eafedbeeb93b3e4784c18ad5501a6b07b711e8799b4"
+        );
+    }
+
+    #[test]
+    fn test_bump_fee_invalid() {
+        // the replacement pays at least 1 sat/vbyte over the 226 vbytes replaced
+        let mut input = BtcForkFeeBumpInput {
+            tx: Some(rbf_tx_input()),
+            signed_tx: RBF_TX.to_string(),
+            fee: 100225,
+        };
+        assert_eq!(
+            format!("{}", bump_fee(&input).err().unwrap()),
+            "fee_bump_too_small"
+        );
+        input.fee = 100226;
+        assert!(bump_fee(&input).is_ok());
+
+        // the sequence of the replaced transaction is final
+        input.signed_tx = RBF_TX.replace("fdffffff02", "ffffffff02");
+        assert_eq!(
+            format!("{}", bump_fee(&input).err().unwrap()),
+            "tx_not_replaceable"
+        );
+
+        input.signed_tx = RBF_TX.to_string();
+        input.tx.as_mut().unwrap().unspents[0].vout = 1;
+        assert_eq!(
+            format!("{}", bump_fee(&input).err().unwrap()),
+            "fee_bump_inputs_mismatch"
+        );
+
+        input.signed_tx = "0100".to_string();
+        assert_eq!(
+            format!("{}", bump_fee(&input).err().unwrap()),
+            "invalid_signed_tx"
+        );
+    }
+}
@@ -1,6 +1,14 @@
 pub mod address;
+pub mod bech32m;
 pub mod bip143_with_forkid;
+pub mod coin_selection;
+pub mod descriptor;
+pub mod fee_bump;
+pub mod message;
+pub mod multisig;
+pub mod psbt;
 pub mod signer;
+pub mod taproot;
 pub mod transaction;
 
 use core::result;
@@ -18,8 +26,23 @@ extern crate tcx_chain;
 
 pub type Result<T> = result::Result<T, failure::Error>;
 
-pub use signer::{BitcoinForkSinger, BtcForkSegWitTransaction, BtcForkTransaction};
-pub use transaction::{BtcForkSignedTxOutput, BtcForkTxInput, Utxo};
+pub use coin_selection::{select_coins, select_unspents, CoinSelection};
+pub use descriptor::{descriptor_checksum, Descriptor, DescriptorKey};
+pub use fee_bump::bump_fee;
+pub use message::verify_message;
+pub use multisig::{multisig_address, sorted_multisig_script};
+pub use psbt::{combine_psbt, finalize_psbt, Psbt};
+pub use signer::{
+    BitcoinForkSinger, BtcForkNativeSegWitTransaction, BtcForkSegWitTransaction,
+    BtcForkTaprootTransaction, BtcForkTransaction,
+};
+pub use transaction::{
+    BtcForkDescriptorOutput, BtcForkExportDescriptorParam, BtcForkFeeBumpInput,
+    BtcForkImportDescriptorOutput, BtcForkImportDescriptorParam, BtcForkMultisigAddressOutput,
+    BtcForkMultisigAddressParam, BtcForkSignedTxOutput, BtcForkTxInput, BtcMessageInput,
+    BtcMessageOutput, BtcVerifyMessageInput, BtcVerifyMessageOutput, CoinSelectionInput,
+    CoinSelectionOutput, PsbtCombineInput, PsbtInput, PsbtOutput, TxOutput, Utxo,
+};
 
 pub use address::{BtcForkAddress, PubKeyScript, WifDisplay};
 pub use signer::ScriptPubKeyComponent;
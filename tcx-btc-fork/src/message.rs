@@ -0,0 +1,509 @@
+//! Bitcoin message signatures: the BIP137 compact signatures of the "Signed Message" digest, and
+//! the BIP322 ones proving the address script can be spent.
+// ref:
+// - https://github.com/bitcoin/bips/blob/master/bip-0137.mediawiki
+// - https://github.com/bitcoin/bips/blob/master/bip-0322.mediawiki
+
+use crate::address::BtcForkAddress;
+use crate::psbt::{p2pkh_script, p2wpkh_script};
+use crate::taproot::{
+    schnorr_sign, schnorr_verify, tagged_hash, taproot_sighash, taproot_sighash_with_type,
+    tweak_private_key, SIGHASH_DEFAULT,
+};
+use crate::transaction::{
+    BtcMessageInput, BtcMessageOutput, BtcVerifyMessageInput, BtcVerifyMessageOutput,
+};
+use crate::Result;
+use bitcoin::blockdata::opcodes;
+use bitcoin::blockdata::script::Builder;
+use bitcoin::consensus::encode::VarInt;
+use bitcoin::consensus::{deserialize, serialize};
+use bitcoin::util::address::Payload;
+use bitcoin::util::bip143::SighashComponents;
+use bitcoin::{OutPoint, Script, Transaction, TxIn, TxOut, Txid};
+use bitcoin_hashes::{hash160, sha256d, Hash};
+use secp256k1::recovery::{RecoverableSignature, RecoveryId};
+use secp256k1::{Message, Secp256k1, Signature};
+use std::str::FromStr;
+use tcx_chain::{Keystore, MessageSigner};
+use tcx_primitive::{PrivateKey, PublicKey, Secp256k1PrivateKey};
+
+pub const BIP137: &str = "BIP137";
+pub const BIP322_SIMPLE: &str = "BIP322_SIMPLE";
+pub const BIP322_FULL: &str = "BIP322_FULL";
+
+const SIGHASH_ALL: u8 = 0x01;
+
+fn message_prefix(chain_type: &str) -> &'static str {
+    match chain_type {
+        "LITECOIN" => "Litecoin Signed Message:\n",
+        "DOGECOIN" => "Dogecoin Signed Message:\n",
+        _ => "Bitcoin Signed Message:\n",
+    }
+}
+
+/// `dsha256(varint ‖ prefix ‖ varint ‖ message)`, the custom forks share the Bitcoin prefix
+pub fn signed_message_hash(chain_type: &str, message: &[u8]) -> [u8; 32] {
+    let prefix = message_prefix(chain_type).as_bytes();
+    let mut data = serialize(&VarInt(prefix.len() as u64));
+    data.extend(prefix);
+    data.extend(serialize(&VarInt(message.len() as u64)));
+    data.extend(message);
+    sha256d::Hash::hash(&data).into_inner()
+}
+
+/// BIP137 signature of a compressed key, the header is 31, 35 or 39 for the P2PKH, P2SH-P2WPKH
+/// and P2WPKH addresses, plus the recovery id
+pub fn sign_bip137(
+    key: &Secp256k1PrivateKey,
+    chain_type: &str,
+    address: &BtcForkAddress,
+    message: &[u8],
+) -> Result<String> {
+    let header = match &address.payload {
+        Payload::PubkeyHash(_) => 31,
+        Payload::ScriptHash(_) => 35,
+        Payload::WitnessProgram { version, .. } if version.to_u8() == 0 => 39,
+        _ => return Err(format_err!("unsupported_signature_type")),
+    };
+    let signature = key.sign_recoverable(&signed_message_hash(chain_type, message))?;
+    Ok(base64::encode(
+        &[&[header + signature[64]], &signature[..64]].concat(),
+    ))
+}
+
+pub fn verify_bip137(
+    chain_type: &str,
+    address: &BtcForkAddress,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<bool> {
+    ensure!(
+        signature.len() == 65 && (27..=42).contains(&signature[0]),
+        "invalid_signature"
+    );
+    let header = signature[0];
+    let recovery_id = RecoveryId::from_i32(i32::from((header - 27) % 4))
+        .map_err(|_| format_err!("invalid_signature"))?;
+    let sig = RecoverableSignature::from_compact(&signature[1..], recovery_id)
+        .map_err(|_| format_err!("invalid_signature"))?;
+    let msg = Message::from_slice(&signed_message_hash(chain_type, message))
+        .map_err(|_| format_err!("invalid_signature"))?;
+    let pub_key = match Secp256k1::verification_only().recover(&msg, &sig) {
+        Ok(pub_key) => pub_key,
+        Err(_) => return Ok(false),
+    };
+
+    let network = &address.network;
+    let expected = match header {
+        27..=30 => BtcForkAddress::p2pkh(&pub_key.serialize_uncompressed(), network)?,
+        // the signers predating the segwit headers leave the type to the address
+        31..=34 => match &address.payload {
+            Payload::PubkeyHash(_) => BtcForkAddress::p2pkh(&pub_key.serialize(), network)?,
+            Payload::ScriptHash(_) => BtcForkAddress::p2shwpkh(&pub_key.serialize(), network)?,
+            _ => BtcForkAddress::p2wpkh(&pub_key.serialize(), network)?,
+        },
+        35..=38 => BtcForkAddress::p2shwpkh(&pub_key.serialize(), network)?,
+        _ => BtcForkAddress::p2wpkh(&pub_key.serialize(), network)?,
+    };
+    Ok(expected.payload == address.payload)
+}
+
+/// The `to_spend` transaction, its only output pays the challenged `script_pubkey`
+fn to_spend(script_pubkey: &Script, message: &[u8]) -> Transaction {
+    let message_hash = tagged_hash("BIP0322-signed-message", message);
+    Transaction {
+        version: 0,
+        lock_time: 0,
+        input: vec![TxIn {
+            previous_output: OutPoint {
+                txid: Txid::from_inner([0; 32]),
+                vout: 0xFFFF_FFFF,
+            },
+            script_sig: Builder::new()
+                .push_int(0)
+                .push_slice(&message_hash)
+                .into_script(),
+            sequence: 0,
+            witness: vec![],
+        }],
+        output: vec![TxOut {
+            value: 0,
+            script_pubkey: script_pubkey.clone(),
+        }],
+    }
+}
+
+/// The unsigned `to_sign` transaction, spending `to_spend` to an `OP_RETURN` output
+fn to_sign(to_spend: &Transaction) -> Transaction {
+    Transaction {
+        version: 0,
+        lock_time: 0,
+        input: vec![TxIn {
+            previous_output: OutPoint {
+                txid: to_spend.txid(),
+                vout: 0,
+            },
+            script_sig: Script::new(),
+            sequence: 0,
+            witness: vec![],
+        }],
+        output: vec![TxOut {
+            value: 0,
+            script_pubkey: Builder::new()
+                .push_opcode(opcodes::all::OP_RETURN)
+                .into_script(),
+        }],
+    }
+}
+
+/// BIP322 signature of the P2SH-P2WPKH, P2WPKH and P2TR addresses, the witness stack of `to_sign`
+/// when `full` is false, which the P2SH-P2WPKH addresses don't support
+pub fn sign_bip322(
+    key: &Secp256k1PrivateKey,
+    address: &BtcForkAddress,
+    message: &[u8],
+    full: bool,
+) -> Result<String> {
+    let to_spend = to_spend(&address.script_pubkey(), message);
+    let mut tx = to_sign(&to_spend);
+    match &address.payload {
+        Payload::WitnessProgram { version, .. } if version.to_u8() == 1 => {
+            let hash = taproot_sighash(&tx, 0, &to_spend.output)?;
+            let signature = schnorr_sign(&tweak_private_key(&key.to_bytes())?, &hash, &[0u8; 32])?;
+            tx.input[0].witness = vec![signature];
+        }
+        Payload::WitnessProgram { version, .. } if version.to_u8() == 0 => {
+            tx.input[0].witness = p2wpkh_witness(&tx, key)?;
+        }
+        Payload::ScriptHash(_) if full => {
+            let pub_key_hash = hash160::Hash::hash(&key.public_key().to_bytes()).into_inner();
+            tx.input[0].script_sig = Builder::new()
+                .push_slice(p2wpkh_script(&pub_key_hash).as_bytes())
+                .into_script();
+            tx.input[0].witness = p2wpkh_witness(&tx, key)?;
+        }
+        _ => return Err(format_err!("unsupported_signature_type")),
+    }
+
+    if full {
+        Ok(base64::encode(&serialize(&tx)))
+    } else {
+        Ok(base64::encode(&serialize(&tx.input[0].witness)))
+    }
+}
+
+fn p2wpkh_witness(tx: &Transaction, key: &Secp256k1PrivateKey) -> Result<Vec<Vec<u8>>> {
+    let pub_key = key.public_key().to_bytes();
+    let pub_key_hash = hash160::Hash::hash(&pub_key).into_inner();
+    let hash =
+        SighashComponents::new(tx).sighash_all(&tx.input[0], &p2pkh_script(&pub_key_hash), 0);
+    let signature = [key.sign(&hash.into_inner())?, vec![SIGHASH_ALL]].concat();
+    Ok(vec![signature, pub_key])
+}
+
+pub fn verify_bip322(address: &BtcForkAddress, message: &[u8], signature: &[u8]) -> Result<bool> {
+    let to_spend = to_spend(&address.script_pubkey(), message);
+    let expected = to_sign(&to_spend);
+    let tx = match deserialize::<Transaction>(signature) {
+        Ok(tx) => {
+            if tx.version != 0
+                || tx.lock_time != 0
+                || tx.input.len() != 1
+                || tx.input[0].previous_output != expected.input[0].previous_output
+                || tx.input[0].sequence != 0
+                || tx.output != expected.output
+            {
+                return Ok(false);
+            }
+            tx
+        }
+        Err(_) => {
+            let witness: Vec<Vec<u8>> =
+                deserialize(signature).map_err(|_| format_err!("invalid_signature"))?;
+            let mut tx = expected;
+            tx.input[0].witness = witness;
+            tx
+        }
+    };
+
+    let tx_in = &tx.input[0];
+    match &address.payload {
+        Payload::WitnessProgram { version, program } if version.to_u8() == 1 => {
+            if !tx_in.script_sig.is_empty() || tx_in.witness.len() != 1 {
+                return Ok(false);
+            }
+            let signature = &tx_in.witness[0];
+            let sighash_type = match signature.len() {
+                64 => SIGHASH_DEFAULT,
+                65 if signature[64] == SIGHASH_ALL => SIGHASH_ALL,
+                _ => return Ok(false),
+            };
+            let hash = taproot_sighash_with_type(&tx, 0, &to_spend.output, sighash_type)?;
+            schnorr_verify(program, &hash, &signature[..64])
+        }
+        Payload::WitnessProgram { version, program }
+            if version.to_u8() == 0 && program.len() == 20 =>
+        {
+            Ok(tx_in.script_sig.is_empty() && verify_p2wpkh(&tx, program)?)
+        }
+        Payload::ScriptHash(script_hash) => {
+            if tx_in.witness.len() != 2 {
+                return Ok(false);
+            }
+            let pub_key_hash = hash160::Hash::hash(&tx_in.witness[1]).into_inner();
+            let redeem_script = p2wpkh_script(&pub_key_hash);
+            let script_sig = Builder::new()
+                .push_slice(redeem_script.as_bytes())
+                .into_script();
+            Ok(tx_in.script_sig == script_sig
+                && hash160::Hash::hash(redeem_script.as_bytes())[..] == script_hash[..]
+                && verify_p2wpkh(&tx, &pub_key_hash)?)
+        }
+        _ => Err(format_err!("unsupported_signature_type")),
+    }
+}
+
+fn verify_p2wpkh(tx: &Transaction, pub_key_hash: &[u8]) -> Result<bool> {
+    let witness = &tx.input[0].witness;
+    if witness.len() != 2 || hash160::Hash::hash(&witness[1])[..] != *pub_key_hash {
+        return Ok(false);
+    }
+    let (signature, sighash_type) = match witness[0].split_last() {
+        Some((sighash_type, signature)) => (signature, *sighash_type),
+        None => return Ok(false),
+    };
+    let (mut signature, pub_key) = match (
+        Signature::from_der(signature),
+        secp256k1::PublicKey::from_slice(&witness[1]),
+    ) {
+        (Ok(signature), Ok(pub_key)) if sighash_type == SIGHASH_ALL => (signature, pub_key),
+        _ => return Ok(false),
+    };
+    signature.normalize_s();
+
+    let hash = SighashComponents::new(tx).sighash_all(&tx.input[0], &p2pkh_script(pub_key_hash), 0);
+    let msg =
+        Message::from_slice(&hash.into_inner()).map_err(|_| format_err!("invalid_signature"))?;
+    Ok(Secp256k1::verification_only()
+        .verify(&msg, &signature, &pub_key)
+        .is_ok())
+}
+
+pub fn verify_message(input: &BtcVerifyMessageInput) -> Result<BtcVerifyMessageOutput> {
+    let address =
+        BtcForkAddress::from_str(&input.address).map_err(|_| format_err!("address_invalid"))?;
+    let signature =
+        base64::decode(&input.signature).map_err(|_| format_err!("invalid_signature"))?;
+    let message = input.message.as_bytes();
+
+    // the BIP322 signatures start with the witness item count or the transaction version
+    let is_valid = if signature.len() == 65 && (27..=42).contains(&signature[0]) {
+        verify_bip137(&input.chain_type, &address, message, &signature)?
+    } else {
+        verify_bip322(&address, message, &signature)?
+    };
+    Ok(BtcVerifyMessageOutput { is_valid })
+}
+
+impl MessageSigner<BtcMessageInput, BtcMessageOutput> for Keystore {
+    fn sign_message(
+        &mut self,
+        symbol: &str,
+        address: &str,
+        message: &BtcMessageInput,
+    ) -> Result<BtcMessageOutput> {
+        let btc_address =
+            BtcForkAddress::from_str(address).map_err(|_| format_err!("address_invalid"))?;
+        let key = self
+            .find_private_key(symbol, address)?
+            .as_secp256k1()?
+            .clone();
+        let data = message.message.as_bytes();
+
+        let signature_type = match (message.signature_type.as_str(), &btc_address.payload) {
+            ("", Payload::PubkeyHash(_)) | ("", Payload::ScriptHash(_)) => BIP137,
+            ("", _) => BIP322_SIMPLE,
+            (signature_type, _) => signature_type,
+        };
+        let signature = match signature_type {
+            BIP137 => sign_bip137(&key, symbol, &btc_address, data)?,
+            BIP322_SIMPLE => sign_bip322(&key, &btc_address, data, false)?,
+            BIP322_FULL => sign_bip322(&key, &btc_address, data, true)?,
+            _ => return Err(format_err!("unsupported_signature_type")),
+        };
+        Ok(BtcMessageOutput { signature })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verify(chain_type: &str, address: &str, message: &str, signature: &str) -> bool {
+        verify_message(&BtcVerifyMessageInput {
+            chain_type: chain_type.to_string(),
+            address: address.to_string(),
+            message: message.to_string(),
+            signature: signature.to_string(),
+        })
+        .unwrap()
+        .is_valid
+    }
+
+    #[test]
+    fn test_sign_bip137() {
+        let key =
+            Secp256k1PrivateKey::from_wif("L4rK1yDtCWekvXuE6oXD9jCYfFNV2cWRpVuPLBcCU2z8TrisoyY1")
+                .unwrap();
+        let message = "This is an example of a signed message.";
+        let vectors = vec![
+            (
+                "BITCOIN",
+                "1F3sAm6ZtwLAUnj7d38pGFxtP3RVEvtsbV",
+                "H9L5yLFjti0QTHhPyFrZCT1V/MMnBtXKmoiKDZ78NDBjERki6ZTQZdSMCtkgoNmp17By9ItJr8o7ChX0XxY91nk=",
+            ),
+            (
+                "BITCOIN",
+                "3DnW8JGpPViEZdpqat8qky1zc26EKbXnmM",
+                "I9L5yLFjti0QTHhPyFrZCT1V/MMnBtXKmoiKDZ78NDBjERki6ZTQZdSMCtkgoNmp17By9ItJr8o7ChX0XxY91nk=",
+            ),
+            (
+                "BITCOIN",
+                "bc1qngw83fg8dz0k749cg7k3emc7v98wy0c74dlrkd",
+                "J9L5yLFjti0QTHhPyFrZCT1V/MMnBtXKmoiKDZ78NDBjERki6ZTQZdSMCtkgoNmp17By9ItJr8o7ChX0XxY91nk=",
+            ),
+            (
+                "LITECOIN",
+                "LZGpRyQPybaDjbRGoB87YH2ebFnmKYmRui",
+                "H21RXhfhR3uOi9zqIi3dTVopWSmAzC9fBuEo4S+ZHkfsHPkDnLUM2t7BBTNgVQhyofQXU7zQgZdO0GV3pRhwNfY=",
+            ),
+            (
+                "DOGECOIN",
+                "DKBxi23DCMET1nuiMd8Np28VGB9nbiXyhd",
+                "H4ysUSkAU1pRCJxq76P2e/D/jq3tq0Jls4pGRuGLPojrTToWLnzLIQryeKR4IkxckGTLWmBXZuhuIg28OBnxmtk=",
+            ),
+        ];
+        for (chain_type, address, expected) in vectors {
+            let btc_address = BtcForkAddress::from_str(address).unwrap();
+            let signature =
+                sign_bip137(&key, chain_type, &btc_address, message.as_bytes()).unwrap();
+            assert_eq!(signature, expected);
+            assert!(verify(chain_type, address, message, &signature));
+            assert!(!verify(chain_type, address, "another message", &signature));
+        }
+
+        // the P2PKH header with a segwit address
+        assert!(verify(
+            "BITCOIN",
+            "bc1qngw83fg8dz0k749cg7k3emc7v98wy0c74dlrkd",
+            message,
+            "H9L5yLFjti0QTHhPyFrZCT1V/MMnBtXKmoiKDZ78NDBjERki6ZTQZdSMCtkgoNmp17By9ItJr8o7ChX0XxY91nk="
+        ));
+        // the P2WPKH header with a P2PKH address
+        assert!(!verify(
+            "BITCOIN",
+            "1F3sAm6ZtwLAUnj7d38pGFxtP3RVEvtsbV",
+            message,
+            "J9L5yLFjti0QTHhPyFrZCT1V/MMnBtXKmoiKDZ78NDBjERki6ZTQZdSMCtkgoNmp17By9ItJr8o7ChX0XxY91nk="
+        ));
+
+        let taproot = BtcForkAddress::from_str(
+            "bc1ppv609nr0vr25u07u95waq5lucwfm6tde4nydujnu8npg4q75mr5sxq8lt3",
+        )
+        .unwrap();
+        let ret = sign_bip137(&key, "BITCOIN", &taproot, message.as_bytes());
+        assert_eq!(
+            format!("{}", ret.err().unwrap()),
+            "unsupported_signature_type"
+        );
+    }
+
+    #[test]
+    fn test_verify_bip322() {
+        // bip-0322 test vectors
+        let p2wpkh = "bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l";
+        assert!(verify(
+            "BITCOIN",
+            p2wpkh,
+            "",
+            "AkcwRAIgM2gBAQqvZX15ZiysmKmQpDrG83avLIT492QBzLnQIxYCIBaTpOaD20qRlEylyxFSeEA2ba9YOixpX8z46TSDtS40ASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI="
+        ));
+        assert!(verify(
+            "BITCOIN",
+            p2wpkh,
+            "Hello World",
+            "AkcwRAIgZRfIY3p7/DoVTty6YZbWS71bc5Vct9p9Fia83eRmw2QCICK/ENGfwLtptFluMGs2KsqoNSk89pO7F29zJLUx9a/sASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI="
+        ));
+        assert!(!verify(
+            "BITCOIN",
+            p2wpkh,
+            "",
+            "AkcwRAIgZRfIY3p7/DoVTty6YZbWS71bc5Vct9p9Fia83eRmw2QCICK/ENGfwLtptFluMGs2KsqoNSk89pO7F29zJLUx9a/sASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI="
+        ));
+        // signed with SIGHASH_ALL
+        assert!(verify(
+            "BITCOIN",
+            "bc1ppv609nr0vr25u07u95waq5lucwfm6tde4nydujnu8npg4q75mr5sxq8lt3",
+            "Hello World",
+            "AUHd69PrJQEv+oKTfZ8l+WROBHuy9HKrbFCJu7U1iK2iiEy1vMU5EfMtjc+VSHM7aU0SDbak5IUZRVno2P5mjSafAQ=="
+        ));
+
+        let ret = verify_message(&BtcVerifyMessageInput {
+            chain_type: "BITCOIN".to_string(),
+            address: p2wpkh.to_string(),
+            message: "".to_string(),
+            signature: "invalid".to_string(),
+        });
+        assert_eq!(format!("{}", ret.err().unwrap()), "invalid_signature");
+    }
+
+    #[test]
+    fn test_sign_bip322() {
+        let key =
+            Secp256k1PrivateKey::from_wif("L3VFeEujGtevx9w18HD1fhRbCH67Az2dpCymeRE1SoPK6XQtaN2k")
+                .unwrap();
+        let message = "Hello World";
+        let vectors = vec![
+            (
+                "bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l",
+                false,
+                "AkgwRQIhAOzyynlqt93lOKJr+wmmxIens//zPzl9tqIOua93wO6MAiBi5n5EyAcPScOjf1lAqIUIQtr3zKNeavYabHyR8eGhowEhAsfxIAMZZEKUPYWI4BruhAQjzFT8FSFSajuFwrDL1Yhy",
+            ),
+            (
+                "bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l",
+                true,
+                "AAAAAAABASs1A9aiYU3q8XFsIzJcU+BRS0r8mBAcdxdSrUBnGZ23AAAAAAAAAAAAAQAAAAAAAAAAAWoCSDBFAiEA7PLKeWq33eU4omv7CabEh6ez//M/OX22og65r3fA7owCIGLmfkTIBw9Jw6N/WUCohQhC2vfMo15q9hpsfJHx4aGjASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHIAAAAA",
+            ),
+            (
+                "37qyp7jQAzqb2rCBpMvVtLDuuzKAUCVnJb",
+                true,
+                "AAAAAAABAcbRsnNWkk3JpUbXQM8ONPeKRfpyUCEoGI9hMjBvVKIjAAAAABcWABQrBdVk5qejPAh/FuD3MNFEASN5nQAAAAABAAAAAAAAAAABagJHMEQCIEX6thn7/YLhXFwH9r0BCUh1/BT+Troh7NQ0itSNMJP9AiBj86pukDT0wybpItePDkNg2ybV8q6+9bOMWD8KIEFLqQEhAsfxIAMZZEKUPYWI4BruhAQjzFT8FSFSajuFwrDL1YhyAAAAAA==",
+            ),
+            (
+                "bc1ppv609nr0vr25u07u95waq5lucwfm6tde4nydujnu8npg4q75mr5sxq8lt3",
+                false,
+                "AUDjpClYFHngjnqQ3F0/3dyrLsOHFNEm4rKaaAc9GsfhC5+DngPJmXTeAmz+yfsVRa61PD2k9/CEQnLDvNUn9Qug",
+            ),
+        ];
+        for (address, full, expected) in vectors {
+            let btc_address = BtcForkAddress::from_str(address).unwrap();
+            let signature = sign_bip322(&key, &btc_address, message.as_bytes(), full).unwrap();
+            assert_eq!(signature, expected);
+            assert!(verify("BITCOIN", address, message, &signature));
+            assert!(!verify("BITCOIN", address, "another message", &signature));
+        }
+
+        for (address, full) in &[
+            ("37qyp7jQAzqb2rCBpMvVtLDuuzKAUCVnJb", false),
+            ("14vV3aCHBeStb5bkenkNHbe2YAFinYdXgc", true),
+        ] {
+            let btc_address = BtcForkAddress::from_str(address).unwrap();
+            let ret = sign_bip322(&key, &btc_address, message.as_bytes(), *full);
+            assert_eq!(
+                format!("{}", ret.err().unwrap()),
+                "unsupported_signature_type"
+            );
+        }
+    }
+}
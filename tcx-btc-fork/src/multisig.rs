@@ -0,0 +1,274 @@
+//! BIP67 sorted m-of-n multisig scripts and their P2SH, P2SH-P2WSH and P2WSH addresses.
+//! The inputs spending them are signed and finalized through the psbt module.
+// ref:
+// - https://github.com/bitcoin/bips/blob/master/bip-0067.mediawiki
+// - https://github.com/bitcoin/bips/blob/master/bip-0141.mediawiki#p2wsh
+
+use crate::address::BtcForkAddress;
+use crate::transaction::BtcForkMultisigAddressOutput;
+use crate::Result;
+
+use bitcoin::blockdata::opcodes::all::OP_CHECKMULTISIG;
+use bitcoin::blockdata::script::Builder;
+use bitcoin::hash_types::ScriptHash;
+use bitcoin::util::address::Payload;
+use bitcoin::Script;
+use bitcoin_hashes::hex::ToHex;
+use bitcoin_hashes::{sha256, Hash};
+use tcx_constants::btc_fork_network::BtcForkNetwork;
+use tcx_primitive::{Bip32DeterministicPublicKey, Derive, DeterministicPublicKey, PublicKey};
+
+pub const P2SH: &str = "P2SH";
+pub const P2SH_P2WSH: &str = "P2SH_P2WSH";
+pub const P2WSH: &str = "P2WSH";
+
+/// The CHECKMULTISIG keys are pushed with OP_1..OP_16, P2SH redeem scripts are limited to 520
+/// bytes, i.e. 15 compressed keys
+const MAX_MULTISIG_KEYS: usize = 15;
+
+/// The m-of-n CHECKMULTISIG script of the compressed public keys sorted lexicographically
+pub fn sorted_multisig_script(threshold: usize, pub_keys: &[Vec<u8>]) -> Result<Script> {
+    ensure!(
+        !pub_keys.is_empty() && pub_keys.len() <= MAX_MULTISIG_KEYS,
+        "invalid_multisig_public_keys"
+    );
+    ensure!(
+        (1..=pub_keys.len()).contains(&threshold),
+        "invalid_multisig_threshold"
+    );
+
+    let mut sorted = pub_keys.to_vec();
+    sorted.sort();
+    sorted.dedup();
+    ensure!(
+        sorted.len() == pub_keys.len() && sorted.iter().all(|x| x.len() == 33),
+        "invalid_multisig_public_keys"
+    );
+
+    let mut builder = Builder::new().push_int(threshold as i64);
+    for pub_key in &sorted {
+        builder = builder.push_slice(pub_key);
+    }
+    Ok(builder
+        .push_int(sorted.len() as i64)
+        .push_opcode(OP_CHECKMULTISIG)
+        .into_script())
+}
+
+/// The threshold and the public keys, in script order, of a m-of-n CHECKMULTISIG script
+pub(crate) fn parse_multisig_script(script: &[u8]) -> Option<(usize, Vec<Vec<u8>>)> {
+    let op_n = |x: u8| match x {
+        0x51..=0x60 => Some(usize::from(x - 0x50)),
+        _ => None,
+    };
+    let len = script.len();
+    if len < 3 || script[len - 1] != OP_CHECKMULTISIG.into_u8() {
+        return None;
+    }
+    let threshold = op_n(script[0])?;
+    let count = op_n(script[len - 2])?;
+
+    let mut pub_keys = vec![];
+    let mut pos = 1;
+    while pos < len - 2 {
+        let key_len = usize::from(script[pos]);
+        if (key_len != 33 && key_len != 65) || pos + 1 + key_len > len - 2 {
+            return None;
+        }
+        pub_keys.push(script[pos + 1..pos + 1 + key_len].to_vec());
+        pos += 1 + key_len;
+    }
+    if pub_keys.len() == count && threshold <= count {
+        Some((threshold, pub_keys))
+    } else {
+        None
+    }
+}
+
+pub(crate) fn p2wsh_script(witness_script: &[u8]) -> Script {
+    Script::from([&[0x00, 0x20], &sha256::Hash::hash(witness_script)[..]].concat())
+}
+
+/// The address of the multisig script of the public keys derived from the cosigner extended
+/// public keys at the relative `path`, the network of a P2WSH one must have a bech32 hrp
+pub fn multisig_address(
+    network: &BtcForkNetwork,
+    script_type: &str,
+    threshold: usize,
+    xpubs: &[Bip32DeterministicPublicKey],
+    path: &str,
+) -> Result<BtcForkMultisigAddressOutput> {
+    let pub_keys = xpubs
+        .iter()
+        .map(|x| Ok(x.derive(path)?.public_key().to_bytes()))
+        .collect::<Result<Vec<Vec<u8>>>>()?;
+    sorted_multisig_address(network, script_type, threshold, pub_keys)
+}
+
+/// The address of the multisig script of the cosigner public keys
+pub(crate) fn sorted_multisig_address(
+    network: &BtcForkNetwork,
+    script_type: &str,
+    threshold: usize,
+    mut pub_keys: Vec<Vec<u8>>,
+) -> Result<BtcForkMultisigAddressOutput> {
+    let script = sorted_multisig_script(threshold, &pub_keys)?;
+    pub_keys.sort();
+
+    let (payload, redeem_script, witness_script) = match script_type.to_uppercase().as_str() {
+        P2SH => (
+            Payload::ScriptHash(ScriptHash::hash(script.as_bytes())),
+            script.to_hex(),
+            "".to_string(),
+        ),
+        P2SH_P2WSH => {
+            let redeem_script = p2wsh_script(script.as_bytes());
+            (
+                Payload::ScriptHash(ScriptHash::hash(redeem_script.as_bytes())),
+                redeem_script.to_hex(),
+                script.to_hex(),
+            )
+        }
+        P2WSH => {
+            ensure!(!network.hrp.is_empty(), "unsupported_chain");
+            (
+                Payload::WitnessProgram {
+                    version: bech32::u5::try_from_u8(0)?,
+                    program: sha256::Hash::hash(script.as_bytes()).to_vec(),
+                },
+                "".to_string(),
+                script.to_hex(),
+            )
+        }
+        _ => return Err(format_err!("unsupported_script_type")),
+    };
+
+    let address = BtcForkAddress {
+        network: network.clone(),
+        payload,
+    };
+    Ok(BtcForkMultisigAddressOutput {
+        address: address.to_string(),
+        public_keys: pub_keys.iter().map(|x| x.to_hex()).collect(),
+        redeem_script,
+        witness_script,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{multisig_address, parse_multisig_script, sorted_multisig_script};
+    use bitcoin_hashes::hex::ToHex;
+    use tcx_constants::btc_fork_network::network_from_param;
+    use tcx_primitive::{Bip32DeterministicPublicKey, Ss58Codec};
+
+    const MULTISIG_SCRIPT: &str = "522102c5e54fb0559fccdc206f819995dd361cef9018394c329e4a190747938d8f126021030c3005c53914c467a5a0af727f6ce62ec50f99c76c769dabd00c1f665cac18a52103d474fb6b20cc07d4fcdc2f665f4b89bf9ae5ade1a8dfc3db54e9ef942053de6b53ae";
+
+    #[test]
+    fn test_sorted_multisig_script() {
+        // BIP67 test vector 1
+        let pub_keys = vec![
+            hex::decode("02ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f8")
+                .unwrap(),
+            hex::decode("02fe6f0a5a297eb38c391581c4413e084773ea23954d93f7753db7dc0adc188b2f")
+                .unwrap(),
+        ];
+        let script = sorted_multisig_script(2, &pub_keys).unwrap();
+        assert_eq!(script.to_hex(), "522102fe6f0a5a297eb38c391581c4413e084773ea23954d93f7753db7dc0adc188b2f2102ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f852ae");
+        assert_eq!(
+            parse_multisig_script(script.as_bytes()),
+            Some((2, vec![pub_keys[1].clone(), pub_keys[0].clone()]))
+        );
+        assert_eq!(parse_multisig_script(&script.as_bytes()[1..]), None);
+        assert_eq!(
+            parse_multisig_script(
+                &hex::decode("76a914000102030405060708090a0b0c0d0e0f1011121388ac").unwrap()
+            ),
+            None
+        );
+
+        assert_eq!(
+            format!("{}", sorted_multisig_script(0, &pub_keys).unwrap_err()),
+            "invalid_multisig_threshold"
+        );
+        assert_eq!(
+            format!("{}", sorted_multisig_script(3, &pub_keys).unwrap_err()),
+            "invalid_multisig_threshold"
+        );
+        assert_eq!(
+            format!("{}", sorted_multisig_script(1, &[]).unwrap_err()),
+            "invalid_multisig_public_keys"
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                sorted_multisig_script(1, &[pub_keys[0].clone(), pub_keys[0].clone()]).unwrap_err()
+            ),
+            "invalid_multisig_public_keys"
+        );
+    }
+
+    #[test]
+    fn test_multisig_address() {
+        // the m/48'/0'/0' accounts of three cosigners
+        let xpubs = vec![
+            "xpub6CJWFLqLfYKRssptbhvkwbqcQR2eWKJwLNbyxbMagAT75TQDcLxJ7xBkeokJhkTHC7Msh5SL5RRh7C9ekqKLh9xMBQ8dhfaH3ktX8o9MWQQ",
+            "xpub6CKZtUaK1YHpQbg6CLaGRmsMKLQB1iKzsvmxtyHD6X7gzLqCB2VNZYd1XCxrccQnE8hhDxtYbR1Sakkvisy2J4CcTxWeeGjmkasCoNS9vZm",
+            "xpub6CETL9tkmWBQkYmxxxGAEzVZNHCZLx24pj58FYqe41qhXgyAcabP9iyXaVCJXcZWcVbzttVPdoJpJvYNfBnQeFcunvuxcsKKAwxMw6S5S7s",
+        ]
+        .iter()
+        .map(|x| Bip32DeterministicPublicKey::from_ss58check_with_version(x).unwrap().0)
+        .collect::<Vec<Bip32DeterministicPublicKey>>();
+        let network = network_from_param("BITCOIN", "MAINNET", "NONE").unwrap();
+        let segwit_network = network_from_param("BITCOIN", "MAINNET", "SEGWIT").unwrap();
+
+        let output = multisig_address(&network, "P2SH", 2, &xpubs, "0/0").unwrap();
+        assert_eq!(output.address, "3AnQQiWgzLALrDZ8bSHQdGrqUgnS5QKMBo");
+        assert_eq!(output.redeem_script, MULTISIG_SCRIPT);
+        assert_eq!(output.witness_script, "");
+        assert_eq!(
+            output.public_keys,
+            vec![
+                "02c5e54fb0559fccdc206f819995dd361cef9018394c329e4a190747938d8f1260",
+                "030c3005c53914c467a5a0af727f6ce62ec50f99c76c769dabd00c1f665cac18a5",
+                "03d474fb6b20cc07d4fcdc2f665f4b89bf9ae5ade1a8dfc3db54e9ef942053de6b",
+            ]
+        );
+
+        let output = multisig_address(&network, "P2SH_P2WSH", 2, &xpubs, "0/0").unwrap();
+        assert_eq!(output.address, "3LykZ3Cx5PpTJVMayrvZMuWLnGU7JtugU2");
+        assert_eq!(
+            output.redeem_script,
+            "002030c6f48b994c14b9f90997579244661e148af337db78299dbbeb161883418f53"
+        );
+        assert_eq!(output.witness_script, MULTISIG_SCRIPT);
+
+        let output = multisig_address(&segwit_network, "P2WSH", 2, &xpubs, "0/0").unwrap();
+        assert_eq!(
+            output.address,
+            "bc1qxrr0fzuefs2tn7gfjatey3rxrc2g4uehmduzn8dmavtp3q6p3afsa02c5u"
+        );
+        assert_eq!(output.redeem_script, "");
+        assert_eq!(output.witness_script, MULTISIG_SCRIPT);
+
+        let ltc_network = network_from_param("LITECOIN", "MAINNET", "NONE").unwrap();
+        let output = multisig_address(&ltc_network, "P2SH", 2, &xpubs, "0/0").unwrap();
+        assert_eq!(output.address, "MGzYibvewT1meiq2hKGkSv7EoPNt3TTXLq");
+
+        assert_eq!(
+            format!(
+                "{}",
+                multisig_address(&network, "P2WSH", 2, &xpubs, "0/0").unwrap_err()
+            ),
+            "unsupported_chain"
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                multisig_address(&network, "P2TR", 2, &xpubs, "0/0").unwrap_err()
+            ),
+            "unsupported_script_type"
+        );
+        assert!(multisig_address(&network, "P2SH", 2, &xpubs, "0'/0").is_err());
+    }
+}
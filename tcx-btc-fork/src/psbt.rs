@@ -0,0 +1,972 @@
+//! BIP174 (v0) and BIP370 (v2) partially signed bitcoin transactions.
+//! The maps are kept as raw key-value pairs so the fields this crate does not use, e.g. the
+//! proprietary ones, survive a sign round trip.
+// ref:
+// - https://github.com/bitcoin/bips/blob/master/bip-0174.mediawiki
+// - https://github.com/bitcoin/bips/blob/master/bip-0370.mediawiki
+// - https://github.com/bitcoin/bips/blob/master/bip-0371.mediawiki
+
+use crate::multisig::{p2wsh_script, parse_multisig_script};
+use crate::taproot::{schnorr_sign, taproot_sighash, tweak_private_key, tweak_public_key};
+use crate::transaction::{BtcForkSignedTxOutput, PsbtCombineInput, PsbtInput, PsbtOutput};
+use crate::Result;
+
+use bitcoin::blockdata::script::Builder;
+use bitcoin::consensus::{deserialize, serialize};
+use bitcoin::util::bip143::SighashComponents;
+use bitcoin::{OutPoint, Script, Transaction, TxIn, TxOut};
+use bitcoin_hashes::hash160;
+use bitcoin_hashes::hex::ToHex;
+use bitcoin_hashes::Hash;
+use std::collections::BTreeMap;
+use tcx_chain::{Account, Keystore, TransactionSigner};
+use tcx_primitive::{get_account_path, PrivateKey, PublicKey, Secp256k1PrivateKey};
+
+type KeyValueMap = BTreeMap<Vec<u8>, Vec<u8>>;
+
+const PSBT_MAGIC: &[u8] = b"psbt\xff";
+const SIGHASH_ALL: u8 = 0x01;
+
+const PSBT_GLOBAL_UNSIGNED_TX: u8 = 0x00;
+const PSBT_GLOBAL_TX_VERSION: u8 = 0x02;
+const PSBT_GLOBAL_FALLBACK_LOCKTIME: u8 = 0x03;
+const PSBT_GLOBAL_INPUT_COUNT: u8 = 0x04;
+const PSBT_GLOBAL_OUTPUT_COUNT: u8 = 0x05;
+const PSBT_GLOBAL_VERSION: u8 = 0xfb;
+
+const PSBT_IN_NON_WITNESS_UTXO: u8 = 0x00;
+const PSBT_IN_WITNESS_UTXO: u8 = 0x01;
+const PSBT_IN_PARTIAL_SIG: u8 = 0x02;
+const PSBT_IN_SIGHASH_TYPE: u8 = 0x03;
+const PSBT_IN_REDEEM_SCRIPT: u8 = 0x04;
+const PSBT_IN_WITNESS_SCRIPT: u8 = 0x05;
+const PSBT_IN_BIP32_DERIVATION: u8 = 0x06;
+const PSBT_IN_FINAL_SCRIPTSIG: u8 = 0x07;
+const PSBT_IN_FINAL_SCRIPTWITNESS: u8 = 0x08;
+const PSBT_IN_PREVIOUS_TXID: u8 = 0x0e;
+const PSBT_IN_OUTPUT_INDEX: u8 = 0x0f;
+const PSBT_IN_SEQUENCE: u8 = 0x10;
+const PSBT_IN_REQUIRED_TIME_LOCKTIME: u8 = 0x11;
+const PSBT_IN_REQUIRED_HEIGHT_LOCKTIME: u8 = 0x12;
+const PSBT_IN_TAP_KEY_SIG: u8 = 0x13;
+const PSBT_IN_TAP_BIP32_DERIVATION: u8 = 0x16;
+const PSBT_IN_TAP_MERKLE_ROOT: u8 = 0x18;
+
+/// The input fields a finalizer removes, everything but the utxos, the BIP370 outpoint and
+/// locktime fields and the unknown ones
+const PSBT_IN_SIGNING_DATA: &[u8] = &[
+    0x02, 0x03, 0x04, 0x05, 0x06, 0x0a, 0x0b, 0x0c, 0x0d, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18,
+];
+
+const PSBT_OUT_AMOUNT: u8 = 0x03;
+const PSBT_OUT_SCRIPT: u8 = 0x04;
+
+fn read_compact_size(data: &[u8], pos: &mut usize) -> Result<u64> {
+    let prefix = *data.get(*pos).ok_or_else(|| format_err!("invalid_psbt"))?;
+    *pos += 1;
+    let len = match prefix {
+        0xfd => 2,
+        0xfe => 4,
+        0xff => 8,
+        _ => return Ok(u64::from(prefix)),
+    };
+    let bytes = read_bytes(data, pos, len)?;
+    Ok(bytes
+        .iter()
+        .rev()
+        .fold(0u64, |acc, x| (acc << 8) | u64::from(*x)))
+}
+
+fn read_bytes<'a>(data: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8]> {
+    ensure!(data.len() - *pos >= len, "invalid_psbt");
+    let bytes = &data[*pos..*pos + len];
+    *pos += len;
+    Ok(bytes)
+}
+
+fn write_compact_size(out: &mut Vec<u8>, value: usize) {
+    match value {
+        0..=0xfc => out.push(value as u8),
+        0xfd..=0xffff => {
+            out.push(0xfd);
+            out.extend(&(value as u16).to_le_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(0xfe);
+            out.extend(&(value as u32).to_le_bytes());
+        }
+        _ => {
+            out.push(0xff);
+            out.extend(&(value as u64).to_le_bytes());
+        }
+    }
+}
+
+fn read_map(data: &[u8], pos: &mut usize) -> Result<KeyValueMap> {
+    let mut map = KeyValueMap::new();
+    loop {
+        let key_len = read_compact_size(data, pos)? as usize;
+        if key_len == 0 {
+            return Ok(map);
+        }
+        let key = read_bytes(data, pos, key_len)?.to_vec();
+        let value_len = read_compact_size(data, pos)? as usize;
+        let value = read_bytes(data, pos, value_len)?.to_vec();
+        if map.insert(key, value).is_some() {
+            return Err(format_err!("invalid_psbt_duplicated_key"));
+        }
+    }
+}
+
+fn write_map(out: &mut Vec<u8>, map: &KeyValueMap) {
+    for (key, value) in map {
+        write_compact_size(out, key.len());
+        out.extend(key);
+        write_compact_size(out, value.len());
+        out.extend(value);
+    }
+    out.push(0x00);
+}
+
+fn le_u32(value: &[u8]) -> Result<u32> {
+    ensure!(value.len() == 4, "invalid_psbt");
+    let mut bytes = [0; 4];
+    bytes.copy_from_slice(value);
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn le_u64(value: &[u8]) -> Result<u64> {
+    ensure!(value.len() == 8, "invalid_psbt");
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(value);
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn path_to_string(path: &[u32]) -> String {
+    let mut ret = "m".to_string();
+    for index in path {
+        if *index >= 0x8000_0000 {
+            ret.push_str(&format!("/{}'", index - 0x8000_0000));
+        } else {
+            ret.push_str(&format!("/{}", index));
+        }
+    }
+    ret
+}
+
+pub(crate) fn p2pkh_script(pub_key_hash: &[u8]) -> Script {
+    Script::from([&[0x76, 0xa9, 0x14], pub_key_hash, &[0x88, 0xac]].concat())
+}
+
+pub(crate) fn p2wpkh_script(pub_key_hash: &[u8]) -> Script {
+    Script::from([&[0x00, 0x14], pub_key_hash].concat())
+}
+
+fn is_p2sh_of(script: &Script, redeem_script: &[u8]) -> bool {
+    script.is_p2sh() && script.as_bytes()[2..22] == hash160::Hash::hash(redeem_script)[..]
+}
+
+fn taproot_output_key(script: &Script) -> Option<&[u8]> {
+    let bytes = script.as_bytes();
+    if bytes.len() == 34 && bytes[0] == 0x51 && bytes[1] == 0x20 {
+        Some(&bytes[2..])
+    } else {
+        None
+    }
+}
+
+/// The CHECKMULTISIG script spent by a P2SH, P2SH-P2WSH or P2WSH input
+struct MultisigSpend {
+    script: Script,
+    is_witness: bool,
+    threshold: usize,
+    pub_keys: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Psbt {
+    pub global: KeyValueMap,
+    pub inputs: Vec<KeyValueMap>,
+    pub outputs: Vec<KeyValueMap>,
+}
+
+impl Psbt {
+    pub fn from_bytes(data: &[u8]) -> Result<Psbt> {
+        ensure!(data.starts_with(PSBT_MAGIC), "invalid_psbt");
+        let mut pos = PSBT_MAGIC.len();
+        let global = read_map(data, &mut pos)?;
+        let mut psbt = Psbt {
+            global,
+            inputs: vec![],
+            outputs: vec![],
+        };
+
+        let (input_count, output_count) = match psbt.version()? {
+            0 => {
+                let tx = psbt.unsigned_tx()?;
+                ensure!(
+                    tx.input
+                        .iter()
+                        .all(|x| x.script_sig.is_empty() && x.witness.is_empty()),
+                    "invalid_psbt_unsigned_tx"
+                );
+                (tx.input.len(), tx.output.len())
+            }
+            _ => {
+                ensure!(
+                    !psbt.global.contains_key(&vec![PSBT_GLOBAL_UNSIGNED_TX]),
+                    "invalid_psbt"
+                );
+                (
+                    psbt.global_count(PSBT_GLOBAL_INPUT_COUNT)?,
+                    psbt.global_count(PSBT_GLOBAL_OUTPUT_COUNT)?,
+                )
+            }
+        };
+        for _ in 0..input_count {
+            psbt.inputs.push(read_map(data, &mut pos)?);
+        }
+        for _ in 0..output_count {
+            psbt.outputs.push(read_map(data, &mut pos)?);
+        }
+        ensure!(pos == data.len(), "invalid_psbt");
+
+        // the v2 transaction fields are all present
+        psbt.unsigned_tx()?;
+        Ok(psbt)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = PSBT_MAGIC.to_vec();
+        write_map(&mut out, &self.global);
+        for map in self.inputs.iter().chain(self.outputs.iter()) {
+            write_map(&mut out, map);
+        }
+        out
+    }
+
+    pub fn from_base64(data: &str) -> Result<Psbt> {
+        let bytes = base64::decode(data).map_err(|_| format_err!("invalid_psbt"))?;
+        Psbt::from_bytes(&bytes)
+    }
+
+    pub fn to_base64(&self) -> String {
+        base64::encode(&self.to_bytes())
+    }
+
+    pub fn version(&self) -> Result<u32> {
+        match self.global.get(&vec![PSBT_GLOBAL_VERSION]) {
+            None => Ok(0),
+            Some(value) => match le_u32(value)? {
+                version @ 0 | version @ 2 => Ok(version),
+                _ => Err(format_err!("unsupported_psbt_version")),
+            },
+        }
+    }
+
+    fn global_count(&self, key_type: u8) -> Result<usize> {
+        let value = self
+            .global
+            .get(&vec![key_type])
+            .ok_or_else(|| format_err!("invalid_psbt"))?;
+        let mut pos = 0;
+        let count = read_compact_size(value, &mut pos)?;
+        ensure!(pos == value.len(), "invalid_psbt");
+        Ok(count as usize)
+    }
+
+    /// BIP370 locktime, the max of the required ones, heights being preferred when both
+    /// types are possible
+    fn v2_lock_time(&self) -> Result<u32> {
+        let mut heights = vec![];
+        let mut times = vec![];
+        let mut height_possible = true;
+        let mut time_possible = true;
+        for input in &self.inputs {
+            let height = input.get(&vec![PSBT_IN_REQUIRED_HEIGHT_LOCKTIME]);
+            let time = input.get(&vec![PSBT_IN_REQUIRED_TIME_LOCKTIME]);
+            if height.is_none() && time.is_none() {
+                continue;
+            }
+            match height {
+                Some(value) => heights.push(le_u32(value)?),
+                None => height_possible = false,
+            }
+            match time {
+                Some(value) => times.push(le_u32(value)?),
+                None => time_possible = false,
+            }
+        }
+
+        if heights.is_empty() && times.is_empty() {
+            match self.global.get(&vec![PSBT_GLOBAL_FALLBACK_LOCKTIME]) {
+                Some(value) => le_u32(value),
+                None => Ok(0),
+            }
+        } else if height_possible {
+            Ok(heights.into_iter().max().unwrap_or(0))
+        } else if time_possible {
+            Ok(times.into_iter().max().unwrap_or(0))
+        } else {
+            Err(format_err!("invalid_psbt_locktime"))
+        }
+    }
+
+    /// The transaction without any signature
+    pub fn unsigned_tx(&self) -> Result<Transaction> {
+        if self.version()? == 0 {
+            let tx = self
+                .global
+                .get(&vec![PSBT_GLOBAL_UNSIGNED_TX])
+                .ok_or_else(|| format_err!("invalid_psbt"))?;
+            return deserialize(tx).map_err(|_| format_err!("invalid_psbt_unsigned_tx"));
+        }
+
+        let field = |map: &KeyValueMap, key_type: u8| -> Result<Vec<u8>> {
+            map.get(&vec![key_type])
+                .cloned()
+                .ok_or_else(|| format_err!("invalid_psbt"))
+        };
+        let mut input = vec![];
+        for map in &self.inputs {
+            let txid = field(map, PSBT_IN_PREVIOUS_TXID)?;
+            ensure!(txid.len() == 32, "invalid_psbt");
+            input.push(TxIn {
+                previous_output: OutPoint {
+                    txid: bitcoin::hash_types::Txid::from_slice(&txid)
+                        .map_err(|_| format_err!("invalid_psbt"))?,
+                    vout: le_u32(&field(map, PSBT_IN_OUTPUT_INDEX)?)?,
+                },
+                script_sig: Script::new(),
+                sequence: match map.get(&vec![PSBT_IN_SEQUENCE]) {
+                    Some(value) => le_u32(value)?,
+                    None => 0xFFFF_FFFF,
+                },
+                witness: vec![],
+            });
+        }
+        let mut output = vec![];
+        for map in &self.outputs {
+            output.push(TxOut {
+                value: le_u64(&field(map, PSBT_OUT_AMOUNT)?)?,
+                script_pubkey: Script::from(field(map, PSBT_OUT_SCRIPT)?),
+            });
+        }
+        Ok(Transaction {
+            version: le_u32(&field(&self.global, PSBT_GLOBAL_TX_VERSION)?)? as i32,
+            lock_time: self.v2_lock_time()?,
+            input,
+            output,
+        })
+    }
+
+    /// The output spent by the input `index`
+    pub fn prevout(&self, index: usize) -> Result<TxOut> {
+        let input = self
+            .inputs
+            .get(index)
+            .ok_or_else(|| format_err!("invalid_psbt_input_index"))?;
+        if let Some(value) = input.get(&vec![PSBT_IN_WITNESS_UTXO]) {
+            return deserialize(value).map_err(|_| format_err!("invalid_psbt_utxo"));
+        }
+        let value = input
+            .get(&vec![PSBT_IN_NON_WITNESS_UTXO])
+            .ok_or_else(|| format_err!("psbt_missing_utxo"))?;
+        let prev_tx: Transaction =
+            deserialize(value).map_err(|_| format_err!("invalid_psbt_utxo"))?;
+        let outpoint = self.unsigned_tx()?.input[index].previous_output;
+        ensure!(prev_tx.txid() == outpoint.txid, "invalid_psbt_utxo");
+        prev_tx
+            .output
+            .get(outpoint.vout as usize)
+            .cloned()
+            .ok_or_else(|| format_err!("invalid_psbt_utxo"))
+    }
+
+    /// The (public key, path) pairs of the BIP32 derivations of the input `index`,
+    /// the taproot ones come with x-only public keys
+    pub fn bip32_derivations(&self, index: usize) -> Result<Vec<(Vec<u8>, Vec<u32>)>> {
+        let input = self
+            .inputs
+            .get(index)
+            .ok_or_else(|| format_err!("invalid_psbt_input_index"))?;
+        let mut derivations = vec![];
+        for (key, value) in input {
+            let origin = match key[0] {
+                PSBT_IN_BIP32_DERIVATION => &value[..],
+                PSBT_IN_TAP_BIP32_DERIVATION => {
+                    // the leaf hashes precede the key origin
+                    let mut pos = 0;
+                    let leaf_count = read_compact_size(value, &mut pos)? as usize;
+                    let leaf_hashes_size = leaf_count
+                        .checked_mul(32)
+                        .ok_or_else(|| format_err!("invalid_psbt"))?;
+                    read_bytes(value, &mut pos, leaf_hashes_size)?;
+                    &value[pos..]
+                }
+                _ => continue,
+            };
+            ensure!(
+                origin.len() >= 4 && origin.len() % 4 == 0,
+                "invalid_psbt_bip32_derivation"
+            );
+            let path = origin[4..]
+                .chunks(4)
+                .map(le_u32)
+                .collect::<Result<Vec<u32>>>()?;
+            derivations.push((key[1..].to_vec(), path));
+        }
+        Ok(derivations)
+    }
+
+    fn is_finalized(&self, index: usize) -> bool {
+        let input = &self.inputs[index];
+        input.contains_key(&vec![PSBT_IN_FINAL_SCRIPTSIG])
+            || input.contains_key(&vec![PSBT_IN_FINAL_SCRIPTWITNESS])
+    }
+
+    /// The multisig script of the input `index` spending `script`, given by its redeem or
+    /// witness script
+    fn multisig_spend(&self, index: usize, script: &Script) -> Option<MultisigSpend> {
+        let input = &self.inputs[index];
+        let redeem_script = input.get(&vec![PSBT_IN_REDEEM_SCRIPT]);
+        let witness_script = input.get(&vec![PSBT_IN_WITNESS_SCRIPT]);
+        let (multisig, is_witness) = match (redeem_script, witness_script) {
+            (Some(redeem), Some(witness))
+                if is_p2sh_of(script, redeem) && *redeem == p2wsh_script(witness).as_bytes() =>
+            {
+                (witness, true)
+            }
+            (None, Some(witness)) if *script == p2wsh_script(witness) => (witness, true),
+            (Some(redeem), None) if is_p2sh_of(script, redeem) => (redeem, false),
+            _ => return None,
+        };
+        let (threshold, pub_keys) = parse_multisig_script(multisig)?;
+        Some(MultisigSpend {
+            script: Script::from(multisig.clone()),
+            is_witness,
+            threshold,
+            pub_keys,
+        })
+    }
+
+    /// Signs the input `index` when it spends a P2PKH, P2SH-P2WPKH, P2WPKH or BIP86 P2TR
+    /// output of `key`, or a P2SH, P2SH-P2WSH or P2WSH multisig output `key` is a cosigner of,
+    /// returns whether a signature was added
+    pub fn sign_input(&mut self, index: usize, key: &Secp256k1PrivateKey) -> Result<bool> {
+        ensure!(index < self.inputs.len(), "invalid_psbt_input_index");
+        if self.is_finalized(index) {
+            return Ok(false);
+        }
+        let tx = self.unsigned_tx()?;
+        let prevout = self.prevout(index)?;
+        let pub_key = key.public_key().to_bytes();
+        let script = &prevout.script_pubkey;
+
+        if let Some(output_key) = taproot_output_key(script) {
+            let input = &self.inputs[index];
+            if input.contains_key(&vec![PSBT_IN_TAP_KEY_SIG])
+                || input.contains_key(&vec![PSBT_IN_TAP_MERKLE_ROOT])
+                || output_key != &tweak_public_key(&pub_key)?[..]
+            {
+                return Ok(false);
+            }
+            match input.get(&vec![PSBT_IN_SIGHASH_TYPE]) {
+                Some(value) if le_u32(value)? != 0 => {
+                    return Err(format_err!("unsupported_sighash_type"))
+                }
+                _ => {}
+            }
+            let prevouts = (0..self.inputs.len())
+                .map(|i| self.prevout(i))
+                .collect::<Result<Vec<TxOut>>>()?;
+            let hash = taproot_sighash(&tx, index, &prevouts)?;
+            let signature = schnorr_sign(&tweak_private_key(&key.to_bytes())?, &hash, &[0u8; 32])?;
+            self.inputs[index].insert(vec![PSBT_IN_TAP_KEY_SIG], signature);
+            return Ok(true);
+        }
+
+        let partial_sig_key = [&[PSBT_IN_PARTIAL_SIG], &pub_key[..]].concat();
+        if self.inputs[index].contains_key(&partial_sig_key) {
+            return Ok(false);
+        }
+        match self.inputs[index].get(&vec![PSBT_IN_SIGHASH_TYPE]) {
+            Some(value) if le_u32(value)? != u32::from(SIGHASH_ALL) => {
+                return Err(format_err!("unsupported_sighash_type"))
+            }
+            _ => {}
+        }
+
+        let pub_key_hash = hash160::Hash::hash(&pub_key).into_inner();
+        let script_code = p2pkh_script(&pub_key_hash);
+        let wpkh = p2wpkh_script(&pub_key_hash);
+        let is_nested_wpkh = self.inputs[index]
+            .get(&vec![PSBT_IN_REDEEM_SCRIPT])
+            .map_or(false, |x| *x == wpkh.as_bytes() && is_p2sh_of(script, x));
+        let hash = if *script == script_code {
+            tx.signature_hash(index, script, u32::from(SIGHASH_ALL))
+        } else if *script == wpkh || is_nested_wpkh {
+            SighashComponents::new(&tx).sighash_all(&tx.input[index], &script_code, prevout.value)
+        } else if let Some(multisig) = self.multisig_spend(index, script) {
+            if !multisig.pub_keys.contains(&pub_key) {
+                return Ok(false);
+            }
+            if multisig.is_witness {
+                SighashComponents::new(&tx).sighash_all(
+                    &tx.input[index],
+                    &multisig.script,
+                    prevout.value,
+                )
+            } else {
+                tx.signature_hash(index, &multisig.script, u32::from(SIGHASH_ALL))
+            }
+        } else {
+            return Ok(false);
+        };
+
+        let signature = [key.sign(&hash.into_inner())?, vec![SIGHASH_ALL]].concat();
+        self.inputs[index].insert(partial_sig_key, signature);
+        Ok(true)
+    }
+
+    /// Builds the final scriptSig and witness of every input from its signatures
+    pub fn finalize(&mut self) -> Result<()> {
+        for index in 0..self.inputs.len() {
+            if self.is_finalized(index) {
+                continue;
+            }
+            let script = self.prevout(index)?.script_pubkey;
+            let input = &self.inputs[index];
+
+            let (script_sig, witness) = if taproot_output_key(&script).is_some() {
+                let signature = input
+                    .get(&vec![PSBT_IN_TAP_KEY_SIG])
+                    .ok_or_else(|| format_err!("psbt_input_not_finalizable"))?;
+                (None, Some(vec![signature.clone()]))
+            } else if let Some(multisig) = self.multisig_spend(index, &script) {
+                let signatures = multisig
+                    .pub_keys
+                    .iter()
+                    .filter_map(|x| input.get(&[&[PSBT_IN_PARTIAL_SIG], &x[..]].concat()))
+                    .take(multisig.threshold)
+                    .cloned()
+                    .collect::<Vec<Vec<u8>>>();
+                ensure!(
+                    signatures.len() == multisig.threshold,
+                    "psbt_input_not_finalizable"
+                );
+                // CHECKMULTISIG pops an extra item, the signatures follow the key order
+                let items = [vec![vec![]], signatures, vec![multisig.script.to_bytes()]].concat();
+                if !multisig.is_witness {
+                    let script_sig = items
+                        .iter()
+                        .fold(Builder::new(), |builder, x| builder.push_slice(x))
+                        .into_script();
+                    (Some(script_sig), None)
+                } else if script.is_p2sh() {
+                    let redeem_script = p2wsh_script(multisig.script.as_bytes());
+                    let script_sig = Builder::new()
+                        .push_slice(redeem_script.as_bytes())
+                        .into_script();
+                    (Some(script_sig), Some(items))
+                } else {
+                    (None, Some(items))
+                }
+            } else {
+                let redeem_script = input.get(&vec![PSBT_IN_REDEEM_SCRIPT]);
+                input
+                    .iter()
+                    .filter(|(key, _)| key[0] == PSBT_IN_PARTIAL_SIG)
+                    .find_map(|(key, signature)| {
+                        let pub_key = key[1..].to_vec();
+                        let pub_key_hash = hash160::Hash::hash(&pub_key).into_inner();
+                        let wpkh = p2wpkh_script(&pub_key_hash);
+                        if script == p2pkh_script(&pub_key_hash) {
+                            let script_sig = Builder::new()
+                                .push_slice(signature)
+                                .push_slice(&pub_key)
+                                .into_script();
+                            Some((Some(script_sig), None))
+                        } else if script == wpkh {
+                            Some((None, Some(vec![signature.clone(), pub_key])))
+                        } else if redeem_script
+                            .map_or(false, |x| *x == wpkh.as_bytes() && is_p2sh_of(&script, x))
+                        {
+                            let script_sig =
+                                Builder::new().push_slice(wpkh.as_bytes()).into_script();
+                            Some((Some(script_sig), Some(vec![signature.clone(), pub_key])))
+                        } else {
+                            None
+                        }
+                    })
+                    .ok_or_else(|| format_err!("psbt_input_not_finalizable"))?
+            };
+
+            let input = &mut self.inputs[index];
+            input.retain(|key, _| !PSBT_IN_SIGNING_DATA.contains(&key[0]));
+            if let Some(script_sig) = script_sig {
+                input.insert(vec![PSBT_IN_FINAL_SCRIPTSIG], script_sig.to_bytes());
+            }
+            if let Some(witness) = witness {
+                let mut value = vec![];
+                write_compact_size(&mut value, witness.len());
+                for item in witness {
+                    write_compact_size(&mut value, item.len());
+                    value.extend(item);
+                }
+                input.insert(vec![PSBT_IN_FINAL_SCRIPTWITNESS], value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Merges the key-value pairs of another psbt of the same transaction, e.g. the signatures
+    /// of the other cosigners, the pairs present in both are kept from `self`
+    pub fn combine(&mut self, other: &Psbt) -> Result<()> {
+        ensure!(
+            self.version()? == other.version()?
+                && self.inputs.len() == other.inputs.len()
+                && self.outputs.len() == other.outputs.len()
+                && self.unsigned_tx()? == other.unsigned_tx()?,
+            "psbt_mismatch"
+        );
+
+        let merge = |map: &mut KeyValueMap, other: &KeyValueMap| {
+            for (key, value) in other {
+                map.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+        };
+        merge(&mut self.global, &other.global);
+        for (map, other) in self.inputs.iter_mut().zip(&other.inputs) {
+            merge(map, other);
+        }
+        for (map, other) in self.outputs.iter_mut().zip(&other.outputs) {
+            merge(map, other);
+        }
+        Ok(())
+    }
+
+    /// The network serialized transaction of a finalized psbt
+    pub fn extract_tx(&self) -> Result<Transaction> {
+        let mut tx = self.unsigned_tx()?;
+        for (index, input) in self.inputs.iter().enumerate() {
+            ensure!(self.is_finalized(index), "psbt_input_not_finalized");
+            if let Some(script_sig) = input.get(&vec![PSBT_IN_FINAL_SCRIPTSIG]) {
+                tx.input[index].script_sig = Script::from(script_sig.clone());
+            }
+            if let Some(value) = input.get(&vec![PSBT_IN_FINAL_SCRIPTWITNESS]) {
+                let mut pos = 0;
+                let count = read_compact_size(value, &mut pos)?;
+                for _ in 0..count {
+                    let len = read_compact_size(value, &mut pos)? as usize;
+                    tx.input[index]
+                        .witness
+                        .push(read_bytes(value, &mut pos, len)?.to_vec());
+                }
+                ensure!(pos == value.len(), "invalid_psbt");
+            }
+        }
+        Ok(tx)
+    }
+}
+
+/// Finalizes every input of the psbt and extracts the transaction
+pub fn finalize_psbt(input: &PsbtInput) -> Result<BtcForkSignedTxOutput> {
+    let mut psbt = Psbt::from_base64(&input.psbt)?;
+    psbt.finalize()?;
+    let tx = psbt.extract_tx()?;
+    Ok(BtcForkSignedTxOutput {
+        signature: serialize(&tx).to_hex(),
+        tx_hash: tx.txid().into_inner().to_hex(),
+        change_address: "".to_string(),
+        change_path: "".to_string(),
+    })
+}
+
+/// Combines the psbts of the same transaction signed by different cosigners
+pub fn combine_psbt(input: &PsbtCombineInput) -> Result<PsbtInput> {
+    let mut psbts = input.psbts.iter().map(|x| Psbt::from_base64(x));
+    let mut combined = psbts.next().ok_or_else(|| format_err!("missing_psbt"))??;
+    for psbt in psbts {
+        combined.combine(&psbt?)?;
+    }
+    Ok(PsbtInput {
+        psbt: combined.to_base64(),
+    })
+}
+
+/// The private key of the keystore matching the BIP32 derivation `(pub_key, path)`
+fn find_psbt_key(
+    keystore: &mut Keystore,
+    symbol: &str,
+    address: &str,
+    accounts: &[Account],
+    pub_key: &[u8],
+    path: &[u32],
+) -> Result<Option<Secp256k1PrivateKey>> {
+    let is_key_of = |key: &Secp256k1PrivateKey| {
+        let key_bytes = key.public_key().to_bytes();
+        key_bytes == pub_key || (pub_key.len() == 32 && key_bytes[1..] == *pub_key)
+    };
+
+    if !keystore.determinable() {
+        let key = keystore
+            .find_private_key(symbol, address)?
+            .as_secp256k1()?
+            .clone();
+        return Ok(Some(key).filter(is_key_of));
+    }
+
+    let path = path_to_string(path);
+    for account in accounts {
+        let account_path = format!("{}/", get_account_path(&account.derivation_path)?);
+        if let Some(relative_path) = path.strip_prefix(&account_path) {
+            let key = keystore
+                .find_private_key_by_path(symbol, &account.address, relative_path)?
+                .as_secp256k1()?
+                .clone();
+            if is_key_of(&key) {
+                return Ok(Some(key));
+            }
+        }
+    }
+    Ok(None)
+}
+
+impl TransactionSigner<PsbtInput, PsbtOutput> for Keystore {
+    fn sign_transaction(
+        &mut self,
+        symbol: &str,
+        address: &str,
+        tx: &PsbtInput,
+    ) -> Result<PsbtOutput> {
+        let mut psbt = Psbt::from_base64(&tx.psbt)?;
+        let accounts: Vec<Account> = self
+            .accounts()
+            .iter()
+            .filter(|x| x.coin == symbol)
+            .cloned()
+            .collect();
+
+        let mut signed_inputs = vec![];
+        for index in 0..psbt.inputs.len() {
+            for (pub_key, path) in psbt.bip32_derivations(index)? {
+                if let Some(key) = find_psbt_key(self, symbol, address, &accounts, &pub_key, &path)?
+                {
+                    if psbt.sign_input(index, &key)? && !signed_inputs.contains(&(index as u32)) {
+                        signed_inputs.push(index as u32);
+                    }
+                }
+            }
+        }
+
+        Ok(PsbtOutput {
+            psbt: psbt.to_base64(),
+            signed_inputs,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{combine_psbt, finalize_psbt, Psbt};
+    use crate::transaction::{PsbtCombineInput, PsbtInput};
+    use bitcoin::consensus::serialize;
+    use bitcoin_hashes::hex::ToHex;
+    use tcx_primitive::{PrivateKey, Secp256k1PrivateKey};
+
+    // inputs spending P2PKH, P2WPKH, P2SH-P2WPKH and P2TR outputs of the same key
+    const UNSIGNED_PSBT: &str = "cHNidP8BAM0CAAAABBntgicHNNERE0c0lYKWtsiuOMfQW/JYDTOgXkfs7vDMAAAAAAD9////zjf37WbhDSCbpwOm6ALGGTn/RcT8T9Hp1I1pgzcxleYAAAAAAP3///9nDG8+V7sHV8VjwtFcITJzBdrkvl/PwI/riT6hDl72rQAAAAAA/f///36/F81ddRVPF/9gz8DBelJa//7kAjcQZF8BtjThNnqxAAAAAAD9////AZBfAQAAAAAAFgAUAAECAwQFBgcICQoLDA0ODxAREhMAAAAAAAEAVQEAAAABAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEAAAAAAP////8BMHUAAAAAAAAZdqkU7939tM1SEczVRX5sI3yrytFNTzmIrAAAAAAiBgPMikvGTYl73cX7wvZw96i6CzhneRBs8SI8b8XXzW/BFRgAAAAALAAAgAAAAIAAAACAAAAAAAAAAAAAAQEfMHUAAAAAAAAWABTv3f20zVIRzNVFfmwjfKvK0U1POSIGA8yKS8ZNiXvdxfvC9nD3qLoLOGd5EGzxIjxvxdfNb8EVGAAAAABUAACAAAAAgAAAAIAAAAAAAAAAAAABASAgTgAAAAAAABepFFj3XuENHe2S0enKdOLyV4JYmMJwhwEEFgAU7939tM1SEczVRX5sI3yrytFNTzkiBgPMikvGTYl73cX7wvZw96i6CzhneRBs8SI8b8XXzW/BFRgAAAAAMQAAgAAAAIAAAACAAAAAAAAAAAAAAQErIE4AAAAAAAAiUSCmCGnw288dxlnJzsuvgFATXqnozcSHBT8dxogJSdxoTCEWzIpLxk2Je93F+8L2cPeougs4Z3kQbPEiPG/F181vwRUZAAAAAABWAACAAAAAgAAAAIAAAAAAAAAAAAAA";
+    const SIGNED_PSBT: &str = "cHNidP8BAM0CAAAABBntgicHNNERE0c0lYKWtsiuOMfQW/JYDTOgXkfs7vDMAAAAAAD9////zjf37WbhDSCbpwOm6ALGGTn/RcT8T9Hp1I1pgzcxleYAAAAAAP3///9nDG8+V7sHV8VjwtFcITJzBdrkvl/PwI/riT6hDl72rQAAAAAA/f///36/F81ddRVPF/9gz8DBelJa//7kAjcQZF8BtjThNnqxAAAAAAD9////AZBfAQAAAAAAFgAUAAECAwQFBgcICQoLDA0ODxAREhMAAAAAAAEAVQEAAAABAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEAAAAAAP////8BMHUAAAAAAAAZdqkU7939tM1SEczVRX5sI3yrytFNTzmIrAAAAAAiAgPMikvGTYl73cX7wvZw96i6CzhneRBs8SI8b8XXzW/BFUgwRQIhALY4DxYqvLfN08KrbMuuxYbzzib4zno38MBPNe0gop9+AiBpXQqk6jRRQ41dgz0s5hR0AM7RMLoN3bYuBiIGRskNRgEiBgPMikvGTYl73cX7wvZw96i6CzhneRBs8SI8b8XXzW/BFRgAAAAALAAAgAAAAIAAAACAAAAAAAAAAAAAAQEfMHUAAAAAAAAWABTv3f20zVIRzNVFfmwjfKvK0U1POSICA8yKS8ZNiXvdxfvC9nD3qLoLOGd5EGzxIjxvxdfNb8EVRzBEAiAMFkcabUuaD+vP8Aq5UDCrC9bqnJ86HaBNroT7ojNUWAIgKMd9HNtDQRcnpWPEJDpQgL0r4DJBsrnF5JCb7SrEgSwBIgYDzIpLxk2Je93F+8L2cPeougs4Z3kQbPEiPG/F181vwRUYAAAAAFQAAIAAAACAAAAAgAAAAAAAAAAAAAEBICBOAAAAAAAAF6kUWPde4Q0d7ZLR6cp04vJXgliYwnCHIgIDzIpLxk2Je93F+8L2cPeougs4Z3kQbPEiPG/F181vwRVHMEQCIGCahuKIAARmO96YGXuwszhBolCbZW1WFqQPYFhJ+AJsAiAvZKH60wkhLbZcPQC5tZW60qEZbdbs5FMz//JrE1ID0AEBBBYAFO/d/bTNUhHM1UV+bCN8q8rRTU85IgYDzIpLxk2Je93F+8L2cPeougs4Z3kQbPEiPG/F181vwRUYAAAAADEAAIAAAACAAAAAgAAAAAAAAAAAAAEBKyBOAAAAAAAAIlEgpghp8NvPHcZZyc7Lr4BQE16p6M3EhwU/HcaICUncaEwBE0DFKxAjlzFsL3mgsuiDqQYsB7n2enazv4lEnNx3Za3wV3OoxF1f9LlFg22AyMKWz5Mak6SeodbWaiK4jKfSXqO2IRbMikvGTYl73cX7wvZw96i6CzhneRBs8SI8b8XXzW/BFRkAAAAAAFYAAIAAAACAAAAAgAAAAAAAAAAAAAA=";
+    const FINALIZED_PSBT: &str = "cHNidP8BAM0CAAAABBntgicHNNERE0c0lYKWtsiuOMfQW/JYDTOgXkfs7vDMAAAAAAD9////zjf37WbhDSCbpwOm6ALGGTn/RcT8T9Hp1I1pgzcxleYAAAAAAP3///9nDG8+V7sHV8VjwtFcITJzBdrkvl/PwI/riT6hDl72rQAAAAAA/f///36/F81ddRVPF/9gz8DBelJa//7kAjcQZF8BtjThNnqxAAAAAAD9////AZBfAQAAAAAAFgAUAAECAwQFBgcICQoLDA0ODxAREhMAAAAAAAEAVQEAAAABAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEAAAAAAP////8BMHUAAAAAAAAZdqkU7939tM1SEczVRX5sI3yrytFNTzmIrAAAAAABB2tIMEUCIQC2OA8WKry3zdPCq2zLrsWG884m+M56N/DATzXtIKKffgIgaV0KpOo0UUONXYM9LOYUdADO0TC6Dd22LgYiBkbJDUYBIQPMikvGTYl73cX7wvZw96i6CzhneRBs8SI8b8XXzW/BFQABAR8wdQAAAAAAABYAFO/d/bTNUhHM1UV+bCN8q8rRTU85AQhrAkcwRAIgDBZHGm1Lmg/rz/AKuVAwqwvW6pyfOh2gTa6E+6IzVFgCICjHfRzbQ0EXJ6VjxCQ6UIC9K+AyQbK5xeSQm+0qxIEsASEDzIpLxk2Je93F+8L2cPeougs4Z3kQbPEiPG/F181vwRUAAQEgIE4AAAAAAAAXqRRY917hDR3tktHpynTi8leCWJjCcIcBBxcWABTv3f20zVIRzNVFfmwjfKvK0U1POQEIawJHMEQCIGCahuKIAARmO96YGXuwszhBolCbZW1WFqQPYFhJ+AJsAiAvZKH60wkhLbZcPQC5tZW60qEZbdbs5FMz//JrE1ID0AEhA8yKS8ZNiXvdxfvC9nD3qLoLOGd5EGzxIjxvxdfNb8EVAAEBKyBOAAAAAAAAIlEgpghp8NvPHcZZyc7Lr4BQE16p6M3EhwU/HcaICUncaEwBCEIBQMUrECOXMWwveaCy6IOpBiwHufZ6drO/iUSc3HdlrfBXc6jEXV/0uUWDbYDIwpbPkxqTpJ6h1tZqIriMp9Jeo7YAAA==";
+    const UNSIGNED_PSBT_V2: &str = "cHNidP8BAgQCAAAAAQMEAAAAAAEEAQQBBQEBAfsEAgAAAAABAFUBAAAAAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAAAAAAD/////ATB1AAAAAAAAGXapFO/d/bTNUhHM1UV+bCN8q8rRTU85iKwAAAAAIgYDzIpLxk2Je93F+8L2cPeougs4Z3kQbPEiPG/F181vwRUYAAAAACwAAIAAAACAAAAAgAAAAAAAAAAAAQ4gGe2CJwc00RETRzSVgpa2yK44x9Bb8lgNM6BeR+zu8MwBDwQAAAAAARAE/f///wABAR8wdQAAAAAAABYAFO/d/bTNUhHM1UV+bCN8q8rRTU85IgYDzIpLxk2Je93F+8L2cPeougs4Z3kQbPEiPG/F181vwRUYAAAAAFQAAIAAAACAAAAAgAAAAAAAAAAAAQ4gzjf37WbhDSCbpwOm6ALGGTn/RcT8T9Hp1I1pgzcxleYBDwQAAAAAARAE/f///wABASAgTgAAAAAAABepFFj3XuENHe2S0enKdOLyV4JYmMJwhwEEFgAU7939tM1SEczVRX5sI3yrytFNTzkiBgPMikvGTYl73cX7wvZw96i6CzhneRBs8SI8b8XXzW/BFRgAAAAAMQAAgAAAAIAAAACAAAAAAAAAAAABDiBnDG8+V7sHV8VjwtFcITJzBdrkvl/PwI/riT6hDl72rQEPBAAAAAABEAT9////AAEBKyBOAAAAAAAAIlEgpghp8NvPHcZZyc7Lr4BQE16p6M3EhwU/HcaICUncaEwBDiB+vxfNXXUVTxf/YM/AwXpSWv/+5AI3EGRfAbY04TZ6sQEPBAAAAAABEAT9////IRbMikvGTYl73cX7wvZw96i6CzhneRBs8SI8b8XXzW/BFRkAAAAAAFYAAIAAAACAAAAAgAAAAAAAAAAAAAEDCJBfAQAAAAAAAQQWABQAAQIDBAUGBwgJCgsMDQ4PEBESEwA=";
+    const SIGNED_TX: &str = "0200000000010419ed82270734d111134734958296b6c8ae38c7d05bf2580d33a05e47eceef0cc000000006b483045022100b6380f162abcb7cdd3c2ab6ccbaec586f3ce26f8ce7a37f0c04f35ed20a29f7e0220695d0aa4ea3451438d5d833d2ce6147400ced130ba0dddb62e06220646c90d46012103cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115fdffffffce37f7ed66e10d209ba703a6e802c61939ff45c4fc4fd1e9d48d6983373195e60000000000fdffffff670c6f3e57bb0757c563c2d15c21327305dae4be5fcfc08feb893ea10e5ef6ad0000000017160014efddfdb4cd5211ccd5457e6c237cabcad14d4f39fdffffff7ebf17cd5d75154f17ff60cfc0c17a525afffee4023710645f01b634e1367ab10000000000fdffffff01905f010000000000160014000102030405060708090a0b0c0d0e0f10111213000247304402200c16471a6d4b9a0febcff00ab95030ab0bd6ea9c9f3a1da04dae84fba2335458022028c77d1cdb43411727a563c4243a5080bd2be03241b2b9c5e4909bed2ac4812c012103cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115024730440220609a86e2880004663bde98197bb0b33841a2509b656d5616a40f605849f8026c02202f64a1fad309212db65c3d00b9b595bad2a1196dd6ece45333fff26b135203d0012103cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc1150140c52b102397316c2f79a0b2e883a9062c07b9f67a76b3bf89449cdc7765adf05773a8c45d5ff4b945836d80c8c296cf931a93a49ea1d6d66a22b88ca7d25ea3b600000000";
+
+    // 2-of-3 P2SH, P2SH-P2WSH and P2WSH inputs of the cosigner keys at m/48'/0'/0'/0/0
+    const UNSIGNED_MULTISIG_PSBT: &str = "cHNidP8BAKQCAAAAAxbHlNL5In1RpQxhdkUdqjgst2bHHK3V7XIPryP4mWmzAAAAAAD9////5N0BOVxETJk/vI/yqx5aMu0TzygXpTP/QZyA9HjOE3AAAAAAAP3///+0RnNiqdD2rqeoZPgTBs9KtQQFlgZ73IvdmgegIOafbwAAAAAA/f///wEITAEAAAAAABYAFAABAgMEBQYHCAkKCwwNDg8QERITAAAAAAABAFMBAAAAAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAAAAAAD/////ASBOAAAAAAAAF6kUY737ZRr+ZJeiJZw8W4O74KWormuHAAAAAAEEaVIhAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgIQMMMAXFORTEZ6Wgr3J/bOYuxQ+Zx2x2navQDB9mXKwYpSED1HT7ayDMB9T83C9mX0uJv5rlreGo38PbVOnvlCBT3mtTriIGAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGAwwwBcU5FMRnpaCvcn9s5i7FD5nHbHadq9AMH2ZcrBilGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGA9R0+2sgzAfU/NwvZl9Lib+a5a3hqN/D21Tp75QgU95rGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAAAABASAwdQAAAAAAABepFNOUoV+rstZknr008uY+q7X5zBvNhwEEIgAgMMb0i5lMFLn5CZdXkkRmHhSK8zfbeCmdu+sWGINBj1MBBWlSIQLF5U+wVZ/M3CBvgZmV3TYc75AYOUwynkoZB0eTjY8SYCEDDDAFxTkUxGeloK9yf2zmLsUPmcdsdp2r0AwfZlysGKUhA9R0+2sgzAfU/NwvZl9Lib+a5a3hqN/D21Tp75QgU95rU64iBgLF5U+wVZ/M3CBvgZmV3TYc75AYOUwynkoZB0eTjY8SYBgAAAAAMAAAgAAAAIAAAACAAAAAAAAAAAAiBgMMMAXFORTEZ6Wgr3J/bOYuxQ+Zx2x2navQDB9mXKwYpRgAAAAAMAAAgAAAAIAAAACAAAAAAAAAAAAiBgPUdPtrIMwH1PzcL2ZfS4m/muWt4ajfw9tU6e+UIFPeaxgAAAAAMAAAgAAAAIAAAACAAAAAAAAAAAAAAQErQJwAAAAAAAAiACAwxvSLmUwUufkJl1eSRGYeFIrzN9t4KZ276xYYg0GPUwEFaVIhAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgIQMMMAXFORTEZ6Wgr3J/bOYuxQ+Zx2x2navQDB9mXKwYpSED1HT7ayDMB9T83C9mX0uJv5rlreGo38PbVOnvlCBT3mtTriIGAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGAwwwBcU5FMRnpaCvcn9s5i7FD5nHbHadq9AMH2ZcrBilGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGA9R0+2sgzAfU/NwvZl9Lib+a5a3hqN/D21Tp75QgU95rGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAAAAA";
+    const SIGNED_MULTISIG_PSBT_A: &str = "cHNidP8BAKQCAAAAAxbHlNL5In1RpQxhdkUdqjgst2bHHK3V7XIPryP4mWmzAAAAAAD9////5N0BOVxETJk/vI/yqx5aMu0TzygXpTP/QZyA9HjOE3AAAAAAAP3///+0RnNiqdD2rqeoZPgTBs9KtQQFlgZ73IvdmgegIOafbwAAAAAA/f///wEITAEAAAAAABYAFAABAgMEBQYHCAkKCwwNDg8QERITAAAAAAABAFMBAAAAAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAAAAAAD/////ASBOAAAAAAAAF6kUY737ZRr+ZJeiJZw8W4O74KWormuHAAAAACICAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgSDBFAiEA7l194+i0DYl1tyCjBr0hGatKWUG+ZuHaLoxmuX7ZDsoCIFmC5E5DhJrs1C6Jyft2jirzce1bzdtZkxXKxYRObzk0AQEEaVIhAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgIQMMMAXFORTEZ6Wgr3J/bOYuxQ+Zx2x2navQDB9mXKwYpSED1HT7ayDMB9T83C9mX0uJv5rlreGo38PbVOnvlCBT3mtTriIGAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGAwwwBcU5FMRnpaCvcn9s5i7FD5nHbHadq9AMH2ZcrBilGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGA9R0+2sgzAfU/NwvZl9Lib+a5a3hqN/D21Tp75QgU95rGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAAAABASAwdQAAAAAAABepFNOUoV+rstZknr008uY+q7X5zBvNhyICAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgRzBEAiBlMIZvdUv2Mds8HNz3jMaWV0OJS5eMUmwB6D73o9dJ8QIgYIGTj/bDkxNMzbG+oqxfd6nLJj/3Y0YHM9e/0cgDIr0BAQQiACAwxvSLmUwUufkJl1eSRGYeFIrzN9t4KZ276xYYg0GPUwEFaVIhAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgIQMMMAXFORTEZ6Wgr3J/bOYuxQ+Zx2x2navQDB9mXKwYpSED1HT7ayDMB9T83C9mX0uJv5rlreGo38PbVOnvlCBT3mtTriIGAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGAwwwBcU5FMRnpaCvcn9s5i7FD5nHbHadq9AMH2ZcrBilGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGA9R0+2sgzAfU/NwvZl9Lib+a5a3hqN/D21Tp75QgU95rGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAAAABAStAnAAAAAAAACIAIDDG9IuZTBS5+QmXV5JEZh4UivM323gpnbvrFhiDQY9TIgICxeVPsFWfzNwgb4GZld02HO+QGDlMMp5KGQdHk42PEmBHMEQCIE3VNH1oxndaU9LqIX4czMW74XKrA6F4N1hBCDd9hem3AiBQGlUxU2UjPZhp9E82lO76DgkjDhSyLD9F1bzIJI0HhQEBBWlSIQLF5U+wVZ/M3CBvgZmV3TYc75AYOUwynkoZB0eTjY8SYCEDDDAFxTkUxGeloK9yf2zmLsUPmcdsdp2r0AwfZlysGKUhA9R0+2sgzAfU/NwvZl9Lib+a5a3hqN/D21Tp75QgU95rU64iBgLF5U+wVZ/M3CBvgZmV3TYc75AYOUwynkoZB0eTjY8SYBgAAAAAMAAAgAAAAIAAAACAAAAAAAAAAAAiBgMMMAXFORTEZ6Wgr3J/bOYuxQ+Zx2x2navQDB9mXKwYpRgAAAAAMAAAgAAAAIAAAACAAAAAAAAAAAAiBgPUdPtrIMwH1PzcL2ZfS4m/muWt4ajfw9tU6e+UIFPeaxgAAAAAMAAAgAAAAIAAAACAAAAAAAAAAAAAAA==";
+    const SIGNED_MULTISIG_PSBT_B: &str = "cHNidP8BAKQCAAAAAxbHlNL5In1RpQxhdkUdqjgst2bHHK3V7XIPryP4mWmzAAAAAAD9////5N0BOVxETJk/vI/yqx5aMu0TzygXpTP/QZyA9HjOE3AAAAAAAP3///+0RnNiqdD2rqeoZPgTBs9KtQQFlgZ73IvdmgegIOafbwAAAAAA/f///wEITAEAAAAAABYAFAABAgMEBQYHCAkKCwwNDg8QERITAAAAAAABAFMBAAAAAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAAAAAAD/////ASBOAAAAAAAAF6kUY737ZRr+ZJeiJZw8W4O74KWormuHAAAAACICAwwwBcU5FMRnpaCvcn9s5i7FD5nHbHadq9AMH2ZcrBilSDBFAiEAvZ6cL1uGma8dCQ75SMl6F+siN+6OQqHwm/zK1a5htSgCIDQ6+ZOHb5TmFeJkAU70oN3OzkE1fYV28Dnnbj2G5DAYAQEEaVIhAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgIQMMMAXFORTEZ6Wgr3J/bOYuxQ+Zx2x2navQDB9mXKwYpSED1HT7ayDMB9T83C9mX0uJv5rlreGo38PbVOnvlCBT3mtTriIGAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGAwwwBcU5FMRnpaCvcn9s5i7FD5nHbHadq9AMH2ZcrBilGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGA9R0+2sgzAfU/NwvZl9Lib+a5a3hqN/D21Tp75QgU95rGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAAAABASAwdQAAAAAAABepFNOUoV+rstZknr008uY+q7X5zBvNhyICAwwwBcU5FMRnpaCvcn9s5i7FD5nHbHadq9AMH2ZcrBilRzBEAiBrvQaRngeyqQRJF/tV1by+jeR0QJfGmkQ48hbvrubBbAIgQB4HXC2yEBEZw/TxeMpUZXuzkuyLIxxlDFO7pn43f8cBAQQiACAwxvSLmUwUufkJl1eSRGYeFIrzN9t4KZ276xYYg0GPUwEFaVIhAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgIQMMMAXFORTEZ6Wgr3J/bOYuxQ+Zx2x2navQDB9mXKwYpSED1HT7ayDMB9T83C9mX0uJv5rlreGo38PbVOnvlCBT3mtTriIGAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGAwwwBcU5FMRnpaCvcn9s5i7FD5nHbHadq9AMH2ZcrBilGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGA9R0+2sgzAfU/NwvZl9Lib+a5a3hqN/D21Tp75QgU95rGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAAAABAStAnAAAAAAAACIAIDDG9IuZTBS5+QmXV5JEZh4UivM323gpnbvrFhiDQY9TIgIDDDAFxTkUxGeloK9yf2zmLsUPmcdsdp2r0AwfZlysGKVHMEQCIHE0qjnRpcUZl6e1udgh8OtFs0S+foBAHJCZK+bwkMMgAiBQWwh7PYeqshpu7WcAhRGrtQrnT+LbITmL2ji4qRFm/gEBBWlSIQLF5U+wVZ/M3CBvgZmV3TYc75AYOUwynkoZB0eTjY8SYCEDDDAFxTkUxGeloK9yf2zmLsUPmcdsdp2r0AwfZlysGKUhA9R0+2sgzAfU/NwvZl9Lib+a5a3hqN/D21Tp75QgU95rU64iBgLF5U+wVZ/M3CBvgZmV3TYc75AYOUwynkoZB0eTjY8SYBgAAAAAMAAAgAAAAIAAAACAAAAAAAAAAAAiBgMMMAXFORTEZ6Wgr3J/bOYuxQ+Zx2x2navQDB9mXKwYpRgAAAAAMAAAgAAAAIAAAACAAAAAAAAAAAAiBgPUdPtrIMwH1PzcL2ZfS4m/muWt4ajfw9tU6e+UIFPeaxgAAAAAMAAAgAAAAIAAAACAAAAAAAAAAAAAAA==";
+    const FINALIZED_MULTISIG_PSBT: &str = "cHNidP8BAKQCAAAAAxbHlNL5In1RpQxhdkUdqjgst2bHHK3V7XIPryP4mWmzAAAAAAD9////5N0BOVxETJk/vI/yqx5aMu0TzygXpTP/QZyA9HjOE3AAAAAAAP3///+0RnNiqdD2rqeoZPgTBs9KtQQFlgZ73IvdmgegIOafbwAAAAAA/f///wEITAEAAAAAABYAFAABAgMEBQYHCAkKCwwNDg8QERITAAAAAAABAFMBAAAAAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAAAAAAD/////ASBOAAAAAAAAF6kUY737ZRr+ZJeiJZw8W4O74KWormuHAAAAAAEH/f4AAEgwRQIhAO5dfePotA2Jdbcgowa9IRmrSllBvmbh2i6MZrl+2Q7KAiBZguROQ4Sa7NQuicn7do4q83HtW83bWZMVysWETm85NAFIMEUCIQC9npwvW4aZrx0JDvlIyXoX6yI37o5CofCb/MrVrmG1KAIgNDr5k4dvlOYV4mQBTvSg3c7OQTV9hXbwOeduPYbkMBgBTGlSIQLF5U+wVZ/M3CBvgZmV3TYc75AYOUwynkoZB0eTjY8SYCEDDDAFxTkUxGeloK9yf2zmLsUPmcdsdp2r0AwfZlysGKUhA9R0+2sgzAfU/NwvZl9Lib+a5a3hqN/D21Tp75QgU95rU64AAQEgMHUAAAAAAAAXqRTTlKFfq7LWZJ69NPLmPqu1+cwbzYcBByMiACAwxvSLmUwUufkJl1eSRGYeFIrzN9t4KZ276xYYg0GPUwEI/AQARzBEAiBlMIZvdUv2Mds8HNz3jMaWV0OJS5eMUmwB6D73o9dJ8QIgYIGTj/bDkxNMzbG+oqxfd6nLJj/3Y0YHM9e/0cgDIr0BRzBEAiBrvQaRngeyqQRJF/tV1by+jeR0QJfGmkQ48hbvrubBbAIgQB4HXC2yEBEZw/TxeMpUZXuzkuyLIxxlDFO7pn43f8cBaVIhAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgIQMMMAXFORTEZ6Wgr3J/bOYuxQ+Zx2x2navQDB9mXKwYpSED1HT7ayDMB9T83C9mX0uJv5rlreGo38PbVOnvlCBT3mtTrgABAStAnAAAAAAAACIAIDDG9IuZTBS5+QmXV5JEZh4UivM323gpnbvrFhiDQY9TAQj8BABHMEQCIE3VNH1oxndaU9LqIX4czMW74XKrA6F4N1hBCDd9hem3AiBQGlUxU2UjPZhp9E82lO76DgkjDhSyLD9F1bzIJI0HhQFHMEQCIHE0qjnRpcUZl6e1udgh8OtFs0S+foBAHJCZK+bwkMMgAiBQWwh7PYeqshpu7WcAhRGrtQrnT+LbITmL2ji4qRFm/gFpUiECxeVPsFWfzNwgb4GZld02HO+QGDlMMp5KGQdHk42PEmAhAwwwBcU5FMRnpaCvcn9s5i7FD5nHbHadq9AMH2ZcrBilIQPUdPtrIMwH1PzcL2ZfS4m/muWt4ajfw9tU6e+UIFPea1OuAAA=";
+    const SIGNED_MULTISIG_TX: &str = "0200000000010316c794d2f9227d51a50c6176451daa382cb766c71cadd5ed720faf23f89969b300000000fdfe0000483045022100ee5d7de3e8b40d8975b720a306bd2119ab4a5941be66e1da2e8c66b97ed90eca02205982e44e43849aecd42e89c9fb768e2af371ed5bcddb599315cac5844e6f393401483045022100bd9e9c2f5b8699af1d090ef948c97a17eb2237ee8e42a1f09bfccad5ae61b5280220343af993876f94e615e264014ef4a0ddcece41357d8576f039e76e3d86e43018014c69522102c5e54fb0559fccdc206f819995dd361cef9018394c329e4a190747938d8f126021030c3005c53914c467a5a0af727f6ce62ec50f99c76c769dabd00c1f665cac18a52103d474fb6b20cc07d4fcdc2f665f4b89bf9ae5ade1a8dfc3db54e9ef942053de6b53aefdffffffe4dd01395c444c993fbc8ff2ab1e5a32ed13cf2817a533ff419c80f478ce1370000000002322002030c6f48b994c14b9f90997579244661e148af337db78299dbbeb161883418f53fdffffffb4467362a9d0f6aea7a864f81306cf4ab5040596067bdc8bdd9a07a020e69f6f0000000000fdffffff01084c010000000000160014000102030405060708090a0b0c0d0e0f1011121300040047304402206530866f754bf631db3c1cdcf78cc6965743894b978c526c01e83ef7a3d749f102206081938ff6c393134ccdb1bea2ac5f77a9cb263ff763460733d7bfd1c80322bd0147304402206bbd06919e07b2a9044917fb55d5bcbe8de4744097c69a4438f216efaee6c16c0220401e075c2db2101119c3f4f178ca54657bb392ec8b231c650c53bba67e377fc70169522102c5e54fb0559fccdc206f819995dd361cef9018394c329e4a190747938d8f126021030c3005c53914c467a5a0af727f6ce62ec50f99c76c769dabd00c1f665cac18a52103d474fb6b20cc07d4fcdc2f665f4b89bf9ae5ade1a8dfc3db54e9ef942053de6b53ae040047304402204dd5347d68c6775a53d2ea217e1cccc5bbe172ab03a17837584108377d85e9b70220501a55315365233d9869f44f3694eefa0e09230e14b22c3f45d5bcc8248d07850147304402207134aa39d1a5c51997a7b5b9d821f0eb45b344be7e80401c90992be6f090c3200220505b087b3d87aab21a6eed67008511abb50ae74fe2db21398bda38b8a91166fe0169522102c5e54fb0559fccdc206f819995dd361cef9018394c329e4a190747938d8f126021030c3005c53914c467a5a0af727f6ce62ec50f99c76c769dabd00c1f665cac18a52103d474fb6b20cc07d4fcdc2f665f4b89bf9ae5ade1a8dfc3db54e9ef942053de6b53ae00000000";
+
+    fn test_key() -> Secp256k1PrivateKey {
+        Secp256k1PrivateKey::from_slice(
+            &hex::decode("41f41d69260df4cf277826a9b65a3717e4eeddbeedf637f212ca096576479361")
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_sign_psbt() {
+        let key = test_key();
+        let mut psbt = Psbt::from_base64(UNSIGNED_PSBT).unwrap();
+        assert_eq!(psbt.to_base64(), UNSIGNED_PSBT);
+        assert_eq!(
+            psbt.bip32_derivations(3).unwrap(),
+            vec![(
+                key.public_key().to_bytes()[1..].to_vec(),
+                vec![0x8000_0056, 0x8000_0000, 0x8000_0000, 0, 0]
+            )]
+        );
+
+        for index in 0..4 {
+            assert!(psbt.sign_input(index, &key).unwrap());
+            assert!(!psbt.sign_input(index, &key).unwrap());
+        }
+        assert_eq!(psbt.to_base64(), SIGNED_PSBT);
+
+        psbt.finalize().unwrap();
+        assert_eq!(psbt.to_base64(), FINALIZED_PSBT);
+        let tx = psbt.extract_tx().unwrap();
+        assert_eq!(serialize(&tx).to_hex(), SIGNED_TX);
+
+        let output = finalize_psbt(&PsbtInput {
+            psbt: SIGNED_PSBT.to_string(),
+        })
+        .unwrap();
+        assert_eq!(output.signature, SIGNED_TX);
+        assert_eq!(
+            output.tx_hash,
+            "e37000ab5d15eb6c6fdb955dd0d8e437e13e1dbcea97e82790a72a186cbb9233"
+        );
+    }
+
+    #[test]
+    fn test_sign_psbt_v2() {
+        let key = test_key();
+        let mut psbt = Psbt::from_base64(UNSIGNED_PSBT_V2).unwrap();
+        assert_eq!(psbt.version().unwrap(), 2);
+        assert_eq!(
+            psbt.unsigned_tx().unwrap(),
+            Psbt::from_base64(UNSIGNED_PSBT)
+                .unwrap()
+                .unsigned_tx()
+                .unwrap()
+        );
+
+        for index in 0..4 {
+            assert!(psbt.sign_input(index, &key).unwrap());
+        }
+        psbt.finalize().unwrap();
+        assert_eq!(serialize(&psbt.extract_tx().unwrap()).to_hex(), SIGNED_TX);
+    }
+
+    #[test]
+    fn test_sign_psbt_invalid() {
+        assert_eq!(
+            format!("{}", Psbt::from_base64("cHNidP8=").unwrap_err()),
+            "invalid_psbt"
+        );
+        assert_eq!(
+            format!("{}", Psbt::from_base64(SIGNED_TX).unwrap_err()),
+            "invalid_psbt"
+        );
+
+        let mut psbt = Psbt::from_base64(UNSIGNED_PSBT).unwrap();
+        let other_key = Secp256k1PrivateKey::from_slice(&[1u8; 32]).unwrap();
+        assert!(!psbt.sign_input(1, &other_key).unwrap());
+        assert!(!psbt.sign_input(3, &other_key).unwrap());
+        assert_eq!(
+            format!("{}", psbt.sign_input(4, &test_key()).unwrap_err()),
+            "invalid_psbt_input_index"
+        );
+        assert_eq!(
+            format!("{}", psbt.extract_tx().unwrap_err()),
+            "psbt_input_not_finalized"
+        );
+        assert_eq!(
+            format!("{}", psbt.finalize().unwrap_err()),
+            "psbt_input_not_finalizable"
+        );
+
+        psbt.inputs[1].remove(&vec![0x01]);
+        assert_eq!(
+            format!("{}", psbt.sign_input(1, &test_key()).unwrap_err()),
+            "psbt_missing_utxo"
+        );
+
+        let mut psbt = Psbt::from_base64(UNSIGNED_PSBT).unwrap();
+        psbt.inputs[1].insert(vec![0x03], vec![0x02, 0, 0, 0]);
+        assert_eq!(
+            format!("{}", psbt.sign_input(1, &test_key()).unwrap_err()),
+            "unsupported_sighash_type"
+        );
+
+        // a taproot derivation claiming 2^64 - 1 leaf hashes
+        let mut psbt = Psbt::from_base64(UNSIGNED_PSBT).unwrap();
+        psbt.inputs[3].insert(
+            [vec![0x16], vec![1u8; 32]].concat(),
+            [vec![0xff], vec![0xff; 8]].concat(),
+        );
+        assert_eq!(
+            format!("{}", psbt.bip32_derivations(3).unwrap_err()),
+            "invalid_psbt"
+        );
+    }
+
+    #[test]
+    fn test_sign_multisig_psbt() {
+        let cosigner_key =
+            |x: &str| Secp256k1PrivateKey::from_slice(&hex::decode(x).unwrap()).unwrap();
+        let key_a =
+            cosigner_key("f7c16e665f111e8cb876f6d62a9df4f744aecc8fa1985ea1ee4d6a29dcf7eb88");
+        let key_b =
+            cosigner_key("8d1270fd10b159ab3dae63a57c672cb48124a6f7a051662dc1c2772a6ad8b65e");
+
+        let mut psbt_a = Psbt::from_base64(UNSIGNED_MULTISIG_PSBT).unwrap();
+        let mut psbt_b = psbt_a.clone();
+        for index in 0..3 {
+            assert!(!psbt_a.sign_input(index, &test_key()).unwrap());
+            assert!(psbt_a.sign_input(index, &key_a).unwrap());
+            assert!(!psbt_a.sign_input(index, &key_a).unwrap());
+            assert!(psbt_b.sign_input(index, &key_b).unwrap());
+        }
+        assert_eq!(psbt_a.to_base64(), SIGNED_MULTISIG_PSBT_A);
+        assert_eq!(psbt_b.to_base64(), SIGNED_MULTISIG_PSBT_B);
+        assert_eq!(
+            format!("{}", psbt_a.clone().finalize().unwrap_err()),
+            "psbt_input_not_finalizable"
+        );
+
+        psbt_a.combine(&psbt_b).unwrap();
+        let combined = combine_psbt(&PsbtCombineInput {
+            psbts: vec![
+                SIGNED_MULTISIG_PSBT_B.to_string(),
+                SIGNED_MULTISIG_PSBT_A.to_string(),
+            ],
+        })
+        .unwrap();
+        assert_eq!(combined.psbt, psbt_a.to_base64());
+
+        psbt_a.finalize().unwrap();
+        assert_eq!(psbt_a.to_base64(), FINALIZED_MULTISIG_PSBT);
+        let output = finalize_psbt(&combined).unwrap();
+        assert_eq!(output.signature, SIGNED_MULTISIG_TX);
+        assert_eq!(
+            output.tx_hash,
+            "2c9b3092282d79a1838c036270fc6ab12844ba7c994b06344bfd1dcc70942d56"
+        );
+    }
+
+    #[test]
+    fn test_combine_psbt_invalid() {
+        assert_eq!(
+            format!(
+                "{}",
+                combine_psbt(&PsbtCombineInput { psbts: vec![] }).unwrap_err()
+            ),
+            "missing_psbt"
+        );
+        let input = PsbtCombineInput {
+            psbts: vec![
+                UNSIGNED_MULTISIG_PSBT.to_string(),
+                UNSIGNED_PSBT.to_string(),
+            ],
+        };
+        assert_eq!(
+            format!("{}", combine_psbt(&input).unwrap_err()),
+            "psbt_mismatch"
+        );
+    }
+}
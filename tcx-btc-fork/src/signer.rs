@@ -5,11 +5,13 @@ use bitcoin::{OutPoint, Script, Transaction, TxIn, TxOut};
 use bitcoin_hashes::Hash;
 
 use crate::Result;
+use bitcoin::blockdata::opcodes;
 use bitcoin::blockdata::script::Builder;
 use bitcoin::consensus::serialize;
 use std::str::FromStr;
 
 use crate::address::BtcForkAddress;
+use crate::taproot::{schnorr_sign, taproot_sighash, tweak_private_key};
 use crate::transaction::{BtcForkSignedTxOutput, BtcForkTxInput, Utxo};
 use bitcoin::util::bip143::SighashComponents;
 use bitcoin_hashes::hash160;
@@ -23,8 +25,25 @@ use tcx_primitive::{
     TypedDeterministicPublicKey,
 };
 
-const DUST: u64 = 546;
+pub(crate) const DUST: u64 = 546;
 const SIGHASH_ALL: u8 = 0x01;
+const FINAL_SEQUENCE: u32 = 0xFFFF_FFFF;
+/// the highest sequence which still enables the lock time
+const LOCK_TIME_SEQUENCE: u32 = 0xFFFF_FFFE;
+/// the highest sequence signalling BIP125 replaceability
+pub(crate) const RBF_SEQUENCE: u32 = 0xFFFF_FFFD;
+/// the data size limit of the standard OP_RETURN outputs
+const MAX_OP_RETURN_SIZE: usize = 80;
+
+/// The `OP_RETURN <data>` script of the hex encoded `data`
+pub fn op_return_script(data: &str) -> Result<Script> {
+    let data = hex::decode(data).map_err(|_| format_err!("invalid_op_return_data"))?;
+    ensure!(data.len() <= MAX_OP_RETURN_SIZE, "op_return_data_too_long");
+    Ok(Builder::new()
+        .push_opcode(opcodes::all::OP_RETURN)
+        .push_slice(&data)
+        .into_script())
+}
 
 pub trait ScriptPubKeyComponent {
     fn address_script_like(target_addr: &str, pub_key: &bitcoin::PublicKey) -> Result<Script>;
@@ -48,12 +67,16 @@ impl<S: ScriptPubKeyComponent + Address, T: BitcoinTransactionSignComponent>
         address: &str,
         tx: &BitcoinForkSinger<S, T>,
     ) -> Result<BtcForkSignedTxOutput> {
-        let change_address = if self.determinable() {
+        let (change_address, change_path) = if self.determinable() {
             let dpk = self.find_deterministic_public_key(symbol, address)?;
-            tx.change_address(&dpk)?
+            let seg_wit = self
+                .account(symbol, address)
+                .map_or(tx.coin_info.seg_wit.clone(), |x| x.seg_wit.clone());
+            tx.change_address(&dpk, &seg_wit)?
         } else {
-            S::address_script_pub_key(&address)?
+            (address.to_string(), "".to_string())
         };
+        let change_script = S::address_script_pub_key(&change_address)?;
 
         let mut sks = vec![];
 
@@ -73,7 +96,12 @@ impl<S: ScriptPubKeyComponent + Address, T: BitcoinTransactionSignComponent>
             }
         }
 
-        tx.sign_transaction(&sks, change_address)
+        let mut output = tx.sign_transaction(&sks, change_script)?;
+        if tx.change_amount() >= DUST as i64 {
+            output.change_address = change_address;
+            output.change_path = change_path;
+        }
+        Ok(output)
     }
 }
 
@@ -89,20 +117,41 @@ impl<S: ScriptPubKeyComponent + Address, T: BitcoinTransactionSignComponent>
         }
     }
 
-    fn receive_script_pubkey(&self) -> Result<Script> {
-        S::address_script_pub_key(&self.tx_input.to)
+    /// The `to` and `amount` pair, unless only `outputs` are set, followed by the `outputs`
+    fn receivers(&self) -> Vec<(&str, i64)> {
+        let mut receivers = vec![];
+        if !self.tx_input.to.is_empty() || self.tx_input.outputs.is_empty() {
+            receivers.push((self.tx_input.to.as_str(), self.tx_input.amount));
+        }
+        for output in &self.tx_input.outputs {
+            receivers.push((output.address.as_str(), output.amount));
+        }
+        receivers
     }
 
-    fn change_address(&self, dpk: &TypedDeterministicPublicKey) -> Result<Script> {
+    /// The explicit change address, otherwise the address of the account key at the change path
+    /// `1/{change_address_index}` in the `seg_wit` script type of the account, with that path
+    fn change_address(
+        &self,
+        dpk: &TypedDeterministicPublicKey,
+        seg_wit: &str,
+    ) -> Result<(String, String)> {
         if !self.tx_input.change_address.is_empty() {
-            S::address_script_pub_key(&self.tx_input.change_address)
-        } else {
-            let from = &self.tx_input.unspents.first().expect("first_utxo").address;
-            // todo: address is error
-            let _change_path = format!("1/{}", &self.tx_input.change_address_index);
-            let pub_key = dpk.public_key().as_secp256k1()?.0;
-            S::address_script_like(&from, &pub_key)
+            return Ok((self.tx_input.change_address.clone(), "".to_string()));
         }
+
+        let change_path = format!("1/{}", self.tx_input.change_address_index);
+        let pub_key = dpk.derive(&change_path)?.public_key();
+        let mut coin_info = self.coin_info.clone();
+        coin_info.seg_wit = seg_wit.to_string();
+        Ok((S::from_public_key(&pub_key, &coin_info)?, change_path))
+    }
+
+    /// What is left of the unspents after paying the receivers and the fee
+    fn change_amount(&self) -> i64 {
+        let total_amount: i64 = self.tx_input.unspents.iter().map(|x| x.amount).sum();
+        let send_amount: i64 = self.receivers().iter().map(|(_, amount)| amount).sum();
+        total_amount - send_amount - self.tx_input.fee
     }
 
     pub fn derive_pub_key_at_path(xpub: &str, child_path: &str) -> Result<bitcoin::PublicKey> {
@@ -120,25 +169,33 @@ impl<S: ScriptPubKeyComponent + Address, T: BitcoinTransactionSignComponent>
             total_amount += unspent.amount;
         }
 
-        ensure!(
-            self.tx_input.amount >= DUST as i64,
-            "amount_less_than_minimum"
-        );
+        let receivers = self.receivers();
+        let mut send_amount = 0;
+        for (_, amount) in &receivers {
+            ensure!(*amount >= DUST as i64, "amount_less_than_minimum");
+            send_amount += amount;
+        }
 
         ensure!(
-            total_amount >= (self.tx_input.amount + self.tx_input.fee),
+            total_amount >= (send_amount + self.tx_input.fee),
             "total amount must ge amount + fee"
         );
 
         let mut tx_outs: Vec<TxOut> = vec![];
 
-        let receive_script_pubkey = self.receive_script_pubkey()?;
-        let receiver_tx_out = TxOut {
-            value: self.tx_input.amount as u64,
-            script_pubkey: receive_script_pubkey,
-        };
-        tx_outs.push(receiver_tx_out);
-        let change_amount = total_amount - self.tx_input.amount - self.tx_input.fee;
+        for (address, amount) in receivers {
+            tx_outs.push(TxOut {
+                value: amount as u64,
+                script_pubkey: S::address_script_pub_key(address)?,
+            });
+        }
+        if !self.tx_input.op_return.is_empty() {
+            tx_outs.push(TxOut {
+                value: 0,
+                script_pubkey: op_return_script(&self.tx_input.op_return)?,
+            });
+        }
+        let change_amount = self.change_amount();
 
         if change_amount >= DUST as i64 {
             let change_tx_out = TxOut {
@@ -150,21 +207,44 @@ impl<S: ScriptPubKeyComponent + Address, T: BitcoinTransactionSignComponent>
         Ok(tx_outs)
     }
 
-    fn tx_inputs(&self) -> Vec<TxIn> {
+    /// An unset (zero) utxo sequence falls back to the one signalling RBF when requested,
+    /// otherwise to the one that still lets the lock time take effect.
+    fn tx_inputs(&self) -> Result<Vec<TxIn>> {
+        let default_sequence = if self.tx_input.rbf {
+            RBF_SEQUENCE
+        } else if self.tx_input.lock_time > 0 {
+            LOCK_TIME_SEQUENCE
+        } else {
+            FINAL_SEQUENCE
+        };
         let mut tx_inputs: Vec<TxIn> = vec![];
 
         for unspent in &self.tx_input.unspents {
+            ensure!(
+                unspent.sequence >= 0 && unspent.sequence <= i64::from(FINAL_SEQUENCE),
+                "invalid_sequence"
+            );
+            let sequence = if unspent.sequence == 0 {
+                default_sequence
+            } else {
+                unspent.sequence as u32
+            };
             tx_inputs.push(TxIn {
                 previous_output: OutPoint {
                     txid: bitcoin::hash_types::Txid::from_hex(&unspent.tx_hash).expect("tx_hash"),
                     vout: unspent.vout as u32,
                 },
                 script_sig: Script::new(),
-                sequence: 0xFFFF_FFFF,
+                sequence,
                 witness: vec![],
             });
         }
-        tx_inputs
+        // the lock time is only enforced when an input is not final
+        ensure!(
+            self.tx_input.lock_time == 0 || tx_inputs.iter().any(|x| x.sequence != FINAL_SEQUENCE),
+            "lock_time_disabled_by_sequence"
+        );
+        Ok(tx_inputs)
     }
 
     pub fn sign_transaction(
@@ -173,10 +253,10 @@ impl<S: ScriptPubKeyComponent + Address, T: BitcoinTransactionSignComponent>
         change_addr_pubkey: Script,
     ) -> Result<BtcForkSignedTxOutput> {
         let tx_outs = self.tx_outs(change_addr_pubkey)?;
-        let tx_inputs = self.tx_inputs();
+        let tx_inputs = self.tx_inputs()?;
         let tx = Transaction {
             version: T::tx_version(),
-            lock_time: 0,
+            lock_time: self.tx_input.lock_time,
             input: tx_inputs,
             output: tx_outs,
         };
@@ -187,6 +267,8 @@ impl<S: ScriptPubKeyComponent + Address, T: BitcoinTransactionSignComponent>
         Ok(BtcForkSignedTxOutput {
             signature: tx_bytes.to_hex(),
             tx_hash: signed_tx.txid().into_inner().to_hex(),
+            change_address: "".to_string(),
+            change_path: "".to_string(),
         })
     }
 }
@@ -281,6 +363,40 @@ impl BitcoinTransactionSignComponent for SegWitTransactionSignComponent {
     }
 }
 
+/// Spends native P2WPKH outputs, the script sig stays empty and the witness carries the
+/// signature and the public key
+pub struct NativeSegWitTransactionSignComponent {}
+
+impl BitcoinTransactionSignComponent for NativeSegWitTransactionSignComponent {
+    fn sign_inputs(
+        tx: &Transaction,
+        unspents: &[Utxo],
+        keys: &[impl PrivateKey],
+    ) -> Result<Transaction> {
+        let witnesses = SegWitTransactionSignComponent::witness_sign(tx, unspents, keys)?;
+        let input_with_sigs = tx
+            .input
+            .iter()
+            .zip(witnesses)
+            .map(|(txin, (sig, pub_key))| TxIn {
+                script_sig: Script::new(),
+                witness: vec![sig, pub_key],
+                ..*txin
+            })
+            .collect();
+        Ok(Transaction {
+            version: Self::tx_version(),
+            lock_time: tx.lock_time,
+            input: input_with_sigs,
+            output: tx.output.clone(),
+        })
+    }
+
+    fn tx_version() -> i32 {
+        2
+    }
+}
+
 pub struct LegacyTransactionSignComponent<H: SignHasher> {
     _maker: PhantomData<H>,
 }
@@ -362,17 +478,69 @@ impl<H: SignHasher> BitcoinTransactionSignComponent for LegacyTransactionSignCom
     }
 }
 
+/// Spends BIP86 taproot outputs through the key path, every input is signed by a BIP340
+/// signature of its BIP341 sighash
+pub struct TaprootTransactionSignComponent {}
+
+impl BitcoinTransactionSignComponent for TaprootTransactionSignComponent {
+    fn sign_inputs(
+        tx: &Transaction,
+        unspents: &[Utxo],
+        keys: &[impl PrivateKey],
+    ) -> Result<Transaction> {
+        let prevouts = unspents
+            .iter()
+            .map(|x| {
+                Ok(TxOut {
+                    value: x.amount as u64,
+                    script_pubkey: BtcForkAddress::from_str(&x.address)?.script_pubkey(),
+                })
+            })
+            .collect::<Result<Vec<TxOut>>>()?;
+
+        let mut input_with_sigs = vec![];
+        for (i, txin) in tx.input.iter().enumerate() {
+            let hash = taproot_sighash(tx, i, &prevouts)?;
+            let tweaked_key = tweak_private_key(&keys[i].to_bytes())?;
+            // the nonce is derived from the key and the sighash, no auxiliary randomness
+            let signature = schnorr_sign(&tweaked_key, &hash, &[0u8; 32])?;
+            input_with_sigs.push(TxIn {
+                script_sig: Script::new(),
+                witness: vec![signature],
+                ..*txin
+            });
+        }
+        Ok(Transaction {
+            version: Self::tx_version(),
+            lock_time: tx.lock_time,
+            input: input_with_sigs,
+            output: tx.output.clone(),
+        })
+    }
+
+    fn tx_version() -> i32 {
+        2
+    }
+}
+
 pub type BtcForkTransaction =
     BitcoinForkSinger<BtcForkAddress, LegacyTransactionSignComponent<LegacySignHasher>>;
 
 pub type BtcForkSegWitTransaction =
     BitcoinForkSinger<BtcForkAddress, SegWitTransactionSignComponent>;
 
+pub type BtcForkNativeSegWitTransaction =
+    BitcoinForkSinger<BtcForkAddress, NativeSegWitTransactionSignComponent>;
+
+pub type BtcForkTaprootTransaction =
+    BitcoinForkSinger<BtcForkAddress, TaprootTransactionSignComponent>;
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     use super::BitcoinForkSinger;
+    use crate::transaction::TxOutput;
     use tcx_chain::{Keystore, TransactionSigner};
     use tcx_constants::coin_info::coin_info_from_param;
     use tcx_primitive::Secp256k1PrivateKey;
@@ -397,6 +565,10 @@ mod tests {
             change_address: "".to_string(),
             network: "TESTNET".to_string(),
             seg_wit: "NONE".to_string(),
+            outputs: vec![],
+            op_return: "".to_string(),
+            rbf: false,
+            lock_time: 0,
         };
         let coin_info = coin_info_from_param("LITECOIN", "TESTNET", "NONE", "").unwrap();
         let tran =
@@ -440,6 +612,10 @@ mod tests {
             change_address: "mkeNU5nVnozJiaACDELLCsVUc8Wxoh1rQN".to_string(),
             network: "TESTNET".to_string(),
             seg_wit: "NONE".to_string(),
+            outputs: vec![],
+            op_return: "".to_string(),
+            rbf: false,
+            lock_time: 0,
         };
         let coin_info = coin_info_from_param("LITECOIN", "TESTNET", "NONE", "").unwrap();
         let tran =
@@ -498,6 +674,10 @@ mod tests {
             change_address: "".to_string(),
             network: "TESTNET".to_string(),
             seg_wit: "NONE".to_string(),
+            outputs: vec![],
+            op_return: "".to_string(),
+            rbf: false,
+            lock_time: 0,
         };
         let coin_info = coin_info_from_param("LITECOIN", "TESTNET", "NONE", "").unwrap();
         let tran =
@@ -515,9 +695,15 @@ mod tests {
             .unwrap();
         assert_eq!(
             expected.tx_hash,
-            "1d95b4e3cac9b81fb9459799cb6c7ffc38b5b78ee38fdcfdb972dc17f77db761"
+            "96fe3a5ff0e01e533f19642a0bd05ed4925dfdc271124bc08c3aa4a8bdb9d5c8"
         );
-        assert_eq!(expected.signature, "010000000201a1d4a6b051e732341c0101a62488051bb5bf969d1f15324bbf6a1d2035c957000000006a47304402202b007c693e784805eaae1e68a35d8d4a15331a29aa19a27fecd5a530cd6bba95022000dfa238c90a5a48c66817e0a0a69e275d159e566c1eb661b6c362b249f8aa510121033d710ab45bb54ac99618ad23b3c1da661631aa25f23bfe9d22b41876f1d46e4effffffff00a1d4a6b051e732341c0101a62488051bb5bf969d1f15324bbf6a1d2035c957000000006a4730440220320b151cab9701c0e031310e4c7e6ff4712b291b79bee2a7f20bd2acd40a5a93022030fc72cf9f2f66aa5d88d97ef0e3c537dfa11f9d1ef6cac7bd68e45d3cb410700121033d710ab45bb54ac99618ad23b3c1da661631aa25f23bfe9d22b41876f1d46e4effffffff02e0c81000000000001976a914461bf9360ec1bc9fe438df19ef36c7c2bb26ef8288ac92a40d00000000001976a9145ec105a23edc97d73a8de1e49d498684c40aa84988ac00000000");
+        assert_eq!(expected.signature, "010000000201a1d4a6b051e732341c0101a62488051bb5bf969d1f15324bbf6a1d2035c957000000006b483045022100a49798664e490075f9d111c6b6e8541781a5a88df1b95eb910dd307298ead4e802203adb4a21f2e680e1d05f6346ec25b1077f60e58c6289606cc9dad15698b5368d0121033d710ab45bb54ac99618ad23b3c1da661631aa25f23bfe9d22b41876f1d46e4effffffff00a1d4a6b051e732341c0101a62488051bb5bf969d1f15324bbf6a1d2035c957000000006a473044022100c7e2dba307022d45067e7b3eceb2b288f49037f43c8bac271ccc831f250b9438021f14103613f41f6d6811f70359077ae96dc2055fcb9dd5aff21469e1fb51a9870121033d710ab45bb54ac99618ad23b3c1da661631aa25f23bfe9d22b41876f1d46e4effffffff02e0c81000000000001976a914461bf9360ec1bc9fe438df19ef36c7c2bb26ef8288ac92a40d00000000001976a9143770c8c6671d27e2a9f4502d74932bf740c1ff8688ac00000000");
+        // the change goes to m/44'/1'/0'/1/1 of the account
+        assert_eq!(
+            expected.change_address,
+            "mka6ZzaxDdD49uzxMrtz9H8NVxCEoVMHu3"
+        );
+        assert_eq!(expected.change_path, "1/1");
     }
 
     #[test]
@@ -543,6 +729,10 @@ mod tests {
             change_address: "mkeNU5nVnozJiaACDELLCsVUc8Wxoh1rQN".to_string(),
             network: "TESTNET".to_string(),
             seg_wit: "NONE".to_string(),
+            outputs: vec![],
+            op_return: "".to_string(),
+            rbf: false,
+            lock_time: 0,
         };
         let coin_info = coin_info_from_param("LITECOIN", "TESTNET", "NONE", "").unwrap();
         let tran =
@@ -588,6 +778,10 @@ mod tests {
             change_address: "mkeNU5nVnozJiaACDELLCsVUc8Wxoh1rQN".to_string(),
             network: "TESTNET".to_string(),
             seg_wit: "NONE".to_string(),
+            outputs: vec![],
+            op_return: "".to_string(),
+            rbf: false,
+            lock_time: 0,
         };
         let coin_info = coin_info_from_param("LITECOIN", "TESTNET", "NONE", "").unwrap();
         let tran =
@@ -632,6 +826,10 @@ mod tests {
             change_address: "mkeNU5nVnozJiaACDELLCsVUc8Wxoh1rQN".to_string(),
             network: "TESTNET".to_string(),
             seg_wit: "NONE".to_string(),
+            outputs: vec![],
+            op_return: "".to_string(),
+            rbf: false,
+            lock_time: 0,
         };
         let coin_info = coin_info_from_param("LITECOIN", "TESTNET", "NONE", "").unwrap();
         let tran =
@@ -671,6 +869,10 @@ mod tests {
             change_address: "".to_string(),
             network: "TESTNET".to_string(),
             seg_wit: "NONE".to_string(),
+            outputs: vec![],
+            op_return: "".to_string(),
+            rbf: false,
+            lock_time: 0,
         };
         let tran =
             BitcoinForkSinger::<BtcForkAddress, LegacyTransactionSignComponent<LegacySignHasher>> {
@@ -714,6 +916,10 @@ mod tests {
             change_address: "".to_string(),
             network: "TESTNET".to_string(),
             seg_wit: "NONE".to_string(),
+            outputs: vec![],
+            op_return: "".to_string(),
+            rbf: false,
+            lock_time: 0,
         };
         let tran =
             BitcoinForkSinger::<BtcForkAddress, LegacyTransactionSignComponent<LegacySignHasher>> {
@@ -759,6 +965,10 @@ mod tests {
                 change_address: "".to_string(),
                 network: "TESTNET".to_string(),
                 seg_wit: "NONE".to_string(),
+                outputs: vec![],
+                op_return: "".to_string(),
+                rbf: false,
+                lock_time: 0,
             };
             let tran = BitcoinForkSinger::<
                 BtcForkAddress,
@@ -801,6 +1011,10 @@ mod tests {
             change_address: "mszYqVnqKoQx4jcTdJXxwKAissE3Jbrrc1".to_string(),
             network: "TESTNET".to_string(),
             seg_wit: "NONE".to_string(),
+            outputs: vec![],
+            op_return: "".to_string(),
+            rbf: false,
+            lock_time: 0,
         };
         let coin_info = coin_info_from_param("LITECOIN", "TESTNET", "NONE", "").unwrap();
         let tran =
@@ -821,6 +1035,223 @@ mod tests {
         assert_eq!(actual.signature, "01000000015884e5db9de218238671572340b207ee85b628074e7e467096c267266baf77a4000000006b483045022100eefdd6cace70ee64d6a29bca5f52c338b2b3ecf6e6c7b222818c9bba60f094fb022053535e23a77afc7255c18ae8c6e6bf0f8b6e3f552d08519455714cbe59e489cf01210223078d2942df62c45621d209fab84ea9a7a23346201b7727b9b45a29c4e76f5effffffff0220a10700000000001976a9147821c0a3768aa9d1a37e16cf76002aef5373f1a888ac801a0600000000001976a91488d9931ea73d60eaf7e5671efc0552b912911f2a88ac00000000");
     }
 
+    #[test]
+    fn test_sign_ltc_multi_outputs() {
+        let unspents = vec![Utxo {
+            tx_hash: "a477af6b2667c29670467e4e0728b685ee07b240235771862318e29ddbe58458".to_string(),
+            vout: 0,
+            amount: 1000000,
+            address: "mszYqVnqKoQx4jcTdJXxwKAissE3Jbrrc1".to_string(),
+            script_pub_key: "76a91488d9931ea73d60eaf7e5671efc0552b912911f2a88ac".to_string(),
+            derived_path: "0/0".to_string(),
+            sequence: 0,
+        }];
+        let output = |address: &str, amount: i64| TxOutput {
+            address: address.to_string(),
+            amount,
+        };
+        let mut tx_input = BtcForkTxInput {
+            to: "mrU9pEmAx26HcbKVrABvgL7AwA5fjNFoDc".to_string(),
+            amount: 300000,
+            unspents,
+            fee: 100000,
+            change_address_index: 1u32,
+            change_address: "".to_string(),
+            network: "TESTNET".to_string(),
+            seg_wit: "NONE".to_string(),
+            outputs: vec![
+                output("mkeNU5nVnozJiaACDELLCsVUc8Wxoh1rQN", 200000),
+                output("QLfctE6KMv3ZzQod6UA37w3EPTuLS4tg1T", 100000),
+            ],
+            op_return: "".to_string(),
+            rbf: false,
+            lock_time: 0,
+        };
+        let coin_info = coin_info_from_param("LITECOIN", "TESTNET", "NONE", "").unwrap();
+        let prv_key =
+            Secp256k1PrivateKey::from_wif("cSBnVM4xvxarwGQuAfQFwqDg9k5tErHUHzgWsEfD4zdwUasvqRVY")
+                .unwrap();
+        let change_addr = BtcForkAddress::from_str("mgBCJAsvzgT2qNNeXsoECg2uPKrUsZ76up").unwrap();
+
+        let tran = BtcForkTransaction::new(tx_input.clone(), coin_info.clone());
+        let expected = tran
+            .sign_transaction(&vec![prv_key.clone()], change_addr.script_pubkey())
+            .unwrap();
+        assert_eq!(expected.signature, "01000000015884e5db9de218238671572340b207ee85b628074e7e467096c267266baf77a4000000006a473044022057a4fe7002873905971febe833aed949cc020958733b0fbf070a391c23cf552202203ee8e12d11521b381bc29440090174bd72d4e72dad3cea1e20d090ffeb84b95801210223078d2942df62c45621d209fab84ea9a7a23346201b7727b9b45a29c4e76f5effffffff04e0930400000000001976a9147821c0a3768aa9d1a37e16cf76002aef5373f1a888ac400d0300000000001976a914383fb81cb0a3fc724b5e08cf8bbd404336d711f688aca08601000000000017a91400aff21f24bc08af58e41e4186d8492a10b84f9e87e0930400000000001976a914073b7eae2823efa349e3b9155b8a735526463a0f88ac00000000");
+        assert_eq!(
+            expected.tx_hash,
+            "92a8389821eb25eb9ba3070ff7cb73ba9972c3e7c841e5d6b887729897adc70a"
+        );
+
+        // the receivers can all be given as outputs
+        tx_input
+            .outputs
+            .insert(0, output(&tx_input.to, tx_input.amount));
+        tx_input.to = "".to_string();
+        tx_input.amount = 0;
+        let tran = BtcForkTransaction::new(tx_input.clone(), coin_info.clone());
+        let ret = tran
+            .sign_transaction(&vec![prv_key.clone()], change_addr.script_pubkey())
+            .unwrap();
+        assert_eq!(ret, expected);
+
+        tx_input.outputs[2].amount = 545;
+        let tran = BtcForkTransaction::new(tx_input.clone(), coin_info.clone());
+        let ret = tran.sign_transaction(&vec![prv_key.clone()], change_addr.script_pubkey());
+        assert_eq!(
+            format!("{}", ret.err().unwrap()),
+            "amount_less_than_minimum"
+        );
+
+        tx_input.outputs[2].amount = 500001;
+        let tran = BtcForkTransaction::new(tx_input, coin_info);
+        let ret = tran.sign_transaction(&vec![prv_key], change_addr.script_pubkey());
+        assert_eq!(
+            format!("{}", ret.err().unwrap()),
+            "total amount must ge amount + fee"
+        );
+    }
+
+    #[test]
+    fn test_sign_ltc_op_return() {
+        let unspents = vec![Utxo {
+            tx_hash: "a477af6b2667c29670467e4e0728b685ee07b240235771862318e29ddbe58458".to_string(),
+            vout: 0,
+            amount: 1000000,
+            address: "mszYqVnqKoQx4jcTdJXxwKAissE3Jbrrc1".to_string(),
+            script_pub_key: "76a91488d9931ea73d60eaf7e5671efc0552b912911f2a88ac".to_string(),
+            derived_path: "0/0".to_string(),
+            sequence: 0,
+        }];
+        let mut tx_input = BtcForkTxInput {
+            to: "mrU9pEmAx26HcbKVrABvgL7AwA5fjNFoDc".to_string(),
+            amount: 500000,
+            unspents,
+            fee: 100000,
+            change_address_index: 1u32,
+            change_address: "".to_string(),
+            network: "TESTNET".to_string(),
+            seg_wit: "NONE".to_string(),
+            outputs: vec![],
+            // an omni simple send of 8 USDT
+            op_return: "6f6d6e69000000000000001f000000002faf0800".to_string(),
+            rbf: false,
+            lock_time: 0,
+        };
+        let coin_info = coin_info_from_param("LITECOIN", "TESTNET", "NONE", "").unwrap();
+        let prv_key =
+            Secp256k1PrivateKey::from_wif("cSBnVM4xvxarwGQuAfQFwqDg9k5tErHUHzgWsEfD4zdwUasvqRVY")
+                .unwrap();
+        let change_addr = BtcForkAddress::from_str("mgBCJAsvzgT2qNNeXsoECg2uPKrUsZ76up").unwrap();
+
+        let tran = BtcForkTransaction::new(tx_input.clone(), coin_info.clone());
+        let expected = tran
+            .sign_transaction(&vec![prv_key.clone()], change_addr.script_pubkey())
+            .unwrap();
+        assert_eq!(expected.signature, "01000000015884e5db9de218238671572340b207ee85b628074e7e467096c267266baf77a4000000006b483045022100a280d91eadb510e77c590fdb10e2f35dbf05e94fd5346ee1fd978719e6e9324302207d6663271f9e4408618686a4c8086fbd94e6ce3ab34e0029191db557ccb7007701210223078d2942df62c45621d209fab84ea9a7a23346201b7727b9b45a29c4e76f5effffffff0320a10700000000001976a9147821c0a3768aa9d1a37e16cf76002aef5373f1a888ac0000000000000000166a146f6d6e69000000000000001f000000002faf0800801a0600000000001976a914073b7eae2823efa349e3b9155b8a735526463a0f88ac00000000");
+        assert_eq!(
+            expected.tx_hash,
+            "e368ceadeb5bcea6b510e39b8b46f8cc726cd535932b12b488458daf137aa247"
+        );
+
+        tx_input.op_return = "00".repeat(81);
+        let tran = BtcForkTransaction::new(tx_input.clone(), coin_info.clone());
+        let ret = tran.sign_transaction(&vec![prv_key.clone()], change_addr.script_pubkey());
+        assert_eq!(format!("{}", ret.err().unwrap()), "op_return_data_too_long");
+
+        tx_input.op_return = "memo".to_string();
+        let tran = BtcForkTransaction::new(tx_input, coin_info);
+        let ret = tran.sign_transaction(&vec![prv_key], change_addr.script_pubkey());
+        assert_eq!(format!("{}", ret.err().unwrap()), "invalid_op_return_data");
+    }
+
+    #[test]
+    fn test_sign_ltc_sequence_and_lock_time() {
+        let unspents = vec![Utxo {
+            tx_hash: "a477af6b2667c29670467e4e0728b685ee07b240235771862318e29ddbe58458".to_string(),
+            vout: 0,
+            amount: 1000000,
+            address: "mszYqVnqKoQx4jcTdJXxwKAissE3Jbrrc1".to_string(),
+            script_pub_key: "76a91488d9931ea73d60eaf7e5671efc0552b912911f2a88ac".to_string(),
+            derived_path: "0/0".to_string(),
+            sequence: 0,
+        }];
+        let mut tx_input = BtcForkTxInput {
+            to: "mrU9pEmAx26HcbKVrABvgL7AwA5fjNFoDc".to_string(),
+            amount: 500000,
+            unspents,
+            fee: 100000,
+            change_address_index: 1u32,
+            change_address: "".to_string(),
+            network: "TESTNET".to_string(),
+            seg_wit: "NONE".to_string(),
+            outputs: vec![],
+            op_return: "".to_string(),
+            rbf: true,
+            lock_time: 1234567,
+        };
+        let coin_info = coin_info_from_param("LITECOIN", "TESTNET", "NONE", "").unwrap();
+        let prv_key =
+            Secp256k1PrivateKey::from_wif("cSBnVM4xvxarwGQuAfQFwqDg9k5tErHUHzgWsEfD4zdwUasvqRVY")
+                .unwrap();
+        let change_addr = BtcForkAddress::from_str("mgBCJAsvzgT2qNNeXsoECg2uPKrUsZ76up").unwrap();
+
+        let tran = BtcForkTransaction::new(tx_input.clone(), coin_info.clone());
+        let expected = tran
+            .sign_transaction(&vec![prv_key.clone()], change_addr.script_pubkey())
+            .unwrap();
+        assert_eq!(expected.signature, "01000000015884e5db9de218238671572340b207ee85b628074e7e467096c267266baf77a4000000006b483045022100b9b060dcedaa888fdb892dfa376c5c304ca790b2f30cd2a0dcd069f5890c991202203f492ea82019cf710a581066632d9da020a91ffdd56ffaca97328b8f420d04e601210223078d2942df62c45621d209fab84ea9a7a23346201b7727b9b45a29c4e76f5efdffffff0220a10700000000001976a9147821c0a3768aa9d1a37e16cf76002aef5373f1a888ac801a0600000000001976a914073b7eae2823efa349e3b9155b8a735526463a0f88ac87d61200");
+        assert_eq!(
+            expected.tx_hash,
+            "110451f57730aac6fb3fb97e20e38bbfba34772720db39fd4742085b0e60c84a"
+        );
+
+        // the lock time alone makes the inputs non final
+        tx_input.rbf = false;
+        let tran = BtcForkTransaction::new(tx_input.clone(), coin_info.clone());
+        let expected = tran
+            .sign_transaction(&vec![prv_key.clone()], change_addr.script_pubkey())
+            .unwrap();
+        assert_eq!(expected.signature, "01000000015884e5db9de218238671572340b207ee85b628074e7e467096c267266baf77a4000000006a473044022040411c9adacfbdadd23e35e5470eba8d542d6debbc10546ae533ca215a527d5b02201c17a4dc9c1d693714cc877da84286cb8f68f8e7a71c4dacb56ff9e8eca9a9bb01210223078d2942df62c45621d209fab84ea9a7a23346201b7727b9b45a29c4e76f5efeffffff0220a10700000000001976a9147821c0a3768aa9d1a37e16cf76002aef5373f1a888ac801a0600000000001976a914073b7eae2823efa349e3b9155b8a735526463a0f88ac87d61200");
+
+        // the utxo sequence takes precedence
+        tx_input.lock_time = 0;
+        tx_input.unspents[0].sequence = 0xFFFF_FFF0;
+        let tran = BtcForkTransaction::new(tx_input.clone(), coin_info.clone());
+        let expected = tran
+            .sign_transaction(&vec![prv_key.clone()], change_addr.script_pubkey())
+            .unwrap();
+        assert_eq!(expected.signature, "01000000015884e5db9de218238671572340b207ee85b628074e7e467096c267266baf77a4000000006a473044022076100866caa9d7554362464fe8ab6fb3d5a18d721f0623eb81817f1225f642fd0220256f91d8862942328e1ba9c5e79bcb136b643cb13999e8f98f1a2f88b715e94001210223078d2942df62c45621d209fab84ea9a7a23346201b7727b9b45a29c4e76f5ef0ffffff0220a10700000000001976a9147821c0a3768aa9d1a37e16cf76002aef5373f1a888ac801a0600000000001976a914073b7eae2823efa349e3b9155b8a735526463a0f88ac00000000");
+
+        tx_input.lock_time = 1234567;
+        tx_input.unspents[0].sequence = 0xFFFF_FFFF;
+        let tran = BtcForkTransaction::new(tx_input.clone(), coin_info.clone());
+        let ret = tran.sign_transaction(&vec![prv_key.clone()], change_addr.script_pubkey());
+        assert_eq!(
+            format!("{}", ret.err().unwrap()),
+            "lock_time_disabled_by_sequence"
+        );
+
+        tx_input.unspents[0].sequence = -1;
+        let tran = BtcForkTransaction::new(tx_input, coin_info);
+        let ret = tran.sign_transaction(&vec![prv_key], change_addr.script_pubkey());
+        assert_eq!(format!("{}", ret.err().unwrap()), "invalid_sequence");
+    }
+
+    #[test]
+    fn test_op_return_script() {
+        assert_eq!(
+            op_return_script("").unwrap().to_bytes(),
+            hex::decode("6a00").unwrap()
+        );
+        // 76 to 80 bytes are pushed with OP_PUSHDATA1
+        let data = "ab".repeat(80);
+        assert_eq!(
+            op_return_script(&data).unwrap().to_bytes(),
+            hex::decode(format!("6a4c50{}", data)).unwrap()
+        );
+    }
+
     #[test]
     fn test_sign_segwit_ltc() {
         let unspents = vec![Utxo {
@@ -841,6 +1272,10 @@ mod tests {
             change_address: "".to_string(),
             network: "".to_string(),
             seg_wit: "".to_string(),
+            outputs: vec![],
+            op_return: "".to_string(),
+            rbf: false,
+            lock_time: 0,
         };
         let coin_info = coin_info_from_param("LITECOIN", "MAINNET", "NONE", "").unwrap();
         let tran = BitcoinForkSinger::<BtcForkAddress, SegWitTransactionSignComponent> {
@@ -862,4 +1297,123 @@ mod tests {
             .unwrap();
         assert_eq!(expected.signature, "020000000001018bba45b98e54a14d79ca2a5e253f727bff45cf58b5ac5421dd6a37756eb668e801000000171600147b03478d2f7c984179084baa38f790ed1d37629bffffffff01c01f2e010000000017a91400aff21f24bc08af58e41e4186d8492a10b84f9e8702483045022100d0cc3d94c7b7b34fdcc2adc4fd3f735560407581afd6caa11c8d04b963a048a00220777d98e0122fe97206875f49556a401dfc449739ec30e44cb9ed9b92a0b3ff1b01210209c629c64829ec2e99703600ee86c7161a9ed13213e714726210274c29cf780900000000");
     }
+
+    #[test]
+    fn test_sign_native_segwit_ltc() {
+        let unspents = vec![Utxo {
+            tx_hash: "e868b66e75376add2154acb558cf45ff7b723f255e2aca794da1548eb945ba8b".to_string(),
+            vout: 1,
+            amount: 19850000,
+            address: "ltc1q0vp50rf00jvyz7ggfw4r3ausa5wnwc5mk7auz9".to_string(),
+            script_pub_key: "00147b03478d2f7c984179084baa38f790ed1d37629b".to_string(),
+            derived_path: "1/0".to_string(),
+            sequence: 0,
+        }];
+        let tx_input = BtcForkTxInput {
+            to: "M7xo1Mi1gULZSwgvu7VVEvrwMRqngmFkVd".to_string(),
+            amount: 19800000,
+            unspents,
+            fee: 50000,
+            change_address_index: 1u32,
+            change_address: "".to_string(),
+            network: "".to_string(),
+            seg_wit: "SEGWIT".to_string(),
+            outputs: vec![],
+            op_return: "".to_string(),
+            rbf: false,
+            lock_time: 0,
+        };
+        let coin_info = coin_info_from_param("LITECOIN", "MAINNET", "NONE", "").unwrap();
+        let tran = BtcForkNativeSegWitTransaction {
+            tx_input,
+            coin_info,
+            _marker_s: PhantomData,
+            _marker_t: PhantomData,
+        };
+
+        let pair = Secp256k1PrivateKey::from_slice(
+            &hex::decode("f3731f49d830c109e054522df01a9378383814af5b01a9cd150511f12db39e6e")
+                .unwrap(),
+        )
+        .unwrap();
+
+        let change_addr =
+            BtcForkAddress::from_str("ltc1q0vp50rf00jvyz7ggfw4r3ausa5wnwc5mk7auz9").unwrap();
+        let expected = tran
+            .sign_transaction(&vec![pair], change_addr.script_pubkey())
+            .unwrap();
+        // the signature of test_sign_segwit_ltc, as the script sig is not signed
+        assert_eq!(expected.signature, "020000000001018bba45b98e54a14d79ca2a5e253f727bff45cf58b5ac5421dd6a37756eb668e80100000000ffffffff01c01f2e010000000017a91400aff21f24bc08af58e41e4186d8492a10b84f9e8702483045022100d0cc3d94c7b7b34fdcc2adc4fd3f735560407581afd6caa11c8d04b963a048a00220777d98e0122fe97206875f49556a401dfc449739ec30e44cb9ed9b92a0b3ff1b01210209c629c64829ec2e99703600ee86c7161a9ed13213e714726210274c29cf780900000000");
+        assert_eq!(
+            expected.tx_hash,
+            "2428988e9b91b4ac5d6d10834293682c0e6d4d7b086829eb3ca47b8ef66d33c8"
+        );
+    }
+
+    #[test]
+    fn test_sign_taproot() {
+        let unspents = vec![
+            Utxo {
+                tx_hash: "a477af6b2667c29670467e4e0728b685ee07b240235771862318e29ddbe58458"
+                    .to_string(),
+                vout: 0,
+                amount: 50000,
+                address: "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+                    .to_string(),
+                script_pub_key:
+                    "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c"
+                        .to_string(),
+                derived_path: "0/0".to_string(),
+                sequence: 0,
+            },
+            Utxo {
+                tx_hash: "e868b66e75376add2154acb558cf45ff7b723f255e2aca794da1548eb945ba8b"
+                    .to_string(),
+                vout: 1,
+                amount: 60000,
+                address: "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+                    .to_string(),
+                script_pub_key:
+                    "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c"
+                        .to_string(),
+                derived_path: "0/0".to_string(),
+                sequence: 0,
+            },
+        ];
+        let tx_input = BtcForkTxInput {
+            to: "bc1qum864wd9nwsc0u9ytkctz6wzrw6g7zdntm7f4e".to_string(),
+            amount: 80000,
+            unspents,
+            fee: 1000,
+            change_address_index: 0,
+            change_address: "".to_string(),
+            network: "MAINNET".to_string(),
+            seg_wit: "P2TR".to_string(),
+            outputs: vec![],
+            op_return: "".to_string(),
+            rbf: false,
+            lock_time: 0,
+        };
+        let coin_info = coin_info_from_param("BITCOIN", "MAINNET", "P2TR", "").unwrap();
+        let tran = BtcForkTaprootTransaction::new(tx_input, coin_info);
+
+        // m/86'/0'/0'/0/0 of "abandon abandon ... about"
+        let prv_key = Secp256k1PrivateKey::from_slice(
+            &hex::decode("41f41d69260df4cf277826a9b65a3717e4eeddbeedf637f212ca096576479361")
+                .unwrap(),
+        )
+        .unwrap();
+        let change_addr = BtcForkAddress::from_str(
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+        )
+        .unwrap();
+        let actual = tran
+            .sign_transaction(&vec![prv_key.clone(), prv_key], change_addr.script_pubkey())
+            .unwrap();
+        assert_eq!(actual.signature, "020000000001025884e5db9de218238671572340b207ee85b628074e7e467096c267266baf77a40000000000ffffffff8bba45b98e54a14d79ca2a5e253f727bff45cf58b5ac5421dd6a37756eb668e80100000000ffffffff028038010000000000160014e6cfaab9a59ba187f0a45db0b169c21bb48f09b34871000000000000225120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c0140a6a15d8ff0ec3656b543c9faeb0c251c757a27dc906f1d31287fd26842e7be8b4d1660f9cb7b59b28a5b9101ab8d4c3f3e2c375d83b0b3d03d929c8e1d78e1b50140dd85b8f6c353c52921ad38ea428e02ad68236ca1e8cf503d9f7a89688c2cb3c66b80ce5a34d3542f07035d8210a4df2d98cfb60c46edc47e86262199116ce08600000000");
+        assert_eq!(
+            actual.tx_hash,
+            "72acd0436beaa5dd72b35b8f75d63d56fe2ff88783fda680842ba76f4bc3861d"
+        );
+    }
 }
@@ -0,0 +1,266 @@
+//! Taproot key path spending: the BIP86 key tweak, BIP340 Schnorr signatures and the BIP341
+//! signature hash.
+// ref:
+// - https://github.com/bitcoin/bips/blob/master/bip-0086.mediawiki
+// - https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+// - https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki
+
+use crate::Result;
+use bitcoin::consensus::serialize;
+use bitcoin::{Transaction, TxOut};
+use bitcoin_hashes::sha256;
+use bitcoin_hashes::Hash;
+use num_bigint::BigUint;
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+/// Commits to all the inputs and outputs like SIGHASH_ALL, and is not appended to the signature
+pub const SIGHASH_DEFAULT: u8 = 0x00;
+
+const CURVE_ORDER: &str = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+
+/// `sha256(sha256(tag) ‖ sha256(tag) ‖ data)`
+pub fn tagged_hash(tag: &str, data: &[u8]) -> [u8; 32] {
+    let tag_hash = sha256::Hash::hash(tag.as_bytes());
+    sha256::Hash::hash(&[&tag_hash[..], &tag_hash[..], data].concat()).into_inner()
+}
+
+fn reduce_scalar(hash: &[u8]) -> [u8; 32] {
+    let order = BigUint::parse_bytes(CURVE_ORDER.as_bytes(), 16).expect("curve_order");
+    let bytes = (BigUint::from_bytes_be(hash) % order).to_bytes_be();
+    let mut scalar = [0; 32];
+    scalar[32 - bytes.len()..].copy_from_slice(&bytes);
+    scalar
+}
+
+/// The x-only output key `P + H_TapTweak(P)G` of the BIP86 single key script tree,
+/// `P` being `pub_key` with an even y
+pub fn tweak_public_key(pub_key: &[u8]) -> Result<Vec<u8>> {
+    let secp = Secp256k1::verification_only();
+    let mut key = PublicKey::from_slice(pub_key)?;
+    if key.serialize()[0] == 0x03 {
+        key.negate_assign(&secp);
+    }
+    let tweak = tagged_hash("TapTweak", &key.serialize()[1..]);
+    key.add_exp_assign(&secp, &tweak)?;
+    Ok(key.serialize()[1..].to_vec())
+}
+
+/// The private key of the output key returned by `tweak_public_key`
+pub fn tweak_private_key(secret: &[u8]) -> Result<Vec<u8>> {
+    let secp = Secp256k1::signing_only();
+    let mut key = SecretKey::from_slice(secret)?;
+    let pub_key = PublicKey::from_secret_key(&secp, &key).serialize();
+    if pub_key[0] == 0x03 {
+        key.negate_assign();
+    }
+    key.add_assign(&tagged_hash("TapTweak", &pub_key[1..]))?;
+    Ok(key[..].to_vec())
+}
+
+/// BIP340 signature of the 32 bytes `msg`, `aux_rand` is mixed into the nonce derived
+/// from the key and the message
+pub fn schnorr_sign(secret: &[u8], msg: &[u8], aux_rand: &[u8; 32]) -> Result<Vec<u8>> {
+    ensure!(msg.len() == 32, "invalid_sign_hash");
+    let secp = Secp256k1::signing_only();
+    let mut key = SecretKey::from_slice(secret)?;
+    let pub_key = PublicKey::from_secret_key(&secp, &key).serialize();
+    if pub_key[0] == 0x03 {
+        key.negate_assign();
+    }
+
+    let masked_key: Vec<u8> = key[..]
+        .iter()
+        .zip(tagged_hash("BIP0340/aux", aux_rand).iter())
+        .map(|(a, b)| a ^ b)
+        .collect();
+    let nonce = tagged_hash("BIP0340/nonce", &[&masked_key, &pub_key[1..], msg].concat());
+    let mut nonce = SecretKey::from_slice(&reduce_scalar(&nonce))?;
+    let r = PublicKey::from_secret_key(&secp, &nonce).serialize();
+    if r[0] == 0x03 {
+        nonce.negate_assign();
+    }
+
+    let challenge = tagged_hash("BIP0340/challenge", &[&r[1..], &pub_key[1..], msg].concat());
+    let mut s = key;
+    s.mul_assign(&reduce_scalar(&challenge))?;
+    s.add_assign(&nonce[..])?;
+    Ok([&r[1..], &s[..]].concat())
+}
+
+/// Verifies the BIP340 signature of the 32 bytes `msg` by the x-only `pub_key`
+pub fn schnorr_verify(pub_key: &[u8], msg: &[u8], signature: &[u8]) -> Result<bool> {
+    ensure!(
+        pub_key.len() == 32 && msg.len() == 32 && signature.len() == 64,
+        "invalid_schnorr_signature"
+    );
+    let secp = Secp256k1::new();
+    let key = match PublicKey::from_slice(&[&[0x02], pub_key].concat()) {
+        Ok(key) => key,
+        Err(_) => return Ok(false),
+    };
+    let s = match SecretKey::from_slice(&signature[32..]) {
+        Ok(s) => s,
+        Err(_) => return Ok(false),
+    };
+
+    // R = sG - eP must have an even y and the x of the signature
+    let challenge = tagged_hash(
+        "BIP0340/challenge",
+        &[&signature[..32], pub_key, msg].concat(),
+    );
+    let mut e_key = key;
+    if e_key.mul_assign(&secp, &reduce_scalar(&challenge)).is_err() {
+        return Ok(false);
+    }
+    e_key.negate_assign(&secp);
+    let r = match PublicKey::from_secret_key(&secp, &s).combine(&e_key) {
+        Ok(r) => r.serialize(),
+        Err(_) => return Ok(false),
+    };
+    Ok(r[0] == 0x02 && r[1..] == signature[..32])
+}
+
+/// BIP341 key path signature hash of the input `index` with SIGHASH_DEFAULT,
+/// `prevouts` are the outputs spent by all the inputs of `tx`
+pub fn taproot_sighash(tx: &Transaction, index: usize, prevouts: &[TxOut]) -> Result<[u8; 32]> {
+    taproot_sighash_with_type(tx, index, prevouts, SIGHASH_DEFAULT)
+}
+
+/// `taproot_sighash` of SIGHASH_DEFAULT or SIGHASH_ALL, the two commit to the same data
+pub(crate) fn taproot_sighash_with_type(
+    tx: &Transaction,
+    index: usize,
+    prevouts: &[TxOut],
+    sighash_type: u8,
+) -> Result<[u8; 32]> {
+    ensure!(
+        prevouts.len() == tx.input.len() && index < tx.input.len(),
+        "invalid_taproot_prevouts"
+    );
+    ensure!(
+        sighash_type == SIGHASH_DEFAULT || sighash_type == 0x01,
+        "unsupported_sighash_type"
+    );
+
+    let mut prevouts_data = vec![];
+    let mut sequences_data = vec![];
+    for tx_in in &tx.input {
+        prevouts_data.extend(serialize(&tx_in.previous_output));
+        sequences_data.extend(&tx_in.sequence.to_le_bytes());
+    }
+    let mut amounts_data = vec![];
+    let mut script_pubkeys_data = vec![];
+    for prevout in prevouts {
+        amounts_data.extend(&prevout.value.to_le_bytes());
+        script_pubkeys_data.extend(serialize(&prevout.script_pubkey));
+    }
+    let mut outputs_data = vec![];
+    for tx_out in &tx.output {
+        outputs_data.extend(serialize(tx_out));
+    }
+
+    // epoch 0 ‖ hash type ‖ tx data ‖ spend type 0, key path without annex ‖ input index
+    let mut data = vec![0x00, sighash_type];
+    data.extend(&tx.version.to_le_bytes());
+    data.extend(&tx.lock_time.to_le_bytes());
+    for part in &[
+        prevouts_data,
+        amounts_data,
+        script_pubkeys_data,
+        sequences_data,
+        outputs_data,
+    ] {
+        data.extend(&sha256::Hash::hash(part)[..]);
+    }
+    data.push(0x00);
+    data.extend(&(index as u32).to_le_bytes());
+    Ok(tagged_hash("TapSighash", &data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{schnorr_sign, schnorr_verify, tweak_private_key, tweak_public_key};
+    use secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+    #[test]
+    fn test_schnorr_sign() {
+        // bip-0340 test vectors 0-3
+        let vectors = vec![
+            (
+                "0000000000000000000000000000000000000000000000000000000000000003",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "e907831f80848d1069a5371b402410364bdf1c5f8307b0084c55f1ce2dca821525f66a4a85ea8b71e482a74f382d2ce5ebeee8fdb2172f477df4900d310536c0",
+            ),
+            (
+                "b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89",
+                "6896bd60eeae296db48a229ff71dfe071bde413e6d43f917dc8dcf8c78de33418906d11ac976abccb20b091292bff4ea897efcb639ea871cfa95f6de339e4b0a",
+            ),
+            (
+                "c90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74020bbea63b14e5c9",
+                "c87aa53824b4d7ae2eb035a2b5bbbccc080e76cdc6d1692c4b0b62d798e6d906",
+                "7e2d58d8b3bcdf1abadec7829054f90dda9805aab56c77333024b9d0a508b75c",
+                "5831aaeed7b44bb74e5eab94ba9d4294c49bcf2a60728d8b4c200f50dd313c1bab745879a5ad954a72c45a91c3a51d3c7adea98d82f8481e0e1e03674a6f3fb7",
+            ),
+            (
+                "0b432b2677937381aef05bb02a66ecd012773062cf3fa2549e44f58ed2401710",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "7eb0509757e246f19449885651611cb965ecc1a187dd51b64fda1edc9637d5ec97582b9cb13db3933705b32ba982af5af25fd78881ebb32771fc5922efc66ea3",
+            ),
+        ];
+        let secp = Secp256k1::new();
+        for (secret, aux_rand, msg, expected) in vectors {
+            let mut aux = [0u8; 32];
+            aux.copy_from_slice(&hex::decode(aux_rand).unwrap());
+            let secret = hex::decode(secret).unwrap();
+            let msg = hex::decode(msg).unwrap();
+            let sig = schnorr_sign(&secret, &msg, &aux).unwrap();
+            assert_eq!(hex::encode(&sig), expected);
+
+            let pub_key =
+                PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&secret).unwrap());
+            let pub_key = &pub_key.serialize()[1..];
+            assert!(schnorr_verify(pub_key, &msg, &sig).unwrap());
+
+            let mut tampered_msg = msg.clone();
+            tampered_msg[31] ^= 1;
+            assert!(!schnorr_verify(pub_key, &tampered_msg, &sig).unwrap());
+            let mut tampered_sig = sig.clone();
+            tampered_sig[0] ^= 1;
+            assert!(!schnorr_verify(pub_key, &msg, &tampered_sig).unwrap());
+        }
+
+        // bip-0340 test vector 5, the public key is not on the curve
+        assert!(!schnorr_verify(
+            &hex::decode("eefdea4cdb677750a420fee807eacf21eb9898ae79b9768766e4faa04a2d4a34").unwrap(),
+            &hex::decode("243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89").unwrap(),
+            &hex::decode("6896bd60eeae296db48a229ff71dfe071bde413e6d43f917dc8dcf8c78de33418906d11ac976abccb20b091292bff4ea897efcb639ea871cfa95f6de339e4b0a").unwrap(),
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn test_tweak_key() {
+        // bip-0086 m/86'/0'/0'/0/0 of "abandon ... about"
+        let secret =
+            hex::decode("41f41d69260df4cf277826a9b65a3717e4eeddbeedf637f212ca096576479361")
+                .unwrap();
+        let pub_key =
+            hex::decode("03cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115")
+                .unwrap();
+        let output_key = tweak_public_key(&pub_key).unwrap();
+        assert_eq!(
+            hex::encode(&output_key),
+            "a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c"
+        );
+
+        let tweaked = tweak_private_key(&secret).unwrap();
+        let secp = Secp256k1::new();
+        let tweaked_pub_key =
+            PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&tweaked).unwrap());
+        assert_eq!(tweaked_pub_key.serialize()[1..].to_vec(), output_key);
+    }
+}
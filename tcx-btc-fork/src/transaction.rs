@@ -18,6 +18,8 @@ pub struct Utxo {
     pub script_pub_key: std::string::String,
     #[prost(string, tag = "6")]
     pub derived_path: std::string::String,
+    /// 0 means unset and falls back to the sequence implied by `rbf` and `lock_time`,
+    /// a zero sequence can't be requested
     #[prost(int64, tag = "7")]
     pub sequence: i64,
 }
@@ -39,6 +41,24 @@ pub struct BtcForkTxInput {
     pub network: std::string::String,
     #[prost(string, tag = "8")]
     pub seg_wit: std::string::String,
+    /// the receivers paid along with `to`, which can be left empty when there are any
+    #[prost(message, repeated, tag = "9")]
+    pub outputs: ::std::vec::Vec<TxOutput>,
+    /// hex encoded data of a zero value OP_RETURN output, at most 80 bytes
+    #[prost(string, tag = "10")]
+    pub op_return: std::string::String,
+    /// signal BIP125 replaceability on the inputs whose sequence is left unset
+    #[prost(bool, tag = "11")]
+    pub rbf: bool,
+    #[prost(uint32, tag = "12")]
+    pub lock_time: u32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TxOutput {
+    #[prost(string, tag = "1")]
+    pub address: std::string::String,
+    #[prost(int64, tag = "2")]
+    pub amount: i64,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct BtcForkSignedTxOutput {
@@ -46,4 +66,207 @@ pub struct BtcForkSignedTxOutput {
     pub signature: std::string::String,
     #[prost(string, tag = "2")]
     pub tx_hash: std::string::String,
+    /// the address of the change output, empty without one
+    #[prost(string, tag = "3")]
+    pub change_address: std::string::String,
+    /// the path of the change address relative to the account, empty when not derived
+    #[prost(string, tag = "4")]
+    pub change_path: std::string::String,
+}
+/// FUNCTION: btc_fork_bump_fee(SignParam{input: BtcForkFeeBumpInput}): BtcForkSignedTxOutput
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BtcForkFeeBumpInput {
+    /// the input the replaced transaction was signed from
+    #[prost(message, optional, tag = "1")]
+    pub tx: ::std::option::Option<BtcForkTxInput>,
+    /// hex encoded replaced transaction
+    #[prost(string, tag = "2")]
+    pub signed_tx: std::string::String,
+    /// the fee of the replacement
+    #[prost(int64, tag = "3")]
+    pub fee: i64,
+}
+/// FUNCTION: sign_psbt(SignParam{input: PsbtInput}): PsbtOutput
+/// FUNCTION: finalize_psbt(PsbtInput): BtcForkSignedTxOutput
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PsbtInput {
+    /// base64 encoded BIP174 or BIP370 psbt
+    #[prost(string, tag = "1")]
+    pub psbt: std::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PsbtOutput {
+    #[prost(string, tag = "1")]
+    pub psbt: std::string::String,
+    #[prost(uint32, repeated, tag = "2")]
+    pub signed_inputs: ::std::vec::Vec<u32>,
+}
+/// FUNCTION: combine_psbt(PsbtCombineInput): PsbtInput
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PsbtCombineInput {
+    /// the psbts of the same transaction signed by different cosigners
+    #[prost(string, repeated, tag = "1")]
+    pub psbts: ::std::vec::Vec<std::string::String>,
+}
+/// FUNCTION: btc_fork_select_coins(CoinSelectionInput): CoinSelectionOutput
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CoinSelectionInput {
+    #[prost(string, tag = "1")]
+    pub chain_type: std::string::String,
+    #[prost(string, tag = "2")]
+    pub network: std::string::String,
+    /// the P2SH unspents are taken as P2SH-P2WPKH only with P2WPKH
+    #[prost(string, tag = "3")]
+    pub seg_wit: std::string::String,
+    #[prost(string, tag = "4")]
+    pub to: std::string::String,
+    #[prost(int64, tag = "5")]
+    pub amount: i64,
+    #[prost(message, repeated, tag = "6")]
+    pub unspents: ::std::vec::Vec<Utxo>,
+    /// satoshis per virtual byte
+    #[prost(int64, tag = "7")]
+    pub fee_rate: i64,
+    #[prost(string, tag = "8")]
+    pub change_address: std::string::String,
+    #[prost(string, tag = "9")]
+    pub op_return: std::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CoinSelectionOutput {
+    #[prost(message, repeated, tag = "1")]
+    pub unspents: ::std::vec::Vec<Utxo>,
+    #[prost(int64, tag = "2")]
+    pub fee: i64,
+    #[prost(int64, tag = "3")]
+    pub change: i64,
+    #[prost(int64, tag = "4")]
+    pub vsize: i64,
+}
+/// FUNCTION: btc_sign_message(SignParam{input: BtcMessageInput}): BtcMessageOutput
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BtcMessageInput {
+    #[prost(string, tag = "1")]
+    pub message: std::string::String,
+    /// BIP137, BIP322_SIMPLE or BIP322_FULL, by default BIP137 for the P2PKH and P2SH-P2WPKH
+    /// addresses and BIP322_SIMPLE for the others
+    #[prost(string, tag = "2")]
+    pub signature_type: std::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BtcMessageOutput {
+    /// base64 encoded
+    #[prost(string, tag = "1")]
+    pub signature: std::string::String,
+}
+/// FUNCTION: btc_verify_message(BtcVerifyMessageInput): BtcVerifyMessageOutput
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BtcVerifyMessageInput {
+    #[prost(string, tag = "1")]
+    pub chain_type: std::string::String,
+    #[prost(string, tag = "2")]
+    pub address: std::string::String,
+    #[prost(string, tag = "3")]
+    pub message: std::string::String,
+    /// base64 encoded BIP137 or BIP322 signature
+    #[prost(string, tag = "4")]
+    pub signature: std::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BtcVerifyMessageOutput {
+    #[prost(bool, tag = "1")]
+    pub is_valid: bool,
+}
+/// FUNCTION: btc_fork_multisig_address(BtcForkMultisigAddressParam): BtcForkMultisigAddressOutput
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BtcForkMultisigAddressParam {
+    #[prost(string, tag = "1")]
+    pub id: std::string::String,
+    #[prost(string, tag = "2")]
+    pub password: std::string::String,
+    #[prost(string, tag = "3")]
+    pub chain_type: std::string::String,
+    /// the local HD account, its extended public key is one of the cosigner keys
+    #[prost(string, tag = "4")]
+    pub address: std::string::String,
+    /// P2SH, P2SH_P2WSH or P2WSH
+    #[prost(string, tag = "5")]
+    pub script_type: std::string::String,
+    #[prost(uint32, tag = "6")]
+    pub threshold: u32,
+    /// the account extended public keys of the other cosigners
+    #[prost(string, repeated, tag = "7")]
+    pub cosigner_xpubs: ::std::vec::Vec<std::string::String>,
+    /// the path relative to the account extended public keys, e.g. 0/0
+    #[prost(string, tag = "8")]
+    pub path: std::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BtcForkMultisigAddressOutput {
+    #[prost(string, tag = "1")]
+    pub address: std::string::String,
+    /// hex encoded, in BIP67 order
+    #[prost(string, repeated, tag = "2")]
+    pub public_keys: ::std::vec::Vec<std::string::String>,
+    /// hex encoded, empty for P2WSH
+    #[prost(string, tag = "3")]
+    pub redeem_script: std::string::String,
+    /// hex encoded, empty for P2SH
+    #[prost(string, tag = "4")]
+    pub witness_script: std::string::String,
+}
+/// FUNCTION: btc_fork_export_descriptor(BtcForkExportDescriptorParam): BtcForkDescriptorOutput
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BtcForkExportDescriptorParam {
+    #[prost(string, tag = "1")]
+    pub id: std::string::String,
+    #[prost(string, tag = "2")]
+    pub password: std::string::String,
+    #[prost(string, tag = "3")]
+    pub chain_type: std::string::String,
+    /// the HD account to export
+    #[prost(string, tag = "4")]
+    pub address: std::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BtcForkDescriptorOutput {
+    /// the receiving addresses descriptor, with its checksum
+    #[prost(string, tag = "1")]
+    pub descriptor: std::string::String,
+    /// the change addresses descriptor, with its checksum
+    #[prost(string, tag = "2")]
+    pub change_descriptor: std::string::String,
+}
+/// FUNCTION: btc_fork_import_descriptor(BtcForkImportDescriptorParam): BtcForkImportDescriptorOutput
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BtcForkImportDescriptorParam {
+    /// the HD keystore to derive the account in, empty to only watch the descriptor addresses.
+    /// sortedmulti descriptors are always watch only
+    #[prost(string, tag = "1")]
+    pub id: std::string::String,
+    #[prost(string, tag = "2")]
+    pub password: std::string::String,
+    #[prost(string, tag = "3")]
+    pub chain_type: std::string::String,
+    /// the version bytes of the descriptor keys must belong to it, e.g. xpub/zpub on MAINNET
+    #[prost(string, tag = "4")]
+    pub network: std::string::String,
+    #[prost(string, tag = "5")]
+    pub descriptor: std::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BtcForkImportDescriptorOutput {
+    /// the normalized descriptor, with its checksum
+    #[prost(string, tag = "1")]
+    pub descriptor: std::string::String,
+    /// the first address of the descriptor
+    #[prost(string, tag = "2")]
+    pub address: std::string::String,
+    #[prost(bool, tag = "3")]
+    pub watch_only: bool,
+    /// the account derived in the keystore, empty when watch only
+    #[prost(string, tag = "4")]
+    pub account_address: std::string::String,
+    #[prost(string, tag = "5")]
+    pub path: std::string::String,
 }
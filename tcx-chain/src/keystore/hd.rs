@@ -123,6 +123,22 @@ impl HdKeystore {
         TypedDeterministicPublicKey::from_hex(account.curve, &account.ext_pub_key)
     }
 
+    pub(crate) fn find_deterministic_private_key(
+        &self,
+        symbol: &str,
+        address: &str,
+    ) -> Result<TypedDeterministicPrivateKey> {
+        let cache = self.cache.as_ref().ok_or(Error::KeystoreLocked)?;
+
+        let account = self
+            .account(symbol, address)
+            .ok_or(Error::AccountNotFound)?;
+
+        let root = TypedDeterministicPrivateKey::from_mnemonic(account.curve, &cache.mnemonic)?;
+
+        root.derive(&get_account_path(&account.derivation_path)?)
+    }
+
     pub(crate) fn find_private_key_by_path(
         &mut self,
         symbol: &str,
@@ -16,7 +16,9 @@ pub use self::{
 
 use crate::signer::ChainSigner;
 use tcx_crypto::{Crypto, Pbkdf2Params};
-use tcx_primitive::{TypedDeterministicPublicKey, TypedPrivateKey, TypedPublicKey};
+use tcx_primitive::{
+    TypedDeterministicPrivateKey, TypedDeterministicPublicKey, TypedPrivateKey, TypedPublicKey,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -314,6 +316,17 @@ impl Keystore {
         }
     }
 
+    pub fn find_deterministic_private_key(
+        &mut self,
+        symbol: &str,
+        address: &str,
+    ) -> Result<TypedDeterministicPrivateKey> {
+        match self {
+            Keystore::Hd(ks) => ks.find_deterministic_private_key(symbol, address),
+            _ => Err(Error::CannotDeriveKey.into()),
+        }
+    }
+
     pub fn account(&self, symbol: &str, address: &str) -> Option<&Account> {
         match self {
             Keystore::PrivateKey(ks) => ks.account(symbol, address),
@@ -638,6 +651,17 @@ mod tests {
             .unwrap();
         assert_eq!(public_key.to_hex(), "031064f6a580000000251d72997d4cf931a7e6819f7da37725166100fc7dae9ca6afc3f8fd8a3d3a7f0303f2f84851514bf2f40a46b5bb9dbf4e5913fbacde1a96968cda08f9fd882caa");
 
+        let private_key = keystore
+            .find_deterministic_private_key(
+                "BITCOINCASH",
+                "qzld7dav7d2sfjdl6x9snkvf6raj8lfxjcj5fa8y2r",
+            )
+            .unwrap();
+        assert_eq!(
+            private_key.deterministic_public_key().to_hex(),
+            public_key.to_hex()
+        );
+
         let acc = keystore.account("BITCOINCASH", "qzld7dav7d2sfjdl6x9snkvf6raj8lfxjcj5fa8y2");
         assert!(acc.is_none());
 
@@ -1,12 +1,16 @@
 use crate::CoinInfo;
+use crate::Result;
+use failure::format_err;
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct BtcForkNetwork {
-    pub coin: &'static str,
-    pub network: &'static str,
-    pub seg_wit: &'static str,
-    pub hrp: &'static str,
+    pub coin: String,
+    pub network: String,
+    pub seg_wit: String,
+    pub hrp: String,
     pub p2pkh_prefix: u8,
     pub p2sh_prefix: u8,
     pub private_prefix: u8,
@@ -19,14 +23,24 @@ pub struct HdVersion {
     prv_version: [u8; 4],
 }
 
+/// SLIP-132 version bytes, selected by the network's plain `xpub_prefix` and the script type.
+/// An empty `coin` matches any chain sharing the same `xpub_prefix`.
+struct Slip132Version {
+    coin: &'static str,
+    xpub_prefix: [u8; 4],
+    seg_wit: &'static str,
+    pub_version: [u8; 4],
+    prv_version: [u8; 4],
+}
+
 lazy_static! {
     static ref BTC_FORK_NETWORKS: RwLock<Vec<BtcForkNetwork>> = {
         let mut networks = Vec::new();
         networks.push(BtcForkNetwork {
-            coin: "LITECOIN",
-            network: "MAINNET",
-            seg_wit: "NONE",
-            hrp: "",
+            coin: "LITECOIN".to_string(),
+            network: "MAINNET".to_string(),
+            seg_wit: "NONE".to_string(),
+            hrp: "".to_string(),
             p2pkh_prefix: 0x30,
             p2sh_prefix: 0x32,
             private_prefix: 0xb0,
@@ -34,10 +48,10 @@ lazy_static! {
             xprv_prefix: [0x04, 0x88, 0xAD, 0xE4],
         });
         networks.push(BtcForkNetwork {
-            coin: "LITECOIN",
-            network: "MAINNET",
-            seg_wit: "P2WPKH",
-            hrp: "",
+            coin: "LITECOIN".to_string(),
+            network: "MAINNET".to_string(),
+            seg_wit: "P2WPKH".to_string(),
+            hrp: "".to_string(),
             p2pkh_prefix: 0x30,
             p2sh_prefix: 0x32,
             private_prefix: 0xb0,
@@ -45,10 +59,10 @@ lazy_static! {
             xprv_prefix: [0x04, 0x88, 0xAD, 0xE4],
         });
         networks.push(BtcForkNetwork {
-            coin: "LITECOIN",
-            network: "MAINNET",
-            seg_wit: "SEGWIT",
-            hrp: "ltc",
+            coin: "LITECOIN".to_string(),
+            network: "MAINNET".to_string(),
+            seg_wit: "SEGWIT".to_string(),
+            hrp: "ltc".to_string(),
             p2pkh_prefix: 0x30,
             p2sh_prefix: 0x32,
             private_prefix: 0xb0,
@@ -56,10 +70,10 @@ lazy_static! {
             xprv_prefix: [0x04, 0x88, 0xAD, 0xE4],
         });
         networks.push(BtcForkNetwork {
-            coin: "LITECOIN",
-            network: "TESTNET",
-            seg_wit: "NONE",
-            hrp: "",
+            coin: "LITECOIN".to_string(),
+            network: "TESTNET".to_string(),
+            seg_wit: "NONE".to_string(),
+            hrp: "".to_string(),
             p2pkh_prefix: 0x6f,
             p2sh_prefix: 0x3a,
             private_prefix: 0xef,
@@ -69,10 +83,10 @@ lazy_static! {
             xprv_prefix: [0x04, 0x35, 0x83, 0x94],
         });
         networks.push(BtcForkNetwork {
-            coin: "LITECOIN",
-            network: "TESTNET",
-            seg_wit: "P2WPKH",
-            hrp: "",
+            coin: "LITECOIN".to_string(),
+            network: "TESTNET".to_string(),
+            seg_wit: "P2WPKH".to_string(),
+            hrp: "".to_string(),
             p2pkh_prefix: 0x6f,
             p2sh_prefix: 0x3a,
             private_prefix: 0xef,
@@ -80,10 +94,10 @@ lazy_static! {
             xprv_prefix: [0x04, 0x35, 0x83, 0x94],
         });
         networks.push(BtcForkNetwork {
-            coin: "BITCOIN",
-            network: "MAINNET",
-            seg_wit: "NONE",
-            hrp: "",
+            coin: "BITCOIN".to_string(),
+            network: "MAINNET".to_string(),
+            seg_wit: "NONE".to_string(),
+            hrp: "".to_string(),
             p2pkh_prefix: 0x0,
             p2sh_prefix: 0x05,
             private_prefix: 0x80,
@@ -91,10 +105,10 @@ lazy_static! {
             xprv_prefix: [0x04, 0x88, 0xAD, 0xE4],
         });
         networks.push(BtcForkNetwork {
-            coin: "BITCOIN",
-            network: "MAINNET",
-            seg_wit: "P2WPKH",
-            hrp: "",
+            coin: "BITCOIN".to_string(),
+            network: "MAINNET".to_string(),
+            seg_wit: "P2WPKH".to_string(),
+            hrp: "".to_string(),
             p2pkh_prefix: 0x0,
             p2sh_prefix: 0x05,
             private_prefix: 0x80,
@@ -102,10 +116,10 @@ lazy_static! {
             xprv_prefix: [0x04, 0x88, 0xAD, 0xE4],
         });
         networks.push(BtcForkNetwork {
-            coin: "BITCOIN",
-            network: "MAINNET",
-            seg_wit: "SEGWIT",
-            hrp: "bc",
+            coin: "BITCOIN".to_string(),
+            network: "MAINNET".to_string(),
+            seg_wit: "P2TR".to_string(),
+            hrp: "bc".to_string(),
             p2pkh_prefix: 0x0,
             p2sh_prefix: 0x05,
             private_prefix: 0x80,
@@ -113,10 +127,21 @@ lazy_static! {
             xprv_prefix: [0x04, 0x88, 0xAD, 0xE4],
         });
         networks.push(BtcForkNetwork {
-            coin: "BITCOIN",
-            network: "TESTNET",
-            seg_wit: "NONE",
-            hrp: "",
+            coin: "BITCOIN".to_string(),
+            network: "MAINNET".to_string(),
+            seg_wit: "SEGWIT".to_string(),
+            hrp: "bc".to_string(),
+            p2pkh_prefix: 0x0,
+            p2sh_prefix: 0x05,
+            private_prefix: 0x80,
+            xpub_prefix: [0x04, 0x88, 0xB2, 0x1E],
+            xprv_prefix: [0x04, 0x88, 0xAD, 0xE4],
+        });
+        networks.push(BtcForkNetwork {
+            coin: "BITCOIN".to_string(),
+            network: "TESTNET".to_string(),
+            seg_wit: "NONE".to_string(),
+            hrp: "".to_string(),
             p2pkh_prefix: 0x6f,
             p2sh_prefix: 0xc4,
             private_prefix: 0xef,
@@ -124,10 +149,21 @@ lazy_static! {
             xprv_prefix: [0x04, 0x35, 0x83, 0x94],
         });
         networks.push(BtcForkNetwork {
-            coin: "BITCOIN",
-            network: "TESTNET",
-            seg_wit: "P2WPKH",
-            hrp: "",
+            coin: "BITCOIN".to_string(),
+            network: "TESTNET".to_string(),
+            seg_wit: "P2WPKH".to_string(),
+            hrp: "".to_string(),
+            p2pkh_prefix: 0x6f,
+            p2sh_prefix: 0xc4,
+            private_prefix: 0xef,
+            xpub_prefix: [0x04, 0x35, 0x87, 0xCF],
+            xprv_prefix: [0x04, 0x35, 0x83, 0x94],
+        });
+        networks.push(BtcForkNetwork {
+            coin: "BITCOIN".to_string(),
+            network: "TESTNET".to_string(),
+            seg_wit: "P2TR".to_string(),
+            hrp: "tb".to_string(),
             p2pkh_prefix: 0x6f,
             p2sh_prefix: 0xc4,
             private_prefix: 0xef,
@@ -136,10 +172,10 @@ lazy_static! {
         });
         //Definition of BitcoinCash networks https://github.com/bitpay/bitcore/blob/master/packages/bitcore-lib-cash/lib/networks.js#L168
         networks.push(BtcForkNetwork {
-            coin: "BITCOINCASH",
-            network: "MAINNET",
-            seg_wit: "NONE",
-            hrp: "bitcoincash",
+            coin: "BITCOINCASH".to_string(),
+            network: "MAINNET".to_string(),
+            seg_wit: "NONE".to_string(),
+            hrp: "bitcoincash".to_string(),
             p2pkh_prefix: 0x0,
             p2sh_prefix: 0x05,
             private_prefix: 0x80,
@@ -147,10 +183,10 @@ lazy_static! {
             xprv_prefix: [0x04, 0x88, 0xAD, 0xE4],
         });
         networks.push(BtcForkNetwork {
-            coin: "BITCOINCASH",
-            network: "TESTNET",
-            seg_wit: "NONE",
-            hrp: "bitcoincash",
+            coin: "BITCOINCASH".to_string(),
+            network: "TESTNET".to_string(),
+            seg_wit: "NONE".to_string(),
+            hrp: "bitcoincash".to_string(),
             p2pkh_prefix: 0x6f,
             p2sh_prefix: 0xc4,
             private_prefix: 0xef,
@@ -158,10 +194,10 @@ lazy_static! {
             xprv_prefix: [0x04, 0x35, 0x83, 0x94],
         });
         networks.push(BtcForkNetwork {
-            coin: "DOGECOIN",
-            network: "MAINNET",
-            seg_wit: "NONE",
-            hrp: "",
+            coin: "DOGECOIN".to_string(),
+            network: "MAINNET".to_string(),
+            seg_wit: "NONE".to_string(),
+            hrp: "".to_string(),
             p2pkh_prefix: 0x1e,
             p2sh_prefix: 0x16,
             private_prefix: 0x9e,
@@ -169,10 +205,10 @@ lazy_static! {
             xprv_prefix: [0x02, 0xfa, 0xc3, 0x98],
         });
         networks.push(BtcForkNetwork {
-            coin: "DOGECOIN",
-            network: "MAINNET",
-            seg_wit: "P2WPKH",
-            hrp: "",
+            coin: "DOGECOIN".to_string(),
+            network: "MAINNET".to_string(),
+            seg_wit: "P2WPKH".to_string(),
+            hrp: "".to_string(),
             p2pkh_prefix: 0x1e,
             p2sh_prefix: 0x16,
             private_prefix: 0x9e,
@@ -180,10 +216,10 @@ lazy_static! {
             xprv_prefix: [0x02, 0xfa, 0xc3, 0x98],
         });
         networks.push(BtcForkNetwork {
-            coin: "DOGECOIN",
-            network: "TESTNET",
-            seg_wit: "NONE",
-            hrp: "",
+            coin: "DOGECOIN".to_string(),
+            network: "TESTNET".to_string(),
+            seg_wit: "NONE".to_string(),
+            hrp: "".to_string(),
             p2pkh_prefix: 0x71,
             p2sh_prefix: 0xc4,
             private_prefix: 0xf1,
@@ -191,10 +227,10 @@ lazy_static! {
             xprv_prefix: [0x04, 0x35, 0x83, 0x94],
         });
         networks.push(BtcForkNetwork {
-            coin: "DOGECOIN",
-            network: "TESTNET",
-            seg_wit: "P2WPKH",
-            hrp: "",
+            coin: "DOGECOIN".to_string(),
+            network: "TESTNET".to_string(),
+            seg_wit: "P2WPKH".to_string(),
+            hrp: "".to_string(),
             p2pkh_prefix: 0x71,
             p2sh_prefix: 0xc4,
             private_prefix: 0xf1,
@@ -204,6 +240,8 @@ lazy_static! {
         RwLock::new(networks)
     };
 
+    static ref CUSTOM_BTC_FORK_NETWORKS: RwLock<Vec<BtcForkNetwork>> = RwLock::new(Vec::new());
+
     static ref HD_VERSIONS: RwLock<Vec<HdVersion>> = {
         let mut versions = Vec::new();
         versions.push(HdVersion {
@@ -216,6 +254,58 @@ lazy_static! {
         });
         RwLock::new(versions)
     };
+
+    // https://github.com/satoshilabs/slips/blob/master/slip-0132.md
+    static ref SLIP132_VERSIONS: Vec<Slip132Version> = vec![
+        // Ltub/Ltpv
+        Slip132Version {
+            coin: "LITECOIN",
+            xpub_prefix: [0x04, 0x88, 0xB2, 0x1E],
+            seg_wit: "NONE",
+            pub_version: [0x01, 0x9D, 0xA4, 0x62],
+            prv_version: [0x01, 0x9D, 0x9C, 0xFE],
+        },
+        // Mtub/Mtpv
+        Slip132Version {
+            coin: "LITECOIN",
+            xpub_prefix: [0x04, 0x88, 0xB2, 0x1E],
+            seg_wit: "P2WPKH",
+            pub_version: [0x01, 0xB2, 0x6E, 0xF6],
+            prv_version: [0x01, 0xB2, 0x67, 0x92],
+        },
+        // ypub/yprv
+        Slip132Version {
+            coin: "",
+            xpub_prefix: [0x04, 0x88, 0xB2, 0x1E],
+            seg_wit: "P2WPKH",
+            pub_version: [0x04, 0x9D, 0x7C, 0xB2],
+            prv_version: [0x04, 0x9D, 0x78, 0x78],
+        },
+        // zpub/zprv
+        Slip132Version {
+            coin: "",
+            xpub_prefix: [0x04, 0x88, 0xB2, 0x1E],
+            seg_wit: "SEGWIT",
+            pub_version: [0x04, 0xB2, 0x47, 0x46],
+            prv_version: [0x04, 0xB2, 0x43, 0x0C],
+        },
+        // upub/uprv
+        Slip132Version {
+            coin: "",
+            xpub_prefix: [0x04, 0x35, 0x87, 0xCF],
+            seg_wit: "P2WPKH",
+            pub_version: [0x04, 0x4A, 0x52, 0x62],
+            prv_version: [0x04, 0x4A, 0x4E, 0x28],
+        },
+        // vpub/vprv
+        Slip132Version {
+            coin: "",
+            xpub_prefix: [0x04, 0x35, 0x87, 0xCF],
+            seg_wit: "SEGWIT",
+            pub_version: [0x04, 0x5F, 0x1C, 0xF6],
+            prv_version: [0x04, 0x5F, 0x18, 0xBC],
+        },
+    ];
 }
 
 // LTC address prefix: https://bitcoin.stackexchange.com/questions/62781/litecoin-constants-and-prefixes
@@ -232,9 +322,11 @@ pub fn network_from_param(
     seg_wit: &str,
 ) -> Option<BtcForkNetwork> {
     let networks = BTC_FORK_NETWORKS.read();
+    let custom_networks = CUSTOM_BTC_FORK_NETWORKS.read();
     //    let coin_uppercase = coin.to_uppercase();
     let mut ret: Vec<BtcForkNetwork> = networks
         .iter()
+        .chain(custom_networks.iter())
         .filter(|x| x.coin.eq(&chain_type.to_uppercase()))
         .filter(|x| x.network.eq(&network.to_uppercase()))
         .filter(|x| x.seg_wit.eq(&seg_wit.to_uppercase()))
@@ -245,8 +337,10 @@ pub fn network_from_param(
 
 pub fn network_form_hrp(hrp: &str) -> Option<BtcForkNetwork> {
     let networks = BTC_FORK_NETWORKS.read();
+    let custom_networks = CUSTOM_BTC_FORK_NETWORKS.read();
     let mut ret: Vec<BtcForkNetwork> = networks
         .iter()
+        .chain(custom_networks.iter())
         .filter(|x| x.hrp.eq(hrp))
         .map(|x| x.clone())
         .collect::<Vec<BtcForkNetwork>>();
@@ -255,12 +349,78 @@ pub fn network_form_hrp(hrp: &str) -> Option<BtcForkNetwork> {
 
 pub fn coin_from_xpub_prefix(prefix: &[u8]) -> Option<String> {
     let networks = BTC_FORK_NETWORKS.read();
+    let custom_networks = CUSTOM_BTC_FORK_NETWORKS.read();
     networks
         .iter()
+        .chain(custom_networks.iter())
         .find(|x| x.xpub_prefix.eq(prefix))
         .map(|x| x.coin.to_string())
 }
 
+/// All btc fork networks, the built-in ones first
+pub fn btc_fork_networks() -> Vec<BtcForkNetwork> {
+    let networks = BTC_FORK_NETWORKS.read();
+    let custom_networks = CUSTOM_BTC_FORK_NETWORKS.read();
+    networks
+        .iter()
+        .chain(custom_networks.iter())
+        .map(|x| x.clone())
+        .collect()
+}
+
+/// The btc fork networks registered at runtime
+pub fn custom_btc_fork_networks() -> Vec<BtcForkNetwork> {
+    CUSTOM_BTC_FORK_NETWORKS.read().clone()
+}
+
+pub fn register_btc_fork_network(network: BtcForkNetwork) -> Result<()> {
+    let network = BtcForkNetwork {
+        coin: network.coin.to_uppercase(),
+        network: network.network.to_uppercase(),
+        seg_wit: network.seg_wit.to_uppercase(),
+        ..network
+    };
+    if network.coin.is_empty() || network.network.is_empty() {
+        return Err(format_err!("btc_fork_network_invalid"));
+    }
+    if !["NONE", "P2WPKH", "SEGWIT", "P2TR"].contains(&network.seg_wit.as_str()) {
+        return Err(format_err!("btc_fork_network_invalid_seg_wit"));
+    }
+    if (network.seg_wit == "SEGWIT" || network.seg_wit == "P2TR") && network.hrp.is_empty() {
+        return Err(format_err!("btc_fork_network_missing_hrp"));
+    }
+
+    let networks = BTC_FORK_NETWORKS.read();
+    let mut custom_networks = CUSTOM_BTC_FORK_NETWORKS.write();
+    if networks.iter().chain(custom_networks.iter()).any(|x| {
+        x.coin == network.coin && x.network == network.network && x.seg_wit == network.seg_wit
+    }) {
+        return Err(format_err!("btc_fork_network_already_exists"));
+    }
+    custom_networks.push(network);
+    Ok(())
+}
+
+/// Only the btc fork networks registered at runtime can be removed
+pub fn remove_btc_fork_network(coin: &str, network: &str, seg_wit: &str) -> Result<()> {
+    let mut custom_networks = CUSTOM_BTC_FORK_NETWORKS.write();
+    let len = custom_networks.len();
+    custom_networks.retain(|x| {
+        !(x.coin.eq_ignore_ascii_case(coin)
+            && x.network.eq_ignore_ascii_case(network)
+            && x.seg_wit.eq_ignore_ascii_case(seg_wit))
+    });
+    if custom_networks.len() == len {
+        Err(format_err!("custom_btc_fork_network_not_found"))
+    } else {
+        Ok(())
+    }
+}
+
+pub fn clear_custom_btc_fork_networks() {
+    CUSTOM_BTC_FORK_NETWORKS.write().clear()
+}
+
 pub fn pub_version_from_prv_version(prefix: &[u8]) -> Option<[u8; 4]> {
     let networks = HD_VERSIONS.read();
     networks.iter().find(|x| x.prv_version.eq(prefix)).map(|x| {
@@ -269,3 +429,97 @@ pub fn pub_version_from_prv_version(prefix: &[u8]) -> Option<[u8; 4]> {
         version
     })
 }
+
+/// Returns the SLIP-132 (public, private) version bytes for the network.
+/// Falls back to the network's own `xpub_prefix`/`xprv_prefix` when SLIP-132 defines nothing
+/// more specific, e.g. legacy BITCOIN accounts or DOGECOIN.
+pub fn slip132_versions(network: &BtcForkNetwork) -> ([u8; 4], [u8; 4]) {
+    let matched = |coin: &str| {
+        SLIP132_VERSIONS.iter().find(|x| {
+            x.coin.eq(coin)
+                && x.xpub_prefix.eq(&network.xpub_prefix)
+                && x.seg_wit.eq(&network.seg_wit)
+        })
+    };
+
+    matched(&network.coin)
+        .or_else(|| matched(""))
+        .map(|x| (x.pub_version, x.prv_version))
+        .unwrap_or((network.xpub_prefix, network.xprv_prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        network_form_hrp, network_from_param, register_btc_fork_network, remove_btc_fork_network,
+        slip132_versions, BtcForkNetwork,
+    };
+
+    #[test]
+    fn test_slip132_versions() {
+        let cases = vec![
+            ("BITCOIN", "MAINNET", "NONE", [0x04, 0x88, 0xB2, 0x1E]),
+            ("BITCOIN", "MAINNET", "P2WPKH", [0x04, 0x9D, 0x7C, 0xB2]),
+            ("BITCOIN", "MAINNET", "SEGWIT", [0x04, 0xB2, 0x47, 0x46]),
+            ("BITCOIN", "TESTNET", "NONE", [0x04, 0x35, 0x87, 0xCF]),
+            ("BITCOIN", "TESTNET", "P2WPKH", [0x04, 0x4A, 0x52, 0x62]),
+            ("LITECOIN", "MAINNET", "NONE", [0x01, 0x9D, 0xA4, 0x62]),
+            ("LITECOIN", "MAINNET", "P2WPKH", [0x01, 0xB2, 0x6E, 0xF6]),
+            ("LITECOIN", "MAINNET", "SEGWIT", [0x04, 0xB2, 0x47, 0x46]),
+            ("DOGECOIN", "MAINNET", "P2WPKH", [0x02, 0xfa, 0xca, 0xfd]),
+        ];
+        for (coin, network, seg_wit, expected) in cases {
+            let network = network_from_param(coin, network, seg_wit).unwrap();
+            assert_eq!(slip132_versions(&network).0, expected);
+        }
+    }
+
+    #[test]
+    fn test_register_btc_fork_network() {
+        let dash = BtcForkNetwork {
+            coin: "DASH".to_string(),
+            network: "MAINNET".to_string(),
+            seg_wit: "NONE".to_string(),
+            hrp: "".to_string(),
+            p2pkh_prefix: 0x4c,
+            p2sh_prefix: 0x10,
+            private_prefix: 0xcc,
+            xpub_prefix: [0x04, 0x88, 0xB2, 0x1E],
+            xprv_prefix: [0x04, 0x88, 0xAD, 0xE4],
+        };
+        assert!(network_from_param("DASH", "MAINNET", "NONE").is_none());
+        register_btc_fork_network(dash.clone()).unwrap();
+        assert_eq!(network_from_param("dash", "mainnet", "none").unwrap(), dash);
+
+        let ret = register_btc_fork_network(dash.clone());
+        assert_eq!(
+            format!("{}", ret.err().unwrap()),
+            "btc_fork_network_already_exists"
+        );
+
+        let ret = register_btc_fork_network(BtcForkNetwork {
+            seg_wit: "SEGWIT".to_string(),
+            ..dash.clone()
+        });
+        assert_eq!(
+            format!("{}", ret.err().unwrap()),
+            "btc_fork_network_missing_hrp"
+        );
+
+        let bitcoin = network_from_param("BITCOIN", "MAINNET", "NONE").unwrap();
+        let ret = register_btc_fork_network(bitcoin);
+        assert_eq!(
+            format!("{}", ret.err().unwrap()),
+            "btc_fork_network_already_exists"
+        );
+
+        remove_btc_fork_network("DASH", "MAINNET", "NONE").unwrap();
+        assert!(network_from_param("DASH", "MAINNET", "NONE").is_none());
+        let ret = remove_btc_fork_network("BITCOIN", "MAINNET", "NONE");
+        assert_eq!(
+            format!("{}", ret.err().unwrap()),
+            "custom_btc_fork_network_not_found"
+        );
+        assert!(network_form_hrp("bc").is_some());
+    }
+}
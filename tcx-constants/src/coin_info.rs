@@ -3,11 +3,13 @@ use crate::Result;
 use failure::format_err;
 
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 
 /// Blockchain basic config
 ///
-/// NOTE: Unique key field is `symbol`
-#[derive(Clone, Debug)]
+/// NOTE: Unique key fields are `coin`, `network`, `seg_wit` and `curve`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CoinInfo {
     pub coin: String,
     pub derivation_path: String,
@@ -33,6 +35,20 @@ lazy_static! {
             network: "TESTNET".to_string(),
             seg_wit: "NONE".to_string(),
         });
+        coin_infos.push(CoinInfo {
+            coin: "BITCOIN".to_string(),
+            derivation_path: "m/86'/0'/0'/0/0".to_string(),
+            curve: CurveType::SECP256k1,
+            network: "MAINNET".to_string(),
+            seg_wit: "P2TR".to_string(),
+        });
+        coin_infos.push(CoinInfo {
+            coin: "BITCOIN".to_string(),
+            derivation_path: "m/86'/1'/0'/0/0".to_string(),
+            curve: CurveType::SECP256k1,
+            network: "TESTNET".to_string(),
+            seg_wit: "P2TR".to_string(),
+        });
         coin_infos.push(CoinInfo {
             coin: "BITCOIN".to_string(),
             derivation_path: "m/49'/0'/0'/0/0".to_string(),
@@ -279,28 +295,28 @@ lazy_static! {
             seg_wit: "NONE".to_string(),
         });
         coin_infos.push(CoinInfo {
-            coin: "ETHERRUM".to_string(),
+            coin: "ETHEREUM".to_string(),
             derivation_path: "m/44'/60'/0'/0/0".to_string(),
             curve: CurveType::SECP256k1,
             network: "OPTIMISM".to_string(),
             seg_wit: "NONE".to_string(),
         });
         coin_infos.push(CoinInfo {
-            coin: "ETRHEREUM".to_string(),
+            coin: "ETHEREUM".to_string(),
             derivation_path: "m/44'/60'/0'/0/0".to_string(),
             curve: CurveType::SECP256k1,
             network: "OPTIMISM_GOERLI".to_string(),
             seg_wit: "NONE".to_string(),
         });
         coin_infos.push(CoinInfo {
-            coin: "ETHERRUM".to_string(),
+            coin: "ETHEREUM".to_string(),
             derivation_path: "m/44'/60'/0'/0/0".to_string(),
             curve: CurveType::SECP256k1,
             network: "ARBITRUM".to_string(),
             seg_wit: "NONE".to_string(),
         });
         coin_infos.push(CoinInfo {
-            coin: "ETRHEREUM".to_string(),
+            coin: "ETHEREUM".to_string(),
             derivation_path: "m/44'/60'/0'/0/0".to_string(),
             curve: CurveType::SECP256k1,
             network: "ARBITRUM_GOERLI".to_string(),
@@ -422,7 +438,7 @@ lazy_static! {
             coin: "ETHEREUM".to_string(),
             derivation_path: "m/44'/60'/0'/0/0".to_string(),
             curve: CurveType::SECP256k1,
-            network: "ULTRA_LIQUID_MAINET".to_string(),
+            network: "ULTRA_LIQUID_MAINNET".to_string(),
             seg_wit: "NONE".to_string(),
         });
         coin_infos.push(CoinInfo {
@@ -525,6 +541,16 @@ lazy_static! {
         });
         RwLock::new(coin_infos)
     };
+    static ref CUSTOM_COIN_INFOS: RwLock<Vec<CoinInfo>> = RwLock::new(Vec::new());
+}
+
+impl CoinInfo {
+    fn is_same_coin(&self, other: &CoinInfo) -> bool {
+        self.coin == other.coin
+            && self.network == other.network
+            && self.seg_wit == other.seg_wit
+            && self.curve == other.curve
+    }
 }
 
 pub fn coin_info_from_param(
@@ -534,8 +560,10 @@ pub fn coin_info_from_param(
     curve: &str,
 ) -> Result<CoinInfo> {
     let coin_infos = COIN_INFOS.read();
+    let custom_coin_infos = CUSTOM_COIN_INFOS.read();
     let mut coins = coin_infos
         .iter()
+        .chain(custom_coin_infos.iter())
         .filter(|x| {
             x.coin.as_str() == chain_type
                 && (x.network.as_str() == network || network.is_empty())
@@ -551,3 +579,129 @@ pub fn coin_info_from_param(
         Ok(coins.pop().expect("coin_info_from_param"))
     }
 }
+
+/// All coin infos, the built-in ones first
+pub fn coin_infos() -> Vec<CoinInfo> {
+    let coin_infos = COIN_INFOS.read();
+    let custom_coin_infos = CUSTOM_COIN_INFOS.read();
+    coin_infos
+        .iter()
+        .chain(custom_coin_infos.iter())
+        .map(|x| x.clone())
+        .collect()
+}
+
+/// The coin infos registered at runtime
+pub fn custom_coin_infos() -> Vec<CoinInfo> {
+    CUSTOM_COIN_INFOS.read().clone()
+}
+
+pub fn register_coin_info(coin_info: CoinInfo) -> Result<()> {
+    let coin_info = CoinInfo {
+        coin: coin_info.coin.to_uppercase(),
+        network: coin_info.network.to_uppercase(),
+        seg_wit: coin_info.seg_wit.to_uppercase(),
+        ..coin_info
+    };
+    if coin_info.coin.is_empty() || coin_info.network.is_empty() || coin_info.seg_wit.is_empty() {
+        return Err(format_err!("coin_info_invalid"));
+    }
+    if !coin_info.derivation_path.starts_with("m/") {
+        return Err(format_err!("coin_info_invalid_derivation_path"));
+    }
+
+    let coin_infos = COIN_INFOS.read();
+    let mut custom_coin_infos = CUSTOM_COIN_INFOS.write();
+    if coin_infos
+        .iter()
+        .chain(custom_coin_infos.iter())
+        .any(|x| x.is_same_coin(&coin_info))
+    {
+        return Err(format_err!("coin_info_already_exists"));
+    }
+    custom_coin_infos.push(coin_info);
+    Ok(())
+}
+
+/// Only the coin infos registered at runtime can be removed
+pub fn remove_coin_info(coin_info: &CoinInfo) -> Result<()> {
+    let mut custom_coin_infos = CUSTOM_COIN_INFOS.write();
+    let len = custom_coin_infos.len();
+    custom_coin_infos.retain(|x| {
+        !(x.coin.eq_ignore_ascii_case(&coin_info.coin)
+            && x.network.eq_ignore_ascii_case(&coin_info.network)
+            && x.seg_wit.eq_ignore_ascii_case(&coin_info.seg_wit)
+            && x.curve == coin_info.curve)
+    });
+    if custom_coin_infos.len() == len {
+        Err(format_err!("custom_coin_info_not_found"))
+    } else {
+        Ok(())
+    }
+}
+
+pub fn clear_custom_coin_infos() {
+    CUSTOM_COIN_INFOS.write().clear()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{coin_info_from_param, register_coin_info, remove_coin_info, CoinInfo};
+    use crate::CurveType;
+
+    #[test]
+    fn test_register_coin_info() {
+        let coin_info = CoinInfo {
+            coin: "DASH".to_string(),
+            derivation_path: "m/44'/5'/0'/0/0".to_string(),
+            curve: CurveType::SECP256k1,
+            network: "MAINNET".to_string(),
+            seg_wit: "NONE".to_string(),
+        };
+        assert!(coin_info_from_param("DASH", "MAINNET", "NONE", "").is_err());
+
+        register_coin_info(coin_info.clone()).unwrap();
+        assert_eq!(
+            coin_info_from_param("DASH", "MAINNET", "NONE", "").unwrap(),
+            coin_info
+        );
+        let ret = register_coin_info(coin_info.clone());
+        assert_eq!(
+            format!("{}", ret.err().unwrap()),
+            "coin_info_already_exists"
+        );
+
+        let ret = register_coin_info(CoinInfo {
+            coin: "ETHEREUM".to_string(),
+            derivation_path: "m/44'/60'/0'/0/0".to_string(),
+            curve: CurveType::SECP256k1,
+            network: "MAINNET".to_string(),
+            seg_wit: "NONE".to_string(),
+        });
+        assert_eq!(
+            format!("{}", ret.err().unwrap()),
+            "coin_info_already_exists"
+        );
+
+        remove_coin_info(&coin_info).unwrap();
+        assert!(coin_info_from_param("DASH", "MAINNET", "NONE", "").is_err());
+        let ret = remove_coin_info(&coin_info);
+        assert_eq!(
+            format!("{}", ret.err().unwrap()),
+            "custom_coin_info_not_found"
+        );
+    }
+
+    #[test]
+    fn test_fixed_ethereum_coin_typos() {
+        for network in &[
+            "OPTIMISM",
+            "OPTIMISM_GOERLI",
+            "ARBITRUM",
+            "ARBITRUM_GOERLI",
+            "ULTRA_LIQUID_MAINNET",
+        ] {
+            assert!(coin_info_from_param("ETHEREUM", network, "", "").is_ok());
+        }
+    }
+}
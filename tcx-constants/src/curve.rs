@@ -1,4 +1,7 @@
+use crate::Result;
+use failure::format_err;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum CurveType {
@@ -28,3 +31,25 @@ impl CurveType {
         }
     }
 }
+
+impl FromStr for CurveType {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        [
+            CurveType::SECP256k1,
+            CurveType::ED25519,
+            CurveType::ED25519Blake2bNano,
+            CurveType::SubSr25519,
+            CurveType::Curve25519,
+            CurveType::NIST256p1,
+            CurveType::BLS,
+            CurveType::StarknetCurve,
+            CurveType::Sm2,
+        ]
+        .iter()
+        .find(|x| x.as_str().eq_ignore_ascii_case(s))
+        .map(|x| *x)
+        .ok_or(format_err!("unsupported_curve"))
+    }
+}
@@ -3,10 +3,14 @@ pub mod coin_info;
 pub mod curve;
 
 pub use btc_fork_network::{
-    coin_from_xpub_prefix, network_form_hrp, network_from_coin, pub_version_from_prv_version,
-    BtcForkNetwork,
+    btc_fork_networks, coin_from_xpub_prefix, custom_btc_fork_networks, network_form_hrp,
+    network_from_coin, pub_version_from_prv_version, register_btc_fork_network,
+    remove_btc_fork_network, slip132_versions, BtcForkNetwork,
+};
+pub use coin_info::{
+    coin_info_from_param, coin_infos, custom_coin_infos, register_coin_info, remove_coin_info,
+    CoinInfo,
 };
-pub use coin_info::{coin_info_from_param, CoinInfo};
 pub use curve::CurveType;
 
 pub type Result<T> = std::result::Result<T, failure::Error>;
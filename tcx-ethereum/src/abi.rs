@@ -0,0 +1,478 @@
+use crate::eip712::{parse_hex, parse_integer, to_word, type_size};
+use crate::keccak;
+use ethereum_types::U256;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tcx_chain::Result;
+
+/// An input of a JSON ABI function fragment, `components` describes the members of a tuple
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AbiParam {
+    #[serde(default)]
+    pub name: String,
+    #[serde(rename = "type")]
+    pub param_type: String,
+    #[serde(default)]
+    pub components: Vec<AbiParam>,
+}
+
+/// A JSON ABI function fragment, only the name and the inputs matter for encoding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AbiFunction {
+    pub name: String,
+    #[serde(default)]
+    pub inputs: Vec<AbiParam>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamType {
+    Address,
+    Bool,
+    Uint(usize),
+    Int(usize),
+    FixedBytes(usize),
+    Bytes,
+    String,
+    Array(Box<ParamType>),
+    FixedArray(Box<ParamType>, usize),
+    Tuple(Vec<(String, ParamType)>),
+}
+
+impl ParamType {
+    pub fn parse(param_type: &str, components: &[AbiParam]) -> Result<ParamType> {
+        let invalid_type = || format_err!("abi_invalid_type: {}", param_type);
+        if param_type.ends_with(']') {
+            let open = param_type.rfind('[').ok_or_else(invalid_type)?;
+            let item_type = Box::new(ParamType::parse(&param_type[..open], components)?);
+            let length = &param_type[open + 1..param_type.len() - 1];
+            return if length.is_empty() {
+                Ok(ParamType::Array(item_type))
+            } else {
+                let length = length.parse::<usize>().map_err(|_| invalid_type())?;
+                Ok(ParamType::FixedArray(item_type, length))
+            };
+        }
+
+        match param_type {
+            "address" => Ok(ParamType::Address),
+            "bool" => Ok(ParamType::Bool),
+            "bytes" => Ok(ParamType::Bytes),
+            "string" => Ok(ParamType::String),
+            "tuple" => {
+                let mut members = vec![];
+                for component in components {
+                    members.push((
+                        component.name.to_string(),
+                        ParamType::parse(&component.param_type, &component.components)?,
+                    ));
+                }
+                Ok(ParamType::Tuple(members))
+            }
+            _ if param_type.starts_with("bytes") => Ok(ParamType::FixedBytes(
+                type_size(param_type, "bytes", 32).ok_or_else(invalid_type)?,
+            )),
+            _ if param_type.starts_with("uint") => Ok(ParamType::Uint(
+                type_size(param_type, "uint", 256).ok_or_else(invalid_type)?,
+            )),
+            _ if param_type.starts_with("int") => Ok(ParamType::Int(
+                type_size(param_type, "int", 256).ok_or_else(invalid_type)?,
+            )),
+            _ => Err(invalid_type()),
+        }
+    }
+
+    /// The canonical type name used in function signatures, tuples are written as `(T1,T2)`
+    pub fn canonical(&self) -> String {
+        match self {
+            ParamType::Address => "address".to_string(),
+            ParamType::Bool => "bool".to_string(),
+            ParamType::Uint(bits) => format!("uint{}", bits),
+            ParamType::Int(bits) => format!("int{}", bits),
+            ParamType::FixedBytes(size) => format!("bytes{}", size),
+            ParamType::Bytes => "bytes".to_string(),
+            ParamType::String => "string".to_string(),
+            ParamType::Array(item_type) => format!("{}[]", item_type.canonical()),
+            ParamType::FixedArray(item_type, length) => {
+                format!("{}[{}]", item_type.canonical(), length)
+            }
+            ParamType::Tuple(members) => format!(
+                "({})",
+                members
+                    .iter()
+                    .map(|(_, member)| member.canonical())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+        }
+    }
+
+    pub fn is_dynamic(&self) -> bool {
+        match self {
+            ParamType::Bytes | ParamType::String | ParamType::Array(_) => true,
+            ParamType::FixedArray(item_type, _) => item_type.is_dynamic(),
+            ParamType::Tuple(members) => members.iter().any(|(_, member)| member.is_dynamic()),
+            _ => false,
+        }
+    }
+
+    /// The size taken in the head of the enclosing tuple
+    fn head_size(&self) -> Result<usize> {
+        if self.is_dynamic() {
+            return Ok(32);
+        }
+        let too_large = || format_err!("abi_invalid_type: {}", self.canonical());
+        match self {
+            ParamType::FixedArray(item_type, length) => item_type
+                .head_size()?
+                .checked_mul(*length)
+                .ok_or_else(too_large),
+            ParamType::Tuple(members) => members.iter().try_fold(0usize, |size, (_, member)| {
+                size.checked_add(member.head_size()?).ok_or_else(too_large)
+            }),
+            _ => Ok(32),
+        }
+    }
+
+    pub fn encode(&self, value: &Value) -> Result<Vec<u8>> {
+        let invalid_value = || format_err!("abi_invalid_value: {}", self.canonical());
+        match self {
+            ParamType::Address => {
+                let bytes = parse_hex(value).ok_or_else(invalid_value)?;
+                if bytes.len() != 20 {
+                    return Err(invalid_value());
+                }
+                Ok([vec![0u8; 12], bytes].concat())
+            }
+            ParamType::Bool => {
+                let flag = match value {
+                    Value::Bool(flag) => *flag,
+                    Value::String(s) if s == "true" => true,
+                    Value::String(s) if s == "false" => false,
+                    _ => return Err(invalid_value()),
+                };
+                Ok(to_word(U256::from(flag as u8)))
+            }
+            ParamType::Uint(bits) => {
+                let (number, negative) = parse_integer(value).ok_or_else(invalid_value)?;
+                if negative || number.bits() > *bits {
+                    return Err(invalid_value());
+                }
+                Ok(to_word(number))
+            }
+            ParamType::Int(bits) => {
+                let (number, negative) = parse_integer(value).ok_or_else(invalid_value)?;
+                // the magnitude of intN is at most 2^(N-1), and below it for positive values
+                let limit = U256::one() << (bits - 1);
+                if number > limit || (!negative && number == limit) {
+                    return Err(invalid_value());
+                }
+                if negative {
+                    // two's complement
+                    Ok(to_word(number.overflowing_neg().0))
+                } else {
+                    Ok(to_word(number))
+                }
+            }
+            ParamType::FixedBytes(size) => {
+                let mut bytes = parse_hex(value).ok_or_else(invalid_value)?;
+                if bytes.len() != *size {
+                    return Err(invalid_value());
+                }
+                bytes.resize(32, 0);
+                Ok(bytes)
+            }
+            ParamType::Bytes => Ok(encode_bytes(&parse_hex(value).ok_or_else(invalid_value)?)),
+            ParamType::String => Ok(encode_bytes(
+                value.as_str().ok_or_else(invalid_value)?.as_bytes(),
+            )),
+            ParamType::Array(item_type) => {
+                let items = value.as_array().ok_or_else(invalid_value)?;
+                let types = vec![item_type.as_ref().clone(); items.len()];
+                let mut encoded = to_word(U256::from(items.len()));
+                encoded.extend(encode_params(&types, &items.iter().collect::<Vec<_>>())?);
+                Ok(encoded)
+            }
+            ParamType::FixedArray(item_type, length) => {
+                let items = value.as_array().ok_or_else(invalid_value)?;
+                if items.len() != *length {
+                    return Err(invalid_value());
+                }
+                let types = vec![item_type.as_ref().clone(); items.len()];
+                encode_params(&types, &items.iter().collect::<Vec<_>>())
+            }
+            ParamType::Tuple(members) => {
+                // a tuple is given either as an array or as an object keyed by member name
+                let values = match value {
+                    Value::Array(items) if items.len() == members.len() => {
+                        items.iter().collect::<Vec<_>>()
+                    }
+                    Value::Object(object) => members
+                        .iter()
+                        .map(|(name, _)| object.get(name).ok_or_else(invalid_value))
+                        .collect::<Result<Vec<_>>>()?,
+                    _ => return Err(invalid_value()),
+                };
+                let types = members
+                    .iter()
+                    .map(|(_, member)| member.clone())
+                    .collect::<Vec<_>>();
+                encode_params(&types, &values)
+            }
+        }
+    }
+}
+
+fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut encoded = to_word(U256::from(bytes.len()));
+    encoded.extend(bytes);
+    let padding = (32 - bytes.len() % 32) % 32;
+    encoded.extend(vec![0u8; padding]);
+    encoded
+}
+
+/// Encodes values as a tuple, dynamic values are appended after the head and
+/// referenced by their offset from the start of the tuple
+pub fn encode_params(types: &[ParamType], values: &[&Value]) -> Result<Vec<u8>> {
+    if types.len() != values.len() {
+        return Err(format_err!("abi_invalid_arguments"));
+    }
+    let mut head_size: usize = 0;
+    for param_type in types {
+        head_size = head_size
+            .checked_add(param_type.head_size()?)
+            .ok_or_else(|| format_err!("abi_invalid_arguments"))?;
+    }
+    let mut head = vec![];
+    let mut tail = vec![];
+    for (param_type, value) in types.iter().zip(values.iter()) {
+        let encoded = param_type.encode(value)?;
+        if param_type.is_dynamic() {
+            head.extend(to_word(U256::from(head_size + tail.len())));
+            tail.extend(encoded);
+        } else {
+            head.extend(encoded);
+        }
+    }
+    head.extend(tail);
+    Ok(head)
+}
+
+impl AbiFunction {
+    pub fn param_types(&self) -> Result<Vec<ParamType>> {
+        self.inputs
+            .iter()
+            .map(|x| ParamType::parse(&x.param_type, &x.components))
+            .collect()
+    }
+
+    /// `name(type1,type2)`, e.g. `transfer(address,uint256)`
+    pub fn signature(&self) -> Result<String> {
+        let types = self
+            .param_types()?
+            .iter()
+            .map(|x| x.canonical())
+            .collect::<Vec<String>>()
+            .join(",");
+        Ok(format!("{}({})", self.name, types))
+    }
+
+    pub fn selector(&self) -> Result<Vec<u8>> {
+        Ok(keccak(self.signature()?.as_bytes())[..4].to_vec())
+    }
+
+    /// The selector followed by the encoded arguments
+    pub fn encode_input(&self, args: &[Value]) -> Result<Vec<u8>> {
+        let mut data = self.selector()?;
+        data.extend(encode_params(
+            &self.param_types()?,
+            &args.iter().collect::<Vec<_>>(),
+        )?);
+        Ok(data)
+    }
+}
+
+/// Picks the function to call from a single fragment or a full ABI. `method` is
+/// a function name or, to tell overloads apart, a signature like `transfer(address,uint256)`
+fn find_function(abi: &Value, method: &str, arg_count: usize) -> Result<AbiFunction> {
+    let fragments = match abi {
+        Value::Array(fragments) => fragments.iter().collect::<Vec<_>>(),
+        Value::Object(_) => vec![abi],
+        _ => return Err(format_err!("invalid_abi")),
+    };
+
+    let mut candidates = vec![];
+    for fragment in fragments {
+        let fragment_type = fragment
+            .get("type")
+            .and_then(|x| x.as_str())
+            .unwrap_or("function");
+        if fragment_type != "function" {
+            continue;
+        }
+        let function: AbiFunction =
+            serde_json::from_value(fragment.clone()).map_err(|_| format_err!("invalid_abi"))?;
+        if method.is_empty() || function.name == method || function.signature()? == method {
+            candidates.push(function);
+        }
+    }
+    if candidates.len() > 1 {
+        candidates.retain(|x| x.inputs.len() == arg_count);
+    }
+
+    match candidates.len() {
+        0 => Err(format_err!("abi_method_not_found: {}", method)),
+        1 => Ok(candidates.remove(0)),
+        _ => Err(format_err!("abi_ambiguous_method: {}", method)),
+    }
+}
+
+/// Encodes calldata from a JSON ABI (a function fragment or the whole contract ABI)
+/// and a JSON array of argument values
+pub fn encode_function_call(abi: &str, method: &str, args: &str) -> Result<Vec<u8>> {
+    let abi: Value = serde_json::from_str(abi).map_err(|_| format_err!("invalid_abi"))?;
+    let args: Vec<Value> = if args.is_empty() {
+        vec![]
+    } else {
+        serde_json::from_str(args).map_err(|_| format_err!("abi_invalid_arguments"))?
+    };
+    let function = find_function(&abi, method, args.len())?;
+    function.encode_input(&args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_function_call, AbiFunction, ParamType};
+    use serde_json::json;
+
+    fn encode(abi: &str, args: &str) -> String {
+        hex::encode(encode_function_call(abi, "", args).unwrap())
+    }
+
+    #[test]
+    fn test_encode_static() {
+        // examples from the solidity ABI specification
+        let abi = r#"{"name":"baz","inputs":[{"type":"uint32"},{"type":"bool"}]}"#;
+        assert_eq!(encode(abi, "[69, true]"), "cdcd77c000000000000000000000000000000000000000000000000000000000000000450000000000000000000000000000000000000000000000000000000000000001");
+
+        let abi = r#"{"name":"bar","inputs":[{"type":"bytes3[2]"}]}"#;
+        assert_eq!(encode(abi, r#"[["0x616263", "0x646566"]]"#), "fce353f661626300000000000000000000000000000000000000000000000000000000006465660000000000000000000000000000000000000000000000000000000000");
+    }
+
+    #[test]
+    fn test_encode_dynamic() {
+        let abi =
+            r#"{"name":"sam","inputs":[{"type":"bytes"},{"type":"bool"},{"type":"uint256[]"}]}"#;
+        assert_eq!(encode(abi, r#"["0x64617665", true, [1, 2, 3]]"#), "a5643bf20000000000000000000000000000000000000000000000000000000000000060000000000000000000000000000000000000000000000000000000000000000100000000000000000000000000000000000000000000000000000000000000a0000000000000000000000000000000000000000000000000000000000000000464617665000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000003000000000000000000000000000000000000000000000000000000000000000100000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000003");
+
+        let abi = r#"{"name":"f","inputs":[{"type":"uint256"},{"type":"uint32[]"},{"type":"bytes10"},{"type":"bytes"}]}"#;
+        assert_eq!(encode(abi, r#"["0x123", ["0x456", "0x789"], "0x31323334353637383930", "0x48656c6c6f2c20776f726c6421"]"#), "8be6524600000000000000000000000000000000000000000000000000000000000001230000000000000000000000000000000000000000000000000000000000000080313233343536373839300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000e0000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000004560000000000000000000000000000000000000000000000000000000000000789000000000000000000000000000000000000000000000000000000000000000d48656c6c6f2c20776f726c642100000000000000000000000000000000000000");
+
+        let abi = r#"{"name":"g","inputs":[{"type":"uint256[][]"},{"type":"string[]"}]}"#;
+        assert_eq!(encode(abi, r#"[[[1, 2], [3]], ["one", "two", "three"]]"#), "2289b18c000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000001400000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000000a0000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000100000000000000000000000000000000000000000000000000000000000000030000000000000000000000000000000000000000000000000000000000000003000000000000000000000000000000000000000000000000000000000000006000000000000000000000000000000000000000000000000000000000000000a000000000000000000000000000000000000000000000000000000000000000e000000000000000000000000000000000000000000000000000000000000000036f6e650000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000374776f000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000057468726565000000000000000000000000000000000000000000000000000000");
+    }
+
+    #[test]
+    fn test_encode_tuple() {
+        let function: AbiFunction = serde_json::from_value(json!({
+            "name": "submit",
+            "inputs": [{
+                "name": "order",
+                "type": "tuple",
+                "components": [
+                    {"name": "maker", "type": "address"},
+                    {"name": "amount", "type": "int128"},
+                    {"name": "memo", "type": "string"}
+                ]
+            }]
+        }))
+        .unwrap();
+        assert_eq!(
+            function.signature().unwrap(),
+            "submit((address,int128,string))"
+        );
+
+        let as_array = function
+            .encode_input(&[json!([
+                "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826",
+                "-1",
+                "hi"
+            ])])
+            .unwrap();
+        let as_object = function
+            .encode_input(&[json!({
+                "memo": "hi",
+                "maker": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826",
+                "amount": -1
+            })])
+            .unwrap();
+        assert_eq!(as_array, as_object);
+        assert_eq!(hex::encode(&as_array[4..]), "0000000000000000000000000000000000000000000000000000000000000020000000000000000000000000cd2a3d9f938e13cd947ec05abc7fe734df8dd826ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff000000000000000000000000000000000000000000000000000000000000006000000000000000000000000000000000000000000000000000000000000000026869000000000000000000000000000000000000000000000000000000000000");
+    }
+
+    #[test]
+    fn test_select_method() {
+        let abi = r#"[
+            {"type":"constructor","inputs":[]},
+            {"type":"function","name":"transfer","inputs":[{"name":"to","type":"address"},{"name":"amount","type":"uint256"}]},
+            {"type":"function","name":"mint","inputs":[{"type":"uint256"}]},
+            {"type":"function","name":"mint","inputs":[{"type":"address"},{"type":"uint256"}]},
+            {"type":"event","name":"Transfer","inputs":[]}
+        ]"#;
+        let data = encode_function_call(
+            abi,
+            "transfer",
+            r#"["0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826", "1000"]"#,
+        )
+        .unwrap();
+        assert_eq!(hex::encode(&data[..4]), "a9059cbb");
+
+        // overloads are resolved by argument count or by signature
+        let data = encode_function_call(abi, "mint", "[1]").unwrap();
+        assert_eq!(hex::encode(&data[..4]), "a0712d68");
+        let data = encode_function_call(
+            abi,
+            "mint(address,uint256)",
+            r#"["0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826", 1]"#,
+        )
+        .unwrap();
+        assert_eq!(hex::encode(&data[..4]), "40c10f19");
+
+        assert_eq!(
+            format!("{}", encode_function_call(abi, "burn", "[]").unwrap_err()),
+            "abi_method_not_found: burn"
+        );
+        assert_eq!(
+            format!("{}", encode_function_call(abi, "", "[1, 2]").unwrap_err()),
+            "abi_ambiguous_method: "
+        );
+    }
+
+    #[test]
+    fn test_encode_invalid() {
+        let assert_invalid = |param_type: &str, value: serde_json::Value| {
+            let param_type = ParamType::parse(param_type, &[]).unwrap();
+            assert!(param_type.encode(&value).is_err());
+        };
+        assert_invalid("uint8", json!(256));
+        assert_invalid("uint256", json!(-1));
+        assert_invalid("int8", json!(128));
+        assert_invalid("int8", json!(-129));
+        assert_invalid("bytes2", json!("0x01"));
+        assert_invalid("address", json!("0x1234"));
+        assert_invalid("bool", json!(1));
+        assert_invalid("uint8[2]", json!([1]));
+
+        let int8 = ParamType::parse("int8", &[]).unwrap();
+        assert_eq!(
+            hex::encode(int8.encode(&json!(-128)).unwrap()),
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff80"
+        );
+        assert!(ParamType::parse("uint7", &[]).is_err());
+        assert!(ParamType::parse("bytes33", &[]).is_err());
+        assert!(ParamType::parse("fixed128x18", &[]).is_err());
+
+        // the head size of 2^59 words overflows
+        let abi = r#"{"name":"f","inputs":[{"type":"uint256[576460752303423488]"}]}"#;
+        assert!(encode_function_call(abi, "", "[[]]").is_err());
+    }
+}
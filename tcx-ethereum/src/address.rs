@@ -35,7 +35,7 @@ impl Address for EthereumAddress {
     }
 }
 
-fn to_checksum(address: &str) -> String {
+pub(crate) fn to_checksum(address: &str) -> String {
     // https://github.com/miguelmota/rust-eth-checksum/blob/master/src/lib.rs
     let address = address.trim_start_matches("0x").to_lowercase();
 
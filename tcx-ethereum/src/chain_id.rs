@@ -1,10 +1,12 @@
 use failure::format_err;
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 
 pub type Result<T> = std::result::Result<T, failure::Error>;
 
 /// Ethereum chain info
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ChainInfo {
     pub network: String,
     pub network_id: i32,
@@ -206,12 +208,15 @@ lazy_static! {
         });
         RwLock::new(chain_infos)
     };
+    static ref CUSTOM_CHAIN_INFOS: RwLock<Vec<ChainInfo>> = RwLock::new(Vec::new());
 }
 
 pub fn chain_id_from_network(network: &str) -> Result<u64> {
     let chain_infos = CHAIN_INFOS.read();
+    let custom_chain_infos = CUSTOM_CHAIN_INFOS.read();
     let mut res: Vec<u64> = chain_infos
         .iter()
+        .chain(custom_chain_infos.iter())
         .filter(|x| x.network.as_str() == network)
         .map(|x| x.chain_id)
         .collect::<Vec<u64>>();
@@ -221,3 +226,98 @@ pub fn chain_id_from_network(network: &str) -> Result<u64> {
         Err(format_err!("No chain id for network"))
     }
 }
+
+/// All chain infos, the built-in ones first
+pub fn chain_infos() -> Vec<ChainInfo> {
+    let chain_infos = CHAIN_INFOS.read();
+    let custom_chain_infos = CUSTOM_CHAIN_INFOS.read();
+    chain_infos
+        .iter()
+        .chain(custom_chain_infos.iter())
+        .map(|x| x.clone())
+        .collect()
+}
+
+/// The chain infos registered at runtime
+pub fn custom_chain_infos() -> Vec<ChainInfo> {
+    CUSTOM_CHAIN_INFOS.read().clone()
+}
+
+/// Both `network` and `chain_id` must be unique
+pub fn register_chain_info(chain_info: ChainInfo) -> Result<()> {
+    let chain_info = ChainInfo {
+        network: chain_info.network.to_uppercase(),
+        ..chain_info
+    };
+    if chain_info.network.is_empty() || chain_info.chain_id == 0 {
+        return Err(format_err!("chain_info_invalid"));
+    }
+
+    let chain_infos = CHAIN_INFOS.read();
+    let mut custom_chain_infos = CUSTOM_CHAIN_INFOS.write();
+    if chain_infos
+        .iter()
+        .chain(custom_chain_infos.iter())
+        .any(|x| x.network == chain_info.network || x.chain_id == chain_info.chain_id)
+    {
+        return Err(format_err!("chain_info_already_exists"));
+    }
+    custom_chain_infos.push(chain_info);
+    Ok(())
+}
+
+/// Only the chain infos registered at runtime can be removed
+pub fn remove_chain_info(network: &str) -> Result<()> {
+    let mut custom_chain_infos = CUSTOM_CHAIN_INFOS.write();
+    let len = custom_chain_infos.len();
+    custom_chain_infos.retain(|x| !x.network.eq_ignore_ascii_case(network));
+    if custom_chain_infos.len() == len {
+        Err(format_err!("custom_chain_info_not_found"))
+    } else {
+        Ok(())
+    }
+}
+
+pub fn clear_custom_chain_infos() {
+    CUSTOM_CHAIN_INFOS.write().clear()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{chain_id_from_network, register_chain_info, remove_chain_info, ChainInfo};
+
+    #[test]
+    fn test_register_chain_info() {
+        let chain_info = ChainInfo {
+            network: "SCROLL".to_string(),
+            network_id: 534352,
+            chain_id: 534352,
+        };
+        assert!(chain_id_from_network("SCROLL").is_err());
+        register_chain_info(chain_info.clone()).unwrap();
+        assert_eq!(chain_id_from_network("SCROLL").unwrap(), 534352);
+
+        let ret = register_chain_info(chain_info.clone());
+        assert_eq!(
+            format!("{}", ret.err().unwrap()),
+            "chain_info_already_exists"
+        );
+        let ret = register_chain_info(ChainInfo {
+            network: "MY_MAINNET".to_string(),
+            network_id: 1,
+            chain_id: 1,
+        });
+        assert_eq!(
+            format!("{}", ret.err().unwrap()),
+            "chain_info_already_exists"
+        );
+
+        remove_chain_info("SCROLL").unwrap();
+        assert!(chain_id_from_network("SCROLL").is_err());
+        let ret = remove_chain_info("MAINNET");
+        assert_eq!(
+            format!("{}", ret.err().unwrap()),
+            "custom_chain_info_not_found"
+        );
+    }
+}
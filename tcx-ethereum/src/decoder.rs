@@ -0,0 +1,365 @@
+use crate::address::to_checksum;
+use crate::transaction::{
+    EthereumCallArgument, EthereumContractCall, EthereumTxDecodeIn, EthereumTxDecodeOut,
+    EthereumTxIn,
+};
+use crate::types::Transaction;
+use crate::Error;
+use ethereum_types::U256;
+use tcx_chain::Result;
+
+struct KnownMethod {
+    selector: [u8; 4],
+    standard: &'static str,
+    method: &'static str,
+    arguments: &'static [(&'static str, &'static str)],
+}
+
+const KNOWN_METHODS: [KnownMethod; 5] = [
+    // transfer(address,uint256)
+    KnownMethod {
+        selector: [0xa9, 0x05, 0x9c, 0xbb],
+        standard: "ERC20",
+        method: "transfer",
+        arguments: &[("to", "address"), ("amount", "uint256")],
+    },
+    // approve(address,uint256)
+    KnownMethod {
+        selector: [0x09, 0x5e, 0xa7, 0xb3],
+        standard: "ERC20",
+        method: "approve",
+        arguments: &[("spender", "address"), ("amount", "uint256")],
+    },
+    // safeTransferFrom(address,address,uint256)
+    KnownMethod {
+        selector: [0x42, 0x84, 0x2e, 0x0e],
+        standard: "ERC721",
+        method: "safeTransferFrom",
+        arguments: &[
+            ("from", "address"),
+            ("to", "address"),
+            ("tokenId", "uint256"),
+        ],
+    },
+    // safeTransferFrom(address,address,uint256,bytes)
+    KnownMethod {
+        selector: [0xb8, 0x8d, 0x4f, 0xde],
+        standard: "ERC721",
+        method: "safeTransferFrom",
+        arguments: &[
+            ("from", "address"),
+            ("to", "address"),
+            ("tokenId", "uint256"),
+            ("data", "bytes"),
+        ],
+    },
+    // safeTransferFrom(address,address,uint256,uint256,bytes)
+    KnownMethod {
+        selector: [0xf2, 0x42, 0x43, 0x2a],
+        standard: "ERC1155",
+        method: "safeTransferFrom",
+        arguments: &[
+            ("from", "address"),
+            ("to", "address"),
+            ("id", "uint256"),
+            ("amount", "uint256"),
+            ("data", "bytes"),
+        ],
+    },
+];
+
+/// Interprets calldata of the known token methods, returns `None` for anything else
+/// or when the arguments are not well-formed
+pub fn decode_call_data(data: &[u8]) -> Option<EthereumContractCall> {
+    if data.len() < 4 {
+        return None;
+    }
+    let method = KNOWN_METHODS.iter().find(|x| x.selector == data[..4])?;
+    let params = &data[4..];
+
+    let mut arguments = vec![];
+    for (idx, (name, arg_type)) in method.arguments.iter().enumerate() {
+        let word = params.get(idx * 32..(idx + 1) * 32)?;
+        let value = match *arg_type {
+            "address" => {
+                if word[..12].iter().any(|x| *x != 0) {
+                    return None;
+                }
+                to_checksum(&hex::encode(&word[12..]))
+            }
+            "uint256" => U256::from_big_endian(word).to_string(),
+            "bytes" => format!("0x{}", hex::encode(read_dynamic_bytes(params, word)?)),
+            _ => return None,
+        };
+        arguments.push(EthereumCallArgument {
+            name: name.to_string(),
+            r#type: arg_type.to_string(),
+            value,
+        });
+    }
+
+    Some(EthereumContractCall {
+        standard: method.standard.to_string(),
+        method: method.method.to_string(),
+        selector: format!("0x{}", hex::encode(&data[..4])),
+        arguments,
+    })
+}
+
+fn read_dynamic_bytes<'a>(params: &'a [u8], offset_word: &[u8]) -> Option<&'a [u8]> {
+    let offset = U256::from_big_endian(offset_word);
+    if offset > U256::from(params.len()) {
+        return None;
+    }
+    let offset = offset.as_usize();
+    let length = U256::from_big_endian(params.get(offset..offset + 32)?);
+    if length > U256::from(params.len()) {
+        return None;
+    }
+    params.get(offset + 32..offset + 32 + length.as_usize())
+}
+
+/// Decodes a raw transaction into the fields of `EthereumTxIn` so that it can
+/// be shown to the user before signing
+pub fn decode_transaction(input: &EthereumTxDecodeIn) -> Result<EthereumTxDecodeOut> {
+    let mut raw_hex: String = input.raw_tx.clone();
+    if raw_hex.to_uppercase().starts_with("0X") {
+        raw_hex.replace_range(..2, "")
+    }
+    let bytes = hex::decode(&raw_hex).map_err(|_| Error::InvalidRawTransaction)?;
+    let decoded = Transaction::decode(&bytes)?;
+    let from = if decoded.signature.is_some() {
+        to_checksum(&hex::encode(decoded.recover_signer()?.as_bytes()))
+    } else {
+        "".to_string()
+    };
+
+    let tx = &decoded.transaction;
+    let transaction_type = tx.transaction_type.map(|x| x.as_u64());
+    let max_priority_fee_per_gas = if transaction_type == Some(2) || transaction_type == Some(4) {
+        tx.max_priority_fee_per_gas.to_string()
+    } else {
+        "".to_string()
+    };
+    let authorization_list = if transaction_type == Some(4) {
+        serde_json::to_string(&tx.authorization_list)?
+    } else {
+        "".to_string()
+    };
+    let tx_in = EthereumTxIn {
+        nonce: tx.nonce.to_string(),
+        to: tx
+            .to
+            .map(|to| to_checksum(&hex::encode(to.as_bytes()))[2..].to_string())
+            .unwrap_or_default(),
+        value: tx.value.to_string(),
+        gas_price: tx.gas_price.to_string(),
+        gas: tx.gas.to_string(),
+        data: hex::encode(&tx.data),
+        network: "".to_string(),
+        access_list: serde_json::to_string(&tx.access_list)?,
+        max_priority_fee_per_gas,
+        transaction_type: transaction_type.map(|x| x.to_string()).unwrap_or_default(),
+        chain_id: decoded.chain_id.unwrap_or(0),
+        authorization_list,
+    };
+
+    Ok(EthereumTxDecodeOut {
+        tx: Some(tx_in),
+        signed: decoded.signature.is_some(),
+        from,
+        contract_call: decode_call_data(&tx.data),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_call_data, decode_transaction};
+    use crate::transaction::{EthereumTxDecodeIn, EthereumTxIn};
+    use crate::types::Transaction;
+    use core::convert::TryFrom;
+    use ethereum_types::H256;
+
+    fn private_key() -> H256 {
+        H256::from_slice(
+            &hex::decode("2a3526dd05ad2ebba87673f711ef8c336115254ef8fcd38c4d8166db9a8120e4")
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_decode_signed_eip1559() {
+        let input = EthereumTxDecodeIn {
+            raw_tx: "0x02f8720480843b9aca0a843b9aca0a830186a094355972b9007c736515523417c96561f63db4e7bc87038d7ea4c6800080c001a0b88af67e8d892c55539eb6bec47704db4b17173b8a8d8bd3a0c59c87319e150aa00baf8b065dde5601cef06f1b7ddf4ebd125c5abad2250315d092a00cc2c4a4c4".to_string(),
+        };
+        let output = decode_transaction(&input).unwrap();
+        assert!(output.signed);
+        assert_eq!(output.from, "0x3de0e4CD2FF24557bD9ed743e038eF18202F2acd");
+        assert_eq!(output.contract_call, None);
+        let tx = output.tx.unwrap();
+        assert_eq!(
+            tx,
+            EthereumTxIn {
+                nonce: "0".to_string(),
+                to: "355972B9007c736515523417c96561F63db4e7bC".to_string(),
+                value: "1000000000000000".to_string(),
+                gas_price: "1000000010".to_string(),
+                gas: "100000".to_string(),
+                data: "".to_string(),
+                network: "".to_string(),
+                access_list: "[]".to_string(),
+                max_priority_fee_per_gas: "1000000010".to_string(),
+                transaction_type: "2".to_string(),
+                chain_id: 4,
+                authorization_list: "".to_string(),
+            }
+        );
+
+        // the decoded fields sign back to the same transaction
+        let signed = Transaction::try_from(&tx)
+            .unwrap()
+            .sign(&private_key(), tx.chain_id)
+            .unwrap();
+        assert_eq!(hex::encode(signed), &input.raw_tx[2..]);
+    }
+
+    #[test]
+    fn test_decode_roundtrip() {
+        let access_list = r#"[{"address":"0x355972b9007c736515523417c96561f63db4e7bc","storageKeys":["0x0000000000000000000000000000000000000000000000000000000000000001"]}]"#;
+        for (transaction_type, chain_id) in vec![
+            ("", 1u64),
+            ("", 534352),
+            ("", u64::max_value()),
+            ("1", 56),
+            ("2", 0xffff_ffff_ffff),
+        ] {
+            let tx_in = EthereumTxIn {
+                nonce: "8".to_string(),
+                to: "355972B9007c736515523417c96561F63db4e7bC".to_string(),
+                value: "1".to_string(),
+                gas_price: "20000000000".to_string(),
+                gas: "60000".to_string(),
+                data: "a9059cbb000000000000000000000000355972b9007c736515523417c96561f63db4e7bc00000000000000000000000000000000000000000000000000000000000003e8".to_string(),
+                network: "".to_string(),
+                access_list: if transaction_type.is_empty() {
+                    "[]".to_string()
+                } else {
+                    access_list.to_string()
+                },
+                max_priority_fee_per_gas: if transaction_type == "2" {
+                    "1000000000".to_string()
+                } else {
+                    "".to_string()
+                },
+                transaction_type: transaction_type.to_string(),
+                chain_id,
+                authorization_list: "".to_string(),
+            };
+
+            let unsigned = Transaction::try_from(&tx_in)
+                .unwrap()
+                .encode(chain_id, None)
+                .unwrap();
+            let output = decode_transaction(&EthereumTxDecodeIn {
+                raw_tx: hex::encode(&unsigned),
+            })
+            .unwrap();
+            assert!(!output.signed);
+            assert_eq!(output.from, "");
+            assert_eq!(output.tx.as_ref().unwrap(), &tx_in);
+
+            let signed = Transaction::try_from(&tx_in)
+                .unwrap()
+                .sign(&private_key(), chain_id)
+                .unwrap();
+            let output = decode_transaction(&EthereumTxDecodeIn {
+                raw_tx: hex::encode(&signed),
+            })
+            .unwrap();
+            assert!(output.signed);
+            assert_eq!(output.from, "0x3de0e4CD2FF24557bD9ed743e038eF18202F2acd");
+            assert_eq!(output.tx.as_ref().unwrap(), &tx_in);
+
+            let contract_call = output.contract_call.unwrap();
+            assert_eq!(contract_call.standard, "ERC20");
+            assert_eq!(contract_call.method, "transfer");
+        }
+    }
+
+    #[test]
+    fn test_decode_pre_eip155() {
+        // signed by keccak256("cow") without replay protection, v = 27 + recovery id
+        let raw_tx = "f866098504a817c800825208945df9b87991262f6ba471f09758cde1c0fc1de734827a69801ca0f0ed5c31d866a3a6cf8c9283c24ea6ce40b5077338cf90f8ced5e643f739c4cda0304839d731dffb685ba8c7597890806ad2284c24258c901e975ea1a422cf178b";
+        let output = decode_transaction(&EthereumTxDecodeIn {
+            raw_tx: raw_tx.to_string(),
+        })
+        .unwrap();
+        assert!(output.signed);
+        assert_eq!(output.from, "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826");
+        let tx = output.tx.unwrap();
+        assert_eq!(tx.chain_id, 0);
+        assert_eq!(tx.value, "31337");
+        assert_eq!(tx.to, "5DF9B87991262F6BA471F09758CDE1c0FC1De734");
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        // the last one is a set code transaction without a destination
+        for raw_tx in vec![
+            "",
+            "zz",
+            "03c0",
+            "02c0",
+            "c0",
+            "f84980",
+            "04ca0180808080808080c0c0",
+        ] {
+            let ret = decode_transaction(&EthereumTxDecodeIn {
+                raw_tx: raw_tx.to_string(),
+            });
+            assert_eq!(format!("{}", ret.err().unwrap()), "invalid_raw_transaction");
+        }
+    }
+
+    #[test]
+    fn test_decode_call_data() {
+        let data = hex::decode("095ea7b3000000000000000000000000355972b9007c736515523417c96561f63db4e7bcffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff").unwrap();
+        let call = decode_call_data(&data).unwrap();
+        assert_eq!(call.standard, "ERC20");
+        assert_eq!(call.method, "approve");
+        assert_eq!(call.selector, "0x095ea7b3");
+        assert_eq!(call.arguments[0].name, "spender");
+        assert_eq!(
+            call.arguments[0].value,
+            "0x355972B9007c736515523417c96561F63db4e7bC"
+        );
+        assert_eq!(
+            call.arguments[1].value,
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+
+        let data = hex::decode("b88d4fde0000000000000000000000003de0e4cd2ff24557bd9ed743e038ef18202f2acd000000000000000000000000355972b9007c736515523417c96561f63db4e7bc000000000000000000000000000000000000000000000000000000000000002a00000000000000000000000000000000000000000000000000000000000000800000000000000000000000000000000000000000000000000000000000000002beef000000000000000000000000000000000000000000000000000000000000").unwrap();
+        let call = decode_call_data(&data).unwrap();
+        assert_eq!(call.standard, "ERC721");
+        assert_eq!(call.method, "safeTransferFrom");
+        let values = call
+            .arguments
+            .iter()
+            .map(|x| x.value.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            values,
+            vec![
+                "0x3de0e4CD2FF24557bD9ed743e038eF18202F2acd",
+                "0x355972B9007c736515523417c96561F63db4e7bC",
+                "42",
+                "0xbeef"
+            ]
+        );
+
+        // truncated arguments and unknown selectors are not interpreted
+        assert_eq!(decode_call_data(&data[..68]), None);
+        assert_eq!(decode_call_data(&hex::decode("a9059cbb").unwrap()), None);
+        assert_eq!(decode_call_data(&hex::decode("deadbeef").unwrap()), None);
+    }
+}
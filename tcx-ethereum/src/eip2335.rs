@@ -0,0 +1,255 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tcx_chain::Result;
+use tcx_crypto::aes::ctr::{decrypt_nopadding, encrypt_nopadding};
+use tcx_crypto::crypto::{KdfParams, Pbkdf2Params, SCryptParams};
+use tcx_crypto::numberic_util::random_iv;
+use unicode_normalization::UnicodeNormalization;
+use uuid::Uuid;
+
+const EIP2335_VERSION: u32 = 4;
+const CHECKSUM_FUNCTION: &str = "sha256";
+const CIPHER_FUNCTION: &str = "aes-128-ctr";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Eip2335Module {
+    pub function: String,
+    pub params: Value,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Eip2335Crypto {
+    pub kdf: Eip2335Module,
+    pub checksum: Eip2335Module,
+    pub cipher: Eip2335Module,
+}
+
+/// EIP-2335 BLS12-381 keystore, the keystore format of Ethereum validator clients
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Eip2335Keystore {
+    pub crypto: Eip2335Crypto,
+    #[serde(default)]
+    pub description: String,
+    pub pubkey: String,
+    pub path: String,
+    pub uuid: String,
+    pub version: u32,
+}
+
+/// NFKD normalized password without the C0, C1 and Delete control codes
+fn process_password(password: &str) -> Vec<u8> {
+    password
+        .nfkd()
+        .filter(|x| !(*x <= '\u{1f}' || ('\u{7f}'..='\u{9f}').contains(x)))
+        .collect::<String>()
+        .into_bytes()
+}
+
+fn kdf_params<T: KdfParams + DeserializeOwned>(kdf: &Eip2335Module) -> Result<T> {
+    let params: T = serde_json::from_value(kdf.params.clone())
+        .map_err(|_| format_err!("invalid_eip2335_keystore"))?;
+    params.validate()?;
+    Ok(params)
+}
+
+fn derive_key(kdf: &Eip2335Module, password: &str) -> Result<Vec<u8>> {
+    let password = process_password(password);
+    let mut derived_key = vec![0u8; 32];
+    match kdf.function.as_str() {
+        "scrypt" => {
+            kdf_params::<SCryptParams>(kdf)?.generate_derived_key(&password, &mut derived_key)
+        }
+        "pbkdf2" => {
+            kdf_params::<Pbkdf2Params>(kdf)?.generate_derived_key(&password, &mut derived_key)
+        }
+        _ => return Err(format_err!("unsupported_kdf: {}", kdf.function)),
+    }
+    Ok(derived_key)
+}
+
+fn checksum(derived_key: &[u8], cipher_message: &[u8]) -> Vec<u8> {
+    Sha256::digest(&[&derived_key[16..32], cipher_message].concat()).to_vec()
+}
+
+impl Eip2335Keystore {
+    /// Encrypts the 32 bytes big endian `secret` with `password`, `T` is the kdf of the keystore
+    pub fn encrypt<T: KdfParams + Serialize>(
+        secret: &[u8],
+        pubkey: &[u8],
+        path: &str,
+        password: &str,
+    ) -> Result<Eip2335Keystore> {
+        let mut params = T::default();
+        params.set_salt(&hex::encode(random_iv(32)));
+        let kdf = Eip2335Module {
+            function: T::kdf_key(),
+            params: serde_json::to_value(params)?,
+            message: "".to_string(),
+        };
+
+        let derived_key = derive_key(&kdf, password)?;
+        let iv = random_iv(16);
+        let cipher_message = encrypt_nopadding(secret, &derived_key[..16], &iv)?;
+
+        Ok(Eip2335Keystore {
+            crypto: Eip2335Crypto {
+                kdf,
+                checksum: Eip2335Module {
+                    function: CHECKSUM_FUNCTION.to_string(),
+                    params: json!({}),
+                    message: hex::encode(checksum(&derived_key, &cipher_message)),
+                },
+                cipher: Eip2335Module {
+                    function: CIPHER_FUNCTION.to_string(),
+                    params: json!({ "iv": hex::encode(iv) }),
+                    message: hex::encode(cipher_message),
+                },
+            },
+            description: "".to_string(),
+            pubkey: hex::encode(pubkey),
+            path: path.to_string(),
+            uuid: Uuid::new_v4().to_hyphenated().to_string(),
+            version: EIP2335_VERSION,
+        })
+    }
+
+    /// Decrypts the secret after verifying the checksum of the keystore
+    pub fn decrypt(&self, password: &str) -> Result<Vec<u8>> {
+        if self.version != EIP2335_VERSION
+            || self.crypto.checksum.function != CHECKSUM_FUNCTION
+            || self.crypto.cipher.function != CIPHER_FUNCTION
+        {
+            return Err(format_err!("invalid_eip2335_keystore"));
+        }
+        let cipher_message = hex::decode(&self.crypto.cipher.message)
+            .map_err(|_| format_err!("invalid_eip2335_keystore"))?;
+        let iv = self.crypto.cipher.params["iv"]
+            .as_str()
+            .and_then(|x| hex::decode(x).ok())
+            .ok_or_else(|| format_err!("invalid_eip2335_keystore"))?;
+
+        let derived_key = derive_key(&self.crypto.kdf, password)?;
+        if hex::encode(checksum(&derived_key, &cipher_message)) != self.crypto.checksum.message {
+            return Err(tcx_crypto::Error::PasswordIncorrect.into());
+        }
+        decrypt_nopadding(&cipher_message, &derived_key[..16], &iv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{process_password, Eip2335Keystore};
+    use tcx_crypto::crypto::Pbkdf2Params;
+
+    const PASSWORD: &str = "𝔱𝔢𝔰𝔱𝔭𝔞𝔰𝔰𝔴𝔬𝔯𝔡🔑";
+    const SECRET: &str = "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f";
+    const PUBKEY: &str = "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07";
+
+    #[test]
+    fn test_process_password() {
+        assert_eq!(process_password(PASSWORD), "testpassword🔑".as_bytes());
+        assert_eq!(process_password("pass\u{7f}word\u{0}\u{85}"), b"password");
+    }
+
+    #[test]
+    fn test_decrypt_spec_keystores() {
+        // the scrypt keystore of the EIP with n lowered to 1024
+        let scrypt = r#"{
+            "crypto": {
+                "kdf": {
+                    "function": "scrypt",
+                    "params": {
+                        "dklen": 32,
+                        "n": 1024,
+                        "p": 1,
+                        "r": 8,
+                        "salt": "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+                    },
+                    "message": ""
+                },
+                "checksum": {
+                    "function": "sha256",
+                    "params": {},
+                    "message": "8ca4bc8dcb7b226d88b2f4c4fffc395f553d8a84227e30b6ca5f37e72aec73d5"
+                },
+                "cipher": {
+                    "function": "aes-128-ctr",
+                    "params": {
+                        "iv": "264daa3f303d7259501c93d997d84fe6"
+                    },
+                    "message": "f231aab75697116aed3d631d8e590946cab99199a17b7ad8817d4075eca0ce19"
+                }
+            },
+            "description": "This is a test keystore that uses scrypt to secure the secret.",
+            "pubkey": "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07",
+            "path": "m/12381/60/3141592653/589793238",
+            "uuid": "1d85ae20-35c5-4611-98e8-aa14a633906f",
+            "version": 4
+        }"#;
+        let keystore: Eip2335Keystore = serde_json::from_str(scrypt).unwrap();
+        assert_eq!(hex::encode(keystore.decrypt(PASSWORD).unwrap()), SECRET);
+
+        let pbkdf2 = r#"{
+            "crypto": {
+                "kdf": {
+                    "function": "pbkdf2",
+                    "params": {
+                        "dklen": 32,
+                        "c": 262144,
+                        "prf": "hmac-sha256",
+                        "salt": "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+                    },
+                    "message": ""
+                },
+                "checksum": {
+                    "function": "sha256",
+                    "params": {},
+                    "message": "8a9f5d9912ed7e75ea794bc5a89bca5f193721d30868ade6f73043c6ea6febf1"
+                },
+                "cipher": {
+                    "function": "aes-128-ctr",
+                    "params": {
+                        "iv": "264daa3f303d7259501c93d997d84fe6"
+                    },
+                    "message": "cee03fde2af33149775b7223e7845e4fb2c8ae1792e5f99fe9ecf474cc8c16ad"
+                }
+            },
+            "description": "This is a test keystore that uses PBKDF2 to secure the secret.",
+            "pubkey": "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07",
+            "path": "m/12381/60/0/0",
+            "uuid": "64625def-3331-4eea-ab6f-782f3ed16a83",
+            "version": 4
+        }"#;
+        let keystore: Eip2335Keystore = serde_json::from_str(pbkdf2).unwrap();
+        assert_eq!(hex::encode(keystore.decrypt(PASSWORD).unwrap()), SECRET);
+        assert_eq!(
+            format!("{}", keystore.decrypt("testpassword").unwrap_err()),
+            "password_incorrect"
+        );
+    }
+
+    #[test]
+    fn test_encrypt() {
+        let secret = hex::decode(SECRET).unwrap();
+        let pubkey = hex::decode(PUBKEY).unwrap();
+        let keystore = Eip2335Keystore::encrypt::<Pbkdf2Params>(
+            &secret,
+            &pubkey,
+            "m/12381/3600/0/0/0",
+            PASSWORD,
+        )
+        .unwrap();
+        assert_eq!(keystore.version, 4);
+        assert_eq!(keystore.pubkey, PUBKEY);
+        assert_eq!(keystore.crypto.kdf.function, "pbkdf2");
+        assert_eq!(keystore.crypto.kdf.params["prf"], "hmac-sha256");
+        assert_eq!(keystore.crypto.cipher.function, "aes-128-ctr");
+
+        let json = serde_json::to_string(&keystore).unwrap();
+        let keystore: Eip2335Keystore = serde_json::from_str(&json).unwrap();
+        assert_eq!(keystore.decrypt(PASSWORD).unwrap(), secret);
+    }
+}
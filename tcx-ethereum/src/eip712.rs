@@ -0,0 +1,461 @@
+use crate::keccak;
+use core::str::FromStr;
+use ethereum_types::U256;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use tcx_chain::Result;
+
+const EIP712_DOMAIN: &str = "EIP712Domain";
+
+/// A member of an EIP-712 struct type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypedDataField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: String,
+}
+
+/// The `eth_signTypedData_v4` payload
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypedData {
+    pub types: BTreeMap<String, Vec<TypedDataField>>,
+    pub primary_type: String,
+    pub domain: Value,
+    pub message: Value,
+    /// atomic types of other chains encoded as an EIP-712 one, not part of the payload
+    #[serde(skip)]
+    pub type_aliases: BTreeMap<String, String>,
+}
+
+impl FromStr for TypedData {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut typed_data: TypedData =
+            serde_json::from_str(s).map_err(|_| format_err!("invalid_typed_data"))?;
+        if !typed_data.types.contains_key(EIP712_DOMAIN) {
+            let fields = typed_data.infer_domain_fields()?;
+            typed_data.types.insert(EIP712_DOMAIN.to_string(), fields);
+        }
+        Ok(typed_data)
+    }
+}
+
+impl TypedData {
+    /// Some dApps omit `EIP712Domain` from `types`, the domain type is then
+    /// built from the standard domain fields present in `domain`.
+    fn infer_domain_fields(&self) -> Result<Vec<TypedDataField>> {
+        let domain = self
+            .domain
+            .as_object()
+            .ok_or_else(|| format_err!("invalid_typed_data_domain"))?;
+        let standard_fields = [
+            ("name", "string"),
+            ("version", "string"),
+            ("chainId", "uint256"),
+            ("verifyingContract", "address"),
+            ("salt", "bytes32"),
+        ];
+        Ok(standard_fields
+            .iter()
+            .filter(|(name, _)| domain.contains_key(*name))
+            .map(|(name, field_type)| TypedDataField {
+                name: name.to_string(),
+                field_type: field_type.to_string(),
+            })
+            .collect())
+    }
+
+    fn fields(&self, type_name: &str) -> Result<&Vec<TypedDataField>> {
+        self.types
+            .get(type_name)
+            .ok_or_else(|| format_err!("typed_data_type_not_found: {}", type_name))
+    }
+
+    fn collect_dependencies(&self, type_name: &str, found: &mut BTreeSet<String>) {
+        let type_name = struct_name(type_name);
+        if found.contains(type_name) || !self.types.contains_key(type_name) {
+            return;
+        }
+        found.insert(type_name.to_string());
+        for field in self.types[type_name].iter() {
+            self.collect_dependencies(&field.field_type, found);
+        }
+    }
+
+    /// `encodeType`: the primary type followed by its referenced struct types sorted by name
+    pub fn encode_type(&self, primary_type: &str) -> Result<String> {
+        self.fields(primary_type)?;
+        let mut dependencies = BTreeSet::new();
+        self.collect_dependencies(primary_type, &mut dependencies);
+        dependencies.remove(primary_type);
+
+        let mut encoded = String::new();
+        for type_name in
+            std::iter::once(primary_type).chain(dependencies.iter().map(|x| x.as_str()))
+        {
+            let members = self
+                .fields(type_name)?
+                .iter()
+                .map(|field| format!("{} {}", field.field_type, field.name))
+                .collect::<Vec<String>>()
+                .join(",");
+            encoded.push_str(&format!("{}({})", type_name, members));
+        }
+        Ok(encoded)
+    }
+
+    pub fn type_hash(&self, primary_type: &str) -> Result<Vec<u8>> {
+        Ok(keccak(self.encode_type(primary_type)?.as_bytes()))
+    }
+
+    /// `hashStruct(s) = keccak256(typeHash ‖ encodeData(s))`
+    pub fn hash_struct(&self, primary_type: &str, data: &Value) -> Result<Vec<u8>> {
+        let object = data
+            .as_object()
+            .ok_or_else(|| format_err!("typed_data_invalid_struct: {}", primary_type))?;
+        let mut encoded = self.type_hash(primary_type)?;
+        for field in self.fields(primary_type)? {
+            let value = object.get(&field.name).unwrap_or(&Value::Null);
+            encoded.extend(self.encode_value(&field.field_type, value)?);
+        }
+        Ok(keccak(&encoded))
+    }
+
+    fn encode_value(&self, field_type: &str, value: &Value) -> Result<Vec<u8>> {
+        if field_type.ends_with(']') {
+            let open = field_type
+                .rfind('[')
+                .ok_or_else(|| format_err!("typed_data_invalid_type: {}", field_type))?;
+            let item_type = &field_type[..open];
+            let length = &field_type[open + 1..field_type.len() - 1];
+            let items = value
+                .as_array()
+                .ok_or_else(|| format_err!("typed_data_invalid_array: {}", field_type))?;
+            if !length.is_empty() && length.parse::<usize>().ok() != Some(items.len()) {
+                return Err(format_err!(
+                    "typed_data_invalid_array_length: {}",
+                    field_type
+                ));
+            }
+            let mut encoded = vec![];
+            for item in items {
+                encoded.extend(self.encode_value(item_type, item)?);
+            }
+            return Ok(keccak(&encoded));
+        }
+
+        if self.types.contains_key(field_type) {
+            // a missing nested struct is encoded as zero, like eth_signTypedData_v4 does
+            return if value.is_null() {
+                Ok(vec![0u8; 32])
+            } else {
+                self.hash_struct(field_type, value)
+            };
+        }
+
+        if let Some(alias) = self.type_aliases.get(field_type) {
+            return self.encode_value(alias, value);
+        }
+
+        let invalid_value = || format_err!("typed_data_invalid_value: {}", field_type);
+        match field_type {
+            "string" => Ok(keccak(value.as_str().ok_or_else(invalid_value)?.as_bytes())),
+            "bytes" => Ok(keccak(&parse_hex(value).ok_or_else(invalid_value)?)),
+            "bool" => {
+                let flag = value.as_bool().ok_or_else(invalid_value)?;
+                Ok(to_word(U256::from(flag as u8)))
+            }
+            "address" => {
+                let bytes = parse_hex(value).ok_or_else(invalid_value)?;
+                if bytes.len() != 20 {
+                    return Err(invalid_value());
+                }
+                Ok([vec![0u8; 12], bytes].concat())
+            }
+            _ if field_type.starts_with("bytes") => {
+                let size = type_size(field_type, "bytes", 32).ok_or_else(invalid_value)?;
+                let bytes = parse_hex(value).ok_or_else(invalid_value)?;
+                if bytes.len() > size {
+                    return Err(invalid_value());
+                }
+                let mut word = bytes;
+                word.resize(32, 0);
+                Ok(word)
+            }
+            _ if field_type.starts_with("uint") => {
+                type_size(field_type, "uint", 256).ok_or_else(invalid_value)?;
+                let (number, negative) = parse_integer(value).ok_or_else(invalid_value)?;
+                if negative {
+                    return Err(invalid_value());
+                }
+                Ok(to_word(number))
+            }
+            _ if field_type.starts_with("int") => {
+                type_size(field_type, "int", 256).ok_or_else(invalid_value)?;
+                let (number, negative) = parse_integer(value).ok_or_else(invalid_value)?;
+                if negative {
+                    // two's complement
+                    Ok(to_word(number.overflowing_neg().0))
+                } else {
+                    Ok(to_word(number))
+                }
+            }
+            _ => Err(format_err!("typed_data_type_not_found: {}", field_type)),
+        }
+    }
+
+    pub fn domain_separator(&self) -> Result<Vec<u8>> {
+        self.hash_struct(EIP712_DOMAIN, &self.domain)
+    }
+
+    pub fn message_hash(&self) -> Result<Vec<u8>> {
+        self.hash_struct(&self.primary_type, &self.message)
+    }
+
+    /// `keccak256("\x19\x01" ‖ domainSeparator ‖ hashStruct(message))`
+    pub fn sign_hash(&self) -> Result<Vec<u8>> {
+        let mut data = vec![0x19u8, 0x01u8];
+        data.extend(self.domain_separator()?);
+        if self.primary_type != EIP712_DOMAIN {
+            data.extend(self.message_hash()?);
+        }
+        Ok(keccak(&data))
+    }
+}
+
+fn struct_name(field_type: &str) -> &str {
+    match field_type.find('[') {
+        Some(idx) => &field_type[..idx],
+        None => field_type,
+    }
+}
+
+/// Returns the byte size of `bytesN`, or the bit size of `uintN`/`intN`
+pub(crate) fn type_size(field_type: &str, prefix: &str, max: usize) -> Option<usize> {
+    let size = &field_type[prefix.len()..];
+    if size.is_empty() {
+        return if prefix == "bytes" { None } else { Some(max) };
+    }
+    let size = size.parse::<usize>().ok()?;
+    let step = if prefix == "bytes" { 1 } else { 8 };
+    if size == 0 || size > max || size % step != 0 {
+        None
+    } else {
+        Some(size)
+    }
+}
+
+pub(crate) fn parse_hex(value: &Value) -> Option<Vec<u8>> {
+    let s = value.as_str()?;
+    let s = if s.starts_with("0x") || s.starts_with("0X") {
+        &s[2..]
+    } else {
+        s
+    };
+    hex::decode(s).ok()
+}
+
+/// Parses a JSON number, a decimal string or a `0x` prefixed hex string,
+/// returns the absolute value and whether it is negative
+pub(crate) fn parse_integer(value: &Value) -> Option<(U256, bool)> {
+    match value {
+        Value::Number(number) => {
+            if let Some(n) = number.as_u64() {
+                Some((U256::from(n), false))
+            } else {
+                let n = number.as_i64()?;
+                Some((U256::from(n.unsigned_abs()), n < 0))
+            }
+        }
+        Value::String(s) => {
+            let (negative, s) = if s.starts_with('-') {
+                (true, &s[1..])
+            } else {
+                (false, s.as_str())
+            };
+            let number = if s.starts_with("0x") || s.starts_with("0X") {
+                U256::from_str(&s[2..]).ok()?
+            } else {
+                U256::from_dec_str(s).ok()?
+            };
+            Some((number, negative))
+        }
+        _ => None,
+    }
+}
+
+pub(crate) fn to_word(number: U256) -> Vec<u8> {
+    let mut word = vec![0u8; 32];
+    number.to_big_endian(&mut word);
+    word
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TypedData;
+    use std::str::FromStr;
+
+    const MAIL_TYPED_DATA: &str = r#"{
+        "types": {
+            "EIP712Domain": [
+                {"name": "name", "type": "string"},
+                {"name": "version", "type": "string"},
+                {"name": "chainId", "type": "uint256"},
+                {"name": "verifyingContract", "type": "address"}
+            ],
+            "Person": [
+                {"name": "name", "type": "string"},
+                {"name": "wallet", "type": "address"}
+            ],
+            "Mail": [
+                {"name": "from", "type": "Person"},
+                {"name": "to", "type": "Person"},
+                {"name": "contents", "type": "string"}
+            ]
+        },
+        "primaryType": "Mail",
+        "domain": {
+            "name": "Ether Mail",
+            "version": "1",
+            "chainId": 1,
+            "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+        },
+        "message": {
+            "from": {"name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"},
+            "to": {"name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"},
+            "contents": "Hello, Bob!"
+        }
+    }"#;
+
+    #[test]
+    fn test_eip712_spec_example() {
+        let typed_data = TypedData::from_str(MAIL_TYPED_DATA).unwrap();
+        assert_eq!(
+            typed_data.encode_type("Mail").unwrap(),
+            "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+        );
+        assert_eq!(
+            hex::encode(typed_data.type_hash("Mail").unwrap()),
+            "a0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2"
+        );
+        assert_eq!(
+            hex::encode(typed_data.domain_separator().unwrap()),
+            "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+        );
+        assert_eq!(
+            hex::encode(typed_data.message_hash().unwrap()),
+            "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e"
+        );
+        assert_eq!(
+            hex::encode(typed_data.sign_hash().unwrap()),
+            "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+    }
+
+    #[test]
+    fn test_eip712_nested_arrays() {
+        let json = r#"{
+            "types": {
+                "EIP712Domain": [
+                    {"name": "name", "type": "string"},
+                    {"name": "version", "type": "string"},
+                    {"name": "chainId", "type": "uint256"},
+                    {"name": "verifyingContract", "type": "address"}
+                ],
+                "Group": [
+                    {"name": "name", "type": "string"},
+                    {"name": "members", "type": "Person[]"}
+                ],
+                "Mail": [
+                    {"name": "from", "type": "Person"},
+                    {"name": "to", "type": "Person[]"},
+                    {"name": "contents", "type": "string"}
+                ],
+                "Person": [
+                    {"name": "name", "type": "string"},
+                    {"name": "wallets", "type": "address[]"}
+                ]
+            },
+            "domain": {
+                "chainId": 1,
+                "name": "Ether Mail",
+                "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC",
+                "version": "1"
+            },
+            "primaryType": "Mail",
+            "message": {
+                "contents": "Hello, Bob!",
+                "from": {
+                    "name": "Cow",
+                    "wallets": [
+                        "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826",
+                        "0xDeaDbeefdEAdbeefdEadbEEFdeadbeEFdEaDbeeF"
+                    ]
+                },
+                "to": [{
+                    "name": "Bob",
+                    "wallets": [
+                        "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB",
+                        "0xB0BdaBea57B0BDABeA57b0bdABEA57b0BDabEa57",
+                        "0xB0B0b0b0b0b0B000000000000000000000000000"
+                    ]
+                }]
+            }
+        }"#;
+        let typed_data = TypedData::from_str(json).unwrap();
+        assert_eq!(
+            typed_data.encode_type("Mail").unwrap(),
+            "Mail(Person from,Person[] to,string contents)Person(string name,address[] wallets)"
+        );
+        assert_eq!(
+            hex::encode(typed_data.sign_hash().unwrap()),
+            "a85c2e2b118698e88db68a8105b794a8cc7cec074e89ef991cb4f5f533819cc2"
+        );
+    }
+
+    #[test]
+    fn test_eip712_inferred_domain() {
+        let mut value: serde_json::Value = serde_json::from_str(MAIL_TYPED_DATA).unwrap();
+        value["types"]
+            .as_object_mut()
+            .unwrap()
+            .remove("EIP712Domain");
+        let typed_data = TypedData::from_str(&value.to_string()).unwrap();
+        assert_eq!(
+            hex::encode(typed_data.sign_hash().unwrap()),
+            "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+    }
+
+    #[test]
+    fn test_eip712_invalid_value() {
+        let mut value: serde_json::Value = serde_json::from_str(MAIL_TYPED_DATA).unwrap();
+        value["message"]["from"]["wallet"] = serde_json::Value::from("0x1234");
+        let typed_data = TypedData::from_str(&value.to_string()).unwrap();
+        assert_eq!(
+            format!("{}", typed_data.sign_hash().err().unwrap()),
+            "typed_data_invalid_value: address"
+        );
+
+        value["types"]["Person"][1]["type"] = serde_json::Value::from("trcToken");
+        value["message"]["from"]["wallet"] = serde_json::Value::from("1002000");
+        let typed_data = TypedData::from_str(&value.to_string()).unwrap();
+        assert_eq!(
+            format!("{}", typed_data.sign_hash().err().unwrap()),
+            "typed_data_type_not_found: trcToken"
+        );
+
+        value["types"]["Person"][1]["type"] = serde_json::Value::from("address");
+        value["message"]["from"]["wallet"] = serde_json::Value::Null;
+        let typed_data = TypedData::from_str(&value.to_string()).unwrap();
+        assert!(typed_data.sign_hash().is_err());
+
+        assert_eq!(
+            format!("{}", TypedData::from_str("{}").err().unwrap()),
+            "invalid_typed_data"
+        );
+    }
+}
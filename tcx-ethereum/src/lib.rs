@@ -1,13 +1,42 @@
+pub mod abi;
 mod address;
 mod chain_id;
+mod decoder;
+pub mod eip2335;
+pub mod eip712;
 pub mod signature;
 mod signer;
+mod siwe;
+mod staking;
+mod token;
 pub mod transaction;
 pub mod types;
+mod user_operation;
 
+pub use crate::abi::encode_function_call;
 pub use crate::address::EthereumAddress;
-pub use crate::chain_id::{chain_id_from_network, ChainInfo};
-pub use crate::transaction::{EthereumMsgIn, EthereumMsgOut, EthereumTxIn, EthereumTxOut};
+pub use crate::chain_id::{
+    chain_id_from_network, chain_infos, clear_custom_chain_infos, custom_chain_infos,
+    register_chain_info, remove_chain_info, ChainInfo,
+};
+pub use crate::decoder::{decode_call_data, decode_transaction};
+pub use crate::eip712::TypedData;
+pub use crate::siwe::siwe_message;
+pub use crate::staking::{
+    export_validator_keystore, sign_deposit_data, validator_signing_path,
+    validator_withdrawal_path, ValidatorKey,
+};
+pub use crate::token::{build_token_transaction, encode_token_call};
+pub use crate::transaction::{
+    ethereum_token_tx_in, EntryPointVersion, Erc1155Transfer, Erc20Approve, Erc20Transfer,
+    Erc721Transfer, EthereumAbiEncodeIn, EthereumAbiEncodeOut, EthereumAuthorizationIn,
+    EthereumAuthorizationOut, EthereumCallArgument, EthereumContractCall, EthereumDepositDataIn,
+    EthereumDepositDataOut, EthereumMsgIn, EthereumMsgOut, EthereumSiweIn, EthereumSiweOut,
+    EthereumTokenTxIn, EthereumTxDecodeIn, EthereumTxDecodeOut, EthereumTxIn, EthereumTxOut,
+    EthereumTypedDataIn, EthereumTypedDataOut, EthereumUserOperationIn, EthereumUserOperationOut,
+    EthereumValidatorKeystoreIn, EthereumValidatorKeystoreOut, SignatureType,
+};
+pub use crate::user_operation::user_operation_hash;
 use digest::Digest;
 
 #[macro_use]
@@ -50,6 +79,27 @@ pub enum Error {
 
     #[fail(display = "invalid_max_priority_fee_per_gas")]
     InvalidMaxPriorityFeePerGas,
+
+    #[fail(display = "invalid_signature_type")]
+    InvalidSignatureType,
+
+    #[fail(display = "invalid_validator")]
+    InvalidValidator,
+
+    #[fail(display = "invalid_message_hash")]
+    InvalidMessageHash,
+
+    #[fail(display = "invalid_raw_transaction")]
+    InvalidRawTransaction,
+
+    #[fail(display = "invalid_signature")]
+    InvalidSignature,
+
+    #[fail(display = "invalid_authorization_list")]
+    InvalidAuthorizationList,
+
+    #[fail(display = "invalid_authorization_address")]
+    InvalidAuthorizationAddress,
 }
 
 pub fn keccak(bytes: &[u8]) -> Vec<u8> {
@@ -1,6 +1,11 @@
-use ethereum_types::H256;
+use crate::Error;
+use ethereum_types::{H160, H256};
+use secp256k1::recovery::{RecoverableSignature, RecoveryId};
 use secp256k1::{Message, Secp256k1, SecretKey};
+use sha3::Digest;
+use tcx_chain::Result;
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Signature {
     pub(crate) v: u64,
     pub(crate) r: H256,
@@ -20,3 +25,17 @@ pub(crate) fn ecdsa_sign(hash: &[u8], private_key: &[u8]) -> Signature {
         s,
     }
 }
+
+/// Recovers the address which produced `signature` over `hash`, `v` is the recovery id
+pub(crate) fn ecdsa_recover(hash: &[u8], signature: &Signature) -> Result<H160> {
+    let s = Secp256k1::verification_only();
+    let msg = Message::from_slice(hash).map_err(|_| Error::InvalidSignature)?;
+    let recovery_id =
+        RecoveryId::from_i32(signature.v as i32).map_err(|_| Error::InvalidSignature)?;
+    let sig_bytes = [signature.r.as_bytes(), signature.s.as_bytes()].concat();
+    let sig = RecoverableSignature::from_compact(&sig_bytes, recovery_id)
+        .map_err(|_| Error::InvalidSignature)?;
+    let public_key = s.recover(&msg, &sig).map_err(|_| Error::InvalidSignature)?;
+    let hash = sha3::Keccak256::digest(&public_key.serialize_uncompressed()[1..]);
+    Ok(H160::from_slice(&hash[12..]))
+}
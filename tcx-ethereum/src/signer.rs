@@ -1,8 +1,17 @@
+use crate::eip712::TypedData;
 use crate::keccak;
-use crate::transaction::{EthereumMsgIn, EthereumMsgOut, EthereumTxIn, EthereumTxOut};
-use crate::types::Transaction;
+use crate::signature::Signature;
+use crate::siwe::siwe_message;
+use crate::transaction::{
+    EthereumAuthorizationIn, EthereumAuthorizationOut, EthereumMsgIn, EthereumMsgOut,
+    EthereumSiweIn, EthereumSiweOut, EthereumTxIn, EthereumTxOut, EthereumTypedDataIn,
+    EthereumTypedDataOut, EthereumUserOperationIn, EthereumUserOperationOut, SignatureType,
+};
+use crate::types::{Authorization, Transaction};
+use crate::user_operation::user_operation_hash;
 use crate::{chain_id_from_network, Error};
 use core::convert::TryFrom;
+use core::str::FromStr;
 use ethereum_types::{H160, H256, U256, U64};
 
 use tcx_chain::{ChainSigner, Keystore, MessageSigner, Result, TransactionSigner};
@@ -32,14 +41,23 @@ impl TryFrom<&EthereumTxIn> for Transaction {
 
         let mut access_list = Vec::new();
         let mut max_priority_fee_per_gas = U256::zero();
+        let mut authorization_list = Vec::new();
         if let Some(t) = transaction_type {
             access_list =
                 serde_json::from_str(&input.access_list).map_err(|_| Error::InvalidAccessList)?;
-            if t.as_u64() == 2 {
+            if t.as_u64() == 2 || t.as_u64() == 4 {
                 max_priority_fee_per_gas =
                     U256::from_dec_str(input.max_priority_fee_per_gas.as_str())
                         .map_err(|_| Error::InvalidGas)?;
             }
+            if t.as_u64() == 4 {
+                // a set code transaction can not create a contract
+                if to.is_none() {
+                    return Err(Error::InvalidTo);
+                }
+                authorization_list = serde_json::from_str(&input.authorization_list)
+                    .map_err(|_| Error::InvalidAuthorizationList)?;
+            }
         }
 
         Ok(Transaction {
@@ -52,6 +70,7 @@ impl TryFrom<&EthereumTxIn> for Transaction {
             transaction_type,
             access_list,
             max_priority_fee_per_gas,
+            authorization_list,
         })
     }
 }
@@ -77,10 +96,58 @@ impl TransactionSigner<EthereumTxIn, EthereumTxOut> for Keystore {
 
         let private_key = H256::from_slice(private_key.to_bytes().as_slice());
 
-        let chain_id = chain_id_from_network(tx.network.as_str())?;
+        let chain_id = if tx.chain_id != 0 {
+            tx.chain_id
+        } else {
+            chain_id_from_network(tx.network.as_str())?
+        };
+
+        let signed_tx = unsigned_tx.sign(&private_key, chain_id)?;
+        Ok(EthereumTxOut {
+            signature: hex::encode(&signed_tx),
+            tx_hash: format!("0x{}", hex::encode(keccak(&signed_tx))),
+        })
+    }
+}
 
-        let signature = hex::encode(unsigned_tx.sign(&private_key, chain_id));
-        Ok(EthereumTxOut { signature })
+fn decode_hex(value: &str) -> Result<Vec<u8>> {
+    let mut raw_hex: String = value.to_owned();
+    if raw_hex.to_uppercase().starts_with("0X") {
+        raw_hex.replace_range(..2, "")
+    }
+    Ok(hex::decode(&raw_hex)?)
+}
+
+/// Builds the hash to sign according to the EIP-191 version selected by `signature_type`
+pub fn message_hash(message: &EthereumMsgIn) -> Result<Vec<u8>> {
+    let data = if message.is_utf8 {
+        message.value.as_bytes().to_vec()
+    } else {
+        decode_hex(&message.value)?
+    };
+
+    match SignatureType::from_i32(message.signature_type) {
+        Some(SignatureType::Keccak256) => Ok(keccak(&data)),
+        Some(SignatureType::PersonalSign) => {
+            let header = format!("\x19Ethereum Signed Message:\n{}", data.len());
+            Ok(keccak(&[header.as_bytes(), &data].concat()))
+        }
+        Some(SignatureType::IntendedValidator) => {
+            let validator = decode_hex(&message.validator).map_err(|_| Error::InvalidValidator)?;
+            if validator.len() != 20 {
+                return Err(Error::InvalidValidator.into());
+            }
+            Ok(keccak(
+                &[&[0x19u8, 0x00u8], validator.as_slice(), &data].concat(),
+            ))
+        }
+        Some(SignatureType::RawHash) => {
+            if data.len() != 32 {
+                return Err(Error::InvalidMessageHash.into());
+            }
+            Ok(data)
+        }
+        None => Err(Error::InvalidSignatureType.into()),
     }
 }
 
@@ -91,13 +158,7 @@ impl MessageSigner<EthereumMsgIn, EthereumMsgOut> for Keystore {
         address: &str,
         message: &EthereumMsgIn,
     ) -> Result<EthereumMsgOut> {
-        let mut raw_hex: String = message.value.clone();
-        if raw_hex.to_uppercase().starts_with("0X") {
-            raw_hex.replace_range(..2, "")
-        }
-        let data = hex::decode(&raw_hex)?;
-
-        let hash = keccak(&data);
+        let hash = message_hash(message)?;
         let mut sign_result = self.sign_recoverable_hash(&hash, symbol, address, None)?;
         sign_result[64] = sign_result[64] + 27;
         Ok(EthereumMsgOut {
@@ -106,6 +167,107 @@ impl MessageSigner<EthereumMsgIn, EthereumMsgOut> for Keystore {
     }
 }
 
+impl MessageSigner<EthereumTypedDataIn, EthereumTypedDataOut> for Keystore {
+    fn sign_message(
+        &mut self,
+        symbol: &str,
+        address: &str,
+        message: &EthereumTypedDataIn,
+    ) -> Result<EthereumTypedDataOut> {
+        let typed_data = TypedData::from_str(&message.data)?;
+        let hash = typed_data.sign_hash()?;
+        let mut sign_result = self.sign_recoverable_hash(&hash, symbol, address, None)?;
+        sign_result[64] = sign_result[64] + 27;
+        Ok(EthereumTypedDataOut {
+            signature: hex::encode(sign_result),
+        })
+    }
+}
+
+impl MessageSigner<EthereumAuthorizationIn, EthereumAuthorizationOut> for Keystore {
+    fn sign_message(
+        &mut self,
+        symbol: &str,
+        address: &str,
+        message: &EthereumAuthorizationIn,
+    ) -> Result<EthereumAuthorizationOut> {
+        let code_address =
+            decode_hex(&message.address).map_err(|_| Error::InvalidAuthorizationAddress)?;
+        if code_address.len() != 20 {
+            return Err(Error::InvalidAuthorizationAddress.into());
+        }
+        let authorization = Authorization {
+            chain_id: U256::from(message.chain_id),
+            address: H160::from_slice(&code_address),
+            nonce: U64::from(message.nonce),
+        };
+        let sign_result =
+            self.sign_recoverable_hash(&authorization.signing_hash(), symbol, address, None)?;
+        let signature = Signature {
+            v: sign_result[64] as u64,
+            r: H256::from_slice(&sign_result[..32]),
+            s: H256::from_slice(&sign_result[32..64]),
+        };
+        let signed = authorization.into_signed(&signature);
+        Ok(EthereumAuthorizationOut {
+            authorization: serde_json::to_string(&signed)?,
+            signature: hex::encode(sign_result),
+        })
+    }
+}
+
+impl MessageSigner<EthereumUserOperationIn, EthereumUserOperationOut> for Keystore {
+    fn sign_message(
+        &mut self,
+        symbol: &str,
+        address: &str,
+        message: &EthereumUserOperationIn,
+    ) -> Result<EthereumUserOperationOut> {
+        let user_op_hash = user_operation_hash(message)?;
+        // accounts like SimpleAccount verify the signature of the EIP-191 personal message
+        let hash = if message.raw_hash {
+            user_op_hash.clone()
+        } else {
+            let header = format!("\x19Ethereum Signed Message:\n{}", user_op_hash.len());
+            keccak(&[header.as_bytes(), &user_op_hash].concat())
+        };
+        let mut sign_result = self.sign_recoverable_hash(&hash, symbol, address, None)?;
+        sign_result[64] = sign_result[64] + 27;
+        Ok(EthereumUserOperationOut {
+            user_op_hash: format!("0x{}", hex::encode(user_op_hash)),
+            signature: format!("0x{}", hex::encode(sign_result)),
+        })
+    }
+}
+
+impl MessageSigner<EthereumSiweIn, EthereumSiweOut> for Keystore {
+    fn sign_message(
+        &mut self,
+        symbol: &str,
+        address: &str,
+        message: &EthereumSiweIn,
+    ) -> Result<EthereumSiweOut> {
+        if !message.address.eq_ignore_ascii_case(address) {
+            return Err(format_err!("siwe_address_mismatch"));
+        }
+        let siwe = siwe_message(message)?;
+        let signed: EthereumMsgOut = self.sign_message(
+            symbol,
+            address,
+            &EthereumMsgIn {
+                value: siwe.clone(),
+                signature_type: SignatureType::PersonalSign as i32,
+                is_utf8: true,
+                ..EthereumMsgIn::default()
+            },
+        )?;
+        Ok(EthereumSiweOut {
+            message: siwe,
+            signature: signed.signature,
+        })
+    }
+}
+
 #[test]
 fn test_sign() {
     let input = EthereumTxIn {
@@ -119,6 +281,8 @@ fn test_sign() {
         access_list: "[]".to_string(),
         max_priority_fee_per_gas: "1000000010".to_string(),
         transaction_type: "2".to_string(),
+        chain_id: 0,
+        authorization_list: "".to_string(),
     };
     let raw_tx = Transaction::try_from(&input).unwrap();
     let mut data: [u8; 32] = Default::default();
@@ -127,7 +291,7 @@ fn test_sign() {
     );
     let private_key = H256::from_slice(&data);
     let chain_id = chain_id_from_network(input.network.as_str()).unwrap();
-    let raw_rlp_bytes = raw_tx.sign(&private_key, chain_id);
+    let raw_rlp_bytes = raw_tx.sign(&private_key, chain_id).unwrap();
     let result = "02f8720480843b9aca0a843b9aca0a830186a094355972b9007c736515523417c96561f63db4e7bc87038d7ea4c6800080c001a0b88af67e8d892c55539eb6bec47704db4b17173b8a8d8bd3a0c59c87319e150aa00baf8b065dde5601cef06f1b7ddf4ebd125c5abad2250315d092a00cc2c4a4c4";
     assert_eq!(result, hex::encode(raw_rlp_bytes));
 }
@@ -150,3 +314,182 @@ fn sign_message() {
     let result = "4e59b0d97fc748123e52d19d8e792982249d899195cac5b21c8ec6d47aa462f8774b7e5b45966482424e7ca28b92eacafb1a147051282f9d6f12d9b30a669f5c1c";
     assert_eq!(result, hex::encode(signed));
 }
+
+#[test]
+fn test_sign_large_chain_id() {
+    let private_key = H256::from_slice(
+        &hex::decode("2a3526dd05ad2ebba87673f711ef8c336115254ef8fcd38c4d8166db9a8120e4").unwrap(),
+    );
+    for chain_id in vec![4u64, 534352, 0xffff_ffff, u64::MAX] {
+        let input = EthereumTxIn {
+            nonce: "0".to_string(),
+            to: "355972B9007c736515523417c96561F63db4e7bC".to_string(),
+            value: "1000000000000000".to_string(),
+            gas_price: "1000000010".to_string(),
+            gas: "100000".to_string(),
+            data: "".to_string(),
+            network: "".to_string(),
+            access_list: "".to_string(),
+            max_priority_fee_per_gas: "".to_string(),
+            transaction_type: "".to_string(),
+            chain_id,
+            authorization_list: "".to_string(),
+        };
+        let raw_tx = Transaction::try_from(&input).unwrap();
+        let unsigned = raw_tx.encode(chain_id, None).unwrap();
+        let encoded_chain_id: u64 = rlp::Rlp::new(&unsigned).val_at(6).unwrap();
+        assert_eq!(encoded_chain_id, chain_id);
+
+        let signed = raw_tx.sign(&private_key, chain_id).unwrap();
+        let v: U256 = rlp::Rlp::new(&signed).val_at(6).unwrap();
+        let recovery_id = v - U256::from(chain_id) * 2 - 35;
+        assert!(recovery_id <= U256::one());
+    }
+}
+
+#[test]
+fn test_message_hash() {
+    let mut message = EthereumMsgIn {
+        value: "0xdeadbeef".to_string(),
+        chain_id: 0,
+        signature_type: SignatureType::Keccak256 as i32,
+        is_utf8: false,
+        validator: "".to_string(),
+    };
+    assert_eq!(
+        hex::encode(message_hash(&message).unwrap()),
+        "d4fd4e189132273036449fc9e11198c739161b4c0116a9a2dccdfa1c492006f1"
+    );
+
+    message.signature_type = SignatureType::IntendedValidator as i32;
+    assert_eq!(
+        format!("{}", message_hash(&message).err().unwrap()),
+        "invalid_validator"
+    );
+    message.validator = "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC".to_string();
+    assert_eq!(
+        hex::encode(message_hash(&message).unwrap()),
+        "4408180141dee1805a8bf4b739b4ea2bda696f0f2780fc83eec5b126c0f0142e"
+    );
+
+    message.signature_type = SignatureType::RawHash as i32;
+    assert_eq!(
+        format!("{}", message_hash(&message).err().unwrap()),
+        "invalid_message_hash"
+    );
+    message.value =
+        "0xa1de988600a42c4b4ab089b619297c17d53cffae5d5120d82d8a92d0bb3b78f2".to_string();
+    assert_eq!(
+        hex::encode(message_hash(&message).unwrap()),
+        "a1de988600a42c4b4ab089b619297c17d53cffae5d5120d82d8a92d0bb3b78f2"
+    );
+
+    let message = EthereumMsgIn {
+        value: "Hello World".to_string(),
+        chain_id: 0,
+        signature_type: SignatureType::PersonalSign as i32,
+        is_utf8: true,
+        validator: "".to_string(),
+    };
+    assert_eq!(
+        hex::encode(message_hash(&message).unwrap()),
+        "a1de988600a42c4b4ab089b619297c17d53cffae5d5120d82d8a92d0bb3b78f2"
+    );
+    let message = EthereumMsgIn {
+        value: hex::encode("Hello World"),
+        is_utf8: false,
+        ..message
+    };
+    assert_eq!(
+        hex::encode(message_hash(&message).unwrap()),
+        "a1de988600a42c4b4ab089b619297c17d53cffae5d5120d82d8a92d0bb3b78f2"
+    );
+
+    let message = EthereumMsgIn {
+        signature_type: 10,
+        ..message
+    };
+    assert_eq!(
+        format!("{}", message_hash(&message).err().unwrap()),
+        "invalid_signature_type"
+    );
+}
+
+#[test]
+fn test_sign_eip7702() {
+    use crate::signature::ecdsa_sign;
+    use crate::types::SignedAuthorization;
+
+    let private_key = H256::from_slice(
+        &hex::decode("2a3526dd05ad2ebba87673f711ef8c336115254ef8fcd38c4d8166db9a8120e4").unwrap(),
+    );
+    let authorization = Authorization {
+        chain_id: U256::one(),
+        address: H160::from_str("355972B9007c736515523417c96561F63db4e7bC").unwrap(),
+        nonce: U64::one(),
+    };
+    assert_eq!(
+        hex::encode(authorization.signing_hash()),
+        "ab950adca052dd439148e771c25391b4662042513a9317cea3185624cdec9fde"
+    );
+    let signature = ecdsa_sign(&authorization.signing_hash(), private_key.as_bytes());
+    let signed = authorization.into_signed(&signature);
+    assert_eq!(
+        signed.recover_authority().unwrap(),
+        H160::from_str("3de0e4CD2FF24557bD9ed743e038eF18202F2acd").unwrap()
+    );
+    let authorization_list = serde_json::to_string(&vec![signed.clone()]).unwrap();
+    assert_eq!(
+        authorization_list,
+        r#"[{"chainId":"0x1","address":"0x355972b9007c736515523417c96561f63db4e7bc","nonce":"0x1","yParity":"0x0","r":"0x313b85ed720ce76a744b7d1847bcc53c102f72e38a6a12067c81c9cec8645247","s":"0x1dfa8ba38579a17ead8357dafbbc49d20458471046038a540a8008a8f5883134"}]"#
+    );
+    let parsed: Vec<SignedAuthorization> = serde_json::from_str(&authorization_list).unwrap();
+    assert_eq!(parsed, vec![signed]);
+
+    let input = EthereumTxIn {
+        nonce: "0".to_string(),
+        to: "3de0e4CD2FF24557bD9ed743e038eF18202F2acd".to_string(),
+        value: "0".to_string(),
+        gas_price: "2000000000".to_string(),
+        gas: "100000".to_string(),
+        data: "".to_string(),
+        network: "".to_string(),
+        access_list: "[]".to_string(),
+        max_priority_fee_per_gas: "1000000000".to_string(),
+        transaction_type: "4".to_string(),
+        chain_id: 1,
+        authorization_list,
+    };
+    let raw_tx = Transaction::try_from(&input).unwrap();
+    assert_eq!(hex::encode(raw_tx.sign(&private_key, 1).unwrap()), "04f8c90180843b9aca008477359400830186a0943de0e4cd2ff24557bd9ed743e038ef18202f2acd8080c0f85cf85a0194355972b9007c736515523417c96561f63db4e7bc0180a0313b85ed720ce76a744b7d1847bcc53c102f72e38a6a12067c81c9cec8645247a01dfa8ba38579a17ead8357dafbbc49d20458471046038a540a8008a8f588313401a04a44155facb5cb9d92833a67aa97e68dba4d10a978f60b9248f4761571a7daefa05ed0262fdc919bc7fba9e764d86fd2375cafc73967985157436b1638e5398007");
+
+    // a set code transaction must have a destination
+    let input = EthereumTxIn {
+        to: "".to_string(),
+        ..input
+    };
+    assert_eq!(Transaction::try_from(&input).unwrap_err(), Error::InvalidTo);
+    let mut raw_tx = Transaction::try_from(&EthereumTxIn {
+        to: "3de0e4CD2FF24557bD9ed743e038eF18202F2acd".to_string(),
+        ..input.clone()
+    })
+    .unwrap();
+    raw_tx.to = None;
+    assert_eq!(
+        format!("{}", raw_tx.encode(1, None).unwrap_err()),
+        "invalid_to"
+    );
+    assert_eq!(
+        format!("{}", raw_tx.sign(&private_key, 1).unwrap_err()),
+        "invalid_to"
+    );
+    let input = EthereumTxIn {
+        to: "3de0e4CD2FF24557bD9ed743e038eF18202F2acd".to_string(),
+        authorization_list: "".to_string(),
+        ..input
+    };
+    assert_eq!(
+        Transaction::try_from(&input).unwrap_err(),
+        Error::InvalidAuthorizationList
+    );
+}
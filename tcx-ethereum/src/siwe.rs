@@ -0,0 +1,279 @@
+use crate::address::to_checksum;
+use crate::transaction::EthereumSiweIn;
+use tcx_chain::Result;
+
+const SIWE_VERSION: &str = "1";
+
+fn invalid(field: &str) -> failure::Error {
+    format_err!("invalid_siwe_message: {}", field)
+}
+
+fn has_whitespace(value: &str) -> bool {
+    value.chars().any(char::is_whitespace)
+}
+
+/// Checks the `YYYY-MM-DDTHH:MM:SS[.fraction](Z|±HH:MM)` form of RFC 3339 date-time
+fn is_rfc3339(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    if bytes.len() < 20 {
+        return false;
+    }
+    let digits = |range: std::ops::Range<usize>| bytes[range].iter().all(u8::is_ascii_digit);
+    if !(digits(0..4)
+        && bytes[4] == b'-'
+        && digits(5..7)
+        && bytes[7] == b'-'
+        && digits(8..10)
+        && (bytes[10] == b'T' || bytes[10] == b't')
+        && digits(11..13)
+        && bytes[13] == b':'
+        && digits(14..16)
+        && bytes[16] == b':'
+        && digits(17..19))
+    {
+        return false;
+    }
+
+    let mut offset = &value[19..];
+    if offset.starts_with('.') {
+        let fraction = offset[1..].chars().take_while(char::is_ascii_digit).count();
+        if fraction == 0 {
+            return false;
+        }
+        offset = &offset[1 + fraction..];
+    }
+    match offset.as_bytes() {
+        [b'Z'] | [b'z'] => true,
+        [b'+', h1, h2, b':', m1, m2] | [b'-', h1, h2, b':', m1, m2] => {
+            [h1, h2, m1, m2].iter().all(|x| x.is_ascii_digit())
+        }
+        _ => false,
+    }
+}
+
+fn check_timestamp(value: &str, field: &str) -> Result<()> {
+    if is_rfc3339(value) {
+        Ok(())
+    } else {
+        Err(invalid(field))
+    }
+}
+
+/// EIP-55 form of the message address, a mixed case address must carry a valid checksum
+fn checksum_address(address: &str) -> Result<String> {
+    let hex_part = address
+        .strip_prefix("0x")
+        .ok_or_else(|| invalid("address"))?;
+    if hex_part.len() != 40 || hex::decode(hex_part).is_err() {
+        return Err(invalid("address"));
+    }
+    let checksum = to_checksum(hex_part);
+    let is_mixed_case = hex_part.chars().any(|x| x.is_ascii_uppercase())
+        && hex_part.chars().any(|x| x.is_ascii_lowercase());
+    if is_mixed_case && checksum != address {
+        return Err(invalid("address"));
+    }
+    Ok(checksum)
+}
+
+/// Renders the EIP-4361 message, rejecting fields outside of its ABNF
+pub fn siwe_message(input: &EthereumSiweIn) -> Result<String> {
+    if input.domain.is_empty() || has_whitespace(&input.domain) {
+        return Err(invalid("domain"));
+    }
+    if has_whitespace(&input.scheme) {
+        return Err(invalid("scheme"));
+    }
+    let address = checksum_address(&input.address)?;
+    if input.statement.contains('\n') {
+        return Err(invalid("statement"));
+    }
+    if input.uri.is_empty() || !input.uri.contains(':') || has_whitespace(&input.uri) {
+        return Err(invalid("uri"));
+    }
+    let version = if input.version.is_empty() {
+        SIWE_VERSION
+    } else {
+        input.version.as_str()
+    };
+    if version != SIWE_VERSION {
+        return Err(invalid("version"));
+    }
+    if input.chain_id == 0 {
+        return Err(invalid("chain_id"));
+    }
+    if input.nonce.len() < 8 || !input.nonce.chars().all(|x| x.is_ascii_alphanumeric()) {
+        return Err(invalid("nonce"));
+    }
+    check_timestamp(&input.issued_at, "issued_at")?;
+
+    let mut message = String::new();
+    if !input.scheme.is_empty() {
+        message.push_str(&format!("{}://", input.scheme));
+    }
+    message.push_str(&format!(
+        "{} wants you to sign in with your Ethereum account:\n{}\n\n",
+        input.domain, address
+    ));
+    if !input.statement.is_empty() {
+        message.push_str(&input.statement);
+        message.push('\n');
+    }
+    message.push_str(&format!(
+        "\nURI: {}\nVersion: {}\nChain ID: {}\nNonce: {}\nIssued At: {}",
+        input.uri, version, input.chain_id, input.nonce, input.issued_at
+    ));
+    if !input.expiration_time.is_empty() {
+        check_timestamp(&input.expiration_time, "expiration_time")?;
+        message.push_str(&format!("\nExpiration Time: {}", input.expiration_time));
+    }
+    if !input.not_before.is_empty() {
+        check_timestamp(&input.not_before, "not_before")?;
+        message.push_str(&format!("\nNot Before: {}", input.not_before));
+    }
+    if !input.request_id.is_empty() {
+        if input.request_id.contains('\n') {
+            return Err(invalid("request_id"));
+        }
+        message.push_str(&format!("\nRequest ID: {}", input.request_id));
+    }
+    if !input.resources.is_empty() {
+        message.push_str("\nResources:");
+        for resource in &input.resources {
+            if resource.is_empty() || !resource.contains(':') || has_whitespace(resource) {
+                return Err(invalid("resources"));
+            }
+            message.push_str(&format!("\n- {}", resource));
+        }
+    }
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::siwe_message;
+    use crate::transaction::EthereumSiweIn;
+
+    fn siwe_input() -> EthereumSiweIn {
+        EthereumSiweIn {
+            domain: "service.org".to_string(),
+            address: "0x6031564e7b2f5cc33737807b2e58daff870b590b".to_string(),
+            statement: "I accept the ServiceOrg Terms of Service: https://service.org/tos"
+                .to_string(),
+            uri: "https://service.org/login".to_string(),
+            version: "1".to_string(),
+            chain_id: 1,
+            nonce: "32891757".to_string(),
+            issued_at: "2021-09-30T16:25:24.000Z".to_string(),
+            ..EthereumSiweIn::default()
+        }
+    }
+
+    #[test]
+    fn test_siwe_message() {
+        let input = siwe_input();
+        assert_eq!(
+            siwe_message(&input).unwrap(),
+            "service.org wants you to sign in with your Ethereum account:\n\
+             0x6031564e7b2F5cc33737807b2E58DaFF870B590b\n\
+             \n\
+             I accept the ServiceOrg Terms of Service: https://service.org/tos\n\
+             \n\
+             URI: https://service.org/login\n\
+             Version: 1\n\
+             Chain ID: 1\n\
+             Nonce: 32891757\n\
+             Issued At: 2021-09-30T16:25:24.000Z"
+        );
+
+        let input = EthereumSiweIn {
+            scheme: "https".to_string(),
+            statement: "".to_string(),
+            version: "".to_string(),
+            expiration_time: "2021-10-01T16:25:24+08:00".to_string(),
+            not_before: "2021-09-30T16:25:24Z".to_string(),
+            request_id: "some_id".to_string(),
+            resources: vec![
+                "ipfs://bafybeiemxf5abjwjbikoz4mc3a3dla6ual3jsgpdr4cjr3oz3evfyavhwq/".to_string(),
+                "https://example.com/my-web2-claim.json".to_string(),
+            ],
+            ..input
+        };
+        assert_eq!(
+            siwe_message(&input).unwrap(),
+            "https://service.org wants you to sign in with your Ethereum account:\n\
+             0x6031564e7b2F5cc33737807b2E58DaFF870B590b\n\
+             \n\
+             \n\
+             URI: https://service.org/login\n\
+             Version: 1\n\
+             Chain ID: 1\n\
+             Nonce: 32891757\n\
+             Issued At: 2021-09-30T16:25:24.000Z\n\
+             Expiration Time: 2021-10-01T16:25:24+08:00\n\
+             Not Before: 2021-09-30T16:25:24Z\n\
+             Request ID: some_id\n\
+             Resources:\n\
+             - ipfs://bafybeiemxf5abjwjbikoz4mc3a3dla6ual3jsgpdr4cjr3oz3evfyavhwq/\n\
+             - https://example.com/my-web2-claim.json"
+        );
+    }
+
+    #[test]
+    fn test_siwe_message_invalid() {
+        let cases = vec![
+            (
+                EthereumSiweIn {
+                    domain: "service org".to_string(),
+                    ..siwe_input()
+                },
+                "invalid_siwe_message: domain",
+            ),
+            (
+                EthereumSiweIn {
+                    address: "0x6031564e7b2F5cc33737807b2E58DaFF870B590B".to_string(),
+                    ..siwe_input()
+                },
+                "invalid_siwe_message: address",
+            ),
+            (
+                EthereumSiweIn {
+                    statement: "line\nbreak".to_string(),
+                    ..siwe_input()
+                },
+                "invalid_siwe_message: statement",
+            ),
+            (
+                EthereumSiweIn {
+                    version: "2".to_string(),
+                    ..siwe_input()
+                },
+                "invalid_siwe_message: version",
+            ),
+            (
+                EthereumSiweIn {
+                    nonce: "1234".to_string(),
+                    ..siwe_input()
+                },
+                "invalid_siwe_message: nonce",
+            ),
+            (
+                EthereumSiweIn {
+                    issued_at: "2021-09-30 16:25:24".to_string(),
+                    ..siwe_input()
+                },
+                "invalid_siwe_message: issued_at",
+            ),
+            (
+                EthereumSiweIn {
+                    expiration_time: "2021-10-01T16:25:24".to_string(),
+                    ..siwe_input()
+                },
+                "invalid_siwe_message: expiration_time",
+            ),
+        ];
+        for (input, err) in cases {
+            assert_eq!(format!("{}", siwe_message(&input).unwrap_err()), err);
+        }
+    }
+}
@@ -0,0 +1,354 @@
+use crate::eip2335::Eip2335Keystore;
+use crate::transaction::{
+    EthereumDepositDataIn, EthereumDepositDataOut, EthereumValidatorKeystoreIn,
+    EthereumValidatorKeystoreOut,
+};
+use blst::min_pk::SecretKey;
+use sha2::{Digest, Sha256};
+use tcx_chain::Result;
+use tcx_crypto::crypto::{Pbkdf2Params, SCryptParams};
+use tcx_primitive::{Derive, Eip2333DeterministicPrivateKey};
+
+/// The proof of possession ciphersuite of the Ethereum consensus layer
+const BLS_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+const DOMAIN_DEPOSIT: [u8; 4] = [0x03, 0x00, 0x00, 0x00];
+const BLS_WITHDRAWAL_PREFIX: u8 = 0x00;
+const ETH1_ADDRESS_WITHDRAWAL_PREFIX: u8 = 0x01;
+
+/// EIP-2334 path of the signing key of validator `index`
+pub fn validator_signing_path(index: u32) -> String {
+    format!("m/12381/3600/{}/0/0", index)
+}
+
+/// EIP-2334 path of the withdrawal key of validator `index`
+pub fn validator_withdrawal_path(index: u32) -> String {
+    format!("m/12381/3600/{}/0", index)
+}
+
+/// BLS12-381 key of an Ethereum validator
+pub struct ValidatorKey {
+    pub path: String,
+    secret_key: SecretKey,
+}
+
+impl ValidatorKey {
+    pub fn from_mnemonic(mnemonic: &str, path: &str) -> Result<ValidatorKey> {
+        let secret = Eip2333DeterministicPrivateKey::from_mnemonic(mnemonic)?
+            .derive(path)?
+            .to_bytes();
+        Ok(ValidatorKey {
+            path: path.to_string(),
+            secret_key: SecretKey::from_bytes(&secret)
+                .map_err(|_| format_err!("invalid_bls_private_key"))?,
+        })
+    }
+
+    /// The 32 bytes big endian secret key
+    pub fn secret(&self) -> Vec<u8> {
+        self.secret_key.to_bytes().to_vec()
+    }
+
+    /// The 48 bytes compressed G1 public key
+    pub fn public_key(&self) -> Vec<u8> {
+        self.secret_key.sk_to_pk().compress().to_vec()
+    }
+
+    /// The 96 bytes compressed G2 signature of `message`
+    pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+        self.secret_key
+            .sign(message, BLS_DST, &[])
+            .compress()
+            .to_vec()
+    }
+}
+
+fn sha256(data: &[u8]) -> Vec<u8> {
+    Sha256::digest(data).to_vec()
+}
+
+fn hash_pair(left: &[u8], right: &[u8]) -> Vec<u8> {
+    sha256(&[left, right].concat())
+}
+
+/// SSZ `hash_tree_root` of a byte vector longer than 32 bytes
+fn bytes_root(bytes: &[u8]) -> Vec<u8> {
+    let mut chunks = bytes
+        .chunks(32)
+        .map(|x| [x, &vec![0u8; 32 - x.len()]].concat())
+        .collect::<Vec<Vec<u8>>>();
+    while !chunks.len().is_power_of_two() {
+        chunks.push(vec![0u8; 32]);
+    }
+    while chunks.len() > 1 {
+        chunks = chunks.chunks(2).map(|x| hash_pair(&x[0], &x[1])).collect();
+    }
+    chunks.remove(0)
+}
+
+fn uint64_root(value: u64) -> Vec<u8> {
+    [&value.to_le_bytes()[..], &[0u8; 24]].concat()
+}
+
+fn deposit_message_root(pubkey: &[u8], withdrawal_credentials: &[u8], amount: u64) -> Vec<u8> {
+    hash_pair(
+        &hash_pair(&bytes_root(pubkey), withdrawal_credentials),
+        &hash_pair(&uint64_root(amount), &[0u8; 32]),
+    )
+}
+
+fn deposit_data_root(
+    pubkey: &[u8],
+    withdrawal_credentials: &[u8],
+    amount: u64,
+    signature: &[u8],
+) -> Vec<u8> {
+    hash_pair(
+        &hash_pair(&bytes_root(pubkey), withdrawal_credentials),
+        &hash_pair(&uint64_root(amount), &bytes_root(signature)),
+    )
+}
+
+/// `compute_domain(DOMAIN_DEPOSIT, fork_version)`, deposits are valid on every fork so the
+/// genesis validators root is left empty
+fn deposit_domain(fork_version: &[u8]) -> Vec<u8> {
+    let fork_data_root = hash_pair(&[fork_version, &[0u8; 28]].concat(), &[0u8; 32]);
+    [&DOMAIN_DEPOSIT[..], &fork_data_root[..28]].concat()
+}
+
+fn genesis_fork_version(network: &str) -> Result<Vec<u8>> {
+    let fork_version = match network.to_uppercase().as_str() {
+        "" | "MAINNET" => "00000000",
+        "SEPOLIA" => "90000069",
+        "HOLESKY" => "01017000",
+        "HOODI" => "10000910",
+        _ => return Err(format_err!("unsupported_network: {}", network)),
+    };
+    Ok(hex::decode(fork_version).unwrap())
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>> {
+    Ok(hex::decode(value.trim_start_matches("0x"))?)
+}
+
+/// 0x01 credentials of an execution address, or the 0x00 credentials of the BLS withdrawal
+/// key of the validator when `withdrawal_credentials` is empty
+fn withdrawal_credentials(mnemonic: &str, input: &EthereumDepositDataIn) -> Result<Vec<u8>> {
+    if input.withdrawal_credentials.is_empty() {
+        let withdrawal_key =
+            ValidatorKey::from_mnemonic(mnemonic, &validator_withdrawal_path(input.index))?;
+        let mut credentials = sha256(&withdrawal_key.public_key());
+        credentials[0] = BLS_WITHDRAWAL_PREFIX;
+        return Ok(credentials);
+    }
+
+    let credentials = decode_hex(&input.withdrawal_credentials)
+        .map_err(|_| format_err!("invalid_withdrawal_credentials"))?;
+    match credentials.len() {
+        20 => Ok([
+            &[ETH1_ADDRESS_WITHDRAWAL_PREFIX][..],
+            &[0u8; 11],
+            &credentials,
+        ]
+        .concat()),
+        32 => Ok(credentials),
+        _ => Err(format_err!("invalid_withdrawal_credentials")),
+    }
+}
+
+/// Exports the signing key of validator `input.index` as an EIP-2335 keystore
+pub fn export_validator_keystore(
+    mnemonic: &str,
+    input: &EthereumValidatorKeystoreIn,
+) -> Result<EthereumValidatorKeystoreOut> {
+    let key = ValidatorKey::from_mnemonic(mnemonic, &validator_signing_path(input.index))?;
+    let secret = key.secret();
+    let pubkey = key.public_key();
+    let keystore = match input.kdf.as_str() {
+        "" | "scrypt" => Eip2335Keystore::encrypt::<SCryptParams>(
+            &secret,
+            &pubkey,
+            &key.path,
+            &input.keystore_password,
+        )?,
+        "pbkdf2" => Eip2335Keystore::encrypt::<Pbkdf2Params>(
+            &secret,
+            &pubkey,
+            &key.path,
+            &input.keystore_password,
+        )?,
+        _ => return Err(format_err!("unsupported_kdf: {}", input.kdf)),
+    };
+
+    Ok(EthereumValidatorKeystoreOut {
+        pubkey: hex::encode(pubkey),
+        path: key.path,
+        keystore: serde_json::to_string(&keystore)?,
+    })
+}
+
+/// Signs the deposit of validator `input.index`, the output matches the `deposit_data` json of
+/// the staking deposit cli
+pub fn sign_deposit_data(
+    mnemonic: &str,
+    input: &EthereumDepositDataIn,
+) -> Result<EthereumDepositDataOut> {
+    if input.amount == 0 {
+        return Err(format_err!("invalid_deposit_amount"));
+    }
+    let fork_version = if input.fork_version.is_empty() {
+        genesis_fork_version(&input.network)?
+    } else {
+        decode_hex(&input.fork_version)
+            .ok()
+            .filter(|x| x.len() == 4)
+            .ok_or_else(|| format_err!("invalid_fork_version"))?
+    };
+
+    let key = ValidatorKey::from_mnemonic(mnemonic, &validator_signing_path(input.index))?;
+    let pubkey = key.public_key();
+    let withdrawal_credentials = withdrawal_credentials(mnemonic, input)?;
+
+    let message_root = deposit_message_root(&pubkey, &withdrawal_credentials, input.amount);
+    let signing_root = hash_pair(&message_root, &deposit_domain(&fork_version));
+    let signature = key.sign(&signing_root);
+    let data_root = deposit_data_root(&pubkey, &withdrawal_credentials, input.amount, &signature);
+
+    Ok(EthereumDepositDataOut {
+        pubkey: hex::encode(pubkey),
+        withdrawal_credentials: hex::encode(withdrawal_credentials),
+        amount: input.amount,
+        signature: hex::encode(signature),
+        deposit_message_root: hex::encode(message_root),
+        deposit_data_root: hex::encode(data_root),
+        fork_version: hex::encode(fork_version),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export_validator_keystore, sign_deposit_data, ValidatorKey};
+    use crate::eip2335::Eip2335Keystore;
+    use crate::transaction::{EthereumDepositDataIn, EthereumValidatorKeystoreIn};
+    use tcx_constants::TEST_MNEMONIC;
+
+    const PUBKEY: &str = "b809eea8f4c1935fa6c0cc6d454a5b297c7856da4df4da96432ea2ef6fa86abf90f8553aaf8c6d605c94244d68d62eb8";
+
+    #[test]
+    fn test_validator_key() {
+        let key = ValidatorKey::from_mnemonic(TEST_MNEMONIC, "m/12381/3600/0/0/0").unwrap();
+        assert_eq!(
+            hex::encode(key.secret()),
+            "6bf145d39e0f1312ce67b27efae17d104bd85ff687a2524213a6f2f7827d933c"
+        );
+        assert_eq!(hex::encode(key.public_key()), PUBKEY);
+
+        let key = ValidatorKey::from_mnemonic(TEST_MNEMONIC, "m/12381/3600/0/0").unwrap();
+        assert_eq!(hex::encode(key.public_key()), "99833eeee8cfad1bb7a82a5ceecca02590eeb342ad491c64c270fdb9bd739c398b7f8ca8608bfada25ba4efb5d8e5653");
+    }
+
+    #[test]
+    fn test_export_validator_keystore() {
+        let output = export_validator_keystore(
+            TEST_MNEMONIC,
+            &EthereumValidatorKeystoreIn {
+                index: 0,
+                keystore_password: "testpassword".to_string(),
+                kdf: "pbkdf2".to_string(),
+                ..EthereumValidatorKeystoreIn::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(output.pubkey, PUBKEY);
+        assert_eq!(output.path, "m/12381/3600/0/0/0");
+
+        let keystore: Eip2335Keystore = serde_json::from_str(&output.keystore).unwrap();
+        assert_eq!(keystore.pubkey, PUBKEY);
+        assert_eq!(keystore.path, "m/12381/3600/0/0/0");
+        assert_eq!(
+            hex::encode(keystore.decrypt("testpassword").unwrap()),
+            "6bf145d39e0f1312ce67b27efae17d104bd85ff687a2524213a6f2f7827d933c"
+        );
+
+        let ret = export_validator_keystore(
+            TEST_MNEMONIC,
+            &EthereumValidatorKeystoreIn {
+                kdf: "argon2".to_string(),
+                ..EthereumValidatorKeystoreIn::default()
+            },
+        );
+        assert_eq!(format!("{}", ret.unwrap_err()), "unsupported_kdf: argon2");
+    }
+
+    #[test]
+    fn test_sign_deposit_data() {
+        let input = EthereumDepositDataIn {
+            index: 0,
+            withdrawal_credentials: "0x6031564e7b2F5cc33737807b2E58DaFF870B590b".to_string(),
+            amount: 32000000000,
+            network: "MAINNET".to_string(),
+            ..EthereumDepositDataIn::default()
+        };
+        let output = sign_deposit_data(TEST_MNEMONIC, &input).unwrap();
+        assert_eq!(output.pubkey, PUBKEY);
+        assert_eq!(
+            output.withdrawal_credentials,
+            "0100000000000000000000006031564e7b2f5cc33737807b2e58daff870b590b"
+        );
+        assert_eq!(output.amount, 32000000000);
+        assert_eq!(
+            output.deposit_message_root,
+            "b0aa28bfc2d55778a25bf2a164aa11121da3a6d1375a1d9d0d130535447be051"
+        );
+        assert_eq!(output.signature, "b857b2559d559df9ac634ed5df1b31a47b6d4bbc4693d8890364bd75f215509382095855f031ea9df9b2f0ab6089a2880d4c79982d252861e6ae1f2f9b2beef35bdc6616177269303f9ef8447550efdaf5dc259b528d86767af9f95a5953f86c");
+        assert_eq!(
+            output.deposit_data_root,
+            "6a1f3b9abf718a78cf632dc47e7992372731f721f79a8f481bae10f46153fce0"
+        );
+        assert_eq!(output.fork_version, "00000000");
+
+        // BLS withdrawal credentials on holesky
+        let output = sign_deposit_data(
+            TEST_MNEMONIC,
+            &EthereumDepositDataIn {
+                withdrawal_credentials: "".to_string(),
+                network: "HOLESKY".to_string(),
+                ..input.clone()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            output.withdrawal_credentials,
+            "0028046a5a4f95446d490d4c884efffa36ba5e103efc788d652a7e95ddb62b5f"
+        );
+        assert_eq!(output.signature, "87ed0b84d3c1c4bfe59b0a4e7638884b59d119ddbc5a03fc00055184b0188937a7f5a13363abe0d40e58dbe0c0ad211b0cf9db593c9fa12c01394d645bba56fb74a745c08a074b9fb4b875b82fb18a92a477655c85ec9ba601b2428452f1fca8");
+        assert_eq!(
+            output.deposit_data_root,
+            "7ab37a61ac74cf27aa51641d92d64db0a97ce92a336dbeba6b5d4ec8045cf591"
+        );
+        assert_eq!(output.fork_version, "01017000");
+
+        let ret = sign_deposit_data(
+            TEST_MNEMONIC,
+            &EthereumDepositDataIn {
+                withdrawal_credentials: "0x1234".to_string(),
+                ..input.clone()
+            },
+        );
+        assert_eq!(
+            format!("{}", ret.unwrap_err()),
+            "invalid_withdrawal_credentials"
+        );
+
+        let ret = sign_deposit_data(
+            TEST_MNEMONIC,
+            &EthereumDepositDataIn {
+                network: "KOVAN".to_string(),
+                ..input
+            },
+        );
+        assert_eq!(
+            format!("{}", ret.unwrap_err()),
+            "unsupported_network: KOVAN"
+        );
+    }
+}
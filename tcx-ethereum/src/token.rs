@@ -0,0 +1,196 @@
+use crate::abi::{AbiFunction, AbiParam};
+use crate::eip712::parse_hex;
+use crate::transaction::{ethereum_token_tx_in::Call, EthereumTokenTxIn, EthereumTxIn};
+use crate::Error;
+use serde_json::Value;
+use tcx_chain::Result;
+
+fn encode_call(name: &str, inputs: &[&str], args: &[&str]) -> Result<Vec<u8>> {
+    let function = AbiFunction {
+        name: name.to_string(),
+        inputs: inputs
+            .iter()
+            .map(|x| AbiParam {
+                name: "".to_string(),
+                param_type: x.to_string(),
+                components: vec![],
+            })
+            .collect(),
+    };
+    let args = args
+        .iter()
+        .map(|x| Value::String(x.to_string()))
+        .collect::<Vec<Value>>();
+    function.encode_input(&args)
+}
+
+/// Encodes the calldata of a token method call
+pub fn encode_token_call(call: &Call) -> Result<Vec<u8>> {
+    match call {
+        Call::Erc20Transfer(x) => {
+            encode_call("transfer", &["address", "uint256"], &[&x.to, &x.amount])
+        }
+        Call::Erc20Approve(x) => {
+            encode_call("approve", &["address", "uint256"], &[&x.spender, &x.amount])
+        }
+        Call::Erc721Transfer(x) => {
+            if x.data.is_empty() {
+                encode_call(
+                    "safeTransferFrom",
+                    &["address", "address", "uint256"],
+                    &[&x.from, &x.to, &x.token_id],
+                )
+            } else {
+                encode_call(
+                    "safeTransferFrom",
+                    &["address", "address", "uint256", "bytes"],
+                    &[&x.from, &x.to, &x.token_id, &x.data],
+                )
+            }
+        }
+        Call::Erc1155Transfer(x) => encode_call(
+            "safeTransferFrom",
+            &["address", "address", "uint256", "uint256", "bytes"],
+            &[&x.from, &x.to, &x.id, &x.amount, &x.data],
+        ),
+    }
+}
+
+/// Builds the transaction calling the token contract, the other fields of `input.tx` are kept
+pub fn build_token_transaction(input: &EthereumTokenTxIn) -> Result<EthereumTxIn> {
+    let contract = parse_hex(&Value::String(input.contract.to_string()))
+        .filter(|x| x.len() == 20)
+        .ok_or(Error::InvalidTo)?;
+    let call = input
+        .call
+        .as_ref()
+        .ok_or_else(|| format_err!("empty_token_call"))?;
+
+    Ok(EthereumTxIn {
+        to: hex::encode(contract),
+        value: "0".to_string(),
+        data: hex::encode(encode_token_call(call)?),
+        ..input.tx.clone().unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::build_token_transaction;
+    use crate::decode_call_data;
+    use crate::transaction::{
+        ethereum_token_tx_in::Call, Erc1155Transfer, Erc20Approve, Erc20Transfer, Erc721Transfer,
+        EthereumTokenTxIn, EthereumTxIn,
+    };
+
+    const CONTRACT: &str = "0xdAC17F958D2ee523a2206206994597C13D831ec7";
+    const FROM: &str = "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826";
+    const TO: &str = "0x3de0e4CD2FF24557bD9ed743e038eF18202F2acd";
+
+    fn build(call: Call) -> EthereumTxIn {
+        build_token_transaction(&EthereumTokenTxIn {
+            tx: Some(EthereumTxIn {
+                nonce: "8".to_string(),
+                gas_price: "20000000000".to_string(),
+                gas: "60000".to_string(),
+                network: "MAINNET".to_string(),
+                ..EthereumTxIn::default()
+            }),
+            contract: CONTRACT.to_string(),
+            call: Some(call),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_build_erc20() {
+        let tx = build(Call::Erc20Transfer(Erc20Transfer {
+            to: TO.to_string(),
+            amount: "1000000".to_string(),
+        }));
+        assert_eq!(tx.to, "dac17f958d2ee523a2206206994597c13d831ec7");
+        assert_eq!(tx.value, "0");
+        assert_eq!(tx.nonce, "8");
+        assert_eq!(tx.gas, "60000");
+        assert_eq!(tx.data, "a9059cbb0000000000000000000000003de0e4cd2ff24557bd9ed743e038ef18202f2acd00000000000000000000000000000000000000000000000000000000000f4240");
+
+        let tx = build(Call::Erc20Approve(Erc20Approve {
+            spender: TO.to_string(),
+            amount: "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+                .to_string(),
+        }));
+        let call = decode_call_data(&hex::decode(&tx.data).unwrap()).unwrap();
+        assert_eq!(call.method, "approve");
+        assert_eq!(call.arguments[0].value, TO);
+        assert_eq!(
+            call.arguments[1].value,
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+    }
+
+    #[test]
+    fn test_build_nft() {
+        let tx = build(Call::Erc721Transfer(Erc721Transfer {
+            from: FROM.to_string(),
+            to: TO.to_string(),
+            token_id: "1234".to_string(),
+            data: "".to_string(),
+        }));
+        assert_eq!(&tx.data[..8], "42842e0e");
+
+        let tx = build(Call::Erc721Transfer(Erc721Transfer {
+            from: FROM.to_string(),
+            to: TO.to_string(),
+            token_id: "1234".to_string(),
+            data: "0xabcd".to_string(),
+        }));
+        let call = decode_call_data(&hex::decode(&tx.data).unwrap()).unwrap();
+        assert_eq!(call.selector, "0xb88d4fde");
+        assert_eq!(call.arguments[3].value, "0xabcd");
+
+        let tx = build(Call::Erc1155Transfer(Erc1155Transfer {
+            from: FROM.to_string(),
+            to: TO.to_string(),
+            id: "7".to_string(),
+            amount: "3".to_string(),
+            data: "".to_string(),
+        }));
+        assert_eq!(tx.data, "f242432a000000000000000000000000cd2a3d9f938e13cd947ec05abc7fe734df8dd8260000000000000000000000003de0e4cd2ff24557bd9ed743e038ef18202f2acd0000000000000000000000000000000000000000000000000000000000000007000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000000000000000000000000000000000000a00000000000000000000000000000000000000000000000000000000000000000");
+        let call = decode_call_data(&hex::decode(&tx.data).unwrap()).unwrap();
+        assert_eq!(call.standard, "ERC1155");
+        assert_eq!(call.arguments[0].value, FROM);
+        assert_eq!(call.arguments[3].value, "3");
+    }
+
+    #[test]
+    fn test_build_invalid() {
+        let mut input = EthereumTokenTxIn {
+            tx: None,
+            contract: "0x1234".to_string(),
+            call: Some(Call::Erc20Transfer(Erc20Transfer {
+                to: TO.to_string(),
+                amount: "1".to_string(),
+            })),
+        };
+        assert_eq!(
+            format!("{}", build_token_transaction(&input).unwrap_err()),
+            "invalid_to"
+        );
+
+        input.contract = CONTRACT.to_string();
+        input.call = Some(Call::Erc20Transfer(Erc20Transfer {
+            to: TO.to_string(),
+            amount: "-1".to_string(),
+        }));
+        assert_eq!(
+            format!("{}", build_token_transaction(&input).unwrap_err()),
+            "abi_invalid_value: uint256"
+        );
+
+        input.call = None;
+        assert_eq!(
+            format!("{}", build_token_transaction(&input).unwrap_err()),
+            "empty_token_call"
+        );
+    }
+}
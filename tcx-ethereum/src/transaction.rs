@@ -20,19 +20,383 @@ pub struct EthereumTxIn {
     pub max_priority_fee_per_gas: std::string::String,
     #[prost(string, tag = "10")]
     pub transaction_type: std::string::String,
+    /// overrides the chain id looked up from `network` when non-zero
+    #[prost(uint64, tag = "11")]
+    pub chain_id: u64,
+    /// JSON array of signed EIP-7702 authorizations, required by type 4 transactions
+    #[prost(string, tag = "12")]
+    pub authorization_list: std::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct EthereumTxOut {
     #[prost(string, tag = "1")]
     pub signature: std::string::String,
+    /// 0x prefixed keccak256 of the signed transaction
+    #[prost(string, tag = "2")]
+    pub tx_hash: std::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct EthereumMsgIn {
+    /// hex string by default, utf-8 text when `is_utf8` is set
     #[prost(string, tag = "1")]
     pub value: std::string::String,
+    /// informational only, the message signature keeps v at 27/28 whatever the chain id
+    #[prost(uint64, tag = "2")]
+    pub chain_id: u64,
+    #[prost(enumeration = "SignatureType", tag = "3")]
+    pub signature_type: i32,
+    #[prost(bool, tag = "4")]
+    pub is_utf8: bool,
+    /// the validator address of the EIP-191 intended validator (0x00) version
+    #[prost(string, tag = "5")]
+    pub validator: std::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct EthereumMsgOut {
     #[prost(string, tag = "1")]
     pub signature: std::string::String,
 }
+/// eth_signTypedData_v4 input, `data` is the EIP-712 JSON with types, primaryType, domain and message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EthereumTypedDataIn {
+    #[prost(string, tag = "1")]
+    pub data: std::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EthereumTypedDataOut {
+    #[prost(string, tag = "1")]
+    pub signature: std::string::String,
+}
+/// eth_sign_authorization input, an EIP-7702 authorization delegating the code of the
+/// signing account to `address`. When the account also sends the type 4 transaction,
+/// `nonce` must be the transaction nonce plus one
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EthereumAuthorizationIn {
+    /// zero allows the authorization on every chain
+    #[prost(uint64, tag = "1")]
+    pub chain_id: u64,
+    #[prost(string, tag = "2")]
+    pub address: std::string::String,
+    #[prost(uint64, tag = "3")]
+    pub nonce: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EthereumAuthorizationOut {
+    /// JSON of the signed authorization, an item of `EthereumTxIn.authorization_list`
+    #[prost(string, tag = "1")]
+    pub authorization: std::string::String,
+    /// hex of r ‖ s ‖ y_parity
+    #[prost(string, tag = "2")]
+    pub signature: std::string::String,
+}
+/// eth_decode_tx input, `raw_tx` is the hex of an unsigned or signed transaction
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EthereumTxDecodeIn {
+    #[prost(string, tag = "1")]
+    pub raw_tx: std::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EthereumTxDecodeOut {
+    /// the decoded fields, `chain_id` is zero for legacy transactions without replay protection
+    #[prost(message, optional, tag = "1")]
+    pub tx: ::std::option::Option<EthereumTxIn>,
+    #[prost(bool, tag = "2")]
+    pub signed: bool,
+    /// the recovered sender of a signed transaction
+    #[prost(string, tag = "3")]
+    pub from: std::string::String,
+    /// present when `data` calls a known token method
+    #[prost(message, optional, tag = "4")]
+    pub contract_call: ::std::option::Option<EthereumContractCall>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EthereumContractCall {
+    #[prost(string, tag = "1")]
+    pub standard: std::string::String,
+    #[prost(string, tag = "2")]
+    pub method: std::string::String,
+    #[prost(string, tag = "3")]
+    pub selector: std::string::String,
+    #[prost(message, repeated, tag = "4")]
+    pub arguments: ::std::vec::Vec<EthereumCallArgument>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EthereumCallArgument {
+    #[prost(string, tag = "1")]
+    pub name: std::string::String,
+    #[prost(string, tag = "2")]
+    pub r#type: std::string::String,
+    #[prost(string, tag = "3")]
+    pub value: std::string::String,
+}
+/// eth_abi_encode input, `abi` is a JSON function fragment or a whole contract ABI,
+/// `method` is a function name or a signature like `transfer(address,uint256)` and
+/// may be empty for a single fragment, `args` is a JSON array of argument values
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EthereumAbiEncodeIn {
+    #[prost(string, tag = "1")]
+    pub abi: std::string::String,
+    #[prost(string, tag = "2")]
+    pub method: std::string::String,
+    #[prost(string, tag = "3")]
+    pub args: std::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EthereumAbiEncodeOut {
+    /// hex string of the calldata, can be used as `EthereumTxIn.data`
+    #[prost(string, tag = "1")]
+    pub data: std::string::String,
+}
+/// eth_build_token_tx input, the output is the `EthereumTxIn` calling the token contract
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EthereumTokenTxIn {
+    /// nonce, gas and network of the transaction, `to`, `value` and `data` are filled in
+    #[prost(message, optional, tag = "1")]
+    pub tx: ::std::option::Option<EthereumTxIn>,
+    #[prost(string, tag = "2")]
+    pub contract: std::string::String,
+    #[prost(oneof = "ethereum_token_tx_in::Call", tags = "3, 4, 5, 6")]
+    pub call: ::std::option::Option<ethereum_token_tx_in::Call>,
+}
+pub mod ethereum_token_tx_in {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Call {
+        #[prost(message, tag = "3")]
+        Erc20Transfer(super::Erc20Transfer),
+        #[prost(message, tag = "4")]
+        Erc20Approve(super::Erc20Approve),
+        #[prost(message, tag = "5")]
+        Erc721Transfer(super::Erc721Transfer),
+        #[prost(message, tag = "6")]
+        Erc1155Transfer(super::Erc1155Transfer),
+    }
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Erc20Transfer {
+    #[prost(string, tag = "1")]
+    pub to: std::string::String,
+    #[prost(string, tag = "2")]
+    pub amount: std::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Erc20Approve {
+    #[prost(string, tag = "1")]
+    pub spender: std::string::String,
+    #[prost(string, tag = "2")]
+    pub amount: std::string::String,
+}
+/// safeTransferFrom, `data` is an optional hex string passed to the receiver
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Erc721Transfer {
+    #[prost(string, tag = "1")]
+    pub from: std::string::String,
+    #[prost(string, tag = "2")]
+    pub to: std::string::String,
+    #[prost(string, tag = "3")]
+    pub token_id: std::string::String,
+    #[prost(string, tag = "4")]
+    pub data: std::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Erc1155Transfer {
+    #[prost(string, tag = "1")]
+    pub from: std::string::String,
+    #[prost(string, tag = "2")]
+    pub to: std::string::String,
+    #[prost(string, tag = "3")]
+    pub id: std::string::String,
+    #[prost(string, tag = "4")]
+    pub amount: std::string::String,
+    #[prost(string, tag = "5")]
+    pub data: std::string::String,
+}
+/// eth_sign_user_operation input, an ERC-4337 user operation in the `eth_sendUserOperation`
+/// form of its EntryPoint version. Numbers are decimal or 0x prefixed hex strings, bytes are hex
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EthereumUserOperationIn {
+    #[prost(string, tag = "1")]
+    pub entry_point: std::string::String,
+    #[prost(uint64, tag = "2")]
+    pub chain_id: u64,
+    #[prost(enumeration = "EntryPointVersion", tag = "3")]
+    pub entry_point_version: i32,
+    #[prost(string, tag = "4")]
+    pub sender: std::string::String,
+    #[prost(string, tag = "5")]
+    pub nonce: std::string::String,
+    /// v0.6 only
+    #[prost(string, tag = "6")]
+    pub init_code: std::string::String,
+    #[prost(string, tag = "7")]
+    pub call_data: std::string::String,
+    #[prost(string, tag = "8")]
+    pub call_gas_limit: std::string::String,
+    #[prost(string, tag = "9")]
+    pub verification_gas_limit: std::string::String,
+    #[prost(string, tag = "10")]
+    pub pre_verification_gas: std::string::String,
+    #[prost(string, tag = "11")]
+    pub max_fee_per_gas: std::string::String,
+    #[prost(string, tag = "12")]
+    pub max_priority_fee_per_gas: std::string::String,
+    /// v0.6 only
+    #[prost(string, tag = "13")]
+    pub paymaster_and_data: std::string::String,
+    /// v0.7 only, packed into initCode and paymasterAndData
+    #[prost(string, tag = "14")]
+    pub factory: std::string::String,
+    #[prost(string, tag = "15")]
+    pub factory_data: std::string::String,
+    #[prost(string, tag = "16")]
+    pub paymaster: std::string::String,
+    #[prost(string, tag = "17")]
+    pub paymaster_verification_gas_limit: std::string::String,
+    #[prost(string, tag = "18")]
+    pub paymaster_post_op_gas_limit: std::string::String,
+    #[prost(string, tag = "19")]
+    pub paymaster_data: std::string::String,
+    /// sign the user operation hash as is instead of as an EIP-191 personal message
+    #[prost(bool, tag = "20")]
+    pub raw_hash: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EthereumUserOperationOut {
+    /// 0x prefixed hex of the EntryPoint `getUserOpHash`
+    #[prost(string, tag = "1")]
+    pub user_op_hash: std::string::String,
+    /// 0x prefixed hex of r ‖ s ‖ v, the `signature` of `eth_sendUserOperation`
+    #[prost(string, tag = "2")]
+    pub signature: std::string::String,
+}
+/// siwe_sign input, the fields of an EIP-4361 Sign-In with Ethereum message. Optional
+/// fields are left out of the message when empty
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EthereumSiweIn {
+    /// the RFC 3986 authority requesting the signing
+    #[prost(string, tag = "1")]
+    pub domain: std::string::String,
+    /// must be the address of the signing account
+    #[prost(string, tag = "2")]
+    pub address: std::string::String,
+    #[prost(string, tag = "3")]
+    pub statement: std::string::String,
+    #[prost(string, tag = "4")]
+    pub uri: std::string::String,
+    /// "1" when empty
+    #[prost(string, tag = "5")]
+    pub version: std::string::String,
+    #[prost(uint64, tag = "6")]
+    pub chain_id: u64,
+    /// at least 8 alphanumeric characters
+    #[prost(string, tag = "7")]
+    pub nonce: std::string::String,
+    /// RFC 3339 date-time
+    #[prost(string, tag = "8")]
+    pub issued_at: std::string::String,
+    #[prost(string, tag = "9")]
+    pub expiration_time: std::string::String,
+    #[prost(string, tag = "10")]
+    pub not_before: std::string::String,
+    #[prost(string, tag = "11")]
+    pub request_id: std::string::String,
+    #[prost(string, repeated, tag = "12")]
+    pub resources: ::std::vec::Vec<std::string::String>,
+    /// the optional URI scheme of the domain, e.g. "https"
+    #[prost(string, tag = "13")]
+    pub scheme: std::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EthereumSiweOut {
+    /// the rendered EIP-4361 message
+    #[prost(string, tag = "1")]
+    pub message: std::string::String,
+    /// hex of r ‖ s ‖ v of the personal_sign signature
+    #[prost(string, tag = "2")]
+    pub signature: std::string::String,
+}
+/// eth_export_validator_keystore input, exports the EIP-2334 signing key m/12381/3600/{index}/0/0
+/// of an HD wallet as an EIP-2335 keystore
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EthereumValidatorKeystoreIn {
+    #[prost(string, tag = "1")]
+    pub id: std::string::String,
+    #[prost(string, tag = "2")]
+    pub password: std::string::String,
+    #[prost(uint32, tag = "3")]
+    pub index: u32,
+    /// the password of the exported keystore
+    #[prost(string, tag = "4")]
+    pub keystore_password: std::string::String,
+    /// "scrypt" when empty, or "pbkdf2"
+    #[prost(string, tag = "5")]
+    pub kdf: std::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EthereumValidatorKeystoreOut {
+    #[prost(string, tag = "1")]
+    pub pubkey: std::string::String,
+    #[prost(string, tag = "2")]
+    pub path: std::string::String,
+    /// the EIP-2335 keystore json
+    #[prost(string, tag = "3")]
+    pub keystore: std::string::String,
+}
+/// eth_sign_deposit_data input, signs the deposit of validator `index` of an HD wallet
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EthereumDepositDataIn {
+    #[prost(string, tag = "1")]
+    pub id: std::string::String,
+    #[prost(string, tag = "2")]
+    pub password: std::string::String,
+    #[prost(uint32, tag = "3")]
+    pub index: u32,
+    /// 32 bytes credentials, or the 20 bytes execution address of 0x01 credentials. The 0x00
+    /// credentials of the withdrawal key m/12381/3600/{index}/0 are used when empty
+    #[prost(string, tag = "4")]
+    pub withdrawal_credentials: std::string::String,
+    /// in gwei
+    #[prost(uint64, tag = "5")]
+    pub amount: u64,
+    /// MAINNET, SEPOLIA, HOLESKY or HOODI
+    #[prost(string, tag = "6")]
+    pub network: std::string::String,
+    /// hex of the genesis fork version, overrides `network` when set
+    #[prost(string, tag = "7")]
+    pub fork_version: std::string::String,
+}
+/// the fields of an entry of the deposit_data json of the staking deposit cli
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EthereumDepositDataOut {
+    #[prost(string, tag = "1")]
+    pub pubkey: std::string::String,
+    #[prost(string, tag = "2")]
+    pub withdrawal_credentials: std::string::String,
+    #[prost(uint64, tag = "3")]
+    pub amount: u64,
+    #[prost(string, tag = "4")]
+    pub signature: std::string::String,
+    #[prost(string, tag = "5")]
+    pub deposit_message_root: std::string::String,
+    #[prost(string, tag = "6")]
+    pub deposit_data_root: std::string::String,
+    #[prost(string, tag = "7")]
+    pub fork_version: std::string::String,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum EntryPointVersion {
+    EntryPointV06 = 0,
+    EntryPointV07 = 1,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum SignatureType {
+    /// keccak256(value) without any prefix
+    Keccak256 = 0,
+    /// EIP-191 version 0x45, "\x19Ethereum Signed Message:\n" + len(value) + value
+    PersonalSign = 1,
+    /// EIP-191 version 0x00, "\x19\x00" + validator + value
+    IntendedValidator = 2,
+    /// value is a 32 bytes hash and is signed as is
+    RawHash = 3,
+}
@@ -1,7 +1,9 @@
-use crate::signature::{ecdsa_sign, Signature};
+use crate::signature::{ecdsa_recover, ecdsa_sign, Signature};
+use crate::Error;
 use ethereum_types::{H160, H256, U256, U64};
-use rlp::RlpStream;
+use rlp::{DecoderError, Rlp, RlpStream};
 use serde::{Deserialize, Serialize};
+use tcx_chain::Result;
 use tiny_keccak::{Hasher, Keccak};
 
 pub type Address = H160;
@@ -10,6 +12,10 @@ pub type AccessList = Vec<AccessListItem>;
 const LEGACY_TX_ID: u64 = 0;
 const ACCESSLISTS_TX_ID: u64 = 1;
 const EIP1559_TX_ID: u64 = 2;
+const EIP7702_TX_ID: u64 = 4;
+
+/// The prefix of the EIP-7702 authorization signing payload
+const EIP7702_AUTHORIZATION_MAGIC: u8 = 0x05;
 
 /// Access list item
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
@@ -21,6 +27,72 @@ pub struct AccessListItem {
     pub storage_keys: Vec<H256>,
 }
 
+/// An EIP-7702 authorization, delegates the code of the signing account to `address`
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Authorization {
+    /// Zero allows the authorization on every chain
+    pub chain_id: U256,
+    /// Address of the delegated code
+    pub address: Address,
+    /// Nonce of the signing account when the authorization is applied
+    pub nonce: U64,
+}
+
+impl Authorization {
+    /// `keccak256(0x05 ‖ rlp([chain_id, address, nonce]))`
+    pub fn signing_hash(&self) -> Vec<u8> {
+        let mut stream = RlpStream::new();
+        stream.begin_list(3);
+        stream.append(&self.chain_id);
+        stream.append(&self.address);
+        stream.append(&self.nonce);
+        keccak256_hash(&[&[EIP7702_AUTHORIZATION_MAGIC], stream.as_raw()].concat())
+    }
+
+    pub fn into_signed(self, signature: &Signature) -> SignedAuthorization {
+        SignedAuthorization {
+            chain_id: self.chain_id,
+            address: self.address,
+            nonce: self.nonce,
+            y_parity: U64::from(signature.v),
+            r: U256::from_big_endian(signature.r.as_bytes()),
+            s: U256::from_big_endian(signature.s.as_bytes()),
+        }
+    }
+}
+
+/// An item of the authorization list of an EIP-7702 transaction
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedAuthorization {
+    pub chain_id: U256,
+    pub address: Address,
+    pub nonce: U64,
+    pub y_parity: U64,
+    pub r: U256,
+    pub s: U256,
+}
+
+impl SignedAuthorization {
+    pub fn authorization(&self) -> Authorization {
+        Authorization {
+            chain_id: self.chain_id,
+            address: self.address,
+            nonce: self.nonce,
+        }
+    }
+
+    /// Recovers the account which delegates its code
+    pub fn recover_authority(&self) -> Result<Address> {
+        if self.y_parity > U64::one() {
+            return Err(Error::InvalidSignature.into());
+        }
+        let signature = decode_signature(self.y_parity.as_u64(), self.r, self.s);
+        ecdsa_recover(&self.authorization().signing_hash(), &signature)
+    }
+}
+
 /// A transaction used for RLP encoding, hashing and signing.
 #[derive(Debug)]
 pub struct Transaction {
@@ -33,6 +105,7 @@ pub struct Transaction {
     pub transaction_type: Option<U64>,
     pub access_list: AccessList,
     pub max_priority_fee_per_gas: U256,
+    pub authorization_list: Vec<SignedAuthorization>,
 }
 
 impl Transaction {
@@ -114,6 +187,37 @@ impl Transaction {
         stream
     }
 
+    fn encode_eip7702_payload(
+        &self,
+        chain_id: u64,
+        signature: Option<&Signature>,
+    ) -> Result<RlpStream> {
+        let mut stream = RlpStream::new();
+        let list_size = if signature.is_some() { 13 } else { 10 };
+        stream.begin_list(list_size);
+
+        stream.append(&chain_id);
+
+        stream.append(&self.nonce);
+        stream.append(&self.max_priority_fee_per_gas);
+        stream.append(&self.gas_price);
+        stream.append(&self.gas);
+        // from EIP-7702: a set code transaction can not create a contract
+        let to = self.to.ok_or(Error::InvalidTo)?;
+        stream.append(&to);
+        stream.append(&self.value);
+        stream.append(&self.data);
+
+        self.rlp_append_access_list(&mut stream);
+        self.rlp_append_authorization_list(&mut stream);
+
+        if let Some(signature) = signature {
+            self.rlp_append_signature(&mut stream, signature, chain_id);
+        }
+
+        Ok(stream)
+    }
+
     fn rlp_append_signature(
         &self,
         stream: &mut RlpStream,
@@ -124,13 +228,12 @@ impl Transaction {
             Some(LEGACY_TX_ID) | None => true,
             _ => false,
         };
-        let v = if adjust_v_value {
+        if adjust_v_value {
             // When signing with a chain ID, add chain replay protection.
-            signature.v + 35 + chain_id * 2
+            stream.append(&eip155_v(signature.v, chain_id));
         } else {
-            signature.v
-        };
-        stream.append(&v);
+            stream.append(&signature.v);
+        }
         stream.append(&U256::from_big_endian(signature.r.as_bytes()));
         stream.append(&U256::from_big_endian(signature.s.as_bytes()));
     }
@@ -147,8 +250,21 @@ impl Transaction {
         }
     }
 
-    pub fn encode(&self, chain_id: u64, signature: Option<&Signature>) -> Vec<u8> {
-        match self.transaction_type.map(|t| t.as_u64()) {
+    fn rlp_append_authorization_list(&self, stream: &mut RlpStream) -> () {
+        stream.begin_list(self.authorization_list.len());
+        for authorization in self.authorization_list.iter() {
+            stream.begin_list(6);
+            stream.append(&authorization.chain_id);
+            stream.append(&authorization.address);
+            stream.append(&authorization.nonce);
+            stream.append(&authorization.y_parity);
+            stream.append(&authorization.r);
+            stream.append(&authorization.s);
+        }
+    }
+
+    pub fn encode(&self, chain_id: u64, signature: Option<&Signature>) -> Result<Vec<u8>> {
+        let encoded = match self.transaction_type.map(|t| t.as_u64()) {
             Some(LEGACY_TX_ID) | None => {
                 let stream = self.encode_legacy(chain_id, signature);
                 stream.out()
@@ -166,20 +282,27 @@ impl Transaction {
                 [&[tx_id], stream.as_raw()].concat()
             }
 
+            Some(EIP7702_TX_ID) => {
+                let tx_id: u8 = EIP7702_TX_ID as u8;
+                let stream = self.encode_eip7702_payload(chain_id, signature)?;
+                [&[tx_id], stream.as_raw()].concat()
+            }
+
             _ => {
                 panic!("Unsupported transaction type");
             }
-        }
+        };
+        Ok(encoded)
     }
 
     /// Sign and return a raw signed transaction.
-    pub fn sign(self, private_key: &H256, chain_id: u64) -> Vec<u8> {
+    pub fn sign(self, private_key: &H256, chain_id: u64) -> Result<Vec<u8>> {
         let adjust_v_value = match self.transaction_type.map(|t| t.as_u64()) {
             Some(LEGACY_TX_ID) | None => true,
             _ => false,
         };
 
-        let encoded = self.encode(chain_id, None);
+        let encoded = self.encode(chain_id, None)?;
         let hash = keccak256_hash(&encoded);
 
         let signature = if adjust_v_value {
@@ -192,6 +315,274 @@ impl Transaction {
     }
 }
 
+/// A transaction decoded from its unsigned or signed RLP encoding
+#[derive(Debug)]
+pub struct DecodedTransaction {
+    pub transaction: Transaction,
+    /// `None` for legacy transactions without replay protection
+    pub chain_id: Option<u64>,
+    pub signature: Option<Signature>,
+}
+
+impl DecodedTransaction {
+    /// The hash the sender signed
+    pub fn signing_hash(&self) -> Result<Vec<u8>> {
+        match self.chain_id {
+            Some(chain_id) => Ok(keccak256_hash(&self.transaction.encode(chain_id, None)?)),
+            None => {
+                let mut stream = RlpStream::new();
+                stream.begin_list(6);
+                self.transaction.rlp_append_legacy(&mut stream);
+                Ok(keccak256_hash(&stream.out()))
+            }
+        }
+    }
+
+    /// Recovers the sender of a signed transaction
+    pub fn recover_signer(&self) -> Result<Address> {
+        let signature = self.signature.as_ref().ok_or(Error::InvalidSignature)?;
+        ecdsa_recover(&self.signing_hash()?, signature)
+    }
+}
+
+impl Transaction {
+    /// Decodes a legacy, EIP-2930, EIP-1559 or EIP-7702 transaction, either unsigned as
+    /// produced by `encode(chain_id, None)` or signed
+    pub fn decode(bytes: &[u8]) -> Result<DecodedTransaction> {
+        let decoded = match bytes.first() {
+            Some(x) if *x >= 0xc0 => Self::decode_legacy(&Rlp::new(bytes)),
+            Some(x) if *x as u64 == ACCESSLISTS_TX_ID => {
+                Self::decode_eip2930(&Rlp::new(&bytes[1..]))
+            }
+            Some(x) if *x as u64 == EIP1559_TX_ID => Self::decode_eip1559(&Rlp::new(&bytes[1..])),
+            Some(x) if *x as u64 == EIP7702_TX_ID => Self::decode_eip7702(&Rlp::new(&bytes[1..])),
+            _ => return Err(Error::InvalidRawTransaction.into()),
+        };
+        decoded.map_err(|_| Error::InvalidRawTransaction.into())
+    }
+
+    fn decode_legacy(rlp: &Rlp) -> Result<DecodedTransaction> {
+        let item_count = rlp.item_count()?;
+        if item_count != 6 && item_count != 9 {
+            return Err(Error::InvalidRawTransaction.into());
+        }
+        let transaction = Transaction {
+            nonce: rlp.val_at(0)?,
+            gas_price: rlp.val_at(1)?,
+            gas: rlp.val_at(2)?,
+            to: decode_to(&rlp.at(3)?)?,
+            value: rlp.val_at(4)?,
+            data: rlp.val_at(5)?,
+            transaction_type: None,
+            access_list: vec![],
+            max_priority_fee_per_gas: U256::zero(),
+            authorization_list: vec![],
+        };
+        if item_count == 6 {
+            return Ok(DecodedTransaction {
+                transaction,
+                chain_id: None,
+                signature: None,
+            });
+        }
+
+        let v: U256 = rlp.val_at(6)?;
+        let r: U256 = rlp.val_at(7)?;
+        let s: U256 = rlp.val_at(8)?;
+        if r.is_zero() && s.is_zero() {
+            return Ok(DecodedTransaction {
+                transaction,
+                chain_id: Some(u256_to_u64(v)?),
+                signature: None,
+            });
+        }
+
+        let (chain_id, recovery_id) = if v == U256::from(27) || v == U256::from(28) {
+            (None, v.low_u64() - 27)
+        } else if v >= U256::from(35) {
+            let replay_protected = v - 35;
+            (
+                Some(u256_to_u64(replay_protected / 2)?),
+                (replay_protected % 2).low_u64(),
+            )
+        } else {
+            return Err(Error::InvalidRawTransaction.into());
+        };
+        Ok(DecodedTransaction {
+            transaction,
+            chain_id,
+            signature: Some(decode_signature(recovery_id, r, s)),
+        })
+    }
+
+    fn decode_eip2930(rlp: &Rlp) -> Result<DecodedTransaction> {
+        let item_count = rlp.item_count()?;
+        if item_count != 8 && item_count != 11 {
+            return Err(Error::InvalidRawTransaction.into());
+        }
+        let transaction = Transaction {
+            nonce: rlp.val_at(1)?,
+            gas_price: rlp.val_at(2)?,
+            gas: rlp.val_at(3)?,
+            to: decode_to(&rlp.at(4)?)?,
+            value: rlp.val_at(5)?,
+            data: rlp.val_at(6)?,
+            transaction_type: Some(U64::from(ACCESSLISTS_TX_ID)),
+            access_list: decode_access_list(&rlp.at(7)?)?,
+            max_priority_fee_per_gas: U256::zero(),
+            authorization_list: vec![],
+        };
+        let signature = if item_count == 11 {
+            Some(decode_signature(
+                rlp.val_at(8)?,
+                rlp.val_at(9)?,
+                rlp.val_at(10)?,
+            ))
+        } else {
+            None
+        };
+        Ok(DecodedTransaction {
+            transaction,
+            chain_id: Some(rlp.val_at(0)?),
+            signature,
+        })
+    }
+
+    fn decode_eip1559(rlp: &Rlp) -> Result<DecodedTransaction> {
+        let item_count = rlp.item_count()?;
+        if item_count != 9 && item_count != 12 {
+            return Err(Error::InvalidRawTransaction.into());
+        }
+        let transaction = Transaction {
+            nonce: rlp.val_at(1)?,
+            max_priority_fee_per_gas: rlp.val_at(2)?,
+            gas_price: rlp.val_at(3)?,
+            gas: rlp.val_at(4)?,
+            to: decode_to(&rlp.at(5)?)?,
+            value: rlp.val_at(6)?,
+            data: rlp.val_at(7)?,
+            transaction_type: Some(U64::from(EIP1559_TX_ID)),
+            access_list: decode_access_list(&rlp.at(8)?)?,
+            authorization_list: vec![],
+        };
+        let signature = if item_count == 12 {
+            Some(decode_signature(
+                rlp.val_at(9)?,
+                rlp.val_at(10)?,
+                rlp.val_at(11)?,
+            ))
+        } else {
+            None
+        };
+        Ok(DecodedTransaction {
+            transaction,
+            chain_id: Some(rlp.val_at(0)?),
+            signature,
+        })
+    }
+
+    fn decode_eip7702(rlp: &Rlp) -> Result<DecodedTransaction> {
+        let item_count = rlp.item_count()?;
+        if item_count != 10 && item_count != 13 {
+            return Err(Error::InvalidRawTransaction.into());
+        }
+        let transaction = Transaction {
+            nonce: rlp.val_at(1)?,
+            max_priority_fee_per_gas: rlp.val_at(2)?,
+            gas_price: rlp.val_at(3)?,
+            gas: rlp.val_at(4)?,
+            // a set code transaction can not create a contract
+            to: Some(rlp.val_at(5)?),
+            value: rlp.val_at(6)?,
+            data: rlp.val_at(7)?,
+            transaction_type: Some(U64::from(EIP7702_TX_ID)),
+            access_list: decode_access_list(&rlp.at(8)?)?,
+            authorization_list: decode_authorization_list(&rlp.at(9)?)?,
+        };
+        let signature = if item_count == 13 {
+            Some(decode_signature(
+                rlp.val_at(10)?,
+                rlp.val_at(11)?,
+                rlp.val_at(12)?,
+            ))
+        } else {
+            None
+        };
+        Ok(DecodedTransaction {
+            transaction,
+            chain_id: Some(rlp.val_at(0)?),
+            signature,
+        })
+    }
+}
+
+fn decode_to(rlp: &Rlp) -> core::result::Result<Option<Address>, DecoderError> {
+    if rlp.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(rlp.as_val()?))
+    }
+}
+
+fn decode_access_list(rlp: &Rlp) -> core::result::Result<AccessList, DecoderError> {
+    rlp.iter()
+        .map(|item| {
+            Ok(AccessListItem {
+                address: item.val_at(0)?,
+                storage_keys: item.list_at(1)?,
+            })
+        })
+        .collect()
+}
+
+fn decode_authorization_list(
+    rlp: &Rlp,
+) -> core::result::Result<Vec<SignedAuthorization>, DecoderError> {
+    rlp.iter()
+        .map(|item| {
+            if item.item_count()? != 6 {
+                return Err(DecoderError::RlpIncorrectListLen);
+            }
+            Ok(SignedAuthorization {
+                chain_id: item.val_at(0)?,
+                address: item.val_at(1)?,
+                nonce: item.val_at(2)?,
+                y_parity: item.val_at(3)?,
+                r: item.val_at(4)?,
+                s: item.val_at(5)?,
+            })
+        })
+        .collect()
+}
+
+fn decode_signature(recovery_id: u64, r: U256, s: U256) -> Signature {
+    let mut r_bytes = [0u8; 32];
+    let mut s_bytes = [0u8; 32];
+    r.to_big_endian(&mut r_bytes);
+    s.to_big_endian(&mut s_bytes);
+    Signature {
+        v: recovery_id,
+        r: H256::from(r_bytes),
+        s: H256::from(s_bytes),
+    }
+}
+
+fn u256_to_u64(value: U256) -> Result<u64> {
+    if value > U256::from(u64::max_value()) {
+        Err(Error::InvalidRawTransaction.into())
+    } else {
+        Ok(value.low_u64())
+    }
+}
+
+/// Computes the EIP-155 `v` value, `recovery_id + 35 + chain_id * 2`.
+///
+/// Chain IDs are arbitrary-size integers, so the result is computed in 256 bits
+/// to avoid overflowing for chain IDs close to `u64::MAX`.
+pub fn eip155_v(recovery_id: u64, chain_id: u64) -> U256 {
+    U256::from(recovery_id) + U256::from(chain_id) * 2 + 35
+}
+
 fn keccak256_hash(bytes: &[u8]) -> Vec<u8> {
     let mut hasher = Keccak::v256();
     hasher.update(bytes);
@@ -0,0 +1,240 @@
+use crate::eip712::{parse_hex, parse_integer, to_word};
+use crate::keccak;
+use crate::transaction::{EntryPointVersion, EthereumUserOperationIn};
+use ethereum_types::U256;
+use serde_json::Value;
+use tcx_chain::Result;
+
+fn parse_bytes(value: &str, field: &str) -> Result<Vec<u8>> {
+    parse_hex(&Value::String(value.to_string()))
+        .ok_or_else(|| format_err!("invalid_user_operation: {}", field))
+}
+
+fn parse_address(value: &str, field: &str) -> Result<Vec<u8>> {
+    let address = parse_bytes(value, field)?;
+    if address.len() != 20 {
+        return Err(format_err!("invalid_user_operation: {}", field));
+    }
+    Ok(address)
+}
+
+fn parse_uint(value: &str, field: &str) -> Result<U256> {
+    match parse_integer(&Value::String(value.to_string())) {
+        Some((number, false)) => Ok(number),
+        _ => Err(format_err!("invalid_user_operation: {}", field)),
+    }
+}
+
+/// Parses a number packed as 16 bytes by EntryPoint v0.7
+fn parse_uint128(value: &str, field: &str) -> Result<Vec<u8>> {
+    let number = parse_uint(value, field)?;
+    if number.bits() > 128 {
+        return Err(format_err!("invalid_user_operation: {}", field));
+    }
+    Ok(to_word(number)[16..].to_vec())
+}
+
+fn address_word(address: &[u8]) -> Vec<u8> {
+    [&[0u8; 12], address].concat()
+}
+
+/// `initCode` of v0.7 is `factory ‖ factoryData`, empty without factory
+fn packed_init_code(op: &EthereumUserOperationIn) -> Result<Vec<u8>> {
+    if op.factory.is_empty() {
+        return Ok(vec![]);
+    }
+    Ok([
+        parse_address(&op.factory, "factory")?,
+        parse_bytes(&op.factory_data, "factory_data")?,
+    ]
+    .concat())
+}
+
+/// `paymasterAndData` of v0.7 is `paymaster ‖ uint128(paymasterVerificationGasLimit) ‖
+/// uint128(paymasterPostOpGasLimit) ‖ paymasterData`, empty without paymaster
+fn packed_paymaster_and_data(op: &EthereumUserOperationIn) -> Result<Vec<u8>> {
+    if op.paymaster.is_empty() {
+        return Ok(vec![]);
+    }
+    Ok([
+        parse_address(&op.paymaster, "paymaster")?,
+        parse_uint128(
+            &op.paymaster_verification_gas_limit,
+            "paymaster_verification_gas_limit",
+        )?,
+        parse_uint128(
+            &op.paymaster_post_op_gas_limit,
+            "paymaster_post_op_gas_limit",
+        )?,
+        parse_bytes(&op.paymaster_data, "paymaster_data")?,
+    ]
+    .concat())
+}
+
+/// The `abi.encode` of the user operation without signature, dynamic fields are hashed
+fn pack(op: &EthereumUserOperationIn) -> Result<Vec<u8>> {
+    let mut packed = address_word(&parse_address(&op.sender, "sender")?);
+    packed.extend(to_word(parse_uint(&op.nonce, "nonce")?));
+
+    match EntryPointVersion::from_i32(op.entry_point_version) {
+        Some(EntryPointVersion::EntryPointV06) => {
+            packed.extend(keccak(&parse_bytes(&op.init_code, "init_code")?));
+            packed.extend(keccak(&parse_bytes(&op.call_data, "call_data")?));
+            packed.extend(to_word(parse_uint(&op.call_gas_limit, "call_gas_limit")?));
+            packed.extend(to_word(parse_uint(
+                &op.verification_gas_limit,
+                "verification_gas_limit",
+            )?));
+            packed.extend(to_word(parse_uint(
+                &op.pre_verification_gas,
+                "pre_verification_gas",
+            )?));
+            packed.extend(to_word(parse_uint(&op.max_fee_per_gas, "max_fee_per_gas")?));
+            packed.extend(to_word(parse_uint(
+                &op.max_priority_fee_per_gas,
+                "max_priority_fee_per_gas",
+            )?));
+            packed.extend(keccak(&parse_bytes(
+                &op.paymaster_and_data,
+                "paymaster_and_data",
+            )?));
+        }
+        Some(EntryPointVersion::EntryPointV07) => {
+            packed.extend(keccak(&packed_init_code(op)?));
+            packed.extend(keccak(&parse_bytes(&op.call_data, "call_data")?));
+            // accountGasLimits
+            packed.extend(parse_uint128(
+                &op.verification_gas_limit,
+                "verification_gas_limit",
+            )?);
+            packed.extend(parse_uint128(&op.call_gas_limit, "call_gas_limit")?);
+            packed.extend(to_word(parse_uint(
+                &op.pre_verification_gas,
+                "pre_verification_gas",
+            )?));
+            // gasFees
+            packed.extend(parse_uint128(
+                &op.max_priority_fee_per_gas,
+                "max_priority_fee_per_gas",
+            )?);
+            packed.extend(parse_uint128(&op.max_fee_per_gas, "max_fee_per_gas")?);
+            packed.extend(keccak(&packed_paymaster_and_data(op)?));
+        }
+        None => return Err(format_err!("invalid_entry_point_version")),
+    }
+    Ok(packed)
+}
+
+/// `keccak256(abi.encode(keccak256(pack(userOp)), entryPoint, chainId))`, the hash
+/// returned by `getUserOpHash` of the EntryPoint
+pub fn user_operation_hash(op: &EthereumUserOperationIn) -> Result<Vec<u8>> {
+    let mut encoded = keccak(&pack(op)?);
+    encoded.extend(address_word(&parse_address(
+        &op.entry_point,
+        "entry_point",
+    )?));
+    encoded.extend(to_word(U256::from(op.chain_id)));
+    Ok(keccak(&encoded))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::user_operation_hash;
+    use crate::transaction::{EntryPointVersion, EthereumUserOperationIn};
+
+    fn user_operation_v06() -> EthereumUserOperationIn {
+        EthereumUserOperationIn {
+            entry_point: "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789".to_string(),
+            chain_id: 1,
+            entry_point_version: EntryPointVersion::EntryPointV06 as i32,
+            sender: "0x3de0e4CD2FF24557bD9ed743e038eF18202F2acd".to_string(),
+            nonce: "0x1".to_string(),
+            init_code: "0x".to_string(),
+            call_data: "0xb61d27f6".to_string(),
+            call_gas_limit: "100000".to_string(),
+            verification_gas_limit: "200000".to_string(),
+            pre_verification_gas: "50000".to_string(),
+            max_fee_per_gas: "2000000000".to_string(),
+            max_priority_fee_per_gas: "1000000000".to_string(),
+            paymaster_and_data: "".to_string(),
+            ..EthereumUserOperationIn::default()
+        }
+    }
+
+    #[test]
+    fn test_user_operation_hash_v06() {
+        let op = user_operation_v06();
+        assert_eq!(
+            hex::encode(user_operation_hash(&op).unwrap()),
+            "1f26e7db9d9ca7b4a4a6e3cef7cb51fb5a98bb1ffb69ff3f5ca25f3c9f260353"
+        );
+
+        // the hash is bound to the entry point and the chain
+        let other_chain = EthereumUserOperationIn {
+            chain_id: 137,
+            ..op.clone()
+        };
+        assert_ne!(
+            user_operation_hash(&op).unwrap(),
+            user_operation_hash(&other_chain).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_user_operation_hash_v07() {
+        let op = EthereumUserOperationIn {
+            entry_point: "0x0000000071727De22E5E9d8BAf0edAc6f37da032".to_string(),
+            chain_id: 11155111,
+            entry_point_version: EntryPointVersion::EntryPointV07 as i32,
+            sender: "0x3de0e4CD2FF24557bD9ed743e038eF18202F2acd".to_string(),
+            nonce: "0".to_string(),
+            factory: "0x355972B9007c736515523417c96561F63db4e7bC".to_string(),
+            factory_data: "0x5fbfb9cf".to_string(),
+            call_data: "0xb61d27f6".to_string(),
+            call_gas_limit: "0x186a0".to_string(),
+            verification_gas_limit: "0x30d40".to_string(),
+            pre_verification_gas: "50000".to_string(),
+            max_fee_per_gas: "2000000000".to_string(),
+            max_priority_fee_per_gas: "1000000000".to_string(),
+            paymaster: "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826".to_string(),
+            paymaster_verification_gas_limit: "30000".to_string(),
+            paymaster_post_op_gas_limit: "10000".to_string(),
+            paymaster_data: "0xabcd".to_string(),
+            ..EthereumUserOperationIn::default()
+        };
+        assert_eq!(
+            hex::encode(user_operation_hash(&op).unwrap()),
+            "beb23fdab23fa00e4035562e0f76ac1592dc3b279211cac377d39a91f90f28fc"
+        );
+
+        let op = EthereumUserOperationIn {
+            call_gas_limit: "0x100000000000000000000000000000000".to_string(),
+            ..op
+        };
+        assert_eq!(
+            format!("{}", user_operation_hash(&op).unwrap_err()),
+            "invalid_user_operation: call_gas_limit"
+        );
+    }
+
+    #[test]
+    fn test_user_operation_invalid() {
+        let op = EthereumUserOperationIn {
+            sender: "0x1234".to_string(),
+            ..user_operation_v06()
+        };
+        assert_eq!(
+            format!("{}", user_operation_hash(&op).unwrap_err()),
+            "invalid_user_operation: sender"
+        );
+
+        let op = EthereumUserOperationIn {
+            entry_point_version: 9,
+            ..user_operation_v06()
+        };
+        assert_eq!(
+            format!("{}", user_operation_hash(&op).unwrap_err()),
+            "invalid_entry_point_version"
+        );
+    }
+}
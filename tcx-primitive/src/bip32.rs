@@ -112,6 +112,13 @@ impl DeterministicPrivateKey for Bip32DeterministicPrivateKey {
     }
 }
 
+impl Bip32DeterministicPublicKey {
+    /// The key identifier prefix, i.e. the parent fingerprint of its children
+    pub fn fingerprint(&self) -> Vec<u8> {
+        self.0.fingerprint()[..].to_vec()
+    }
+}
+
 impl DeterministicPublicKey for Bip32DeterministicPublicKey {
     type PublicKey = Secp256k1PublicKey;
 
@@ -323,6 +330,17 @@ mod tests {
         assert_eq!(dsk.to_string(), "xprv9yrdwPSRnvomqFK4u1y5uW2SaXS2Vnr3pAYTjJjbyRZR8p9BwoadRsCxtgUFdAKeRPbwvGRcCSYMV69nNK4N2kadevJ6L5iQVy1SwGKDTHQ");
     }
 
+    #[test]
+    fn fingerprint() {
+        let seed = default_seed();
+        let root = Bip32DeterministicPrivateKey::from_seed(seed.as_bytes()).unwrap();
+
+        assert_eq!(
+            hex::encode(root.deterministic_public_key().fingerprint()),
+            "1468dba9"
+        );
+    }
+
     #[test]
     fn from_xpub() {
         let xpub = Bip32DeterministicPublicKey::from_ss58check_with_version("xpub6CqzLtyKdJN53jPY13W6GdyB8ZGWuFZuBPU4Xh9DXm6Q1cULVLtsyfXSjx4G77rNdCRBgi83LByaWxjtDaZfLAKT6vFUq3EhPtNwTpJigx");
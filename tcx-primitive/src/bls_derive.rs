@@ -68,6 +68,51 @@ impl DeterministicPrivateKey for BLSDeterministicPrivateKey {
     }
 }
 
+/// Key tree of the final EIP-2333 and the EIP-2334 paths of Ethereum validators.
+/// `BLSDeterministicPrivateKey` follows an early draft of the EIP and is kept for Filecoin
+#[derive(Clone)]
+pub struct Eip2333DeterministicPrivateKey(pub BigUint);
+
+impl Eip2333DeterministicPrivateKey {
+    pub fn from_seed(seed: &[u8]) -> Result<Self> {
+        Ok(Eip2333DeterministicPrivateKey(eip2333_derive_master_sk(
+            seed,
+        )?))
+    }
+
+    pub fn from_mnemonic(mnemonic: &str) -> Result<Self> {
+        let mn = Mnemonic::from_phrase(mnemonic, Language::English)?;
+        let seed = bip39::Seed::new(&mn, "");
+        Eip2333DeterministicPrivateKey::from_seed(seed.as_bytes())
+    }
+
+    /// The 32 bytes big endian secret key
+    pub fn to_bytes(&self) -> Vec<u8> {
+        eip2333_sk_bytes(&self.0)
+    }
+}
+
+impl Derive for Eip2333DeterministicPrivateKey {
+    fn derive(&self, path: &str) -> Result<Self> {
+        let mut parts = path.split('/').peekable();
+        if *parts.peek().unwrap() == "m" {
+            parts.next();
+        }
+
+        let indexes = parts
+            .map(str::parse)
+            .collect::<std::result::Result<Vec<u32>, _>>()
+            .map_err(|_| KeyError::InvalidDerivationPathFormat)?;
+
+        let mut children_key = self.0.clone();
+        for index in indexes {
+            children_key = eip2333_derive_child(&children_key, index);
+        }
+
+        Ok(Eip2333DeterministicPrivateKey(children_key))
+    }
+}
+
 impl Derive for BLSDeterministicPublicKey {}
 
 impl FromHex for BLSDeterministicPublicKey {
@@ -123,12 +168,16 @@ fn ikm_to_lamport_sk(ikm: &[u8], salt: &[u8], split_bytes: &mut [[u8; DIGEST_SIZ
 fn parent_sk_to_lamport_pk(parent_sk: BigUint, index: BigUint) -> Vec<u8> {
     let salt = index.to_bytes_be();
     let ikm = parent_sk.to_bytes_be();
+    let not_ikm = flip_bits(parent_sk).to_bytes_be();
+    lamport_pk(&ikm, &not_ikm, &salt)
+}
+
+fn lamport_pk(ikm: &[u8], not_ikm: &[u8], salt: &[u8]) -> Vec<u8> {
     let mut lamport_0 = [[0u8; DIGEST_SIZE]; NUM_DIGESTS];
-    ikm_to_lamport_sk(ikm.as_slice(), salt.as_slice(), &mut lamport_0);
+    ikm_to_lamport_sk(ikm, salt, &mut lamport_0);
 
-    let not_ikm = flip_bits(parent_sk).to_bytes_be();
     let mut lamport_1 = [[0u8; DIGEST_SIZE]; NUM_DIGESTS];
-    ikm_to_lamport_sk(not_ikm.as_slice(), salt.as_slice(), &mut lamport_1);
+    ikm_to_lamport_sk(not_ikm, salt, &mut lamport_1);
 
     let mut combined = [[0u8; DIGEST_SIZE]; NUM_DIGESTS * 2];
     combined[..NUM_DIGESTS].clone_from_slice(&lamport_0[..NUM_DIGESTS]);
@@ -166,6 +215,51 @@ fn hkdf_mod_r(ikm: &[u8]) -> BigUint {
     BigUint::from_bytes_be(okm.as_ref()) % r
 }
 
+/// KeyGen of the final EIP-2333, the salt is hashed before every try until the key is non zero
+fn eip2333_hkdf_mod_r(ikm: &[u8]) -> BigUint {
+    let r = BigUint::from_str_radix(
+        "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001",
+        16,
+    )
+    .unwrap();
+
+    let mut tmp = ikm.to_vec();
+    tmp.extend(b"\x00");
+
+    let mut salt = b"BLS-SIG-KEYGEN-SALT-".to_vec();
+    loop {
+        salt = Sha256::digest(&salt).to_vec();
+        let mut okm = [0u8; 48];
+        hkdf(&salt, &tmp, b"\x00\x30", &mut okm);
+        let sk = BigUint::from_bytes_be(okm.as_ref()) % &r;
+        if sk != BigUint::from(0u8) {
+            return sk;
+        }
+    }
+}
+
+fn eip2333_sk_bytes(sk: &BigUint) -> Vec<u8> {
+    let bytes = sk.to_bytes_be();
+    [vec![0u8; 32 - bytes.len()], bytes].concat()
+}
+
+pub fn eip2333_derive_child(parent_sk: &BigUint, index: u32) -> BigUint {
+    let ikm = eip2333_sk_bytes(parent_sk);
+    let not_ikm = ikm.iter().map(|x| !x).collect::<Vec<u8>>();
+    let lamp_pk = lamport_pk(&ikm, &not_ikm, &index.to_be_bytes());
+    eip2333_hkdf_mod_r(lamp_pk.as_ref())
+}
+
+pub fn eip2333_derive_master_sk(seed: &[u8]) -> Result<BigUint> {
+    if seed.len() < 32 {
+        return Err(failure::err_msg(
+            "seed must be greater than or equal to 32 bytes",
+        ));
+    }
+
+    Ok(eip2333_hkdf_mod_r(seed))
+}
+
 pub fn derive_child(parent_sk: BigUint, index: BigUint) -> BigUint {
     let lamp_pk = parent_sk_to_lamport_pk(parent_sk, index);
     hkdf_mod_r(lamp_pk.as_ref())
@@ -184,7 +278,7 @@ pub fn derive_master_sk(seed: &[u8]) -> Result<BigUint> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::bls_derive::BLSDeterministicPrivateKey;
+    use crate::bls_derive::{BLSDeterministicPrivateKey, Eip2333DeterministicPrivateKey};
     use crate::{Derive, DeterministicPrivateKey, PrivateKey};
     use hex;
     use num_bigint::BigUint;
@@ -245,6 +339,54 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_eip2333_final() {
+        let test_vectors = vec!(
+            TestVector{
+                seed : "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+                master_sk : "6083874454709270928345386274498605044986640685124978867557563392430687146096",
+                child_index : "0",
+                child_sk : "20397789859736650942317412262472558107875392172444076792671091975210932703118",
+            },
+            TestVector{
+                seed: "3141592653589793238462643383279502884197169399375105820974944592",
+                master_sk: "29757020647961307431480504535336562678282505419141012933316116377660817309383",
+                child_index: "3141592653",
+                child_sk: "25457201688850691947727629385191704516744796114925897962676248250929345014287",
+            },
+            TestVector{
+                seed: "0099FF991111002299DD7744EE3355BBDD8844115566CC55663355668888CC00",
+                master_sk: "27580842291869792442942448775674722299803720648445448686099262467207037398656",
+                child_index: "4294967295",
+                child_sk: "29358610794459428860402234341874281240803786294062035874021252734817515685787",
+            },
+            TestVector{
+                seed: "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3",
+                master_sk: "19022158461524446591288038168518313374041767046816487870552872741050760015818",
+                child_index: "42",
+                child_sk: "31372231650479070279774297061823572166496564838472787488249775572789064611981",
+            }
+        );
+
+        for t in test_vectors.iter() {
+            let seed = hex::decode(t.seed).expect("invalid seed format");
+            let master_sk = t
+                .master_sk
+                .parse::<BigUint>()
+                .expect("invalid master key format");
+            let child_sk = t
+                .child_sk
+                .parse::<BigUint>()
+                .expect("invalid child key format");
+
+            let dsk = Eip2333DeterministicPrivateKey::from_seed(&seed).unwrap();
+            assert_eq!(dsk.0, master_sk);
+            let child = dsk.derive(&format!("m/{}", t.child_index)).unwrap();
+            assert_eq!(child.0, child_sk);
+            assert_eq!(child.to_bytes().len(), 32);
+        }
+    }
+
     #[test]
     fn test_bls_derive() {
         let dsk = BLSDeterministicPrivateKey::from_seed(
@@ -22,6 +22,7 @@ use core::result;
 pub type Result<T> = result::Result<T, failure::Error>;
 
 pub use crate::bip32::{Bip32DeterministicPrivateKey, Bip32DeterministicPublicKey};
+pub use crate::bls_derive::Eip2333DeterministicPrivateKey;
 pub use crate::derive::{get_account_path, Derive, DeriveJunction, DerivePath};
 pub use crate::ecc::{
     DeterministicPrivateKey, DeterministicPublicKey, PrivateKey, PublicKey,
@@ -42,11 +42,15 @@ impl TransactionSigner<SolanaTxIn, SolanaTxOut> for Keystore {
         let serialized_message = bincode::serialize(&message)?;
         let sk = self.find_private_key(symbol, address)?;
         let sig = sk.sign(&*serialized_message)?;
+        let tx_hash = bs58::encode(&sig).into_string();
         let tx = SolanaTransaction {
             signatures: vec![Signature::new(sig.as_slice())],
             message,
         };
         let serialized_tx = bs58::encode(serialize(&tx)?).into_string();
-        Ok(SolanaTxOut { tx: serialized_tx })
+        Ok(SolanaTxOut {
+            tx: serialized_tx,
+            tx_hash,
+        })
     }
 }
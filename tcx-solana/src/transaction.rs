@@ -15,4 +15,7 @@ pub struct SolanaTxIn {
 pub struct SolanaTxOut {
     #[prost(string, tag = "1")]
     pub tx: std::string::String,
+    /// base58 of the first signature, which identifies the transaction
+    #[prost(string, tag = "2")]
+    pub tx_hash: std::string::String,
 }
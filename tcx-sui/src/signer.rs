@@ -6,6 +6,8 @@ use sha2::{Digest, Sha256};
 use tcx_chain::{Keystore, TransactionSigner};
 use tcx_primitive::TypedPrivateKey;
 
+const TRANSACTION_DIGEST_PREFIX: &str = "TransactionData::";
+
 impl TransactionSigner<SuiTxInput, SuiTxOuput> for Keystore {
     fn sign_transaction(
         &mut self,
@@ -43,19 +45,30 @@ impl TransactionSigner<SuiTxInput, SuiTxOuput> for Keystore {
             _ => return Err(failure::Error::from(Error::InvalidSuiCurveType)),
         };
         signature.append(&mut sk.public_key().to_bytes());
+        let tx_data_bytes =
+            bcs::to_bytes(&unsigned_tx.value).map_err(|_| Error::BcsSerializeFailed)?;
         let tx_data = match &tx.sui_tx_type.as_ref().ok_or(crate::Error::EmptyTxType)? {
             SuiTxType::RawTx(tx) => tx.tx_data.clone(),
-            SuiTxType::Transfer(_) => base64::encode(
-                &bcs::to_bytes(&unsigned_tx.value).map_err(|_| Error::BcsSerializeFailed)?,
-            ),
+            SuiTxType::Transfer(_) => base64::encode(&tx_data_bytes),
         };
         Ok(SuiTxOuput {
             tx_data,
             signature: base64::encode(&signature),
+            tx_hash: tx_digest(&tx_data_bytes),
         })
     }
 }
 
+// the transaction digest is blake2b-256 of the prefixed bcs bytes of TransactionData
+fn tx_digest(tx_data: &[u8]) -> String {
+    let mut digest = [0u8; 32];
+    let mut hasher = blake2b_rs::Blake2bBuilder::new(DEFAULT_HASH_SIZE).build();
+    hasher.update(TRANSACTION_DIGEST_PREFIX.as_bytes());
+    hasher.update(tx_data);
+    hasher.finalize(&mut digest);
+    bs58::encode(digest).into_string()
+}
+
 #[cfg(test)]
 mod tests {
     use crate::transaction::{RawTx, SuiTxInput};
@@ -94,7 +107,11 @@ mod tests {
             .unwrap();
         println!("output: {:?}", output);
         let sig = "ALrW17ATAG4uGcER3rJuxaJ5hClV+nyFIFydSty1jU/V3A/xclIkA/UM7s7j776MFcZbC/Tcaxbdx0DDApfjwgnSMo758Mo+FlkS7gz+o/PNe5nVbgOOsRREJnQTcf8Q4g==".to_string();
-        assert_eq!(sig, output.signature)
+        assert_eq!(sig, output.signature);
+        assert_eq!(
+            output.tx_hash,
+            "91YuuzMcm5bAEoa6A9oKx2dyWGSNT9XwEAfKWSeQWbfv"
+        );
     }
 
     #[test]
@@ -125,5 +142,9 @@ mod tests {
             .unwrap();
         println!("output: {:?}", output);
         assert_eq!(output.signature, "AU3Leyt5EKAYVGWhHQQD3gnyrvTiunynu0VU/wky7vYvE1LWI8dnvt0IwRu8dh5UKizUejU89JXoCKI/z/2oRNMC9uKMHAGame2Juz0DN+uBgBbDj/ZGQwU/rPs5ColiDHY=");
+        assert_eq!(
+            output.tx_hash,
+            "94gJ7vtnv6Ebk94Rptkeo2nmTa565hqHXQUX5MK69qGc"
+        );
     }
 }
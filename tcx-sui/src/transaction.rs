@@ -63,4 +63,7 @@ pub struct SuiTxOuput {
     pub tx_data: std::string::String,
     #[prost(string, tag = "2")]
     pub signature: std::string::String,
+    /// base58 transaction digest
+    #[prost(string, tag = "3")]
+    pub tx_hash: std::string::String,
 }
@@ -33,11 +33,22 @@ impl TraitTransactionSigner<TezosRawTxIn, TezosTxOut> for Keystore {
         edsig_source_data.extend(&edsig_prefix);
         edsig_source_data.extend(sign_result.as_slice());
 
+        //operation hash is the blake2b hash of the signed bytes, without watermark
+        let mut signed_bytes = hex::decode(&raw_data_bytes)?;
+        signed_bytes.extend(sign_result.as_slice());
+        let operation_hash = params.hash(signed_bytes.as_slice());
+        //tezos operation hash prefix
+        let op_prefix: [u8; 2] = [5, 116];
+        let mut op_source_data = vec![];
+        op_source_data.extend(&op_prefix);
+        op_source_data.extend(operation_hash.as_bytes());
+
         let sign_result_hex = hex::encode(sign_result);
         let tx_out = TezosTxOut {
             signature: sign_result_hex.clone(),
             edsig: base58::check_encode_slice(edsig_source_data.as_slice()),
             sbytes: format!("{}{}", tx.raw_data, sign_result_hex),
+            tx_hash: base58::check_encode_slice(op_source_data.as_slice()),
         };
         Ok(tx_out)
     }
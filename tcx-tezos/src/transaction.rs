@@ -11,4 +11,7 @@ pub struct TezosTxOut {
     pub edsig: std::string::String,
     #[prost(string, tag = "3")]
     pub sbytes: std::string::String,
+    /// base58check operation hash, starts with "o"
+    #[prost(string, tag = "4")]
+    pub tx_hash: std::string::String,
 }
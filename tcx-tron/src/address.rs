@@ -7,8 +7,23 @@ use tcx_chain::Result;
 use tcx_constants::CoinInfo;
 use tcx_primitive::TypedPublicKey;
 
+use failure::format_err;
+
 pub struct Address(pub String);
 
+/// Decodes a base58 or 41 prefixed hex address into its 21 bytes
+pub fn decode_address(address: &str) -> Result<Vec<u8>> {
+    let decoded = if address.len() == 42 {
+        hex::decode(address).ok()
+    } else {
+        base58::from_check(address).ok()
+    };
+    match decoded {
+        Some(bytes) if bytes.len() == 21 && bytes[0] == 0x41 => Ok(bytes),
+        _ => Err(format_err!("invalid_tron_address")),
+    }
+}
+
 impl TraitAddress for Address {
     fn from_public_key(public_key: &TypedPublicKey, _coin: &CoinInfo) -> Result<String> {
         let pk = public_key.as_secp256k1()?;
@@ -31,7 +46,7 @@ impl TraitAddress for Address {
 
 #[cfg(test)]
 mod tests {
-    use super::Address;
+    use super::{decode_address, Address};
     use tcx_chain::Address as TraitAddress;
     use tcx_constants::coin_info::coin_info_from_param;
     use tcx_constants::{CoinInfo, CurveType};
@@ -78,4 +93,22 @@ mod tests {
             &coin_info
         ));
     }
+
+    #[test]
+    fn tron_address_decode() {
+        let expected = "41a614f803b6fd780986a42c78ec9c7f77e6ded13c";
+        assert_eq!(
+            hex::encode(decode_address("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t").unwrap()),
+            expected
+        );
+        assert_eq!(hex::encode(decode_address(expected).unwrap()), expected);
+        assert!(decode_address("a614f803b6fd780986a42c78ec9c7f77e6ded13c").is_err());
+        assert_eq!(
+            format!(
+                "{}",
+                decode_address("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6T").unwrap_err()
+            ),
+            "invalid_tron_address"
+        );
+    }
 }
@@ -0,0 +1,412 @@
+use crate::address::decode_address;
+use crate::protocol::{
+    Any, Contract, ContractType, FreezeBalanceV2Contract, ResourceCode, TransactionRaw,
+    TransferAssetContract, TransferContract, TriggerSmartContract, UnfreezeBalanceV2Contract, Vote,
+    VoteWitnessContract,
+};
+use crate::transaction::{tron_tx_build_input, TronResource, TronTxBuildInput, TronTxInput};
+use tcx_chain::Result;
+
+use failure::format_err;
+use num_bigint::BigUint;
+use num_traits::Num;
+use prost::Message;
+
+const TYPE_URL_PREFIX: &str = "type.googleapis.com/protocol.";
+const TRC20_TRANSFER_SELECTOR: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+const TRC20_APPROVE_SELECTOR: [u8; 4] = [0x09, 0x5e, 0xa7, 0xb3];
+
+fn contract<T: Message>(contract_type: ContractType, name: &str, value: &T) -> Result<Contract> {
+    let mut buf = vec![];
+    value.encode(&mut buf)?;
+    Ok(Contract {
+        r#type: contract_type as i32,
+        parameter: Some(Any {
+            type_url: format!("{}{}", TYPE_URL_PREFIX, name),
+            value: buf,
+        }),
+        ..Contract::default()
+    })
+}
+
+fn check_amount(amount: i64) -> Result<i64> {
+    if amount > 0 {
+        Ok(amount)
+    } else {
+        Err(format_err!("invalid_amount"))
+    }
+}
+
+fn resource_code(resource: i32) -> Result<i32> {
+    match TronResource::from_i32(resource) {
+        Some(TronResource::Bandwidth) => Ok(ResourceCode::Bandwidth as i32),
+        Some(TronResource::Energy) => Ok(ResourceCode::Energy as i32),
+        None => Err(format_err!("invalid_resource")),
+    }
+}
+
+/// A decimal or 0x prefixed hex string as the 32 bytes big endian uint256
+fn parse_uint256(value: &str) -> Result<[u8; 32]> {
+    let number = if value.starts_with("0x") || value.starts_with("0X") {
+        BigUint::from_str_radix(&value[2..], 16)
+    } else {
+        BigUint::from_str_radix(value, 10)
+    }
+    .map_err(|_| format_err!("invalid_amount"))?;
+    let bytes = number.to_bytes_be();
+    if bytes.len() > 32 {
+        return Err(format_err!("invalid_amount"));
+    }
+    let mut word = [0u8; 32];
+    word[32 - bytes.len()..].copy_from_slice(&bytes);
+    Ok(word)
+}
+
+/// ABI encoded `method(address,uint256)` calldata, the address drops its 0x41 prefix
+fn trc20_call_data(selector: &[u8; 4], address: &str, amount: &str) -> Result<Vec<u8>> {
+    let mut data = selector.to_vec();
+    data.extend_from_slice(&[0u8; 12]);
+    data.extend_from_slice(&decode_address(address)?[1..]);
+    data.extend_from_slice(&parse_uint256(amount)?);
+    Ok(data)
+}
+
+fn trigger_contract(
+    owner: &str,
+    contract_address: &str,
+    data: Vec<u8>,
+) -> Result<TriggerSmartContract> {
+    Ok(TriggerSmartContract {
+        owner_address: decode_address(owner)?,
+        contract_address: decode_address(contract_address)?,
+        data,
+        ..TriggerSmartContract::default()
+    })
+}
+
+fn build_contract(input: &tron_tx_build_input::Contract) -> Result<Contract> {
+    use tron_tx_build_input::Contract as Input;
+
+    match input {
+        Input::Transfer(x) => contract(
+            ContractType::TransferContract,
+            "TransferContract",
+            &TransferContract {
+                owner_address: decode_address(&x.owner)?,
+                to_address: decode_address(&x.to)?,
+                amount: check_amount(x.amount)?,
+            },
+        ),
+        Input::AssetTransfer(x) => {
+            if x.asset_name.is_empty() {
+                return Err(format_err!("invalid_asset_name"));
+            }
+            contract(
+                ContractType::TransferAssetContract,
+                "TransferAssetContract",
+                &TransferAssetContract {
+                    asset_name: x.asset_name.as_bytes().to_vec(),
+                    owner_address: decode_address(&x.owner)?,
+                    to_address: decode_address(&x.to)?,
+                    amount: check_amount(x.amount)?,
+                },
+            )
+        }
+        Input::Trc20Transfer(x) => contract(
+            ContractType::TriggerSmartContract,
+            "TriggerSmartContract",
+            &trigger_contract(
+                &x.owner,
+                &x.contract,
+                trc20_call_data(&TRC20_TRANSFER_SELECTOR, &x.to, &x.amount)?,
+            )?,
+        ),
+        Input::Trc20Approve(x) => contract(
+            ContractType::TriggerSmartContract,
+            "TriggerSmartContract",
+            &trigger_contract(
+                &x.owner,
+                &x.contract,
+                trc20_call_data(&TRC20_APPROVE_SELECTOR, &x.spender, &x.amount)?,
+            )?,
+        ),
+        Input::FreezeBalance(x) => contract(
+            ContractType::FreezeBalanceV2Contract,
+            "FreezeBalanceV2Contract",
+            &FreezeBalanceV2Contract {
+                owner_address: decode_address(&x.owner)?,
+                frozen_balance: check_amount(x.amount)?,
+                resource: resource_code(x.resource)?,
+            },
+        ),
+        Input::UnfreezeBalance(x) => contract(
+            ContractType::UnfreezeBalanceV2Contract,
+            "UnfreezeBalanceV2Contract",
+            &UnfreezeBalanceV2Contract {
+                owner_address: decode_address(&x.owner)?,
+                unfreeze_balance: check_amount(x.amount)?,
+                resource: resource_code(x.resource)?,
+            },
+        ),
+        Input::VoteWitness(x) => {
+            if x.votes.is_empty() {
+                return Err(format_err!("empty_votes"));
+            }
+            let votes = x
+                .votes
+                .iter()
+                .map(|vote| {
+                    Ok(Vote {
+                        vote_address: decode_address(&vote.witness)?,
+                        vote_count: check_amount(vote.count)?,
+                    })
+                })
+                .collect::<Result<Vec<Vote>>>()?;
+            contract(
+                ContractType::VoteWitnessContract,
+                "VoteWitnessContract",
+                &VoteWitnessContract {
+                    owner_address: decode_address(&x.owner)?,
+                    votes,
+                    support: false,
+                },
+            )
+        }
+    }
+}
+
+/// Builds the raw data of a transaction with the single contract of `input`
+pub fn build_transaction(input: &TronTxBuildInput) -> Result<TronTxInput> {
+    let ref_block_bytes = hex::decode(&input.ref_block_bytes)
+        .ok()
+        .filter(|x| x.len() == 2)
+        .ok_or_else(|| format_err!("invalid_ref_block"))?;
+    let ref_block_hash = hex::decode(&input.ref_block_hash)
+        .ok()
+        .filter(|x| x.len() == 8)
+        .ok_or_else(|| format_err!("invalid_ref_block"))?;
+    if input.expiration <= input.timestamp || input.timestamp < 0 {
+        return Err(format_err!("invalid_expiration"));
+    }
+    if input.fee_limit < 0 {
+        return Err(format_err!("invalid_fee_limit"));
+    }
+    if input.permission_id < 0 {
+        return Err(format_err!("invalid_permission_id"));
+    }
+    let contract = input
+        .contract
+        .as_ref()
+        .ok_or_else(|| format_err!("empty_tron_contract"))?;
+
+    let raw = TransactionRaw {
+        ref_block_bytes,
+        ref_block_hash,
+        expiration: input.expiration,
+        data: input.memo.as_bytes().to_vec(),
+        contract: vec![Contract {
+            permission_id: input.permission_id,
+            ..build_contract(contract)?
+        }],
+        timestamp: input.timestamp,
+        fee_limit: input.fee_limit,
+        ..TransactionRaw::default()
+    };
+    let mut raw_data = vec![];
+    raw.encode(&mut raw_data)?;
+    Ok(TronTxInput {
+        raw_data: hex::encode(raw_data),
+        signatures: vec![],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::build_transaction;
+    use crate::transaction::tron_tx_build_input::Contract;
+    use crate::transaction::{
+        Trc20Approve, Trc20Transfer, TronAssetTransfer, TronFreezeBalance, TronResource,
+        TronTransfer, TronTxBuildInput, TronUnfreezeBalance, TronVote, TronVoteWitness,
+    };
+
+    const OWNER: &str = "TY2uroBeZ5trA9QT96aEWj32XLkAAhQ9R2";
+    const USDT: &str = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t";
+
+    fn build_input(contract: Contract) -> TronTxBuildInput {
+        TronTxBuildInput {
+            ref_block_bytes: "8a45".to_string(),
+            ref_block_hash: "7f2ad38c2ea3f0b1".to_string(),
+            expiration: 1700000060000,
+            contract: Some(contract),
+            ..TronTxBuildInput::default()
+        }
+    }
+
+    #[test]
+    fn test_build_transfer() {
+        let input = TronTxBuildInput {
+            ref_block_bytes: "0831".to_string(),
+            ref_block_hash: "b02efdc02638b61e".to_string(),
+            expiration: 1565866902000,
+            timestamp: 1565866844064,
+            contract: Some(Contract::Transfer(TronTransfer {
+                owner: "TQjHpeTEscirkvpjxGDCYu7xS2yeD9U9VB".to_string(),
+                to: "410b3e84ec677b3e63c99affcadb91a6b4e086798f".to_string(),
+                amount: 100,
+            })),
+            ..TronTxBuildInput::default()
+        };
+        assert_eq!(build_transaction(&input).unwrap().raw_data, "0a0208312208b02efdc02638b61e40f083c3a7c92d5a65080112610a2d747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e5472616e73666572436f6e747261637412300a1541a1e81654258bf14f63feb2e8d1380075d45b0dac1215410b3e84ec677b3e63c99affcadb91a6b4e086798f186470a0bfbfa7c92d");
+    }
+
+    #[test]
+    fn test_build_with_permission_id() {
+        let input = TronTxBuildInput {
+            timestamp: 1700000000000,
+            permission_id: 2,
+            ..build_input(Contract::Transfer(TronTransfer {
+                owner: "TQjHpeTEscirkvpjxGDCYu7xS2yeD9U9VB".to_string(),
+                to: "TAzfL5gZtmuhNs2VaS1PmmGj4tZST9ToXU".to_string(),
+                amount: 1000000,
+            }))
+        };
+        assert_eq!(build_transaction(&input).unwrap().raw_data, "0a028a4522087f2ad38c2ea3f0b140e0a499ffbc315a69080112630a2d747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e5472616e73666572436f6e747261637412320a1541a1e81654258bf14f63feb2e8d1380075d45b0dac1215410b3e84ec677b3e63c99affcadb91a6b4e086798f18c0843d28027080d095ffbc31");
+    }
+
+    #[test]
+    fn test_build_token_transactions() {
+        let input = TronTxBuildInput {
+            timestamp: 1700000000000,
+            fee_limit: 30000000,
+            memo: "invoice-42".to_string(),
+            ..build_input(Contract::Trc20Transfer(Trc20Transfer {
+                owner: OWNER.to_string(),
+                contract: USDT.to_string(),
+                to: "TQjHpeTEscirkvpjxGDCYu7xS2yeD9U9VB".to_string(),
+                amount: "1000000".to_string(),
+            }))
+        };
+        assert_eq!(build_transaction(&input).unwrap().raw_data, "0a028a4522087f2ad38c2ea3f0b140e0a499ffbc31520a696e766f6963652d34325aae01081f12a9010a31747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e54726967676572536d617274436f6e747261637412740a1541f2061f0d467c6b3341713a7362cb39f5a88cefc8121541a614f803b6fd780986a42c78ec9c7f77e6ded13c2244a9059cbb000000000000000000000000a1e81654258bf14f63feb2e8d1380075d45b0dac00000000000000000000000000000000000000000000000000000000000f42407080d095ffbc3190018087a70e");
+
+        let input = TronTxBuildInput {
+            timestamp: 1700000000000,
+            fee_limit: 30000000,
+            ..build_input(Contract::Trc20Approve(Trc20Approve {
+                owner: OWNER.to_string(),
+                contract: USDT.to_string(),
+                spender: "TAzfL5gZtmuhNs2VaS1PmmGj4tZST9ToXU".to_string(),
+                amount: "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+                    .to_string(),
+            }))
+        };
+        assert_eq!(build_transaction(&input).unwrap().raw_data, "0a028a4522087f2ad38c2ea3f0b140e0a499ffbc315aae01081f12a9010a31747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e54726967676572536d617274436f6e747261637412740a1541f2061f0d467c6b3341713a7362cb39f5a88cefc8121541a614f803b6fd780986a42c78ec9c7f77e6ded13c2244095ea7b30000000000000000000000000b3e84ec677b3e63c99affcadb91a6b4e086798fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7080d095ffbc3190018087a70e");
+
+        let input = build_input(Contract::AssetTransfer(TronAssetTransfer {
+            owner: OWNER.to_string(),
+            to: "TQjHpeTEscirkvpjxGDCYu7xS2yeD9U9VB".to_string(),
+            asset_name: "1002000".to_string(),
+            amount: 5,
+        }));
+        assert_eq!(build_transaction(&input).unwrap().raw_data, "0a028a4522087f2ad38c2ea3f0b140e0a499ffbc315a730802126f0a32747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e5472616e736665724173736574436f6e747261637412390a0731303032303030121541f2061f0d467c6b3341713a7362cb39f5a88cefc81a1541a1e81654258bf14f63feb2e8d1380075d45b0dac2005");
+    }
+
+    #[test]
+    fn test_build_staking_transactions() {
+        let input = build_input(Contract::FreezeBalance(TronFreezeBalance {
+            owner: OWNER.to_string(),
+            amount: 1000000000,
+            resource: TronResource::Energy as i32,
+        }));
+        assert_eq!(build_transaction(&input).unwrap().raw_data, "0a028a4522087f2ad38c2ea3f0b140e0a499ffbc315a5b083612570a34747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e467265657a6542616c616e63655632436f6e7472616374121f0a1541f2061f0d467c6b3341713a7362cb39f5a88cefc8108094ebdc031801");
+
+        let input = build_input(Contract::UnfreezeBalance(TronUnfreezeBalance {
+            owner: OWNER.to_string(),
+            amount: 500000000,
+            resource: TronResource::Bandwidth as i32,
+        }));
+        assert_eq!(build_transaction(&input).unwrap().raw_data, "0a028a4522087f2ad38c2ea3f0b140e0a499ffbc315a5b083712570a36747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e556e667265657a6542616c616e63655632436f6e7472616374121d0a1541f2061f0d467c6b3341713a7362cb39f5a88cefc81080cab5ee01");
+
+        let input = build_input(Contract::VoteWitness(TronVoteWitness {
+            owner: OWNER.to_string(),
+            votes: vec![
+                TronVote {
+                    witness: "TQjHpeTEscirkvpjxGDCYu7xS2yeD9U9VB".to_string(),
+                    count: 10,
+                },
+                TronVote {
+                    witness: "TAzfL5gZtmuhNs2VaS1PmmGj4tZST9ToXU".to_string(),
+                    count: 5,
+                },
+            ],
+        }));
+        assert_eq!(build_transaction(&input).unwrap().raw_data, "0a028a4522087f2ad38c2ea3f0b140e0a499ffbc315a860108041281010a30747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e566f74655769746e657373436f6e7472616374124d0a1541f2061f0d467c6b3341713a7362cb39f5a88cefc812190a1541a1e81654258bf14f63feb2e8d1380075d45b0dac100a12190a15410b3e84ec677b3e63c99affcadb91a6b4e086798f1005");
+    }
+
+    #[test]
+    fn test_build_invalid_input() {
+        let transfer = Contract::Transfer(TronTransfer {
+            owner: OWNER.to_string(),
+            to: USDT.to_string(),
+            amount: 1,
+        });
+        let cases = vec![
+            (
+                TronTxBuildInput {
+                    ref_block_hash: "7f2ad38c2ea3f0".to_string(),
+                    ..build_input(transfer.clone())
+                },
+                "invalid_ref_block",
+            ),
+            (
+                TronTxBuildInput {
+                    timestamp: 1700000060000,
+                    ..build_input(transfer.clone())
+                },
+                "invalid_expiration",
+            ),
+            (
+                TronTxBuildInput {
+                    contract: None,
+                    ..build_input(transfer)
+                },
+                "empty_tron_contract",
+            ),
+            (
+                build_input(Contract::Transfer(TronTransfer {
+                    owner: OWNER.to_string(),
+                    to: USDT.to_string(),
+                    amount: 0,
+                })),
+                "invalid_amount",
+            ),
+            (
+                build_input(Contract::Trc20Transfer(Trc20Transfer {
+                    owner: OWNER.to_string(),
+                    contract: USDT.to_string(),
+                    to: "0xa1e81654258bf14f63feb2e8d1380075d45b0dac".to_string(),
+                    amount: "1".to_string(),
+                })),
+                "invalid_tron_address",
+            ),
+            (
+                build_input(Contract::Trc20Transfer(Trc20Transfer {
+                    owner: OWNER.to_string(),
+                    contract: USDT.to_string(),
+                    to: OWNER.to_string(),
+                    amount: "-1".to_string(),
+                })),
+                "invalid_amount",
+            ),
+            (
+                build_input(Contract::VoteWitness(TronVoteWitness {
+                    owner: OWNER.to_string(),
+                    votes: vec![],
+                })),
+                "empty_votes",
+            ),
+        ];
+        for (input, err) in cases {
+            assert_eq!(format!("{}", build_transaction(&input).unwrap_err()), err);
+        }
+    }
+}
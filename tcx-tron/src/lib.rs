@@ -1,8 +1,14 @@
 pub mod address;
+pub mod builder;
+pub mod permission;
+pub mod protocol;
 pub mod signer;
+pub mod tip712;
 pub mod transaction;
 
 pub use crate::address::Address as TrxAddress;
+pub use crate::builder::build_transaction;
+pub use crate::permission::check_permission;
 
 use digest::Digest;
 
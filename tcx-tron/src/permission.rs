@@ -0,0 +1,159 @@
+use crate::address::decode_address;
+use crate::keccak;
+use crate::protocol::TransactionRaw;
+use crate::transaction::{TronPermissionCheckInput, TronPermissionCheckOutput};
+use tcx_chain::Result;
+
+use bitcoin::util::base58;
+use bitcoin_hashes::sha256::Hash;
+use bitcoin_hashes::Hash as TraitHash;
+use failure::format_err;
+use prost::Message;
+use secp256k1::recovery::{RecoverableSignature, RecoveryId};
+use secp256k1::Secp256k1;
+
+/// Recovers the 21 bytes address which produced the 65 bytes `signature` over `hash`,
+/// the recovery id is accepted both as 0/1 and 27/28
+pub fn recover_address(hash: &[u8], signature: &[u8]) -> Result<Vec<u8>> {
+    if signature.len() != 65 {
+        return Err(format_err!("invalid_signature"));
+    }
+    let v = signature[64] as i32;
+    let recovery_id = RecoveryId::from_i32(if v >= 27 { v - 27 } else { v })
+        .map_err(|_| format_err!("invalid_signature"))?;
+    let sig = RecoverableSignature::from_compact(&signature[..64], recovery_id)
+        .map_err(|_| format_err!("invalid_signature"))?;
+    let msg = secp256k1::Message::from_slice(hash).map_err(|_| format_err!("invalid_signature"))?;
+    let public_key = Secp256k1::verification_only()
+        .recover(&msg, &sig)
+        .map_err(|_| format_err!("invalid_signature"))?;
+    let hash = keccak(&public_key.serialize_uncompressed()[1..]);
+    Ok([vec![0x41], hash[12..32].to_vec()].concat())
+}
+
+/// Sums the weights of the permission keys which signed the transaction, every signature
+/// must come from a distinct key of the permission
+pub fn check_permission(input: &TronPermissionCheckInput) -> Result<TronPermissionCheckOutput> {
+    let raw_data = hex::decode(&input.raw_data)?;
+    let raw = TransactionRaw::decode(raw_data.as_slice())
+        .map_err(|_| format_err!("invalid_tron_raw_data"))?;
+    let permission = input
+        .permission
+        .as_ref()
+        .ok_or_else(|| format_err!("empty_tron_permission"))?;
+    if permission.threshold <= 0 {
+        return Err(format_err!("invalid_permission_threshold"));
+    }
+    if raw
+        .contract
+        .iter()
+        .any(|x| x.permission_id != permission.id)
+    {
+        return Err(format_err!("tron_permission_id_mismatch"));
+    }
+    let keys = permission
+        .keys
+        .iter()
+        .map(|key| Ok((decode_address(&key.address)?, key.weight)))
+        .collect::<Result<Vec<(Vec<u8>, i64)>>>()?;
+
+    let hash = Hash::hash(&raw_data);
+    let mut signers: Vec<Vec<u8>> = vec![];
+    let mut weight: i64 = 0;
+    for signature in &input.signatures {
+        let signer = recover_address(&hash[..], &hex::decode(signature)?)?;
+        if signers.contains(&signer) {
+            return Err(format_err!("tron_duplicate_signer"));
+        }
+        let (_, key_weight) = keys
+            .iter()
+            .find(|(address, _)| *address == signer)
+            .ok_or_else(|| format_err!("tron_signer_not_in_permission"))?;
+        weight = weight.saturating_add(*key_weight);
+        signers.push(signer);
+    }
+
+    Ok(TronPermissionCheckOutput {
+        weight,
+        approved: weight >= permission.threshold,
+        signers: signers
+            .iter()
+            .map(|x| base58::check_encode_slice(x))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_permission;
+    use crate::transaction::{TronPermission, TronPermissionCheckInput, TronPermissionKey};
+
+    const RAW_DATA: &str = "0a028a4522087f2ad38c2ea3f0b140e0a499ffbc315a69080112630a2d747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e5472616e73666572436f6e747261637412320a1541a1e81654258bf14f63feb2e8d1380075d45b0dac1215410b3e84ec677b3e63c99affcadb91a6b4e086798f18c0843d28027080d095ffbc31";
+    const SIGNATURE_1: &str = "da87327dd913291052b2bfeef0addb6e0f3f9ebcb7e1ae2cfaa97fd5323c2df73128c8cf37e0b701c6bf6b3dfa05bc1b7108df33a2eb8cbd23f31799b67e70de00";
+    const SIGNATURE_2: &str = "296b7a76362d3f6388725e1bfd714d671f0bc49c32567349aa52d31937d596327347b2dec2164967e7ef3bb4590a0a084214f66110c5ee5d521c1b6f460483df01";
+
+    fn check_input(signatures: &[&str], threshold: i64) -> TronPermissionCheckInput {
+        TronPermissionCheckInput {
+            raw_data: RAW_DATA.to_string(),
+            signatures: signatures.iter().map(|x| x.to_string()).collect(),
+            permission: Some(TronPermission {
+                id: 2,
+                threshold,
+                keys: vec![
+                    TronPermissionKey {
+                        address: "TY2uroBeZ5trA9QT96aEWj32XLkAAhQ9R2".to_string(),
+                        weight: 1,
+                    },
+                    TronPermissionKey {
+                        address: "TLZnqkrSNLUWNrZMug8u9b6pJ3XcTGbzDV".to_string(),
+                        weight: 2,
+                    },
+                ],
+            }),
+        }
+    }
+
+    #[test]
+    fn test_check_permission() {
+        let output = check_permission(&check_input(&[SIGNATURE_1], 3)).unwrap();
+        assert_eq!(output.weight, 1);
+        assert!(!output.approved);
+        assert_eq!(output.signers, vec!["TY2uroBeZ5trA9QT96aEWj32XLkAAhQ9R2"]);
+
+        let output = check_permission(&check_input(&[SIGNATURE_1, SIGNATURE_2], 3)).unwrap();
+        assert_eq!(output.weight, 3);
+        assert!(output.approved);
+        assert_eq!(
+            output.signers,
+            vec![
+                "TY2uroBeZ5trA9QT96aEWj32XLkAAhQ9R2",
+                "TLZnqkrSNLUWNrZMug8u9b6pJ3XcTGbzDV"
+            ]
+        );
+
+        // the recovery id of signatures from other wallets can be 27/28
+        let mut signature = hex::decode(SIGNATURE_2).unwrap();
+        signature[64] += 27;
+        let output = check_permission(&check_input(&[&hex::encode(signature)], 2)).unwrap();
+        assert!(output.approved);
+    }
+
+    #[test]
+    fn test_check_permission_invalid() {
+        let err = check_permission(&check_input(&[SIGNATURE_1, SIGNATURE_1], 1)).unwrap_err();
+        assert_eq!(format!("{}", err), "tron_duplicate_signer");
+
+        let mut input = check_input(&[SIGNATURE_1], 1);
+        input.permission.as_mut().unwrap().keys.remove(0);
+        let err = check_permission(&input).unwrap_err();
+        assert_eq!(format!("{}", err), "tron_signer_not_in_permission");
+
+        let mut input = check_input(&[SIGNATURE_1], 1);
+        input.permission.as_mut().unwrap().id = 0;
+        let err = check_permission(&input).unwrap_err();
+        assert_eq!(format!("{}", err), "tron_permission_id_mismatch");
+
+        let err = check_permission(&check_input(&[&SIGNATURE_1[..128]], 1)).unwrap_err();
+        assert_eq!(format!("{}", err), "invalid_signature");
+    }
+}
@@ -0,0 +1,138 @@
+// Messages of the tron protocol used to build the raw data of a transaction.
+// ref: https://github.com/tronprotocol/protocol/blob/master/core/Tron.proto
+// ref: https://github.com/tronprotocol/protocol/tree/master/core/contract
+
+/// google.protobuf.Any
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Any {
+    #[prost(string, tag = "1")]
+    pub type_url: std::string::String,
+    #[prost(bytes, tag = "2")]
+    pub value: std::vec::Vec<u8>,
+}
+/// Transaction.raw, txID is sha256 of its encoded bytes
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TransactionRaw {
+    #[prost(bytes, tag = "1")]
+    pub ref_block_bytes: std::vec::Vec<u8>,
+    #[prost(int64, tag = "3")]
+    pub ref_block_num: i64,
+    #[prost(bytes, tag = "4")]
+    pub ref_block_hash: std::vec::Vec<u8>,
+    #[prost(int64, tag = "8")]
+    pub expiration: i64,
+    /// memo of the transaction
+    #[prost(bytes, tag = "10")]
+    pub data: std::vec::Vec<u8>,
+    /// only support size = 1, repeated list here for extension
+    #[prost(message, repeated, tag = "11")]
+    pub contract: ::std::vec::Vec<Contract>,
+    #[prost(int64, tag = "14")]
+    pub timestamp: i64,
+    #[prost(int64, tag = "18")]
+    pub fee_limit: i64,
+}
+/// Transaction.Contract
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Contract {
+    #[prost(enumeration = "ContractType", tag = "1")]
+    pub r#type: i32,
+    #[prost(message, optional, tag = "2")]
+    pub parameter: ::std::option::Option<Any>,
+    #[prost(bytes, tag = "3")]
+    pub provider: std::vec::Vec<u8>,
+    #[prost(bytes, tag = "4")]
+    pub contract_name: std::vec::Vec<u8>,
+    #[prost(int32, tag = "5")]
+    pub permission_id: i32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TransferContract {
+    #[prost(bytes, tag = "1")]
+    pub owner_address: std::vec::Vec<u8>,
+    #[prost(bytes, tag = "2")]
+    pub to_address: std::vec::Vec<u8>,
+    #[prost(int64, tag = "3")]
+    pub amount: i64,
+}
+/// TRC-10 transfer, `asset_name` is the token id
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TransferAssetContract {
+    #[prost(bytes, tag = "1")]
+    pub asset_name: std::vec::Vec<u8>,
+    #[prost(bytes, tag = "2")]
+    pub owner_address: std::vec::Vec<u8>,
+    #[prost(bytes, tag = "3")]
+    pub to_address: std::vec::Vec<u8>,
+    #[prost(int64, tag = "4")]
+    pub amount: i64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VoteWitnessContract {
+    #[prost(bytes, tag = "1")]
+    pub owner_address: std::vec::Vec<u8>,
+    #[prost(message, repeated, tag = "2")]
+    pub votes: ::std::vec::Vec<Vote>,
+    #[prost(bool, tag = "3")]
+    pub support: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Vote {
+    #[prost(bytes, tag = "1")]
+    pub vote_address: std::vec::Vec<u8>,
+    #[prost(int64, tag = "2")]
+    pub vote_count: i64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TriggerSmartContract {
+    #[prost(bytes, tag = "1")]
+    pub owner_address: std::vec::Vec<u8>,
+    #[prost(bytes, tag = "2")]
+    pub contract_address: std::vec::Vec<u8>,
+    #[prost(int64, tag = "3")]
+    pub call_value: i64,
+    #[prost(bytes, tag = "4")]
+    pub data: std::vec::Vec<u8>,
+    #[prost(int64, tag = "5")]
+    pub call_token_value: i64,
+    #[prost(int64, tag = "6")]
+    pub token_id: i64,
+}
+/// Stake 2.0 freeze
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FreezeBalanceV2Contract {
+    #[prost(bytes, tag = "1")]
+    pub owner_address: std::vec::Vec<u8>,
+    #[prost(int64, tag = "2")]
+    pub frozen_balance: i64,
+    #[prost(enumeration = "ResourceCode", tag = "3")]
+    pub resource: i32,
+}
+/// Stake 2.0 unfreeze
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnfreezeBalanceV2Contract {
+    #[prost(bytes, tag = "1")]
+    pub owner_address: std::vec::Vec<u8>,
+    #[prost(int64, tag = "2")]
+    pub unfreeze_balance: i64,
+    #[prost(enumeration = "ResourceCode", tag = "3")]
+    pub resource: i32,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ContractType {
+    AccountCreateContract = 0,
+    TransferContract = 1,
+    TransferAssetContract = 2,
+    VoteWitnessContract = 4,
+    TriggerSmartContract = 31,
+    FreezeBalanceV2Contract = 54,
+    UnfreezeBalanceV2Contract = 55,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ResourceCode {
+    Bandwidth = 0,
+    Energy = 1,
+    TronPower = 2,
+}
@@ -1,4 +1,7 @@
-use crate::transaction::{TronMessageInput, TronMessageOutput, TronTxInput, TronTxOutput};
+use crate::tip712::typed_data_hash;
+use crate::transaction::{
+    TronMessageInput, TronMessageOutput, TronTxInput, TronTxOutput, TronTypedDataInput,
+};
 use tcx_chain::{
     ChainSigner, Keystore, MessageSigner as TraitMessageSigner, Result,
     TransactionSigner as TraitTransactionSigner,
@@ -9,7 +12,9 @@ use bitcoin_hashes::Hash as TraitHash;
 
 use failure::format_err;
 
+use crate::address::decode_address;
 use crate::keccak;
+use crate::permission::recover_address;
 
 // http://jsoneditoronline.org/index.html?id=2b86a8503ba641bebed73f32b4ac9c42
 //{
@@ -52,12 +57,25 @@ impl TraitTransactionSigner<TronTxInput, TronTxOutput> for Keystore {
         let data = hex::decode(&tx.raw_data)?;
         let hash = Hash::hash(&data);
 
+        // a multi-signature transaction collects one signature from every key
+        let signer = decode_address(address)?;
+        for signature in &tx.signatures {
+            if recover_address(&hash[..], &hex::decode(signature)?)? == signer {
+                return Err(format_err!("tron_tx_already_signed"));
+            }
+        }
+
         let sign_result = self.sign_recoverable_hash(&hash[..], symbol, address, None);
 
         match sign_result {
-            Ok(r) => Ok(TronTxOutput {
-                signatures: vec![hex::encode(r)],
-            }),
+            Ok(r) => {
+                let mut signatures = tx.signatures.clone();
+                signatures.push(hex::encode(r));
+                Ok(TronTxOutput {
+                    signatures,
+                    tx_hash: hex::encode(&hash[..]),
+                })
+            }
             Err(_e) => Err(format_err!("{}", "can not format error")),
         }
     }
@@ -95,6 +113,22 @@ impl TraitMessageSigner<TronMessageInput, TronMessageOutput> for Keystore {
     }
 }
 
+impl TraitMessageSigner<TronTypedDataInput, TronMessageOutput> for Keystore {
+    fn sign_message(
+        &mut self,
+        symbol: &str,
+        address: &str,
+        message: &TronTypedDataInput,
+    ) -> Result<TronMessageOutput> {
+        let hash = typed_data_hash(&message.data)?;
+        let mut sign_result = self.sign_recoverable_hash(&hash, symbol, address, None)?;
+        sign_result[64] += 27;
+        Ok(TronMessageOutput {
+            signature: hex::encode(sign_result),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -136,7 +170,8 @@ mod tests {
         */
 
         let tx = TronTxInput {
-            raw_data: "0a0208312208b02efdc02638b61e40f083c3a7c92d5a65080112610a2d747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e5472616e73666572436f6e747261637412300a1541a1e81654258bf14f63feb2e8d1380075d45b0dac1215410b3e84ec677b3e63c99affcadb91a6b4e086798f186470a0bfbfa7c92d".to_string()
+            raw_data: "0a0208312208b02efdc02638b61e40f083c3a7c92d5a65080112610a2d747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e5472616e73666572436f6e747261637412300a1541a1e81654258bf14f63feb2e8d1380075d45b0dac1215410b3e84ec677b3e63c99affcadb91a6b4e086798f186470a0bfbfa7c92d".to_string(),
+            signatures: vec![],
         };
 
         let meta = Metadata::default();
@@ -160,10 +195,54 @@ mod tests {
         let signed_tx: TronTxOutput = ks.sign_transaction("TRON", &account.address, &tx)?;
 
         assert_eq!(signed_tx.signatures[0], "beac4045c3ea5136b541a3d5ec2a3e5836d94f28a1371440a01258808612bc161b5417e6f5a342451303cda840f7e21bfaba1011fad5f63538cb8cc132a9768800");
+        assert_eq!(
+            signed_tx.tx_hash,
+            "dc74fc99076e7638067753c5c9c3aa61f9ce208707ef6940e4ab8a4944b5d69f"
+        );
 
         Ok(())
     }
 
+    #[test]
+    fn sign_multi_signature_transaction() {
+        let signature_of_other_key = "296b7a76362d3f6388725e1bfd714d671f0bc49c32567349aa52d31937d596327347b2dec2164967e7ef3bb4590a0a084214f66110c5ee5d521c1b6f460483df01";
+        let tx = TronTxInput {
+            raw_data: "0a028a4522087f2ad38c2ea3f0b140e0a499ffbc315a69080112630a2d747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e5472616e73666572436f6e747261637412320a1541a1e81654258bf14f63feb2e8d1380075d45b0dac1215410b3e84ec677b3e63c99affcadb91a6b4e086798f18c0843d28027080d095ffbc31".to_string(),
+            signatures: vec![signature_of_other_key.to_string()],
+        };
+
+        let mut keystore = Keystore::Hd(
+            HdKeystore::from_mnemonic(&TEST_MNEMONIC, &TEST_PASSWORD, Metadata::default(), "")
+                .unwrap(),
+        );
+        let coin_info = CoinInfo {
+            coin: "TRON".to_string(),
+            derivation_path: "m/44'/195'/0'/0/0".to_string(),
+            curve: CurveType::SECP256k1,
+            network: "".to_string(),
+            seg_wit: "".to_string(),
+        };
+        let mut guard = KeystoreGuard::unlock_by_password(&mut keystore, TEST_PASSWORD).unwrap();
+        let ks = guard.keystore_mut();
+        let account = ks.derive_coin::<Address>(&coin_info).unwrap().clone();
+
+        let signed_tx: TronTxOutput = ks.sign_transaction("TRON", &account.address, &tx).unwrap();
+        assert_eq!(
+            signed_tx.signatures,
+            vec![
+                signature_of_other_key,
+                "da87327dd913291052b2bfeef0addb6e0f3f9ebcb7e1ae2cfaa97fd5323c2df73128c8cf37e0b701c6bf6b3dfa05bc1b7108df33a2eb8cbd23f31799b67e70de00"
+            ]
+        );
+
+        let tx = TronTxInput {
+            signatures: signed_tx.signatures,
+            ..tx
+        };
+        let ret: Result<TronTxOutput> = ks.sign_transaction("TRON", &account.address, &tx);
+        assert_eq!(format!("{}", ret.err().unwrap()), "tron_tx_already_signed");
+    }
+
     #[test]
     fn sign_message() {
         let sk =
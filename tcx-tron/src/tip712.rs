@@ -0,0 +1,143 @@
+use crate::address::decode_address;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use tcx_chain::Result;
+use tcx_ethereum::eip712::TypedDataField;
+use tcx_ethereum::TypedData;
+
+use failure::format_err;
+use serde_json::Value;
+
+const EIP712_DOMAIN: &str = "EIP712Domain";
+
+/// Rewrites the Tron addresses of `value` into the 20 bytes hex form of EIP-712
+fn normalize_addresses(
+    types: &BTreeMap<String, Vec<TypedDataField>>,
+    field_type: &str,
+    value: &mut Value,
+) -> Result<()> {
+    if field_type.ends_with(']') {
+        if let (Some(open), Value::Array(items)) = (field_type.rfind('['), value) {
+            for item in items {
+                normalize_addresses(types, &field_type[..open], item)?;
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(fields) = types.get(field_type) {
+        if let Value::Object(object) = value {
+            for field in fields {
+                if let Some(member) = object.get_mut(&field.name) {
+                    normalize_addresses(types, &field.field_type, member)?;
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    if field_type == "address" {
+        if let Value::String(address) = value {
+            if !address.starts_with("0x") {
+                let bytes = decode_address(address)
+                    .map_err(|_| format_err!("typed_data_invalid_value: address"))?;
+                *address = format!("0x{}", hex::encode(&bytes[1..]));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parses TIP-712 typed data, the EIP-712 payload with base58 or 41 prefixed hex
+/// addresses and the `trcToken` type
+pub fn parse_typed_data(data: &str) -> Result<TypedData> {
+    let mut typed_data = TypedData::from_str(data)?;
+    // the TIP-712 token id type, encoded as uint256
+    typed_data
+        .type_aliases
+        .insert("trcToken".to_string(), "uint256".to_string());
+    normalize_addresses(&typed_data.types, EIP712_DOMAIN, &mut typed_data.domain)?;
+    let primary_type = typed_data.primary_type.clone();
+    normalize_addresses(&typed_data.types, &primary_type, &mut typed_data.message)?;
+    Ok(typed_data)
+}
+
+/// `keccak256("\x19\x01" ‖ domainSeparator ‖ hashStruct(message))` of TIP-712 typed data
+pub fn typed_data_hash(data: &str) -> Result<Vec<u8>> {
+    parse_typed_data(data)?.sign_hash()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::typed_data_hash;
+
+    const MAIL_TYPED_DATA: &str = r#"{
+        "types": {
+            "EIP712Domain": [
+                {"name": "name", "type": "string"},
+                {"name": "version", "type": "string"},
+                {"name": "chainId", "type": "uint256"},
+                {"name": "verifyingContract", "type": "address"}
+            ],
+            "FromPerson": [
+                {"name": "name", "type": "string"},
+                {"name": "wallet", "type": "address"},
+                {"name": "trcTokenId", "type": "trcToken"}
+            ],
+            "ToPerson": [
+                {"name": "name", "type": "string"},
+                {"name": "wallet", "type": "address"},
+                {"name": "trcTokenArr", "type": "trcToken[]"}
+            ],
+            "Mail": [
+                {"name": "from", "type": "FromPerson"},
+                {"name": "to", "type": "ToPerson"},
+                {"name": "contents", "type": "string"},
+                {"name": "tAddr", "type": "address[]"},
+                {"name": "trcTokenId", "type": "trcToken"}
+            ]
+        },
+        "primaryType": "Mail",
+        "domain": {
+            "name": "TRON Mail",
+            "version": "1",
+            "chainId": "0x2b6653dc",
+            "verifyingContract": "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t"
+        },
+        "message": {
+            "from": {
+                "name": "Cow",
+                "wallet": "TY2uroBeZ5trA9QT96aEWj32XLkAAhQ9R2",
+                "trcTokenId": "1002000"
+            },
+            "to": {
+                "name": "Bob",
+                "wallet": "410b3e84ec677b3e63c99affcadb91a6b4e086798f",
+                "trcTokenArr": ["1002000", "1002001"]
+            },
+            "contents": "Hello, Bob!",
+            "tAddr": [
+                "TQjHpeTEscirkvpjxGDCYu7xS2yeD9U9VB",
+                "0xa614f803b6fd780986a42c78ec9c7f77e6ded13c"
+            ],
+            "trcTokenId": "1002000"
+        }
+    }"#;
+
+    #[test]
+    fn test_typed_data_hash() {
+        assert_eq!(
+            hex::encode(typed_data_hash(MAIL_TYPED_DATA).unwrap()),
+            "4f3c7cf8b56e1810924283c3a6ff0d068c56558ed5e0032afd9a71f12cf6d3c5"
+        );
+
+        let invalid = MAIL_TYPED_DATA.replace(
+            "TQjHpeTEscirkvpjxGDCYu7xS2yeD9U9VB",
+            "TQjHpeTEscirkvpjxGDCYu7xS2yeD9U9VC",
+        );
+        assert_eq!(
+            format!("{}", typed_data_hash(&invalid).unwrap_err()),
+            "typed_data_invalid_value: address"
+        );
+    }
+}
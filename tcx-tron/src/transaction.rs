@@ -7,12 +7,19 @@ pub struct TronTxInput {
     /// hex string
     #[prost(string, tag = "1")]
     pub raw_data: std::string::String,
+    /// hex strings, signatures of the other keys of a multi-signature permission,
+    /// the new signature is appended to them
+    #[prost(string, repeated, tag = "2")]
+    pub signatures: ::std::vec::Vec<std::string::String>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TronTxOutput {
     /// hex string
     #[prost(string, repeated, tag = "1")]
     pub signatures: ::std::vec::Vec<std::string::String>,
+    /// hex string, the txID which is sha256 of rawData
+    #[prost(string, tag = "2")]
+    pub tx_hash: std::string::String,
 }
 /// FUNCTION: tron_sign_message(SignParam): TronMessageOutput
 ///
@@ -32,3 +39,190 @@ pub struct TronMessageOutput {
     #[prost(string, tag = "1")]
     pub signature: std::string::String,
 }
+/// FUNCTION: tron_sign_typed_data(SignParam): TronMessageOutput
+///
+/// TIP-712 signing, `data` is the typed data JSON with types, primaryType, domain
+/// and message, addresses may be base58 or 41 prefixed hex strings
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TronTypedDataInput {
+    #[prost(string, tag = "1")]
+    pub data: std::string::String,
+}
+/// FUNCTION: tron_build_tx(TronTxBuildInput): TronTxInput
+///
+/// builds the rawData of a transaction with a single contract, the output
+/// can be signed by `sign_tx` as is. Addresses are base58 or 41 prefixed hex strings
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TronTxBuildInput {
+    /// hex string, bytes 6..8 of the reference block id
+    #[prost(string, tag = "1")]
+    pub ref_block_bytes: std::string::String,
+    /// hex string, bytes 8..16 of the reference block id
+    #[prost(string, tag = "2")]
+    pub ref_block_hash: std::string::String,
+    /// milliseconds, the transaction is dropped after it
+    #[prost(int64, tag = "3")]
+    pub expiration: i64,
+    /// milliseconds
+    #[prost(int64, tag = "4")]
+    pub timestamp: i64,
+    /// sun, the max energy fee paid by a smart contract call
+    #[prost(int64, tag = "5")]
+    pub fee_limit: i64,
+    #[prost(string, tag = "6")]
+    pub memo: std::string::String,
+    #[prost(
+        oneof = "tron_tx_build_input::Contract",
+        tags = "7, 8, 9, 10, 11, 12, 13"
+    )]
+    pub contract: ::std::option::Option<tron_tx_build_input::Contract>,
+    /// the account permission signing the transaction, 0 is the owner
+    /// permission and active permissions start from 2
+    #[prost(int32, tag = "14")]
+    pub permission_id: i32,
+}
+pub mod tron_tx_build_input {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Contract {
+        #[prost(message, tag = "7")]
+        Transfer(super::TronTransfer),
+        #[prost(message, tag = "8")]
+        AssetTransfer(super::TronAssetTransfer),
+        #[prost(message, tag = "9")]
+        Trc20Transfer(super::Trc20Transfer),
+        #[prost(message, tag = "10")]
+        Trc20Approve(super::Trc20Approve),
+        #[prost(message, tag = "11")]
+        FreezeBalance(super::TronFreezeBalance),
+        #[prost(message, tag = "12")]
+        UnfreezeBalance(super::TronUnfreezeBalance),
+        #[prost(message, tag = "13")]
+        VoteWitness(super::TronVoteWitness),
+    }
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TronTransfer {
+    #[prost(string, tag = "1")]
+    pub owner: std::string::String,
+    #[prost(string, tag = "2")]
+    pub to: std::string::String,
+    #[prost(int64, tag = "3")]
+    pub amount: i64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TronAssetTransfer {
+    #[prost(string, tag = "1")]
+    pub owner: std::string::String,
+    #[prost(string, tag = "2")]
+    pub to: std::string::String,
+    /// the token id, like "1002000"
+    #[prost(string, tag = "3")]
+    pub asset_name: std::string::String,
+    #[prost(int64, tag = "4")]
+    pub amount: i64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Trc20Transfer {
+    #[prost(string, tag = "1")]
+    pub owner: std::string::String,
+    #[prost(string, tag = "2")]
+    pub contract: std::string::String,
+    #[prost(string, tag = "3")]
+    pub to: std::string::String,
+    #[prost(string, tag = "4")]
+    pub amount: std::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Trc20Approve {
+    #[prost(string, tag = "1")]
+    pub owner: std::string::String,
+    #[prost(string, tag = "2")]
+    pub contract: std::string::String,
+    #[prost(string, tag = "3")]
+    pub spender: std::string::String,
+    #[prost(string, tag = "4")]
+    pub amount: std::string::String,
+}
+/// FreezeBalanceV2Contract of Stake 2.0
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TronFreezeBalance {
+    #[prost(string, tag = "1")]
+    pub owner: std::string::String,
+    #[prost(int64, tag = "2")]
+    pub amount: i64,
+    #[prost(enumeration = "TronResource", tag = "3")]
+    pub resource: i32,
+}
+/// UnfreezeBalanceV2Contract of Stake 2.0
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TronUnfreezeBalance {
+    #[prost(string, tag = "1")]
+    pub owner: std::string::String,
+    #[prost(int64, tag = "2")]
+    pub amount: i64,
+    #[prost(enumeration = "TronResource", tag = "3")]
+    pub resource: i32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TronVoteWitness {
+    #[prost(string, tag = "1")]
+    pub owner: std::string::String,
+    #[prost(message, repeated, tag = "2")]
+    pub votes: ::std::vec::Vec<TronVote>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TronVote {
+    #[prost(string, tag = "1")]
+    pub witness: std::string::String,
+    #[prost(int64, tag = "2")]
+    pub count: i64,
+}
+/// FUNCTION: tron_check_permission(TronPermissionCheckInput): TronPermissionCheckOutput
+///
+/// checks the signatures of a transaction against a permission of the owner account,
+/// the `owner_permission` or one of the `active_permission` returned by the node
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TronPermissionCheckInput {
+    /// hex string
+    #[prost(string, tag = "1")]
+    pub raw_data: std::string::String,
+    /// hex strings
+    #[prost(string, repeated, tag = "2")]
+    pub signatures: ::std::vec::Vec<std::string::String>,
+    #[prost(message, optional, tag = "3")]
+    pub permission: ::std::option::Option<TronPermission>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TronPermission {
+    #[prost(int32, tag = "1")]
+    pub id: i32,
+    #[prost(int64, tag = "2")]
+    pub threshold: i64,
+    #[prost(message, repeated, tag = "3")]
+    pub keys: ::std::vec::Vec<TronPermissionKey>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TronPermissionKey {
+    #[prost(string, tag = "1")]
+    pub address: std::string::String,
+    #[prost(int64, tag = "2")]
+    pub weight: i64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TronPermissionCheckOutput {
+    /// the sum of the weights of the signers
+    #[prost(int64, tag = "1")]
+    pub weight: i64,
+    /// whether the weight reaches the threshold of the permission
+    #[prost(bool, tag = "2")]
+    pub approved: bool,
+    /// base58 addresses recovered from the signatures
+    #[prost(string, repeated, tag = "3")]
+    pub signers: ::std::vec::Vec<std::string::String>,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum TronResource {
+    Bandwidth = 0,
+    Energy = 1,
+}
@@ -316,6 +316,13 @@ pub struct BtcForkDeriveExtraParam {
     #[prost(string, tag = "2")]
     pub seg_wit: std::string::String,
 }
+/// FUNCTION: hd_store_extended_public_key(HdStoreExtendedPublicKeyParam): HdStoreExtendedPublicKeyResponse
+///
+/// export the account extended public key in plain SLIP-132 format (xpub/ypub/zpub, Ltub/Mtub...)
+///
+/// FUNCTION: hd_store_extended_private_key(HdStoreExtendedPublicKeyParam): HdStoreExtendedPrivateKeyResponse
+///
+/// export the account extended private key in SLIP-132 format (xprv/yprv/zprv, Ltpv/Mtpv...)
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct HdStoreExtendedPublicKeyParam {
     #[prost(string, tag = "1")]
@@ -333,6 +340,11 @@ pub struct HdStoreExtendedPublicKeyResponse {
     pub extended_public_key: std::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HdStoreExtendedPrivateKeyResponse {
+    #[prost(string, tag = "1")]
+    pub extended_private_key: std::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PublicKeyParam {
     #[prost(string, tag = "1")]
     pub id: std::string::String,
@@ -352,6 +364,98 @@ pub struct PublicKeyResult {
     #[prost(string, tag = "4")]
     pub public_key: std::string::String,
 }
+/// Chain Registry
+
+/// FUNCTION: register_coin_info(CoinInfoParam) -> Response
+///
+/// register a coin info at runtime, the `chainType`, `network`, `segWit` and `curve` must be unique
+///
+/// FUNCTION: remove_coin_info(CoinInfoParam) -> Response
+///
+/// remove a coin info which was registered at runtime
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CoinInfoParam {
+    #[prost(string, tag = "1")]
+    pub chain_type: std::string::String,
+    #[prost(string, tag = "2")]
+    pub path: std::string::String,
+    #[prost(string, tag = "3")]
+    pub network: std::string::String,
+    #[prost(string, tag = "4")]
+    pub seg_wit: std::string::String,
+    #[prost(string, tag = "5")]
+    pub curve: std::string::String,
+}
+/// FUNCTION: list_coin_infos(ChainRegistryListParam): CoinInfosResult
+/// FUNCTION: list_btc_fork_networks(ChainRegistryListParam): BtcForkNetworksResult
+/// FUNCTION: list_evm_chain_infos(ChainRegistryListParam): EvmChainInfosResult
+///
+/// list the registered entries, only the ones registered at runtime when `customOnly` is true
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChainRegistryListParam {
+    #[prost(bool, tag = "1")]
+    pub custom_only: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CoinInfosResult {
+    #[prost(message, repeated, tag = "1")]
+    pub coin_infos: ::std::vec::Vec<CoinInfoParam>,
+}
+/// FUNCTION: register_btc_fork_network(BtcForkNetworkParam) -> Response
+///
+/// register a btc fork network at runtime, the `coin`, `network` and `segWit` must be unique.
+/// `xpubPrefix` and `xprvPrefix` are 4 bytes hex string
+///
+/// FUNCTION: remove_btc_fork_network(BtcForkNetworkParam) -> Response
+///
+/// remove a btc fork network which was registered at runtime
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BtcForkNetworkParam {
+    #[prost(string, tag = "1")]
+    pub coin: std::string::String,
+    #[prost(string, tag = "2")]
+    pub network: std::string::String,
+    #[prost(string, tag = "3")]
+    pub seg_wit: std::string::String,
+    #[prost(string, tag = "4")]
+    pub hrp: std::string::String,
+    #[prost(uint32, tag = "5")]
+    pub p2pkh_prefix: u32,
+    #[prost(uint32, tag = "6")]
+    pub p2sh_prefix: u32,
+    #[prost(uint32, tag = "7")]
+    pub private_prefix: u32,
+    #[prost(string, tag = "8")]
+    pub xpub_prefix: std::string::String,
+    #[prost(string, tag = "9")]
+    pub xprv_prefix: std::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BtcForkNetworksResult {
+    #[prost(message, repeated, tag = "1")]
+    pub networks: ::std::vec::Vec<BtcForkNetworkParam>,
+}
+/// FUNCTION: register_evm_chain_info(EvmChainInfoParam) -> Response
+///
+/// register an evm network at runtime, both the `network` and `chainId` must be unique
+///
+/// FUNCTION: remove_evm_chain_info(EvmChainInfoParam) -> Response
+///
+/// remove an evm network which was registered at runtime
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EvmChainInfoParam {
+    #[prost(string, tag = "1")]
+    pub network: std::string::String,
+    #[prost(int32, tag = "2")]
+    pub network_id: i32,
+    #[prost(uint64, tag = "3")]
+    pub chain_id: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EvmChainInfosResult {
+    #[prost(message, repeated, tag = "1")]
+    pub chain_infos: ::std::vec::Vec<EvmChainInfoParam>,
+}
 /// only support two types
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
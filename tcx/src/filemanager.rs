@@ -4,10 +4,29 @@ use std::io::Write;
 use std::path::Path;
 //use std::sync::RwLock;
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 use tcx_chain::Keystore;
+use tcx_constants::btc_fork_network::{clear_custom_btc_fork_networks, custom_btc_fork_networks};
+use tcx_constants::coin_info::{clear_custom_coin_infos, custom_coin_infos};
+use tcx_constants::{register_btc_fork_network, register_coin_info, BtcForkNetwork, CoinInfo};
+use tcx_ethereum::{clear_custom_chain_infos, custom_chain_infos, register_chain_info, ChainInfo};
 
 use crate::error_handling::Result;
 
+// keep the registry out of the top level of the wallet dir, every json file there is a keystore
+const CHAIN_REGISTRY_PATH: &str = "chain_registry/registry.json";
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChainRegistry {
+    #[serde(default)]
+    coin_infos: Vec<CoinInfo>,
+    #[serde(default)]
+    btc_fork_networks: Vec<BtcForkNetwork>,
+    #[serde(default)]
+    chain_infos: Vec<ChainInfo>,
+}
+
 lazy_static! {
     pub static ref KEYSTORE_MAP: RwLock<HashMap<String, Keystore>> = RwLock::new(HashMap::new());
     pub static ref WALLET_FILE_DIR: RwLock<String> = RwLock::new("../test-data".to_string());
@@ -42,3 +61,65 @@ pub fn delete_keystore_file(wid: &str) -> Result<()> {
     fs::remove_file(path)?;
     Ok(())
 }
+
+pub fn flush_chain_registry() -> Result<()> {
+    let registry = ChainRegistry {
+        coin_infos: custom_coin_infos(),
+        btc_fork_networks: custom_btc_fork_networks(),
+        chain_infos: custom_chain_infos(),
+    };
+
+    let file_dir = WALLET_FILE_DIR.read();
+    let registry_path = format!("{}/{}", file_dir, CHAIN_REGISTRY_PATH);
+    let path = Path::new(&registry_path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // write aside then rename, a crash while writing must not truncate the registry
+    let tmp_path = format!("{}.tmp", registry_path);
+    let mut file = fs::File::create(&tmp_path)?;
+    file.write_all(serde_json::to_string(&registry)?.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+pub fn load_chain_registry() -> Result<()> {
+    clear_custom_coin_infos();
+    clear_custom_btc_fork_networks();
+    clear_custom_chain_infos();
+
+    let file_dir = WALLET_FILE_DIR.read();
+    let registry_path = format!("{}/{}", file_dir, CHAIN_REGISTRY_PATH);
+    let path = Path::new(&registry_path);
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let contents = fs::read_to_string(path)?;
+    let registry: ChainRegistry = serde_json::from_str(&contents)?;
+    // the entries which cannot be registered again, e.g. they are built-in since they were
+    // registered, are skipped so the other ones stay usable
+    for coin_info in registry.coin_infos {
+        let desc = format!(
+            "{}/{}/{}",
+            coin_info.coin, coin_info.network, coin_info.seg_wit
+        );
+        if let Err(e) = register_coin_info(coin_info) {
+            log::warn!("skip the registered coin info {}: {}", desc, e);
+        }
+    }
+    for network in registry.btc_fork_networks {
+        let desc = format!("{}/{}/{}", network.coin, network.network, network.seg_wit);
+        if let Err(e) = register_btc_fork_network(network) {
+            log::warn!("skip the registered btc fork network {}: {}", desc, e);
+        }
+    }
+    for chain_info in registry.chain_infos {
+        let desc = format!("{}/{}", chain_info.network, chain_info.chain_id);
+        if let Err(e) = register_chain_info(chain_info) {
+            log::warn!("skip the registered chain info {}: {}", desc, e);
+        }
+    }
+    Ok(())
+}
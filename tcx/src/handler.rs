@@ -2,17 +2,26 @@ use failure::Error;
 use std::fs;
 use std::io::Read;
 use std::path::Path;
+use std::str::FromStr;
 
 use bytes::BytesMut;
 use prost::Message;
 use serde_json::Value;
-use tcx_primitive::{get_account_path, private_key_without_version, FromHex, TypedPrivateKey};
+use tcx_primitive::{
+    get_account_path, private_key_without_version, FromHex, TypedDeterministicPrivateKey,
+    TypedDeterministicPublicKey, TypedPrivateKey,
+};
 
 use tcx_aptos::{AptosAddress, AptosTxIn};
 use tcx_bch::{BchAddress, BchTransaction};
+use tcx_btc_fork::multisig::P2WSH;
 use tcx_btc_fork::{
-    BtcForkAddress, BtcForkSegWitTransaction, BtcForkSignedTxOutput, BtcForkTransaction,
-    BtcForkTxInput, WifDisplay,
+    BtcForkAddress, BtcForkDescriptorOutput, BtcForkExportDescriptorParam, BtcForkFeeBumpInput,
+    BtcForkImportDescriptorOutput, BtcForkImportDescriptorParam, BtcForkMultisigAddressParam,
+    BtcForkNativeSegWitTransaction, BtcForkSegWitTransaction, BtcForkSignedTxOutput,
+    BtcForkTaprootTransaction, BtcForkTransaction, BtcForkTxInput, BtcMessageInput,
+    BtcMessageOutput, BtcVerifyMessageInput, CoinSelectionInput, Descriptor, PsbtCombineInput,
+    PsbtInput, PsbtOutput, WifDisplay,
 };
 use tcx_chain::{key_hash_from_mnemonic, key_hash_from_private_key, Keystore, KeystoreGuard};
 use tcx_chain::{Account, HdKeystore, Metadata, PrivateKeystore, Source};
@@ -28,15 +37,21 @@ use tcx_tron::TrxAddress;
 use crate::api::keystore_common_derive_param::Derivation;
 use crate::api::sign_param::Key;
 use crate::api::{
-    AccountResponse, AccountsResponse, DerivedKeyResult, ExportPrivateKeyParam, HdStoreCreateParam,
-    HdStoreImportParam, KeyType, KeystoreCommonAccountsParam, KeystoreCommonDeriveParam,
-    KeystoreCommonExistsParam, KeystoreCommonExistsResult, KeystoreCommonExportResult,
-    PrivateKeyStoreExportParam, PrivateKeyStoreImportParam, PublicKeyParam, PublicKeyResult,
-    Response, WalletKeyParam, WalletResult,
+    AccountResponse, AccountsResponse, BtcForkNetworkParam, BtcForkNetworksResult,
+    ChainRegistryListParam, CoinInfoParam, CoinInfosResult, DerivedKeyResult, EvmChainInfoParam,
+    EvmChainInfosResult, ExportPrivateKeyParam, HdStoreCreateParam,
+    HdStoreExtendedPrivateKeyResponse, HdStoreExtendedPublicKeyParam,
+    HdStoreExtendedPublicKeyResponse, HdStoreImportParam, KeyType, KeystoreCommonAccountsParam,
+    KeystoreCommonDeriveParam, KeystoreCommonExistsParam, KeystoreCommonExistsResult,
+    KeystoreCommonExportResult, PrivateKeyStoreExportParam, PrivateKeyStoreImportParam,
+    PublicKeyParam, PublicKeyResult, Response, WalletKeyParam, WalletResult,
 };
 use crate::api::{InitTokenCoreXParam, SignParam};
 use crate::error_handling::Result;
-use crate::filemanager::{cache_keystore, clean_keystore, flush_keystore, WALLET_FILE_DIR};
+use crate::filemanager::{
+    cache_keystore, clean_keystore, flush_chain_registry, flush_keystore, load_chain_registry,
+    WALLET_FILE_DIR,
+};
 use crate::filemanager::{delete_keystore_file, KEYSTORE_MAP};
 
 use crate::IS_DEBUG;
@@ -44,13 +59,25 @@ use base58::ToBase58;
 use tcx_chain::tcx_ensure;
 use tcx_chain::Address;
 use tcx_chain::{MessageSigner, TransactionSigner};
+use tcx_constants::btc_fork_network::network_from_param;
 use tcx_constants::coin_info::coin_info_from_param;
-use tcx_constants::CurveType;
+use tcx_constants::{
+    btc_fork_networks, coin_infos, custom_btc_fork_networks, custom_coin_infos, BtcForkNetwork,
+    CoinInfo, CurveType,
+};
 use tcx_crypto::aes::cbc::encrypt_pkcs7;
 use tcx_crypto::hash::dsha256;
 use tcx_crypto::KDF_ROUNDS;
-use tcx_ethereum::{EthereumAddress, EthereumMsgIn, EthereumTxIn};
-use tcx_primitive::{Bip32DeterministicPublicKey, Ss58Codec};
+use tcx_ethereum::{
+    chain_infos, custom_chain_infos, export_validator_keystore, sign_deposit_data, ChainInfo,
+    EthereumAbiEncodeIn, EthereumAbiEncodeOut, EthereumAddress, EthereumAuthorizationIn,
+    EthereumDepositDataIn, EthereumMsgIn, EthereumSiweIn, EthereumTokenTxIn, EthereumTxDecodeIn,
+    EthereumTxIn, EthereumTypedDataIn, EthereumUserOperationIn, EthereumValidatorKeystoreIn,
+};
+use tcx_primitive::{
+    Bip32DeterministicPrivateKey, Bip32DeterministicPublicKey, Derive, DeterministicPrivateKey,
+    Ss58Codec, ToHex,
+};
 use tcx_substrate::{
     decode_substrate_keystore, encode_substrate_keystore, ExportSubstrateKeystoreResult,
     SubstrateAddress, SubstrateKeystore, SubstrateKeystoreParam, SubstrateRawTxIn,
@@ -58,7 +85,9 @@ use tcx_substrate::{
 use tcx_tezos::address::TezosAddress;
 use tcx_tezos::transaction::TezosRawTxIn;
 use tcx_tezos::{build_tezos_base58_private_key, pars_tezos_private_key};
-use tcx_tron::transaction::{TronMessageInput, TronTxInput};
+use tcx_tron::transaction::{
+    TronMessageInput, TronPermissionCheckInput, TronTxBuildInput, TronTxInput, TronTypedDataInput,
+};
 
 pub fn encode_message(msg: impl Message) -> Result<Vec<u8>> {
     if *IS_DEBUG.read() {
@@ -69,6 +98,12 @@ pub fn encode_message(msg: impl Message) -> Result<Vec<u8>> {
     Ok(buf.to_vec())
 }
 
+fn is_custom_btc_fork(chain_type: &str) -> bool {
+    custom_btc_fork_networks()
+        .iter()
+        .any(|x| x.coin.as_str() == chain_type)
+}
+
 fn derive_account<'a, 'b>(keystore: &mut Keystore, derivation: &Derivation) -> Result<Account> {
     let mut coin_info = coin_info_from_param(
         &derivation.chain_type,
@@ -92,6 +127,9 @@ fn derive_account<'a, 'b>(keystore: &mut Keystore, derivation: &Derivation) -> R
         "SUI" => keystore.derive_coin::<SuiAddress>(&coin_info),
         "STARKNET" => keystore.derive_coin::<StarknetAddress>(&coin_info),
         "MTT" => keystore.derive_coin::<CitaAddress>(&coin_info),
+        chain_type if is_custom_btc_fork(chain_type) => {
+            keystore.derive_coin::<BtcForkAddress>(&coin_info)
+        }
         _ => Err(format_err!("derive_account unsupported_chain")),
     }
 }
@@ -113,6 +151,7 @@ pub fn init_token_core_x(data: &[u8]) -> Result<Vec<u8>> {
             *KDF_ROUNDS.write() = 1024;
         }
     }
+    load_chain_registry()?;
     scan_keystores()?;
 
     Ok(vec![])
@@ -272,6 +311,82 @@ fn enc_xpub(xpub: &str, network: &str) -> Result<String> {
     Ok(base64::encode(&encrypted))
 }
 
+fn find_account_coin_info(
+    keystore: &Keystore,
+    chain_type: &str,
+    address: &str,
+) -> Result<CoinInfo> {
+    let account = keystore
+        .account(chain_type, address)
+        .ok_or(format_err!("{}", "account_not_found"))?;
+    Ok(CoinInfo {
+        coin: account.coin.to_string(),
+        derivation_path: account.derivation_path.to_string(),
+        curve: account.curve,
+        network: account.network.to_string(),
+        seg_wit: account.seg_wit.to_string(),
+    })
+}
+
+pub fn hd_store_extended_public_key(data: &[u8]) -> Result<Vec<u8>> {
+    let param: HdStoreExtendedPublicKeyParam =
+        HdStoreExtendedPublicKeyParam::decode(data).expect("hd_store_extended_public_key");
+    let mut map = KEYSTORE_MAP.write();
+    let keystore: &mut Keystore = match map.get_mut(&param.id) {
+        Some(keystore) => Ok(keystore),
+        _ => Err(format_err!("{}", "wallet_not_found")),
+    }?;
+
+    tcx_ensure!(
+        keystore.verify_password(&param.password),
+        format_err!("{}", "password_incorrect")
+    );
+
+    let coin_info = find_account_coin_info(keystore, &param.chain_type, &param.address)?;
+    let xpk = match keystore.find_deterministic_public_key(&param.chain_type, &param.address)? {
+        TypedDeterministicPublicKey::Bip32Sepc256k1(epk) => Ok(epk),
+        _ => Err(format_err!("{}", "unsupported_curve")),
+    }?;
+
+    let ret = HdStoreExtendedPublicKeyResponse {
+        extended_public_key: BtcForkAddress::slip132_extended_public_key(&xpk, &coin_info)?,
+    };
+    encode_message(ret)
+}
+
+pub fn hd_store_extended_private_key(data: &[u8]) -> Result<Vec<u8>> {
+    let param: HdStoreExtendedPublicKeyParam =
+        HdStoreExtendedPublicKeyParam::decode(data).expect("hd_store_extended_private_key");
+    let mut map = KEYSTORE_MAP.write();
+    let keystore: &mut Keystore = match map.get_mut(&param.id) {
+        Some(keystore) => Ok(keystore),
+        _ => Err(format_err!("{}", "wallet_not_found")),
+    }?;
+
+    let mut guard = KeystoreGuard::unlock_by_password(keystore, &param.password)?;
+
+    tcx_ensure!(
+        guard.keystore().determinable(),
+        format_err!("{}", "private_keystore_cannot_export_extended_private_key")
+    );
+
+    let coin_info = find_account_coin_info(guard.keystore(), &param.chain_type, &param.address)?;
+    let xprv = match guard
+        .keystore_mut()
+        .find_deterministic_private_key(&param.chain_type, &param.address)?
+    {
+        TypedDeterministicPrivateKey::Bip32Sepc256k1(dsk) => {
+            BtcForkAddress::slip132_extended_private_key(&dsk, &coin_info)
+        }
+        _ => Err(format_err!("{}", "unsupported_curve")),
+    }?;
+
+    let ret = HdStoreExtendedPrivateKeyResponse {
+        extended_private_key: xprv,
+    };
+    encode_message(ret)
+}
+
 pub fn keystore_common_derive(data: &[u8]) -> Result<Vec<u8>> {
     let param: KeystoreCommonDeriveParam =
         KeystoreCommonDeriveParam::decode(data).expect("keystore_common_derive");
@@ -669,6 +784,9 @@ pub fn sign_tx(data: &[u8]) -> Result<Vec<u8>> {
         "SUI" => sign_sui_tx(&param, guard.keystore_mut()),
         "STARKNET" => sign_starknet_tx(&param, guard.keystore_mut()),
         "MTT" => sign_cita_tx_raw(&param, guard.keystore_mut()),
+        chain_type if is_custom_btc_fork(chain_type) => {
+            sign_btc_fork_transaction(&param, guard.keystore_mut())
+        }
         _ => Err(format_err!("sign_tx unsupported_chain")),
     }
 }
@@ -738,30 +856,364 @@ pub fn sign_btc_fork_transaction(param: &SignParam, keystore: &mut Keystore) ->
             .as_slice(),
     )
     .expect("BitcoinForkTransactionInput");
-    let coin = coin_info_from_param(&param.chain_type, &input.network, &input.seg_wit, "")?;
+    sign_btc_fork_tx_input(&param.chain_type, &param.address, input, keystore)
+}
 
-    let signed_tx: BtcForkSignedTxOutput = if param.chain_type.as_str() == "BITCOINCASH" {
-        if !BchAddress::is_valid(&input.to, &coin) {
-            return Err(format_err!("address_invalid"));
+fn sign_btc_fork_tx_input(
+    chain_type: &str,
+    address: &str,
+    input: BtcForkTxInput,
+    keystore: &mut Keystore,
+) -> Result<Vec<u8>> {
+    let coin = coin_info_from_param(chain_type, &input.network, &input.seg_wit, "")?;
+
+    let is_bch = chain_type == "BITCOINCASH";
+    let is_valid = |address: &str| {
+        if is_bch {
+            BchAddress::is_valid(address, &coin)
+        } else {
+            BtcForkAddress::is_valid(address, &coin)
         }
+    };
+    if ((!input.to.is_empty() || input.outputs.is_empty()) && !is_valid(&input.to))
+        || !input.outputs.iter().all(|x| is_valid(&x.address))
+    {
+        return Err(format_err!("address_invalid"));
+    }
+
+    let signed_tx: BtcForkSignedTxOutput = if is_bch {
         let tran = BchTransaction::new(input, coin);
-        keystore.sign_transaction(&param.chain_type, &param.address, &tran)?
+        keystore.sign_transaction(chain_type, address, &tran)?
+    } else if input.seg_wit.as_str() == "P2TR" {
+        let tran = BtcForkTaprootTransaction::new(input, coin);
+        keystore.sign_transaction(chain_type, address, &tran)?
+    } else if input.seg_wit.as_str() == "SEGWIT" {
+        let tran = BtcForkNativeSegWitTransaction::new(input, coin);
+        keystore.sign_transaction(chain_type, address, &tran)?
     } else if input.seg_wit.as_str() != "NONE" {
-        if !BtcForkAddress::is_valid(&input.to, &coin) {
-            return Err(format_err!("address_invalid"));
-        }
         let tran = BtcForkSegWitTransaction::new(input, coin);
-        keystore.sign_transaction(&param.chain_type, &param.address, &tran)?
+        keystore.sign_transaction(chain_type, address, &tran)?
     } else {
-        if !BtcForkAddress::is_valid(&input.to, &coin) {
-            return Err(format_err!("address_invalid"));
-        }
         let tran = BtcForkTransaction::new(input, coin);
-        keystore.sign_transaction(&param.chain_type, &param.address, &tran)?
+        keystore.sign_transaction(chain_type, address, &tran)?
     };
     encode_message(signed_tx)
 }
 
+pub fn sign_psbt(data: &[u8]) -> Result<Vec<u8>> {
+    let param: SignParam = SignParam::decode(data).expect("SignParam");
+    match param.chain_type.as_str() {
+        "DOGECOIN" | "LITECOIN" | "BITCOIN" => {}
+        chain_type if is_custom_btc_fork(chain_type) => {}
+        _ => return Err(format_err!("sign_psbt unsupported_chain")),
+    }
+
+    let mut map = KEYSTORE_MAP.write();
+    let keystore: &mut Keystore = match map.get_mut(&param.id) {
+        Some(keystore) => Ok(keystore),
+        _ => Err(format_err!("{}", "wallet_not_found")),
+    }?;
+
+    let mut guard = match param.key.unwrap() {
+        Key::Password(password) => KeystoreGuard::unlock_by_password(keystore, &password)?,
+        Key::DerivedKey(derived_key) => {
+            KeystoreGuard::unlock_by_derived_key(keystore, &derived_key)?
+        }
+    };
+
+    let input: PsbtInput =
+        PsbtInput::decode(param.input.expect("PsbtInput").value.clone().as_slice())
+            .expect("PsbtInput");
+    let signed: PsbtOutput =
+        guard
+            .keystore_mut()
+            .sign_transaction(&param.chain_type, &param.address, &input)?;
+    encode_message(signed)
+}
+
+pub fn btc_fork_bump_fee(data: &[u8]) -> Result<Vec<u8>> {
+    let param: SignParam = SignParam::decode(data).expect("SignParam");
+    match param.chain_type.as_str() {
+        "DOGECOIN" | "LITECOIN" | "BITCOIN" => {}
+        chain_type if is_custom_btc_fork(chain_type) => {}
+        _ => return Err(format_err!("btc_fork_bump_fee unsupported_chain")),
+    }
+
+    let mut map = KEYSTORE_MAP.write();
+    let keystore: &mut Keystore = match map.get_mut(&param.id) {
+        Some(keystore) => Ok(keystore),
+        _ => Err(format_err!("{}", "wallet_not_found")),
+    }?;
+
+    let mut guard = match param.key.unwrap() {
+        Key::Password(password) => KeystoreGuard::unlock_by_password(keystore, &password)?,
+        Key::DerivedKey(derived_key) => {
+            KeystoreGuard::unlock_by_derived_key(keystore, &derived_key)?
+        }
+    };
+
+    let input: BtcForkFeeBumpInput = BtcForkFeeBumpInput::decode(
+        param
+            .input
+            .expect("BtcForkFeeBumpInput")
+            .value
+            .clone()
+            .as_slice(),
+    )
+    .expect("BtcForkFeeBumpInput");
+    let tx_input = tcx_btc_fork::bump_fee(&input)?;
+    sign_btc_fork_tx_input(
+        &param.chain_type,
+        &param.address,
+        tx_input,
+        guard.keystore_mut(),
+    )
+}
+
+pub fn btc_sign_message(data: &[u8]) -> Result<Vec<u8>> {
+    let param: SignParam = SignParam::decode(data).expect("SignParam");
+    match param.chain_type.as_str() {
+        "DOGECOIN" | "LITECOIN" | "BITCOIN" => {}
+        chain_type if is_custom_btc_fork(chain_type) => {}
+        _ => return Err(format_err!("btc_sign_message unsupported_chain")),
+    }
+
+    let mut map = KEYSTORE_MAP.write();
+    let keystore: &mut Keystore = match map.get_mut(&param.id) {
+        Some(keystore) => Ok(keystore),
+        _ => Err(format_err!("{}", "wallet_not_found")),
+    }?;
+
+    let mut guard = match param.key.unwrap() {
+        Key::Password(password) => KeystoreGuard::unlock_by_password(keystore, &password)?,
+        Key::DerivedKey(derived_key) => {
+            KeystoreGuard::unlock_by_derived_key(keystore, &derived_key)?
+        }
+    };
+
+    let input: BtcMessageInput = BtcMessageInput::decode(
+        param
+            .input
+            .expect("BtcMessageInput")
+            .value
+            .clone()
+            .as_slice(),
+    )
+    .expect("BtcMessageInput");
+    let signed: BtcMessageOutput =
+        guard
+            .keystore_mut()
+            .sign_message(&param.chain_type, &param.address, &input)?;
+    encode_message(signed)
+}
+
+pub fn btc_verify_message(data: &[u8]) -> Result<Vec<u8>> {
+    let param: BtcVerifyMessageInput =
+        BtcVerifyMessageInput::decode(data).expect("BtcVerifyMessageInput");
+    match param.chain_type.as_str() {
+        "DOGECOIN" | "LITECOIN" | "BITCOIN" => {}
+        chain_type if is_custom_btc_fork(chain_type) => {}
+        _ => return Err(format_err!("btc_verify_message unsupported_chain")),
+    }
+    encode_message(tcx_btc_fork::verify_message(&param)?)
+}
+
+pub fn finalize_psbt(data: &[u8]) -> Result<Vec<u8>> {
+    let param: PsbtInput = PsbtInput::decode(data).expect("PsbtInput");
+    encode_message(tcx_btc_fork::finalize_psbt(&param)?)
+}
+
+pub fn combine_psbt(data: &[u8]) -> Result<Vec<u8>> {
+    let param: PsbtCombineInput = PsbtCombineInput::decode(data).expect("PsbtCombineInput");
+    encode_message(tcx_btc_fork::combine_psbt(&param)?)
+}
+
+pub fn btc_fork_select_coins(data: &[u8]) -> Result<Vec<u8>> {
+    let param: CoinSelectionInput = CoinSelectionInput::decode(data).expect("CoinSelectionInput");
+    if param.chain_type.as_str() == "BITCOINCASH" {
+        return Err(format_err!("btc_fork_select_coins unsupported_chain"));
+    }
+    let coin = coin_info_from_param(&param.chain_type, &param.network, &param.seg_wit, "")?;
+    if !BtcForkAddress::is_valid(&param.to, &coin) {
+        return Err(format_err!("address_invalid"));
+    }
+    encode_message(tcx_btc_fork::select_unspents(&param)?)
+}
+
+pub fn btc_fork_multisig_address(data: &[u8]) -> Result<Vec<u8>> {
+    let param: BtcForkMultisigAddressParam =
+        BtcForkMultisigAddressParam::decode(data).expect("BtcForkMultisigAddressParam");
+    match param.chain_type.as_str() {
+        "DOGECOIN" | "LITECOIN" | "BITCOIN" => {}
+        chain_type if is_custom_btc_fork(chain_type) => {}
+        _ => return Err(format_err!("btc_fork_multisig_address unsupported_chain")),
+    }
+
+    let mut map = KEYSTORE_MAP.write();
+    let keystore: &mut Keystore = match map.get_mut(&param.id) {
+        Some(keystore) => Ok(keystore),
+        _ => Err(format_err!("{}", "wallet_not_found")),
+    }?;
+
+    tcx_ensure!(
+        keystore.verify_password(&param.password),
+        format_err!("{}", "password_incorrect")
+    );
+
+    let account = keystore
+        .account(&param.chain_type, &param.address)
+        .ok_or_else(|| format_err!("{}", "account_not_found"))?;
+    let seg_wit = if param.script_type.eq_ignore_ascii_case(P2WSH) {
+        "SEGWIT"
+    } else {
+        "NONE"
+    };
+    let network = network_from_param(&account.coin, &account.network, seg_wit)
+        .ok_or_else(|| format_err!("{}", "unsupported_chain"))?;
+
+    let local_xpub =
+        match keystore.find_deterministic_public_key(&param.chain_type, &param.address)? {
+            TypedDeterministicPublicKey::Bip32Sepc256k1(epk) => Ok(epk),
+            _ => Err(format_err!("{}", "unsupported_curve")),
+        }?;
+    let mut xpubs = vec![local_xpub];
+    for xpub in &param.cosigner_xpubs {
+        let (xpub, _) = Bip32DeterministicPublicKey::from_ss58check_with_version(xpub)
+            .map_err(|_| format_err!("{}", "invalid_xpub"))?;
+        xpubs.push(xpub);
+    }
+
+    encode_message(tcx_btc_fork::multisig_address(
+        &network,
+        &param.script_type,
+        param.threshold as usize,
+        &xpubs,
+        &param.path,
+    )?)
+}
+
+/// The fingerprint of the master key of the HD keystore
+fn master_fingerprint(keystore: &Keystore) -> Result<Vec<u8>> {
+    let root = Bip32DeterministicPrivateKey::from_mnemonic(&keystore.export()?)?;
+    Ok(root.deterministic_public_key().fingerprint())
+}
+
+pub fn btc_fork_export_descriptor(data: &[u8]) -> Result<Vec<u8>> {
+    let param: BtcForkExportDescriptorParam =
+        BtcForkExportDescriptorParam::decode(data).expect("BtcForkExportDescriptorParam");
+    match param.chain_type.as_str() {
+        "DOGECOIN" | "LITECOIN" | "BITCOIN" => {}
+        chain_type if is_custom_btc_fork(chain_type) => {}
+        _ => return Err(format_err!("btc_fork_export_descriptor unsupported_chain")),
+    }
+
+    let mut map = KEYSTORE_MAP.write();
+    let keystore: &mut Keystore = match map.get_mut(&param.id) {
+        Some(keystore) => Ok(keystore),
+        _ => Err(format_err!("{}", "wallet_not_found")),
+    }?;
+
+    let mut guard = KeystoreGuard::unlock_by_password(keystore, &param.password)?;
+
+    tcx_ensure!(
+        guard.keystore().determinable(),
+        format_err!("{}", "private_keystore_cannot_export_descriptor")
+    );
+
+    let coin_info = find_account_coin_info(guard.keystore(), &param.chain_type, &param.address)?;
+    let xpub = match guard
+        .keystore_mut()
+        .find_deterministic_public_key(&param.chain_type, &param.address)?
+    {
+        TypedDeterministicPublicKey::Bip32Sepc256k1(epk) => Ok(epk),
+        _ => Err(format_err!("{}", "unsupported_curve")),
+    }?;
+    let fingerprint = master_fingerprint(guard.keystore())?;
+    let account_path = get_account_path(&coin_info.derivation_path)?;
+
+    let descriptor = Descriptor::from_account(&fingerprint, &account_path, &xpub, &coin_info, 0)?;
+    let change_descriptor =
+        Descriptor::from_account(&fingerprint, &account_path, &xpub, &coin_info, 1)?;
+    encode_message(BtcForkDescriptorOutput {
+        descriptor: descriptor.to_string_with_checksum()?,
+        change_descriptor: change_descriptor.to_string_with_checksum()?,
+    })
+}
+
+pub fn btc_fork_import_descriptor(data: &[u8]) -> Result<Vec<u8>> {
+    let param: BtcForkImportDescriptorParam =
+        BtcForkImportDescriptorParam::decode(data).expect("BtcForkImportDescriptorParam");
+    match param.chain_type.as_str() {
+        "DOGECOIN" | "LITECOIN" | "BITCOIN" => {}
+        chain_type if is_custom_btc_fork(chain_type) => {}
+        _ => return Err(format_err!("btc_fork_import_descriptor unsupported_chain")),
+    }
+
+    let descriptor = Descriptor::from_str(&param.descriptor)?;
+    descriptor.check_network(&param.chain_type, &param.network)?;
+    let mut output = BtcForkImportDescriptorOutput {
+        descriptor: descriptor.to_string_with_checksum()?,
+        address: descriptor.address(&param.chain_type, &param.network, 0)?,
+        watch_only: true,
+        account_address: "".to_string(),
+        path: "".to_string(),
+    };
+    // the keystore accounts are single key ones, a multisig descriptor is only watched and
+    // its PSBTs are signed by sign_psbt
+    if param.id.is_empty() || matches!(descriptor, Descriptor::SortedMulti(..)) {
+        return encode_message(output);
+    }
+
+    let mut map = KEYSTORE_MAP.write();
+    let keystore: &mut Keystore = match map.get_mut(&param.id) {
+        Some(keystore) => Ok(keystore),
+        _ => Err(format_err!("{}", "wallet_not_found")),
+    }?;
+
+    let mut guard = KeystoreGuard::unlock_by_password(keystore, &param.password)?;
+
+    tcx_ensure!(
+        guard.keystore().determinable(),
+        format_err!("{}", "private_keystore_cannot_import_descriptor")
+    );
+
+    let root = Bip32DeterministicPrivateKey::from_mnemonic(&guard.keystore().export()?)?;
+    let fingerprint = hex::encode(root.deterministic_public_key().fingerprint());
+    let key = descriptor
+        .keys()
+        .into_iter()
+        .find(|x| x.fingerprint == fingerprint)
+        .ok_or_else(|| format_err!("{}", "descriptor_key_not_in_wallet"))?;
+    // only the account level keys, e.g. m/84'/0'/0', are derived in the keystore
+    let segments = key.origin_path.split('/').skip(1).collect::<Vec<&str>>();
+    tcx_ensure!(
+        segments.len() == 3 && segments.iter().all(|x| x.ends_with('\'')),
+        format_err!("{}", "unsupported_descriptor_path")
+    );
+
+    let (xpub, _) = Bip32DeterministicPublicKey::from_ss58check_with_version(&key.xpub)?;
+    tcx_ensure!(
+        root.derive(&key.origin_path)?
+            .deterministic_public_key()
+            .to_hex()
+            == xpub.to_hex(),
+        format_err!("{}", "descriptor_key_mismatch")
+    );
+
+    let mut coin_info = coin_info_from_param(&param.chain_type, &param.network, "", "")?;
+    coin_info.seg_wit = descriptor.seg_wit().to_string();
+    coin_info.derivation_path = format!("{}/0/0", key.origin_path);
+    let account = guard
+        .keystore_mut()
+        .derive_coin::<BtcForkAddress>(&coin_info)?;
+    flush_keystore(guard.keystore())?;
+
+    output.watch_only = false;
+    output.account_address = account.address;
+    output.path = account.derivation_path;
+    encode_message(output)
+}
+
 pub fn sign_nervos_ckb(param: &SignParam, keystore: &mut Keystore) -> Result<Vec<u8>> {
     let input: CkbTxInput = CkbTxInput::decode(
         param
@@ -824,6 +1276,48 @@ pub fn tron_sign_message(data: &[u8]) -> Result<Vec<u8>> {
     encode_message(signed_tx)
 }
 
+pub fn tron_sign_typed_data(data: &[u8]) -> Result<Vec<u8>> {
+    let param: SignParam = SignParam::decode(data).expect("SignParam");
+
+    let mut map = KEYSTORE_MAP.write();
+    let keystore: &mut Keystore = match map.get_mut(&param.id) {
+        Some(keystore) => Ok(keystore),
+        _ => Err(format_err!("{}", "wallet_not_found")),
+    }?;
+
+    let mut guard = match param.key.unwrap() {
+        Key::Password(password) => KeystoreGuard::unlock_by_password(keystore, &password)?,
+        Key::DerivedKey(derived_key) => {
+            KeystoreGuard::unlock_by_derived_key(keystore, &derived_key)?
+        }
+    };
+
+    let input: TronTypedDataInput = TronTypedDataInput::decode(
+        param
+            .input
+            .expect("TronTypedDataInput")
+            .value
+            .clone()
+            .as_slice(),
+    )
+    .expect("TronTypedDataInput");
+    let signed = guard
+        .keystore_mut()
+        .sign_message(&param.chain_type, &param.address, &input)?;
+    encode_message(signed)
+}
+
+pub fn tron_build_tx(data: &[u8]) -> Result<Vec<u8>> {
+    let param: TronTxBuildInput = TronTxBuildInput::decode(data).expect("TronTxBuildInput");
+    encode_message(tcx_tron::build_transaction(&param)?)
+}
+
+pub fn tron_check_permission(data: &[u8]) -> Result<Vec<u8>> {
+    let param: TronPermissionCheckInput =
+        TronPermissionCheckInput::decode(data).expect("TronPermissionCheckInput");
+    encode_message(tcx_tron::check_permission(&param)?)
+}
+
 pub fn eth_sign_message(data: &[u8]) -> Result<Vec<u8>> {
     let param: SignParam = SignParam::decode(data).expect("SignParam");
 
@@ -849,6 +1343,195 @@ pub fn eth_sign_message(data: &[u8]) -> Result<Vec<u8>> {
     encode_message(signed_tx)
 }
 
+pub fn eth_sign_typed_data(data: &[u8]) -> Result<Vec<u8>> {
+    let param: SignParam = SignParam::decode(data).expect("SignParam");
+
+    let mut map = KEYSTORE_MAP.write();
+    let keystore: &mut Keystore = match map.get_mut(&param.id) {
+        Some(keystore) => Ok(keystore),
+        _ => Err(format_err!("{}", "wallet_not_found")),
+    }?;
+
+    let mut guard = match param.key.unwrap() {
+        Key::Password(password) => KeystoreGuard::unlock_by_password(keystore, &password)?,
+        Key::DerivedKey(derived_key) => {
+            KeystoreGuard::unlock_by_derived_key(keystore, &derived_key)?
+        }
+    };
+
+    let input: EthereumTypedDataIn = EthereumTypedDataIn::decode(
+        param
+            .input
+            .expect("EthereumTypedDataIn")
+            .value
+            .clone()
+            .as_slice(),
+    )
+    .expect("EthereumTypedDataIn");
+    let signed = guard
+        .keystore_mut()
+        .sign_message(&param.chain_type, &param.address, &input)?;
+    encode_message(signed)
+}
+
+pub fn eth_sign_authorization(data: &[u8]) -> Result<Vec<u8>> {
+    let param: SignParam = SignParam::decode(data).expect("SignParam");
+
+    let mut map = KEYSTORE_MAP.write();
+    let keystore: &mut Keystore = match map.get_mut(&param.id) {
+        Some(keystore) => Ok(keystore),
+        _ => Err(format_err!("{}", "wallet_not_found")),
+    }?;
+
+    let mut guard = match param.key.unwrap() {
+        Key::Password(password) => KeystoreGuard::unlock_by_password(keystore, &password)?,
+        Key::DerivedKey(derived_key) => {
+            KeystoreGuard::unlock_by_derived_key(keystore, &derived_key)?
+        }
+    };
+
+    let input: EthereumAuthorizationIn = EthereumAuthorizationIn::decode(
+        param
+            .input
+            .expect("EthereumAuthorizationIn")
+            .value
+            .clone()
+            .as_slice(),
+    )
+    .expect("EthereumAuthorizationIn");
+    let signed = guard
+        .keystore_mut()
+        .sign_message(&param.chain_type, &param.address, &input)?;
+    encode_message(signed)
+}
+
+pub fn siwe_sign(data: &[u8]) -> Result<Vec<u8>> {
+    let param: SignParam = SignParam::decode(data).expect("SignParam");
+
+    let mut map = KEYSTORE_MAP.write();
+    let keystore: &mut Keystore = match map.get_mut(&param.id) {
+        Some(keystore) => Ok(keystore),
+        _ => Err(format_err!("{}", "wallet_not_found")),
+    }?;
+
+    let mut guard = match param.key.unwrap() {
+        Key::Password(password) => KeystoreGuard::unlock_by_password(keystore, &password)?,
+        Key::DerivedKey(derived_key) => {
+            KeystoreGuard::unlock_by_derived_key(keystore, &derived_key)?
+        }
+    };
+
+    let input: EthereumSiweIn = EthereumSiweIn::decode(
+        param
+            .input
+            .expect("EthereumSiweIn")
+            .value
+            .clone()
+            .as_slice(),
+    )
+    .expect("EthereumSiweIn");
+    let signed = guard
+        .keystore_mut()
+        .sign_message(&param.chain_type, &param.address, &input)?;
+    encode_message(signed)
+}
+
+pub fn eth_export_validator_keystore(data: &[u8]) -> Result<Vec<u8>> {
+    let param: EthereumValidatorKeystoreIn =
+        EthereumValidatorKeystoreIn::decode(data).expect("EthereumValidatorKeystoreIn");
+    let mut map = KEYSTORE_MAP.write();
+    let keystore: &mut Keystore = match map.get_mut(&param.id) {
+        Some(keystore) => Ok(keystore),
+        _ => Err(format_err!("{}", "wallet_not_found")),
+    }?;
+
+    let guard = KeystoreGuard::unlock_by_password(keystore, &param.password)?;
+
+    tcx_ensure!(
+        guard.keystore().determinable(),
+        format_err!("{}", "private_keystore_cannot_derive_validator_key")
+    );
+
+    let mnemonic = guard.keystore().export()?;
+    encode_message(export_validator_keystore(&mnemonic, &param)?)
+}
+
+pub fn eth_sign_deposit_data(data: &[u8]) -> Result<Vec<u8>> {
+    let param: EthereumDepositDataIn =
+        EthereumDepositDataIn::decode(data).expect("EthereumDepositDataIn");
+    let mut map = KEYSTORE_MAP.write();
+    let keystore: &mut Keystore = match map.get_mut(&param.id) {
+        Some(keystore) => Ok(keystore),
+        _ => Err(format_err!("{}", "wallet_not_found")),
+    }?;
+
+    let guard = KeystoreGuard::unlock_by_password(keystore, &param.password)?;
+
+    tcx_ensure!(
+        guard.keystore().determinable(),
+        format_err!("{}", "private_keystore_cannot_derive_validator_key")
+    );
+
+    let mnemonic = guard.keystore().export()?;
+    encode_message(sign_deposit_data(&mnemonic, &param)?)
+}
+
+pub fn eth_sign_user_operation(data: &[u8]) -> Result<Vec<u8>> {
+    let param: SignParam = SignParam::decode(data).expect("SignParam");
+
+    let mut map = KEYSTORE_MAP.write();
+    let keystore: &mut Keystore = match map.get_mut(&param.id) {
+        Some(keystore) => Ok(keystore),
+        _ => Err(format_err!("{}", "wallet_not_found")),
+    }?;
+
+    let mut guard = match param.key.clone().unwrap() {
+        Key::Password(password) => KeystoreGuard::unlock_by_password(keystore, &password)?,
+        Key::DerivedKey(derived_key) => {
+            KeystoreGuard::unlock_by_derived_key(keystore, &derived_key)?
+        }
+    };
+
+    sign_ethereum_user_operation_raw(&param, guard.keystore_mut())
+}
+
+pub fn sign_ethereum_user_operation_raw(
+    param: &SignParam,
+    keystore: &mut Keystore,
+) -> Result<Vec<u8>> {
+    let input: EthereumUserOperationIn = EthereumUserOperationIn::decode(
+        param
+            .input
+            .as_ref()
+            .expect("raw_tx_input")
+            .value
+            .clone()
+            .as_slice(),
+    )
+    .expect("EthereumUserOperationIn");
+    let signed = keystore.sign_message(&param.chain_type, &param.address, &input)?;
+    encode_message(signed)
+}
+
+pub fn eth_decode_tx(data: &[u8]) -> Result<Vec<u8>> {
+    let param: EthereumTxDecodeIn = EthereumTxDecodeIn::decode(data).expect("EthereumTxDecodeIn");
+    encode_message(tcx_ethereum::decode_transaction(&param)?)
+}
+
+pub fn eth_abi_encode(data: &[u8]) -> Result<Vec<u8>> {
+    let param: EthereumAbiEncodeIn =
+        EthereumAbiEncodeIn::decode(data).expect("EthereumAbiEncodeIn");
+    let call_data = tcx_ethereum::encode_function_call(&param.abi, &param.method, &param.args)?;
+    encode_message(EthereumAbiEncodeOut {
+        data: hex::encode(call_data),
+    })
+}
+
+pub fn eth_build_token_tx(data: &[u8]) -> Result<Vec<u8>> {
+    let param: EthereumTokenTxIn = EthereumTokenTxIn::decode(data).expect("EthereumTokenTxIn");
+    encode_message(tcx_ethereum::build_token_transaction(&param)?)
+}
+
 pub fn get_derived_key(data: &[u8]) -> Result<Vec<u8>> {
     let param: WalletKeyParam = WalletKeyParam::decode(data).unwrap();
     let mut map = KEYSTORE_MAP.write();
@@ -1069,3 +1752,181 @@ pub fn sign_cita_tx_raw(param: &SignParam, keystore: &mut Keystore) -> Result<Ve
     let signed_tx = keystore.sign_transaction(&param.chain_type, &param.address, &input)?;
     encode_message(signed_tx)
 }
+
+fn success_response() -> Result<Vec<u8>> {
+    encode_message(Response {
+        is_success: true,
+        error: "".to_owned(),
+        value: None,
+    })
+}
+
+fn coin_info_from_proto(param: &CoinInfoParam) -> Result<CoinInfo> {
+    let curve = if param.curve.is_empty() {
+        CurveType::SECP256k1
+    } else {
+        CurveType::from_str(&param.curve)?
+    };
+    let seg_wit = if param.seg_wit.is_empty() {
+        "NONE"
+    } else {
+        param.seg_wit.as_str()
+    };
+    Ok(CoinInfo {
+        coin: param.chain_type.to_string(),
+        derivation_path: param.path.to_string(),
+        curve,
+        network: param.network.to_string(),
+        seg_wit: seg_wit.to_string(),
+    })
+}
+
+fn version_prefix_from_hex(prefix: &str) -> Result<[u8; 4]> {
+    let bytes = hex::decode(prefix)?;
+    tcx_ensure!(
+        bytes.len() == 4,
+        format_err!("{}", "btc_fork_network_invalid_prefix")
+    );
+    let mut version = [0; 4];
+    version.copy_from_slice(&bytes);
+    Ok(version)
+}
+
+fn address_prefix_from_u32(prefix: u32) -> Result<u8> {
+    tcx_ensure!(
+        prefix <= 0xff,
+        format_err!("{}", "btc_fork_network_invalid_prefix")
+    );
+    Ok(prefix as u8)
+}
+
+pub fn register_coin_info(data: &[u8]) -> Result<Vec<u8>> {
+    let param: CoinInfoParam = CoinInfoParam::decode(data).expect("register_coin_info");
+    tcx_constants::register_coin_info(coin_info_from_proto(&param)?)?;
+    flush_chain_registry()?;
+    success_response()
+}
+
+pub fn remove_coin_info(data: &[u8]) -> Result<Vec<u8>> {
+    let param: CoinInfoParam = CoinInfoParam::decode(data).expect("remove_coin_info");
+    tcx_constants::remove_coin_info(&coin_info_from_proto(&param)?)?;
+    flush_chain_registry()?;
+    success_response()
+}
+
+pub fn list_coin_infos(data: &[u8]) -> Result<Vec<u8>> {
+    let param: ChainRegistryListParam =
+        ChainRegistryListParam::decode(data).expect("list_coin_infos");
+    let coin_infos = if param.custom_only {
+        custom_coin_infos()
+    } else {
+        coin_infos()
+    };
+    let ret = CoinInfosResult {
+        coin_infos: coin_infos
+            .iter()
+            .map(|x| CoinInfoParam {
+                chain_type: x.coin.to_string(),
+                path: x.derivation_path.to_string(),
+                network: x.network.to_string(),
+                seg_wit: x.seg_wit.to_string(),
+                curve: x.curve.as_str().to_string(),
+            })
+            .collect(),
+    };
+    encode_message(ret)
+}
+
+pub fn register_btc_fork_network(data: &[u8]) -> Result<Vec<u8>> {
+    let param: BtcForkNetworkParam =
+        BtcForkNetworkParam::decode(data).expect("register_btc_fork_network");
+    let network = BtcForkNetwork {
+        coin: param.coin.to_string(),
+        network: param.network.to_string(),
+        seg_wit: param.seg_wit.to_string(),
+        hrp: param.hrp.to_string(),
+        p2pkh_prefix: address_prefix_from_u32(param.p2pkh_prefix)?,
+        p2sh_prefix: address_prefix_from_u32(param.p2sh_prefix)?,
+        private_prefix: address_prefix_from_u32(param.private_prefix)?,
+        xpub_prefix: version_prefix_from_hex(&param.xpub_prefix)?,
+        xprv_prefix: version_prefix_from_hex(&param.xprv_prefix)?,
+    };
+    tcx_constants::register_btc_fork_network(network)?;
+    flush_chain_registry()?;
+    success_response()
+}
+
+pub fn remove_btc_fork_network(data: &[u8]) -> Result<Vec<u8>> {
+    let param: BtcForkNetworkParam =
+        BtcForkNetworkParam::decode(data).expect("remove_btc_fork_network");
+    tcx_constants::remove_btc_fork_network(&param.coin, &param.network, &param.seg_wit)?;
+    flush_chain_registry()?;
+    success_response()
+}
+
+pub fn list_btc_fork_networks(data: &[u8]) -> Result<Vec<u8>> {
+    let param: ChainRegistryListParam =
+        ChainRegistryListParam::decode(data).expect("list_btc_fork_networks");
+    let networks = if param.custom_only {
+        custom_btc_fork_networks()
+    } else {
+        btc_fork_networks()
+    };
+    let ret = BtcForkNetworksResult {
+        networks: networks
+            .iter()
+            .map(|x| BtcForkNetworkParam {
+                coin: x.coin.to_string(),
+                network: x.network.to_string(),
+                seg_wit: x.seg_wit.to_string(),
+                hrp: x.hrp.to_string(),
+                p2pkh_prefix: x.p2pkh_prefix as u32,
+                p2sh_prefix: x.p2sh_prefix as u32,
+                private_prefix: x.private_prefix as u32,
+                xpub_prefix: hex::encode(x.xpub_prefix),
+                xprv_prefix: hex::encode(x.xprv_prefix),
+            })
+            .collect(),
+    };
+    encode_message(ret)
+}
+
+pub fn register_evm_chain_info(data: &[u8]) -> Result<Vec<u8>> {
+    let param: EvmChainInfoParam =
+        EvmChainInfoParam::decode(data).expect("register_evm_chain_info");
+    tcx_ethereum::register_chain_info(ChainInfo {
+        network: param.network.to_string(),
+        network_id: param.network_id,
+        chain_id: param.chain_id,
+    })?;
+    flush_chain_registry()?;
+    success_response()
+}
+
+pub fn remove_evm_chain_info(data: &[u8]) -> Result<Vec<u8>> {
+    let param: EvmChainInfoParam = EvmChainInfoParam::decode(data).expect("remove_evm_chain_info");
+    tcx_ethereum::remove_chain_info(&param.network)?;
+    flush_chain_registry()?;
+    success_response()
+}
+
+pub fn list_evm_chain_infos(data: &[u8]) -> Result<Vec<u8>> {
+    let param: ChainRegistryListParam =
+        ChainRegistryListParam::decode(data).expect("list_evm_chain_infos");
+    let chain_infos = if param.custom_only {
+        custom_chain_infos()
+    } else {
+        chain_infos()
+    };
+    let ret = EvmChainInfosResult {
+        chain_infos: chain_infos
+            .iter()
+            .map(|x| EvmChainInfoParam {
+                network: x.network.to_string(),
+                network_id: x.network_id,
+                chain_id: x.chain_id,
+            })
+            .collect(),
+    };
+    encode_message(ret)
+}
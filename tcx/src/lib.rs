@@ -14,11 +14,17 @@ pub mod handler;
 pub use crate::error_handling::{landingpad, LAST_BACKTRACE, LAST_ERROR};
 #[allow(deprecated)]
 pub use crate::handler::{
-    encode_message, eth_sign_message, export_mnemonic, export_private_key, get_derived_key,
-    hd_store_create, hd_store_export, hd_store_import, keystore_common_accounts,
-    keystore_common_delete, keystore_common_derive, keystore_common_exists, keystore_common_verify,
-    private_key_store_export, private_key_store_import, sign_tx, tron_sign_message,
-    unlock_then_crash,
+    btc_fork_bump_fee, btc_fork_export_descriptor, btc_fork_import_descriptor,
+    btc_fork_multisig_address, btc_fork_select_coins, btc_sign_message, btc_verify_message,
+    combine_psbt, encode_message, eth_abi_encode, eth_build_token_tx, eth_decode_tx,
+    eth_export_validator_keystore, eth_sign_authorization, eth_sign_deposit_data, eth_sign_message,
+    eth_sign_typed_data, eth_sign_user_operation, export_mnemonic, export_private_key,
+    finalize_psbt, get_derived_key, hd_store_create, hd_store_export,
+    hd_store_extended_private_key, hd_store_extended_public_key, hd_store_import,
+    keystore_common_accounts, keystore_common_delete, keystore_common_derive,
+    keystore_common_exists, keystore_common_verify, private_key_store_export,
+    private_key_store_import, sign_psbt, sign_tx, siwe_sign, tron_build_tx, tron_check_permission,
+    tron_sign_message, tron_sign_typed_data, unlock_then_crash,
 };
 
 mod filemanager;
@@ -26,6 +32,12 @@ mod filemanager;
 pub use crate::handler::{
     export_substrate_keystore, get_public_key, import_substrate_keystore, substrate_keystore_exists,
 };
+
+pub use crate::handler::{
+    list_btc_fork_networks, list_coin_infos, list_evm_chain_infos, register_btc_fork_network,
+    register_coin_info, register_evm_chain_info, remove_btc_fork_network, remove_coin_info,
+    remove_evm_chain_info,
+};
 pub use parking_lot::RwLock;
 
 extern crate serde_json;
@@ -70,6 +82,12 @@ pub unsafe extern "C" fn call_tcx_api(hex_str: *const c_char) -> *const c_char {
         "hd_store_import" => landingpad(|| hd_store_import(&action.param.unwrap().value)),
         "hd_store_export" => landingpad(|| hd_store_export(&action.param.unwrap().value)),
         "export_mnemonic" => landingpad(|| export_mnemonic(&action.param.unwrap().value)),
+        "hd_store_extended_public_key" => {
+            landingpad(|| hd_store_extended_public_key(&action.param.unwrap().value))
+        }
+        "hd_store_extended_private_key" => {
+            landingpad(|| hd_store_extended_private_key(&action.param.unwrap().value))
+        }
         "keystore_common_derive" => {
             landingpad(|| keystore_common_derive(&action.param.unwrap().value))
         }
@@ -96,9 +114,49 @@ pub unsafe extern "C" fn call_tcx_api(hex_str: *const c_char) -> *const c_char {
 
         "sign_tx" => landingpad(|| sign_tx(&action.param.unwrap().value)),
         "eth_sign_msg" => landingpad(|| eth_sign_message(&action.param.unwrap().value)),
+        "eth_sign_typed_data" => landingpad(|| eth_sign_typed_data(&action.param.unwrap().value)),
+        "eth_decode_tx" => landingpad(|| eth_decode_tx(&action.param.unwrap().value)),
+        "eth_sign_authorization" => {
+            landingpad(|| eth_sign_authorization(&action.param.unwrap().value))
+        }
+        "eth_sign_user_operation" => {
+            landingpad(|| eth_sign_user_operation(&action.param.unwrap().value))
+        }
+        "eth_export_validator_keystore" => {
+            landingpad(|| eth_export_validator_keystore(&action.param.unwrap().value))
+        }
+        "eth_sign_deposit_data" => {
+            landingpad(|| eth_sign_deposit_data(&action.param.unwrap().value))
+        }
+        "siwe_sign" => landingpad(|| siwe_sign(&action.param.unwrap().value)),
+        "eth_abi_encode" => landingpad(|| eth_abi_encode(&action.param.unwrap().value)),
+        "eth_build_token_tx" => landingpad(|| eth_build_token_tx(&action.param.unwrap().value)),
         "get_public_key" => landingpad(|| get_public_key(&action.param.unwrap().value)),
 
         "tron_sign_msg" => landingpad(|| tron_sign_message(&action.param.unwrap().value)),
+        "tron_sign_typed_data" => landingpad(|| tron_sign_typed_data(&action.param.unwrap().value)),
+        "tron_build_tx" => landingpad(|| tron_build_tx(&action.param.unwrap().value)),
+        "sign_psbt" => landingpad(|| sign_psbt(&action.param.unwrap().value)),
+        "finalize_psbt" => landingpad(|| finalize_psbt(&action.param.unwrap().value)),
+        "combine_psbt" => landingpad(|| combine_psbt(&action.param.unwrap().value)),
+        "btc_fork_multisig_address" => {
+            landingpad(|| btc_fork_multisig_address(&action.param.unwrap().value))
+        }
+        "btc_fork_export_descriptor" => {
+            landingpad(|| btc_fork_export_descriptor(&action.param.unwrap().value))
+        }
+        "btc_fork_import_descriptor" => {
+            landingpad(|| btc_fork_import_descriptor(&action.param.unwrap().value))
+        }
+        "btc_fork_select_coins" => {
+            landingpad(|| btc_fork_select_coins(&action.param.unwrap().value))
+        }
+        "btc_fork_bump_fee" => landingpad(|| btc_fork_bump_fee(&action.param.unwrap().value)),
+        "btc_sign_message" => landingpad(|| btc_sign_message(&action.param.unwrap().value)),
+        "btc_verify_message" => landingpad(|| btc_verify_message(&action.param.unwrap().value)),
+        "tron_check_permission" => {
+            landingpad(|| tron_check_permission(&action.param.unwrap().value))
+        }
 
         "substrate_keystore_exists" => {
             landingpad(|| substrate_keystore_exists(&action.param.unwrap().value))
@@ -112,6 +170,26 @@ pub unsafe extern "C" fn call_tcx_api(hex_str: *const c_char) -> *const c_char {
             landingpad(|| export_substrate_keystore(&action.param.unwrap().value))
         }
 
+        "register_coin_info" => landingpad(|| register_coin_info(&action.param.unwrap().value)),
+        "remove_coin_info" => landingpad(|| remove_coin_info(&action.param.unwrap().value)),
+        "list_coin_infos" => landingpad(|| list_coin_infos(&action.param.unwrap().value)),
+        "register_btc_fork_network" => {
+            landingpad(|| register_btc_fork_network(&action.param.unwrap().value))
+        }
+        "remove_btc_fork_network" => {
+            landingpad(|| remove_btc_fork_network(&action.param.unwrap().value))
+        }
+        "list_btc_fork_networks" => {
+            landingpad(|| list_btc_fork_networks(&action.param.unwrap().value))
+        }
+        "register_evm_chain_info" => {
+            landingpad(|| register_evm_chain_info(&action.param.unwrap().value))
+        }
+        "remove_evm_chain_info" => {
+            landingpad(|| remove_evm_chain_info(&action.param.unwrap().value))
+        }
+        "list_evm_chain_infos" => landingpad(|| list_evm_chain_infos(&action.param.unwrap().value)),
+
         // !!! WARNING !!! used for `cache_dk` feature
         "get_derived_key" => landingpad(|| get_derived_key(&action.param.unwrap().value)),
         // !!! WARNING !!! used for test only
@@ -165,11 +243,14 @@ mod tests {
 
     use crate::api::keystore_common_derive_param::Derivation;
     use crate::api::{
-        AccountsResponse, DerivedKeyResult, ExportPrivateKeyParam, HdStoreCreateParam,
-        InitTokenCoreXParam, KeyType, KeystoreCommonAccountsParam, KeystoreCommonDeriveParam,
-        KeystoreCommonExistsParam, KeystoreCommonExistsResult, KeystoreCommonExportResult,
-        PrivateKeyStoreExportParam, PrivateKeyStoreImportParam, PublicKeyParam, PublicKeyResult,
-        Response, SignParam, WalletKeyParam,
+        AccountsResponse, BtcForkNetworkParam, BtcForkNetworksResult, ChainRegistryListParam,
+        CoinInfoParam, CoinInfosResult, DerivedKeyResult, EvmChainInfoParam, EvmChainInfosResult,
+        ExportPrivateKeyParam, HdStoreCreateParam, HdStoreExtendedPrivateKeyResponse,
+        HdStoreExtendedPublicKeyParam, HdStoreExtendedPublicKeyResponse, InitTokenCoreXParam,
+        KeyType, KeystoreCommonAccountsParam, KeystoreCommonDeriveParam, KeystoreCommonExistsParam,
+        KeystoreCommonExistsResult, KeystoreCommonExportResult, PrivateKeyStoreExportParam,
+        PrivateKeyStoreImportParam, PublicKeyParam, PublicKeyResult, Response, SignParam,
+        WalletKeyParam,
     };
     use crate::api::{HdStoreImportParam, WalletResult};
     use crate::handler::hd_store_import;
@@ -186,12 +267,27 @@ mod tests {
     use sp_core::Public as TraitPublic;
     use sp_runtime::traits::Verify;
     use tcx_aptos::{AptosTxIn, AptosTxOut, AptosTxType};
-    use tcx_btc_fork::BtcForkSignedTxOutput;
+    use tcx_btc_fork::{
+        BtcForkDescriptorOutput, BtcForkExportDescriptorParam, BtcForkFeeBumpInput,
+        BtcForkImportDescriptorOutput, BtcForkImportDescriptorParam, BtcForkMultisigAddressOutput,
+        BtcForkMultisigAddressParam, BtcForkSignedTxOutput, BtcMessageInput, BtcMessageOutput,
+        BtcVerifyMessageInput, BtcVerifyMessageOutput, CoinSelectionInput, CoinSelectionOutput,
+        PsbtCombineInput, PsbtInput, PsbtOutput,
+    };
     use tcx_cita::transaction::{
         SignedTransaction as CitaSignedTransaction, Transaction as CitaTransaction,
     };
     use tcx_ckb::{CachedCell, CellInput, CkbTxInput, CkbTxOutput, OutPoint, Script, Witness};
-    use tcx_ethereum::{EthereumTxIn, EthereumTxOut};
+    use tcx_ethereum::eip2335::Eip2335Keystore;
+    use tcx_ethereum::{
+        ethereum_token_tx_in, EntryPointVersion, Erc20Transfer, EthereumAbiEncodeIn,
+        EthereumAbiEncodeOut, EthereumAuthorizationIn, EthereumAuthorizationOut,
+        EthereumDepositDataIn, EthereumDepositDataOut, EthereumMsgIn, EthereumMsgOut,
+        EthereumSiweIn, EthereumSiweOut, EthereumTokenTxIn, EthereumTxDecodeIn,
+        EthereumTxDecodeOut, EthereumTxIn, EthereumTxOut, EthereumTypedDataIn,
+        EthereumTypedDataOut, EthereumUserOperationIn, EthereumUserOperationOut,
+        EthereumValidatorKeystoreIn, EthereumValidatorKeystoreOut, SignatureType,
+    };
     use tcx_filecoin::{SignedMessage, UnsignedMessage};
     use tcx_solana::{SolanaTxIn, SolanaTxOut};
     use tcx_starknet::{NewTransfer, StarknetTxIn, StarknetTxOut, StarknetTxType};
@@ -201,7 +297,11 @@ mod tests {
     };
     use tcx_sui::{RawTx, SuiTxInput, SuiTxOuput, SuiTxType};
     use tcx_tezos::transaction::{TezosRawTxIn, TezosTxOut};
-    use tcx_tron::transaction::{TronMessageInput, TronMessageOutput, TronTxInput, TronTxOutput};
+    use tcx_tron::transaction::{
+        tron_tx_build_input, Trc20Transfer, TronMessageInput, TronMessageOutput, TronPermission,
+        TronPermissionCheckInput, TronPermissionCheckOutput, TronPermissionKey, TronTransfer,
+        TronTxBuildInput, TronTxInput, TronTxOutput, TronTypedDataInput,
+    };
 
     static OTHER_MNEMONIC: &'static str =
         "calm release clay imitate top extend close draw quiz refuse shuffle injury";
@@ -1431,6 +1531,81 @@ mod tests {
         })
     }
 
+    #[test]
+    pub fn test_hd_store_extended_key() {
+        run_test(|| {
+            let derivations = vec![
+                Derivation {
+                    chain_type: "BITCOIN".to_string(),
+                    path: "m/49'/0'/0'/0/0".to_string(),
+                    network: "MAINNET".to_string(),
+                    seg_wit: "P2WPKH".to_string(),
+                    chain_id: "".to_string(),
+                    curve: "".to_string(),
+                },
+                Derivation {
+                    chain_type: "LITECOIN".to_string(),
+                    path: "m/44'/2'/0'/0/0".to_string(),
+                    network: "MAINNET".to_string(),
+                    seg_wit: "NONE".to_string(),
+                    chain_id: "".to_string(),
+                    curve: "".to_string(),
+                },
+            ];
+            let xpubs = vec![
+                "ypub6Wdz1gzMKLnPxXti2GbSjQGXSqrA5NMKNP3C5JS2ZJKRDEecuZH8AhSvYQs4dZHi7b6Yind7bLekuTH9fNbJcH1MXMy9meoifu2wST55sav",
+                "Ltub2ZUUJ79wDwV6B1ScHhjPksuWkEQtmWHdiwE1pUzcfzywdA5aCZLT5zmBrssEXrRYMxuhhAHCWPBXjk24QzJiU4meQZVzAbtypCL8Vmait4P",
+            ];
+            let xprvs = vec![
+                "yprvAHedcBTTUyE6k3pEvF4SNGKntp1ffudU1A7bGv2QzxnSLSKUN1xscu8Sh8mntfLD3FaAKa4hSugiE1sNftL6pzLK9NbrGQUc9wTM98MQL49",
+                "Ltpv78J8hKjPynx8Z8jn36F1gJ5JJfihhFiyeR7z9WEKjpCM2ntVseJ25PevcWjtCyYbmguGMeWvRTXd5EziknMD62MUoSo5Fuiu3VwhwoeSJDF",
+            ];
+
+            for idx in 0..derivations.len() {
+                let wallet = import_and_derive(derivations[idx].clone());
+                let acc = wallet.accounts.first().unwrap().clone();
+                let param = HdStoreExtendedPublicKeyParam {
+                    id: wallet.id.to_string(),
+                    password: TEST_PASSWORD.to_string(),
+                    chain_type: acc.chain_type.to_string(),
+                    address: acc.address.to_string(),
+                };
+                let ret_bytes = call_api("hd_store_extended_public_key", param.clone()).unwrap();
+                let ret = HdStoreExtendedPublicKeyResponse::decode(ret_bytes.as_slice()).unwrap();
+                assert_eq!(xpubs[idx], ret.extended_public_key);
+
+                let ret_bytes = call_api("hd_store_extended_private_key", param).unwrap();
+                let ret = HdStoreExtendedPrivateKeyResponse::decode(ret_bytes.as_slice()).unwrap();
+                assert_eq!(xprvs[idx], ret.extended_private_key);
+
+                let param = HdStoreExtendedPublicKeyParam {
+                    id: wallet.id.to_string(),
+                    password: "WRONG PASSWORD".to_string(),
+                    chain_type: acc.chain_type.to_string(),
+                    address: acc.address.to_string(),
+                };
+                let ret = call_api("hd_store_extended_private_key", param);
+                assert_eq!(format!("{}", ret.err().unwrap()), "password_incorrect");
+                remove_created_wallet(&wallet.id);
+            }
+
+            let wallet = import_pk_and_derive(derivations[1].clone());
+            let acc = wallet.accounts.first().unwrap().clone();
+            let param = HdStoreExtendedPublicKeyParam {
+                id: wallet.id.to_string(),
+                password: TEST_PASSWORD.to_string(),
+                chain_type: acc.chain_type.to_string(),
+                address: acc.address.to_string(),
+            };
+            let ret = call_api("hd_store_extended_private_key", param);
+            assert_eq!(
+                format!("{}", ret.err().unwrap()),
+                "private_keystore_cannot_export_extended_private_key"
+            );
+            remove_created_wallet(&wallet.id);
+        })
+    }
+
     #[test]
     pub fn test_import_to_pk_which_from_hd() {
         run_test(|| {
@@ -1731,7 +1906,10 @@ mod tests {
             let wallet = import_and_derive(derivation);
 
             let raw_data = "0a0202a22208e216e254e43ee10840c8cbe4e3df2d5a67080112630a2d747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e5472616e73666572436f6e747261637412320a15415c68cc82c87446f602f019e5fd797437f5b79cc212154156a6076cd1537fa317c2606e4edfa4acd3e8e92e18a08d06709084e1e3df2d".to_string();
-            let input = TronTxInput { raw_data };
+            let input = TronTxInput {
+                raw_data,
+                signatures: vec![],
+            };
             let input_value = encode_message(input).unwrap();
             let tx = SignParam {
                 id: wallet.id.to_string(),
@@ -1778,6 +1956,10 @@ mod tests {
             let output: TronTxOutput = TronTxOutput::decode(ret.as_slice()).unwrap();
             let expected_sign = "bbf5ce0549490613a26c3ac4fc8574e748eabda05662b2e49cea818216b9da18691e78cd6379000e9c8a35c13dfbf620f269be90a078b58799b56dc20da3bdf200";
             assert_eq!(expected_sign, output.signatures[0]);
+            assert_eq!(
+                "88817b9c6276e3c535e4f8f15baf546292ca6ad9d44a7d97857bd6f8909d63d4",
+                output.tx_hash
+            );
             remove_created_wallet(&wallet.id);
         })
     }
@@ -2160,6 +2342,262 @@ mod tests {
         })
     }
 
+    #[test]
+    pub fn test_tron_build_tx() {
+        run_test(|| {
+            let wallet = import_and_derive(Derivation {
+                chain_type: "TRON".to_string(),
+                path: "m/44'/195'/0'/0/0".to_string(),
+                network: "".to_string(),
+                seg_wit: "".to_string(),
+                chain_id: "".to_string(),
+                curve: "".to_string(),
+            });
+            let address = wallet.accounts.first().unwrap().address.to_string();
+            assert_eq!(address, "TY2uroBeZ5trA9QT96aEWj32XLkAAhQ9R2");
+
+            let input = TronTxBuildInput {
+                ref_block_bytes: "8a45".to_string(),
+                ref_block_hash: "7f2ad38c2ea3f0b1".to_string(),
+                expiration: 1700000060000,
+                timestamp: 1700000000000,
+                fee_limit: 30000000,
+                memo: "invoice-42".to_string(),
+                contract: Some(tron_tx_build_input::Contract::Trc20Transfer(
+                    Trc20Transfer {
+                        owner: address.to_string(),
+                        contract: "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t".to_string(),
+                        to: "TQjHpeTEscirkvpjxGDCYu7xS2yeD9U9VB".to_string(),
+                        amount: "1000000".to_string(),
+                    },
+                )),
+            };
+            let ret = call_api("tron_build_tx", input.clone()).unwrap();
+            let tx_input = TronTxInput::decode(ret.as_slice()).unwrap();
+
+            let tx = SignParam {
+                id: wallet.id.to_string(),
+                key: Some(Key::Password(TEST_PASSWORD.to_string())),
+                chain_type: "TRON".to_string(),
+                address,
+                input: Some(::prost_types::Any {
+                    type_url: "imtoken".to_string(),
+                    value: encode_message(tx_input).unwrap(),
+                }),
+            };
+            let ret = call_api("sign_tx", tx).unwrap();
+            let output: TronTxOutput = TronTxOutput::decode(ret.as_slice()).unwrap();
+            assert_eq!(output.signatures[0], "d6910b3730414be86ec5bb852e1d0d44b2c6e189cbe2f9473588c2233b944cd651274760bd8003291c7c6fa31fd4f894570f1fde699645ab5b01de0f2db1e3fa00");
+            assert_eq!(
+                output.tx_hash,
+                "a378a51ee03e5a86dd62b527de23c42b8e0fa96a5470b5bdd1eb55b240dc1204"
+            );
+
+            let input = TronTxBuildInput {
+                ref_block_bytes: "8a".to_string(),
+                ..input
+            };
+            let ret = call_api("tron_build_tx", input);
+            assert_eq!(format!("{}", ret.err().unwrap()), "invalid_ref_block");
+            remove_created_wallet(&wallet.id);
+        })
+    }
+
+    #[test]
+    pub fn test_tron_multi_signature() {
+        run_test(|| {
+            let derivation = Derivation {
+                chain_type: "TRON".to_string(),
+                path: "m/44'/195'/0'/0/0".to_string(),
+                network: "".to_string(),
+                seg_wit: "".to_string(),
+                chain_id: "".to_string(),
+                curve: "".to_string(),
+            };
+            let wallet = import_and_derive(derivation.clone());
+
+            let param = HdStoreImportParam {
+                mnemonic: OTHER_MNEMONIC.to_string(),
+                password: TEST_PASSWORD.to_string(),
+                source: "MNEMONIC".to_string(),
+                name: "test-wallet".to_string(),
+                password_hint: "imtoken".to_string(),
+                overwrite: true,
+                encoding: "".to_string(),
+            };
+            let ret = call_api("hd_store_import", param).unwrap();
+            let other_wallet: WalletResult = WalletResult::decode(ret.as_slice()).unwrap();
+            let param = KeystoreCommonDeriveParam {
+                id: other_wallet.id.to_string(),
+                password: TEST_PASSWORD.to_string(),
+                derivations: vec![derivation],
+            };
+            let ret = call_api("keystore_common_derive", param).unwrap();
+            let other_accounts: AccountsResponse =
+                AccountsResponse::decode(ret.as_slice()).unwrap();
+
+            // the active permission 2 of TQjHpeTEscirkvpjxGDCYu7xS2yeD9U9VB needs both keys
+            let input = TronTxBuildInput {
+                ref_block_bytes: "8a45".to_string(),
+                ref_block_hash: "7f2ad38c2ea3f0b1".to_string(),
+                expiration: 1700000060000,
+                timestamp: 1700000000000,
+                permission_id: 2,
+                contract: Some(tron_tx_build_input::Contract::Transfer(TronTransfer {
+                    owner: "TQjHpeTEscirkvpjxGDCYu7xS2yeD9U9VB".to_string(),
+                    to: "TAzfL5gZtmuhNs2VaS1PmmGj4tZST9ToXU".to_string(),
+                    amount: 1000000,
+                })),
+                ..TronTxBuildInput::default()
+            };
+            let ret = call_api("tron_build_tx", input).unwrap();
+            let tx_input = TronTxInput::decode(ret.as_slice()).unwrap();
+
+            let mut signatures = vec![];
+            for (id, address) in vec![
+                (&wallet.id, &wallet.accounts[0].address),
+                (&other_wallet.id, &other_accounts.accounts[0].address),
+            ] {
+                let tx = SignParam {
+                    id: id.to_string(),
+                    key: Some(Key::Password(TEST_PASSWORD.to_string())),
+                    chain_type: "TRON".to_string(),
+                    address: address.to_string(),
+                    input: Some(::prost_types::Any {
+                        type_url: "imtoken".to_string(),
+                        value: encode_message(TronTxInput {
+                            signatures,
+                            ..tx_input.clone()
+                        })
+                        .unwrap(),
+                    }),
+                };
+                let ret = call_api("sign_tx", tx).unwrap();
+                let output: TronTxOutput = TronTxOutput::decode(ret.as_slice()).unwrap();
+                signatures = output.signatures;
+            }
+            assert_eq!(signatures.len(), 2);
+
+            let ret = call_api(
+                "tron_check_permission",
+                TronPermissionCheckInput {
+                    raw_data: tx_input.raw_data,
+                    signatures,
+                    permission: Some(TronPermission {
+                        id: 2,
+                        threshold: 2,
+                        keys: vec![
+                            TronPermissionKey {
+                                address: "TY2uroBeZ5trA9QT96aEWj32XLkAAhQ9R2".to_string(),
+                                weight: 1,
+                            },
+                            TronPermissionKey {
+                                address: "TLZnqkrSNLUWNrZMug8u9b6pJ3XcTGbzDV".to_string(),
+                                weight: 1,
+                            },
+                        ],
+                    }),
+                },
+            )
+            .unwrap();
+            let output = TronPermissionCheckOutput::decode(ret.as_slice()).unwrap();
+            assert_eq!(output.weight, 2);
+            assert!(output.approved);
+            assert_eq!(
+                output.signers,
+                vec![
+                    "TY2uroBeZ5trA9QT96aEWj32XLkAAhQ9R2",
+                    "TLZnqkrSNLUWNrZMug8u9b6pJ3XcTGbzDV"
+                ]
+            );
+            remove_created_wallet(&wallet.id);
+            remove_created_wallet(&other_wallet.id);
+        })
+    }
+
+    #[test]
+    pub fn test_tron_sign_typed_data() {
+        run_test(|| {
+            let wallet = import_and_derive(Derivation {
+                chain_type: "TRON".to_string(),
+                path: "m/44'/195'/0'/0/0".to_string(),
+                network: "".to_string(),
+                seg_wit: "".to_string(),
+                chain_id: "".to_string(),
+                curve: "".to_string(),
+            });
+            let typed_data = r#"{
+                "types": {
+                    "EIP712Domain": [
+                        {"name": "name", "type": "string"},
+                        {"name": "version", "type": "string"},
+                        {"name": "chainId", "type": "uint256"},
+                        {"name": "verifyingContract", "type": "address"}
+                    ],
+                    "FromPerson": [
+                        {"name": "name", "type": "string"},
+                        {"name": "wallet", "type": "address"},
+                        {"name": "trcTokenId", "type": "trcToken"}
+                    ],
+                    "ToPerson": [
+                        {"name": "name", "type": "string"},
+                        {"name": "wallet", "type": "address"},
+                        {"name": "trcTokenArr", "type": "trcToken[]"}
+                    ],
+                    "Mail": [
+                        {"name": "from", "type": "FromPerson"},
+                        {"name": "to", "type": "ToPerson"},
+                        {"name": "contents", "type": "string"},
+                        {"name": "tAddr", "type": "address[]"},
+                        {"name": "trcTokenId", "type": "trcToken"}
+                    ]
+                },
+                "primaryType": "Mail",
+                "domain": {
+                    "name": "TRON Mail",
+                    "version": "1",
+                    "chainId": "0x2b6653dc",
+                    "verifyingContract": "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t"
+                },
+                "message": {
+                    "from": {
+                        "name": "Cow",
+                        "wallet": "TY2uroBeZ5trA9QT96aEWj32XLkAAhQ9R2",
+                        "trcTokenId": "1002000"
+                    },
+                    "to": {
+                        "name": "Bob",
+                        "wallet": "410b3e84ec677b3e63c99affcadb91a6b4e086798f",
+                        "trcTokenArr": ["1002000", "1002001"]
+                    },
+                    "contents": "Hello, Bob!",
+                    "tAddr": [
+                        "TQjHpeTEscirkvpjxGDCYu7xS2yeD9U9VB",
+                        "0xa614f803b6fd780986a42c78ec9c7f77e6ded13c"
+                    ],
+                    "trcTokenId": "1002000"
+                }
+            }"#;
+            let param = SignParam {
+                id: wallet.id.to_string(),
+                key: Some(Key::Password(TEST_PASSWORD.to_string())),
+                chain_type: "TRON".to_string(),
+                address: wallet.accounts.first().unwrap().address.to_string(),
+                input: Some(::prost_types::Any {
+                    type_url: "imtoken".to_string(),
+                    value: encode_message(TronTypedDataInput {
+                        data: typed_data.to_string(),
+                    })
+                    .unwrap(),
+                }),
+            };
+            let ret = call_api("tron_sign_typed_data", param).unwrap();
+            let output: TronMessageOutput = TronMessageOutput::decode(ret.as_slice()).unwrap();
+            assert_eq!(output.signature, "157a9647dbe42bca8d88ee928f2d5fe60806baae213b9f3855ebb413844b46f64fa52f3663079070b9e39a5ea459db744072f7bbadcb722659c56325ae7584891b");
+            remove_created_wallet(&wallet.id);
+        })
+    }
+
     #[test]
     pub fn test_sign_tron_tx_by_pk() {
         run_test(|| {
@@ -2183,7 +2621,10 @@ mod tests {
             let rsp: AccountsResponse = AccountsResponse::decode(ret.as_slice()).unwrap();
 
             let raw_data = "0a0202a22208e216e254e43ee10840c8cbe4e3df2d5a67080112630a2d747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e5472616e73666572436f6e747261637412320a15415c68cc82c87446f602f019e5fd797437f5b79cc212154156a6076cd1537fa317c2606e4edfa4acd3e8e92e18a08d06709084e1e3df2d".to_string();
-            let input = TronTxInput { raw_data };
+            let input = TronTxInput {
+                raw_data,
+                signatures: vec![],
+            };
             let tx = SignParam {
                 id: import_result.id.to_string(),
                 key: Some(Key::Password(TEST_PASSWORD.to_string())),
@@ -2340,7 +2781,10 @@ mod tests {
             let ret_bytes = get_derived_key(&encode_message(param).unwrap()).unwrap();
             let ret: DerivedKeyResult = DerivedKeyResult::decode(ret_bytes.as_slice()).unwrap();
             let raw_data = "0a0202a22208e216e254e43ee10840c8cbe4e3df2d5a67080112630a2d747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e5472616e73666572436f6e747261637412320a15415c68cc82c87446f602f019e5fd797437f5b79cc212154156a6076cd1537fa317c2606e4edfa4acd3e8e92e18a08d06709084e1e3df2d".to_string();
-            let input = TronTxInput { raw_data };
+            let input = TronTxInput {
+                raw_data,
+                signatures: vec![],
+            };
             let tx = SignParam {
                 id: import_result.id.to_string(),
                 key: Some(Key::DerivedKey(ret.derived_key)),
@@ -2548,6 +2992,10 @@ mod tests {
                     change_address: "".to_string(),
                     network: "TESTNET".to_string(),
                     seg_wit: "NONE".to_string(),
+                    outputs: vec![],
+                    op_return: "".to_string(),
+                    rbf: false,
+                    lock_time: 0,
                 };
                 let input_value = encode_message(tx_input).unwrap();
                 let tx = SignParam {
@@ -2585,7 +3033,10 @@ mod tests {
             let wallet = import_and_derive(derivation);
 
             let raw_data = "0a0202a22208e216e254e43ee10840c8cbe4e3df2d5a67080112630a2d747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e5472616e73666572436f6e747261637412320a15415c68cc82c87446f602f019e5fd797437f5b79cc212154156a6076cd1537fa317c2606e4edfa4acd3e8e92e18a08d06709084e1e3df2d".to_string();
-            let input = TronTxInput { raw_data };
+            let input = TronTxInput {
+                raw_data,
+                signatures: vec![],
+            };
             let input_value = encode_message(input).unwrap();
 
             let tx = SignParam {
@@ -2775,6 +3226,10 @@ mod tests {
             let output: TezosTxOut = TezosTxOut::decode(ret.as_slice()).unwrap();
             let expected_sign = "0df020458bdcfe24546488dd81e1bd7e2cb05379dc7c72ad626646ae22df5d3a652fdc4ffd2383dd5823a98fe158780928da07a3f0a234e23b759ce7b3a39a0c";
             assert_eq!(expected_sign, output.signature.as_str());
+            assert_eq!(
+                "ooAToA3tLei4BsV8Wu4to9AEctK3JzdiEyhDApbWLqRvXb4GwUb",
+                output.tx_hash
+            );
             remove_created_wallet(&wallet.id);
         })
     }
@@ -2824,6 +3279,10 @@ mod tests {
                     change_address: "".to_string(),
                     network: "MAINNET".to_string(),
                     seg_wit: "NONE".to_string(),
+                    outputs: vec![],
+                    op_return: "".to_string(),
+                    rbf: false,
+                    lock_time: 0,
                 };
                 let input_value = encode_message(tx_input).unwrap();
                 let tx = SignParam {
@@ -2841,12 +3300,14 @@ mod tests {
                 let rsp: BtcForkSignedTxOutput =
                     BtcForkSignedTxOutput::decode(ret.unwrap().as_slice()).unwrap();
                 assert_eq!(rsp.signature,
-                "01000000015884e5db9de218238671572340b207ee85b628074e7e467096c267266baf77a4000000006b483045022100b1f6443eed90df9f1c18a2bca4d641c963a6413f80d8941ca6bc9eecbaa72b99022046d80ae0652266299ada797f176289166b0599de1918817786931dede181a9230121026b5b6a9d041bc5187e0b34f9e496436c7bff261c6c1b5f3c06b433c61394b868ffffffff0220a10700000000001976a91415c4698fadd6a54dede98c2fbc62fb21b13b0d7788ac801a0600000000001976a914a6381e76634d662f9f66a1d0f43cc058102e98c588ac00000000"
+                "01000000015884e5db9de218238671572340b207ee85b628074e7e467096c267266baf77a4000000006a47304402201af10911537ed436a1ac92003c1079bc715061197d94b2f86139596658d8407a0220713031e9eed66702ffc45f254b6e0f25dd465cde145a07e4aa352eae399b54c10121026b5b6a9d041bc5187e0b34f9e496436c7bff261c6c1b5f3c06b433c61394b868ffffffff0220a10700000000001976a91415c4698fadd6a54dede98c2fbc62fb21b13b0d7788ac801a0600000000001976a914fc2d4694c549d615dc31e3db7a9a64e1245ca05188ac00000000"
                 );
                 assert_eq!(
                     rsp.tx_hash,
-                    "737f9d57b8d1c6f7eb112e6b21fbdf1eaf286c43234d48a36716e8147cac0500"
+                    "be1a942581cfed47441f1ac54a0409a0eb1acddf7f63624ee64ddf4b521ec164"
                 );
+                assert_eq!(rsp.change_address, "1PzPX9rGZkKLamuLQeACfhjVqPSTKSW9rs");
+                assert_eq!(rsp.change_path, "1/1");
             }
 
             remove_created_wallet(&import_result.id);
@@ -2854,81 +3315,1468 @@ mod tests {
     }
 
     #[test]
-    pub fn test_sign_eth_tx() {
+    pub fn test_derive_btc_segwit_account() {
         run_test(|| {
-            let derivation = Derivation {
-                chain_type: "ETHEREUM".to_string(),
-                path: "m/44'/60'/0'/0/0".to_string(),
+            let wallet = import_default_wallet();
+            let derivation = |path: &str, seg_wit: &str| Derivation {
+                chain_type: "BITCOIN".to_string(),
+                path: path.to_string(),
                 network: "MAINNET".to_string(),
-                seg_wit: "".to_string(),
+                seg_wit: seg_wit.to_string(),
                 chain_id: "".to_string(),
                 curve: "".to_string(),
             };
+            let param = KeystoreCommonDeriveParam {
+                id: wallet.id.to_string(),
+                password: TEST_PASSWORD.to_string(),
+                derivations: vec![
+                    derivation("m/84'/0'/0'/0/0", "SEGWIT"),
+                    derivation("m/49'/0'/0'/0/0", "P2WPKH"),
+                ],
+            };
+            let ret = call_api("keystore_common_derive", param).unwrap();
+            let accounts: AccountsResponse = AccountsResponse::decode(ret.as_slice()).unwrap();
+            // SEGWIT accounts are native P2WPKH, P2WPKH ones are nested in P2SH
+            assert_eq!(
+                accounts.accounts[0].address,
+                "bc1q05ec6z8df2vlzkxjxfd2xr3veypzm93wqnazr2"
+            );
+            assert_eq!(
+                accounts.accounts[1].address,
+                "3JmreiUEKn8P3SyLYmZ7C1YCd4r2nFy3Dp"
+            );
+
+            remove_created_wallet(&wallet.id);
+        })
+    }
 
+    #[test]
+    pub fn test_sign_btc_segwit_tx() {
+        run_test(|| {
+            let derivation = Derivation {
+                chain_type: "BITCOIN".to_string(),
+                path: "m/84'/0'/0'/0/0".to_string(),
+                network: "MAINNET".to_string(),
+                seg_wit: "SEGWIT".to_string(),
+                chain_id: "".to_string(),
+                curve: "".to_string(),
+            };
             let wallet = import_and_derive(derivation);
+            let address = wallet.accounts.first().unwrap().address.to_string();
 
-            let input = EthereumTxIn {
-                nonce: "0".to_string(),
-                to: "132D1eA7EF895b6834D25911656a434d7167091C".to_string(),
-                value: U256::zero().to_string(),
-                gas_price: "1000".to_string(),
-                gas: "21240".to_string(),
-                data: "7f7465737432000000000000000000000000000000000000000000000000000000600057"
+            let unspents = vec![Utxo {
+                tx_hash: "a477af6b2667c29670467e4e0728b685ee07b240235771862318e29ddbe58458"
                     .to_string(),
+                vout: 0,
+                amount: 100000,
+                address: address.to_string(),
+                script_pub_key: "00147d338d08ed4a99f158d2325aa30e2cc9022d962e".to_string(),
+                derived_path: "0/0".to_string(),
+                sequence: 0,
+            }];
+            let tx_input = BtcForkTxInput {
+                to: "bc1qum864wd9nwsc0u9ytkctz6wzrw6g7zdntm7f4e".to_string(),
+                amount: 50000,
+                unspents,
+                fee: 1000,
+                change_address_index: 0,
+                change_address: "".to_string(),
                 network: "MAINNET".to_string(),
-                access_list: "[]".to_string(),
-                max_priority_fee_per_gas: "100000".to_string(),
-                transaction_type: "2".to_string(),
+                seg_wit: "SEGWIT".to_string(),
+                outputs: vec![],
+                op_return: "".to_string(),
+                rbf: false,
+                lock_time: 0,
             };
-
             let tx = SignParam {
                 id: wallet.id.to_string(),
                 key: Some(Key::Password(TEST_PASSWORD.to_string())),
-                chain_type: "ETHEREUM".to_string(),
-                address: wallet.accounts.first().unwrap().address.to_string(),
+                chain_type: "BITCOIN".to_string(),
+                address: address.to_string(),
                 input: Some(::prost_types::Any {
                     type_url: "imtoken".to_string(),
-                    value: encode_message(input).unwrap(),
+                    value: encode_message(tx_input).unwrap(),
                 }),
             };
 
             let ret = call_api("sign_tx", tx).unwrap();
-            let _output: EthereumTxOut = EthereumTxOut::decode(ret.as_slice()).unwrap();
+            let rsp: BtcForkSignedTxOutput = BtcForkSignedTxOutput::decode(ret.as_slice()).unwrap();
+            // the input has an empty script sig, the signature and the key are in the witness
+            assert_eq!(rsp.signature, "020000000001015884e5db9de218238671572340b207ee85b628074e7e467096c267266baf77a40000000000ffffffff0250c3000000000000160014e6cfaab9a59ba187f0a45db0b169c21bb48f09b368bf00000000000016001465f66cf43a52c1db62619da4742ee36324514f0902483045022100bcddee90ef89b60dd8bfa78d4e905c042d6d96331a3a4a5c909653ed3102b660022042b42495f401c9bda5b386d26ab9dfea1fb241ea662a61fa6ada888135a458ef01210324778f934a20a9ca06cec3fb7176ccbc054278b9d5d7f0a1077582367af92e7500000000");
+            assert_eq!(
+                rsp.tx_hash,
+                "3c726b96b4f67a564f7d76a0571fe3fde7bd02853324a67f1a902c8d684a34e2"
+            );
+            assert_eq!(
+                rsp.change_address,
+                "bc1qvhmxeap62tqakcnpnkj8gthrvvj9zncfylp46g"
+            );
+            assert_eq!(rsp.change_path, "1/0");
+
             remove_created_wallet(&wallet.id);
         })
     }
 
-    fn import_solana_pk_store() -> WalletResult {
-        let param: PrivateKeyStoreImportParam = PrivateKeyStoreImportParam {
-            private_key: "f3f7c4290567ce10c0672bef4953cb4d8e416ba7f5b6b5c0a978e76860dae5b7"
-                .to_string(),
-            password: TEST_PASSWORD.to_string(),
-            name: "import_solana_pk_store".to_string(),
-            password_hint: "".to_string(),
-            overwrite: true,
-            encoding: "".to_string(),
-        };
-
-        let ret = private_key_store_import(&encode_message(param).unwrap()).unwrap();
-        WalletResult::decode(ret.as_slice()).unwrap()
-    }
-
-    fn import_solana_pk_store_1() -> WalletResult {
-        let param: PrivateKeyStoreImportParam = PrivateKeyStoreImportParam {
-            private_key: "bfd09d018175b9e8f167c4136d75ca37b7a592bcddd3ccbd7b2d1a093111b9b1"
-                .to_string(),
-            password: TEST_PASSWORD.to_string(),
-            name: "import_solana_pk_store".to_string(),
-            password_hint: "".to_string(),
-            overwrite: true,
-            encoding: "".to_string(),
-        };
-
-        let ret = private_key_store_import(&encode_message(param).unwrap()).unwrap();
-        WalletResult::decode(ret.as_slice()).unwrap()
-    }
     #[test]
-    pub fn test_sign_solana_tx() {
+    pub fn test_sign_btc_taproot_tx() {
+        run_test(|| {
+            let derivation = Derivation {
+                chain_type: "BITCOIN".to_string(),
+                path: "m/86'/0'/0'/0/0".to_string(),
+                network: "MAINNET".to_string(),
+                seg_wit: "P2TR".to_string(),
+                chain_id: "".to_string(),
+                curve: "".to_string(),
+            };
+            let wallet = import_and_derive(derivation);
+            let address = wallet.accounts.first().unwrap().address.to_string();
+            assert_eq!(
+                address,
+                "bc1pqvrla5hul9cqdtz60lwwn35zdcx363pyxua0trqnz3wx8hvjxzdsdevceu"
+            );
+
+            let unspents = vec![Utxo {
+                tx_hash: "a477af6b2667c29670467e4e0728b685ee07b240235771862318e29ddbe58458"
+                    .to_string(),
+                vout: 0,
+                amount: 100000,
+                address: address.to_string(),
+                script_pub_key:
+                    "51200307fed2fcf97006ac5a7fdce9c6826e0d1d4424373af58c13145c63dd92309b"
+                        .to_string(),
+                derived_path: "0/0".to_string(),
+                sequence: 0,
+            }];
+            let tx_input = BtcForkTxInput {
+                to: "bc1qum864wd9nwsc0u9ytkctz6wzrw6g7zdntm7f4e".to_string(),
+                amount: 50000,
+                unspents,
+                fee: 1000,
+                change_address_index: 0,
+                change_address: address.to_string(),
+                network: "MAINNET".to_string(),
+                seg_wit: "P2TR".to_string(),
+                outputs: vec![],
+                op_return: "".to_string(),
+                rbf: false,
+                lock_time: 0,
+            };
+            let tx = SignParam {
+                id: wallet.id.to_string(),
+                key: Some(Key::Password(TEST_PASSWORD.to_string())),
+                chain_type: "BITCOIN".to_string(),
+                address: address.to_string(),
+                input: Some(::prost_types::Any {
+                    type_url: "imtoken".to_string(),
+                    value: encode_message(tx_input).unwrap(),
+                }),
+            };
+
+            let ret = call_api("sign_tx", tx).unwrap();
+            let rsp: BtcForkSignedTxOutput = BtcForkSignedTxOutput::decode(ret.as_slice()).unwrap();
+            assert_eq!(rsp.signature, "020000000001015884e5db9de218238671572340b207ee85b628074e7e467096c267266baf77a40000000000ffffffff0250c3000000000000160014e6cfaab9a59ba187f0a45db0b169c21bb48f09b368bf0000000000002251200307fed2fcf97006ac5a7fdce9c6826e0d1d4424373af58c13145c63dd92309b014017f10a4bff014ef1d582a5f7574b92a0d540a7140a948b9756df9cc06e30a0a353f8454adc9692c27ddd0ed7f5d005f62eaa10652122410c61801e50338949d100000000");
+            assert_eq!(
+                rsp.tx_hash,
+                "527c8cb0e3dc957d4eed0af8aba155e1586a682310f63ec73a55bbe6a4f07208"
+            );
+
+            remove_created_wallet(&wallet.id);
+        })
+    }
+
+    #[test]
+    pub fn test_btc_fork_bump_fee() {
+        run_test(|| {
+            let derivation = Derivation {
+                chain_type: "BITCOIN".to_string(),
+                path: "m/86'/0'/0'/0/0".to_string(),
+                network: "MAINNET".to_string(),
+                seg_wit: "P2TR".to_string(),
+                chain_id: "".to_string(),
+                curve: "".to_string(),
+            };
+            let wallet = import_and_derive(derivation);
+            let address = wallet.accounts.first().unwrap().address.to_string();
+
+            let unspents = vec![Utxo {
+                tx_hash: "a477af6b2667c29670467e4e0728b685ee07b240235771862318e29ddbe58458"
+                    .to_string(),
+                vout: 0,
+                amount: 100000,
+                address: address.to_string(),
+                script_pub_key:
+                    "51200307fed2fcf97006ac5a7fdce9c6826e0d1d4424373af58c13145c63dd92309b"
+                        .to_string(),
+                derived_path: "0/0".to_string(),
+                sequence: 0,
+            }];
+            let tx_input = BtcForkTxInput {
+                to: "bc1qum864wd9nwsc0u9ytkctz6wzrw6g7zdntm7f4e".to_string(),
+                amount: 50000,
+                unspents,
+                fee: 1000,
+                change_address_index: 0,
+                change_address: address.to_string(),
+                network: "MAINNET".to_string(),
+                seg_wit: "P2TR".to_string(),
+                outputs: vec![],
+                op_return: "".to_string(),
+                rbf: true,
+                lock_time: 0,
+            };
+            let sign_param = |chain_type: &str, value: Vec<u8>| SignParam {
+                id: wallet.id.to_string(),
+                key: Some(Key::Password(TEST_PASSWORD.to_string())),
+                chain_type: chain_type.to_string(),
+                address: address.to_string(),
+                input: Some(::prost_types::Any {
+                    type_url: "imtoken".to_string(),
+                    value,
+                }),
+            };
+
+            let ret = call_api(
+                "sign_tx",
+                sign_param("BITCOIN", encode_message(tx_input.clone()).unwrap()),
+            )
+            .unwrap();
+            let rsp: BtcForkSignedTxOutput = BtcForkSignedTxOutput::decode(ret.as_slice()).unwrap();
+            assert_eq!(rsp.signature, "020000000001015884e5db9de218238671572340b207ee85b628074e7e467096c267266baf77a40000000000fdffffff0250c3000000000000160014e6cfaab9a59ba187f0a45db0b169c21bb48f09b368bf0000000000002251200307fed2fcf97006ac5a7fdce9c6826e0d1d4424373af58c13145c63dd92309b014037414d273d0b99f07bddacdf750736c039bad4710766cf13616cbd04335f5554fd589e72e3977f64608cac4a1ffabd450ae4704a43b8861a53531b6870a8c67e00000000");
+            assert_eq!(
+                rsp.tx_hash,
+                "5c23399ef92a8ca389e763d1cb8b9f38d367f4f1890d328142e7f7aaa27a96ed"
+            );
+
+            let mut bump_input = BtcForkFeeBumpInput {
+                tx: Some(tx_input),
+                signed_tx: rsp.signature,
+                fee: 2000,
+            };
+            let ret = call_api(
+                "btc_fork_bump_fee",
+                sign_param("BITCOIN", encode_message(bump_input.clone()).unwrap()),
+            )
+            .unwrap();
+            let rsp: BtcForkSignedTxOutput = BtcForkSignedTxOutput::decode(ret.as_slice()).unwrap();
+            assert_eq!(rsp.signature, "020000000001015884e5db9de218238671572340b207ee85b628074e7e467096c267266baf77a40000000000fdffffff0250c3000000000000160014e6cfaab9a59ba187f0a45db0b169c21bb48f09b380bb0000000000002251200307fed2fcf97006ac5a7fdce9c6826e0d1d4424373af58c13145c63dd92309b0140504b9538b55eececbcd6a4b6d6e030e639aff53379fbd35a2a840ded78548400d59a439136f5c4a73751d6ee800127974cefc7a4de316b2faf222f67868be8d600000000");
+            assert_eq!(
+                rsp.tx_hash,
+                "b27db5ff5e45e8ca055ddc60375bc2dab095d057107939ab7fc8c565d43cdb4c"
+            );
+
+            // the replaced transaction weighs 142 vbytes
+            bump_input.fee = 1141;
+            let ret = call_api(
+                "btc_fork_bump_fee",
+                sign_param("BITCOIN", encode_message(bump_input.clone()).unwrap()),
+            );
+            assert_eq!(format!("{}", ret.err().unwrap()), "fee_bump_too_small");
+
+            let ret = call_api(
+                "btc_fork_bump_fee",
+                sign_param("BITCOINCASH", encode_message(bump_input).unwrap()),
+            );
+            assert_eq!(
+                format!("{}", ret.err().unwrap()),
+                "btc_fork_bump_fee unsupported_chain"
+            );
+
+            remove_created_wallet(&wallet.id);
+        })
+    }
+
+    #[test]
+    pub fn test_btc_sign_message() {
+        run_test(|| {
+            let wallet = import_default_wallet();
+            let derivation = |chain_type: &str, path: &str, seg_wit: &str| Derivation {
+                chain_type: chain_type.to_string(),
+                path: path.to_string(),
+                network: "MAINNET".to_string(),
+                seg_wit: seg_wit.to_string(),
+                chain_id: "".to_string(),
+                curve: "".to_string(),
+            };
+            let param = KeystoreCommonDeriveParam {
+                id: wallet.id.to_string(),
+                password: TEST_PASSWORD.to_string(),
+                derivations: vec![
+                    derivation("LITECOIN", "m/44'/2'/0'/0/0", "NONE"),
+                    derivation("BITCOIN", "m/49'/0'/0'/0/0", "P2WPKH"),
+                    derivation("BITCOIN", "m/86'/0'/0'/0/0", "P2TR"),
+                ],
+            };
+            call_api("keystore_common_derive", param).unwrap();
+
+            let vectors = vec![
+                (
+                    "LITECOIN",
+                    "Ldfdegx3hJygDuFDUA7Rkzjjx8gfFhP9DP",
+                    "",
+                    "IPq5fY/jKapUNS+bzjbIWA1/y5osA0WReB+YfR5gfMt3McMNiBVAeEiezmm6oQ41dmOBCYy06gQAbn1VvETBPrY=",
+                ),
+                (
+                    "BITCOIN",
+                    "3JmreiUEKn8P3SyLYmZ7C1YCd4r2nFy3Dp",
+                    "",
+                    "I9vMHPmn2idEnSPXJGxdufLsusIMyiSl3OixtZKChxksbE0lp3QYONulcETq/tCOS171QF4aJnupvbCcGXRDxRo=",
+                ),
+                (
+                    "BITCOIN",
+                    "3JmreiUEKn8P3SyLYmZ7C1YCd4r2nFy3Dp",
+                    "BIP322_FULL",
+                    "AAAAAAABAb9N4yPtcluy0oSgTMGekEnriDJcKtq9XhCCps8EX+2XAAAAABcWABRWY55fpX2tipiIdJBR/6KIN/Go3QAAAAABAAAAAAAAAAABagJHMEQCIACuPJQ5aBpLoF500IBSEPccMfkhMLzsKJNNKb6vX0+JAiAyfL+KGJ7uTLNaJZn2/Zewd0vsLkGR10s0YPdGcy+KAwEhAwNmlcXz3i4nkrFw9ZZ51NuIqFFnKAEuqkKiLOb4v1k7AAAAAA==",
+                ),
+                (
+                    "BITCOIN",
+                    "bc1pqvrla5hul9cqdtz60lwwn35zdcx363pyxua0trqnz3wx8hvjxzdsdevceu",
+                    "",
+                    "AUBdZDODCfe0TTJgN/hE76cX76YFbsnDDYK+SrikQwALz66M55GzpT0IIpt9c4RtJAJNBHjM74UCBWOtElQY2DcK",
+                ),
+            ];
+            for (chain_type, address, signature_type, expected) in vectors {
+                let param = SignParam {
+                    id: wallet.id.to_string(),
+                    key: Some(Key::Password(TEST_PASSWORD.to_string())),
+                    chain_type: chain_type.to_string(),
+                    address: address.to_string(),
+                    input: Some(::prost_types::Any {
+                        type_url: "imtoken".to_string(),
+                        value: encode_message(BtcMessageInput {
+                            message: "hello world".to_string(),
+                            signature_type: signature_type.to_string(),
+                        })
+                        .unwrap(),
+                    }),
+                };
+                let ret = call_api("btc_sign_message", param).unwrap();
+                let rsp: BtcMessageOutput = BtcMessageOutput::decode(ret.as_slice()).unwrap();
+                assert_eq!(rsp.signature, expected);
+
+                let ret = call_api(
+                    "btc_verify_message",
+                    BtcVerifyMessageInput {
+                        chain_type: chain_type.to_string(),
+                        address: address.to_string(),
+                        message: "hello world".to_string(),
+                        signature: rsp.signature,
+                    },
+                )
+                .unwrap();
+                let rsp: BtcVerifyMessageOutput =
+                    BtcVerifyMessageOutput::decode(ret.as_slice()).unwrap();
+                assert!(rsp.is_valid);
+            }
+
+            remove_created_wallet(&wallet.id);
+        })
+    }
+
+    #[test]
+    pub fn test_sign_psbt() {
+        run_test(|| {
+            let wallet = import_default_wallet();
+            let param = KeystoreCommonDeriveParam {
+                id: wallet.id.to_string(),
+                password: TEST_PASSWORD.to_string(),
+                derivations: vec![
+                    Derivation {
+                        chain_type: "BITCOIN".to_string(),
+                        path: "m/49'/0'/0'/0/0".to_string(),
+                        network: "MAINNET".to_string(),
+                        seg_wit: "P2WPKH".to_string(),
+                        chain_id: "".to_string(),
+                        curve: "".to_string(),
+                    },
+                    Derivation {
+                        chain_type: "BITCOIN".to_string(),
+                        path: "m/86'/0'/0'/0/0".to_string(),
+                        network: "MAINNET".to_string(),
+                        seg_wit: "P2TR".to_string(),
+                        chain_id: "".to_string(),
+                        curve: "".to_string(),
+                    },
+                ],
+            };
+            let ret = call_api("keystore_common_derive", param).unwrap();
+            let accounts: AccountsResponse = AccountsResponse::decode(ret.as_slice()).unwrap();
+
+            // a P2SH-P2WPKH input of m/49'/0'/0'/1/2 and a P2TR input of m/86'/0'/0'/0/0
+            let psbt = "cHNidP8BAKYCAAAAAtu7MJU4QRQafEQYM9t4GFTUUD+exsQROO/ChLMpAaY/AAAAAAD9////yrS0jX/ebx1QPDY9K+FvSa7vZ4wJbGpSHsVtMtZC038AAAAAAP3///8CYOoAAAAAAAAWABTmz6q5pZuhh/CkXbCxacIbtI8Js3CUAAAAAAAAIlEgAwf+0vz5cAasWn/c6caCbg0dRCQ3OvWMExRcY92SMJsAAAAAAAEBIFDDAAAAAAAAF6kUIdMbIkH+BJOirwmbc+6GQ+uvm0uHAQQWABQioY+NIPdCe9rMCnAr1i8F249X/iIGA2iXd5aisEWoyOfzDFLexJ4D8oaqufpmEifPsE1iQYMiGBRo26kxAACAAAAAgAAAAIABAAAAAgAAAAABAStQwwAAAAAAACJRIAMH/tL8+XAGrFp/3OnGgm4NHUQkNzr1jBMUXGPdkjCbIRYS1Q+zuje3ZiGc28bjv4vxT9RCfM2CLtPPBxmrDIScmxkAFGjbqVYAAIAAAACAAAAAgAAAAAAAAAAAAAAA";
+            let tx = SignParam {
+                id: wallet.id.to_string(),
+                key: Some(Key::Password(TEST_PASSWORD.to_string())),
+                chain_type: "BITCOIN".to_string(),
+                address: accounts.accounts[0].address.to_string(),
+                input: Some(::prost_types::Any {
+                    type_url: "imtoken".to_string(),
+                    value: encode_message(PsbtInput {
+                        psbt: psbt.to_string(),
+                    })
+                    .unwrap(),
+                }),
+            };
+            let ret = call_api("sign_psbt", tx).unwrap();
+            let rsp: PsbtOutput = PsbtOutput::decode(ret.as_slice()).unwrap();
+            assert_eq!(rsp.signed_inputs, vec![0, 1]);
+            assert_eq!(rsp.psbt, "cHNidP8BAKYCAAAAAtu7MJU4QRQafEQYM9t4GFTUUD+exsQROO/ChLMpAaY/AAAAAAD9////yrS0jX/ebx1QPDY9K+FvSa7vZ4wJbGpSHsVtMtZC038AAAAAAP3///8CYOoAAAAAAAAWABTmz6q5pZuhh/CkXbCxacIbtI8Js3CUAAAAAAAAIlEgAwf+0vz5cAasWn/c6caCbg0dRCQ3OvWMExRcY92SMJsAAAAAAAEBIFDDAAAAAAAAF6kUIdMbIkH+BJOirwmbc+6GQ+uvm0uHIgIDaJd3lqKwRajI5/MMUt7EngPyhqq5+mYSJ8+wTWJBgyJIMEUCIQDwbNbh9RZ7HMQbPqjYTCzKtBa+BJrunrLI5AIi7Kr7qwIgVv4GmEaL0LF0oC+dv1jDx2GiUnK5p2lIHX1C6rafRIkBAQQWABQioY+NIPdCe9rMCnAr1i8F249X/iIGA2iXd5aisEWoyOfzDFLexJ4D8oaqufpmEifPsE1iQYMiGBRo26kxAACAAAAAgAAAAIABAAAAAgAAAAABAStQwwAAAAAAACJRIAMH/tL8+XAGrFp/3OnGgm4NHUQkNzr1jBMUXGPdkjCbARNAEX2BuuipD6HNfNlAnND4fSe1C1SE9e7XxOEoSZp/67C20OTweRUX+pXkRZJKeroxn9ux4RMxDotpbY/6z4JMPiEWEtUPs7o3t2YhnNvG47+L8U/UQnzNgi7TzwcZqwyEnJsZABRo26lWAACAAAAAgAAAAIAAAAAAAAAAAAAAAA==");
+
+            let ret = call_api("finalize_psbt", PsbtInput { psbt: rsp.psbt }).unwrap();
+            let rsp: BtcForkSignedTxOutput = BtcForkSignedTxOutput::decode(ret.as_slice()).unwrap();
+            assert_eq!(rsp.signature, "02000000000102dbbb30953841141a7c441833db781854d4503f9ec6c41138efc284b32901a63f000000001716001422a18f8d20f7427bdacc0a702bd62f05db8f57fefdffffffcab4b48d7fde6f1d503c363d2be16f49aeef678c096c6a521ec56d32d642d37f0000000000fdffffff0260ea000000000000160014e6cfaab9a59ba187f0a45db0b169c21bb48f09b370940000000000002251200307fed2fcf97006ac5a7fdce9c6826e0d1d4424373af58c13145c63dd92309b02483045022100f06cd6e1f5167b1cc41b3ea8d84c2ccab416be049aee9eb2c8e40222ecaafbab022056fe0698468bd0b174a02f9dbf58c3c761a25272b9a769481d7d42eab69f448901210368977796a2b045a8c8e7f30c52dec49e03f286aab9fa661227cfb04d624183220140117d81bae8a90fa1cd7cd9409cd0f87d27b50b5484f5eed7c4e128499a7febb0b6d0e4f0791517fa95e445924a7aba319fdbb1e113310e8b696d8ffacf824c3e00000000");
+            assert_eq!(
+                rsp.tx_hash,
+                "4d4104a36abb35aa21648089a0f21e7a0b4bf01a8b904192f294cd7511b62497"
+            );
+
+            let ret = call_api(
+                "finalize_psbt",
+                PsbtInput {
+                    psbt: psbt.to_string(),
+                },
+            );
+            assert_eq!(
+                format!("{}", ret.err().unwrap()),
+                "psbt_input_not_finalizable"
+            );
+
+            remove_created_wallet(&wallet.id);
+        })
+    }
+
+    #[test]
+    pub fn test_btc_fork_multisig() {
+        run_test(|| {
+            let wallet = import_default_wallet();
+            let param = KeystoreCommonDeriveParam {
+                id: wallet.id.to_string(),
+                password: TEST_PASSWORD.to_string(),
+                derivations: vec![Derivation {
+                    chain_type: "BITCOIN".to_string(),
+                    path: "m/48'/0'/0'/0/0".to_string(),
+                    network: "MAINNET".to_string(),
+                    seg_wit: "NONE".to_string(),
+                    chain_id: "".to_string(),
+                    curve: "".to_string(),
+                }],
+            };
+            let ret = call_api("keystore_common_derive", param).unwrap();
+            let accounts: AccountsResponse = AccountsResponse::decode(ret.as_slice()).unwrap();
+
+            let mut param = BtcForkMultisigAddressParam {
+                id: wallet.id.to_string(),
+                password: TEST_PASSWORD.to_string(),
+                chain_type: "BITCOIN".to_string(),
+                address: accounts.accounts[0].address.to_string(),
+                script_type: "P2WSH".to_string(),
+                threshold: 2,
+                cosigner_xpubs: vec![
+                    "xpub6CKZtUaK1YHpQbg6CLaGRmsMKLQB1iKzsvmxtyHD6X7gzLqCB2VNZYd1XCxrccQnE8hhDxtYbR1Sakkvisy2J4CcTxWeeGjmkasCoNS9vZm".to_string(),
+                    "xpub6CETL9tkmWBQkYmxxxGAEzVZNHCZLx24pj58FYqe41qhXgyAcabP9iyXaVCJXcZWcVbzttVPdoJpJvYNfBnQeFcunvuxcsKKAwxMw6S5S7s".to_string(),
+                ],
+                path: "0/0".to_string(),
+            };
+            let ret = call_api("btc_fork_multisig_address", param.clone()).unwrap();
+            let output = BtcForkMultisigAddressOutput::decode(ret.as_slice()).unwrap();
+            assert_eq!(
+                output.address,
+                "bc1qxrr0fzuefs2tn7gfjatey3rxrc2g4uehmduzn8dmavtp3q6p3afsa02c5u"
+            );
+            assert_eq!(output.witness_script, "522102c5e54fb0559fccdc206f819995dd361cef9018394c329e4a190747938d8f126021030c3005c53914c467a5a0af727f6ce62ec50f99c76c769dabd00c1f665cac18a52103d474fb6b20cc07d4fcdc2f665f4b89bf9ae5ade1a8dfc3db54e9ef942053de6b53ae");
+
+            param.script_type = "P2SH".to_string();
+            let ret = call_api("btc_fork_multisig_address", param.clone()).unwrap();
+            let output = BtcForkMultisigAddressOutput::decode(ret.as_slice()).unwrap();
+            assert_eq!(output.address, "3AnQQiWgzLALrDZ8bSHQdGrqUgnS5QKMBo");
+
+            param.cosigner_xpubs[1] = "xpub".to_string();
+            let ret = call_api("btc_fork_multisig_address", param.clone());
+            assert_eq!(format!("{}", ret.err().unwrap()), "invalid_xpub");
+
+            param.password = "WRONG PASSWORD".to_string();
+            let ret = call_api("btc_fork_multisig_address", param);
+            assert_eq!(format!("{}", ret.err().unwrap()), "password_incorrect");
+
+            // 2-of-3 P2SH, P2SH-P2WSH and P2WSH inputs, the second cosigner has signed
+            let psbt = "cHNidP8BAKQCAAAAAxbHlNL5In1RpQxhdkUdqjgst2bHHK3V7XIPryP4mWmzAAAAAAD9////5N0BOVxETJk/vI/yqx5aMu0TzygXpTP/QZyA9HjOE3AAAAAAAP3///+0RnNiqdD2rqeoZPgTBs9KtQQFlgZ73IvdmgegIOafbwAAAAAA/f///wEITAEAAAAAABYAFAABAgMEBQYHCAkKCwwNDg8QERITAAAAAAABAFMBAAAAAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAAAAAAD/////ASBOAAAAAAAAF6kUY737ZRr+ZJeiJZw8W4O74KWormuHAAAAAAEEaVIhAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgIQMMMAXFORTEZ6Wgr3J/bOYuxQ+Zx2x2navQDB9mXKwYpSED1HT7ayDMB9T83C9mX0uJv5rlreGo38PbVOnvlCBT3mtTriIGAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGAwwwBcU5FMRnpaCvcn9s5i7FD5nHbHadq9AMH2ZcrBilGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGA9R0+2sgzAfU/NwvZl9Lib+a5a3hqN/D21Tp75QgU95rGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAAAABASAwdQAAAAAAABepFNOUoV+rstZknr008uY+q7X5zBvNhwEEIgAgMMb0i5lMFLn5CZdXkkRmHhSK8zfbeCmdu+sWGINBj1MBBWlSIQLF5U+wVZ/M3CBvgZmV3TYc75AYOUwynkoZB0eTjY8SYCEDDDAFxTkUxGeloK9yf2zmLsUPmcdsdp2r0AwfZlysGKUhA9R0+2sgzAfU/NwvZl9Lib+a5a3hqN/D21Tp75QgU95rU64iBgLF5U+wVZ/M3CBvgZmV3TYc75AYOUwynkoZB0eTjY8SYBgAAAAAMAAAgAAAAIAAAACAAAAAAAAAAAAiBgMMMAXFORTEZ6Wgr3J/bOYuxQ+Zx2x2navQDB9mXKwYpRgAAAAAMAAAgAAAAIAAAACAAAAAAAAAAAAiBgPUdPtrIMwH1PzcL2ZfS4m/muWt4ajfw9tU6e+UIFPeaxgAAAAAMAAAgAAAAIAAAACAAAAAAAAAAAAAAQErQJwAAAAAAAAiACAwxvSLmUwUufkJl1eSRGYeFIrzN9t4KZ276xYYg0GPUwEFaVIhAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgIQMMMAXFORTEZ6Wgr3J/bOYuxQ+Zx2x2navQDB9mXKwYpSED1HT7ayDMB9T83C9mX0uJv5rlreGo38PbVOnvlCBT3mtTriIGAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGAwwwBcU5FMRnpaCvcn9s5i7FD5nHbHadq9AMH2ZcrBilGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGA9R0+2sgzAfU/NwvZl9Lib+a5a3hqN/D21Tp75QgU95rGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAAAAA";
+            let cosigner_psbt = "cHNidP8BAKQCAAAAAxbHlNL5In1RpQxhdkUdqjgst2bHHK3V7XIPryP4mWmzAAAAAAD9////5N0BOVxETJk/vI/yqx5aMu0TzygXpTP/QZyA9HjOE3AAAAAAAP3///+0RnNiqdD2rqeoZPgTBs9KtQQFlgZ73IvdmgegIOafbwAAAAAA/f///wEITAEAAAAAABYAFAABAgMEBQYHCAkKCwwNDg8QERITAAAAAAABAFMBAAAAAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAAAAAAD/////ASBOAAAAAAAAF6kUY737ZRr+ZJeiJZw8W4O74KWormuHAAAAACICAwwwBcU5FMRnpaCvcn9s5i7FD5nHbHadq9AMH2ZcrBilSDBFAiEAvZ6cL1uGma8dCQ75SMl6F+siN+6OQqHwm/zK1a5htSgCIDQ6+ZOHb5TmFeJkAU70oN3OzkE1fYV28Dnnbj2G5DAYAQEEaVIhAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgIQMMMAXFORTEZ6Wgr3J/bOYuxQ+Zx2x2navQDB9mXKwYpSED1HT7ayDMB9T83C9mX0uJv5rlreGo38PbVOnvlCBT3mtTriIGAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGAwwwBcU5FMRnpaCvcn9s5i7FD5nHbHadq9AMH2ZcrBilGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGA9R0+2sgzAfU/NwvZl9Lib+a5a3hqN/D21Tp75QgU95rGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAAAABASAwdQAAAAAAABepFNOUoV+rstZknr008uY+q7X5zBvNhyICAwwwBcU5FMRnpaCvcn9s5i7FD5nHbHadq9AMH2ZcrBilRzBEAiBrvQaRngeyqQRJF/tV1by+jeR0QJfGmkQ48hbvrubBbAIgQB4HXC2yEBEZw/TxeMpUZXuzkuyLIxxlDFO7pn43f8cBAQQiACAwxvSLmUwUufkJl1eSRGYeFIrzN9t4KZ276xYYg0GPUwEFaVIhAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgIQMMMAXFORTEZ6Wgr3J/bOYuxQ+Zx2x2navQDB9mXKwYpSED1HT7ayDMB9T83C9mX0uJv5rlreGo38PbVOnvlCBT3mtTriIGAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGAwwwBcU5FMRnpaCvcn9s5i7FD5nHbHadq9AMH2ZcrBilGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGA9R0+2sgzAfU/NwvZl9Lib+a5a3hqN/D21Tp75QgU95rGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAAAABAStAnAAAAAAAACIAIDDG9IuZTBS5+QmXV5JEZh4UivM323gpnbvrFhiDQY9TIgIDDDAFxTkUxGeloK9yf2zmLsUPmcdsdp2r0AwfZlysGKVHMEQCIHE0qjnRpcUZl6e1udgh8OtFs0S+foBAHJCZK+bwkMMgAiBQWwh7PYeqshpu7WcAhRGrtQrnT+LbITmL2ji4qRFm/gEBBWlSIQLF5U+wVZ/M3CBvgZmV3TYc75AYOUwynkoZB0eTjY8SYCEDDDAFxTkUxGeloK9yf2zmLsUPmcdsdp2r0AwfZlysGKUhA9R0+2sgzAfU/NwvZl9Lib+a5a3hqN/D21Tp75QgU95rU64iBgLF5U+wVZ/M3CBvgZmV3TYc75AYOUwynkoZB0eTjY8SYBgAAAAAMAAAgAAAAIAAAACAAAAAAAAAAAAiBgMMMAXFORTEZ6Wgr3J/bOYuxQ+Zx2x2navQDB9mXKwYpRgAAAAAMAAAgAAAAIAAAACAAAAAAAAAAAAiBgPUdPtrIMwH1PzcL2ZfS4m/muWt4ajfw9tU6e+UIFPeaxgAAAAAMAAAgAAAAIAAAACAAAAAAAAAAAAAAA==";
+            let tx = SignParam {
+                id: wallet.id.to_string(),
+                key: Some(Key::Password(TEST_PASSWORD.to_string())),
+                chain_type: "BITCOIN".to_string(),
+                address: accounts.accounts[0].address.to_string(),
+                input: Some(::prost_types::Any {
+                    type_url: "imtoken".to_string(),
+                    value: encode_message(PsbtInput {
+                        psbt: psbt.to_string(),
+                    })
+                    .unwrap(),
+                }),
+            };
+            let ret = call_api("sign_psbt", tx).unwrap();
+            let rsp: PsbtOutput = PsbtOutput::decode(ret.as_slice()).unwrap();
+            assert_eq!(rsp.signed_inputs, vec![0, 1, 2]);
+            assert_eq!(rsp.psbt, "cHNidP8BAKQCAAAAAxbHlNL5In1RpQxhdkUdqjgst2bHHK3V7XIPryP4mWmzAAAAAAD9////5N0BOVxETJk/vI/yqx5aMu0TzygXpTP/QZyA9HjOE3AAAAAAAP3///+0RnNiqdD2rqeoZPgTBs9KtQQFlgZ73IvdmgegIOafbwAAAAAA/f///wEITAEAAAAAABYAFAABAgMEBQYHCAkKCwwNDg8QERITAAAAAAABAFMBAAAAAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAAAAAAD/////ASBOAAAAAAAAF6kUY737ZRr+ZJeiJZw8W4O74KWormuHAAAAACICAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgSDBFAiEA7l194+i0DYl1tyCjBr0hGatKWUG+ZuHaLoxmuX7ZDsoCIFmC5E5DhJrs1C6Jyft2jirzce1bzdtZkxXKxYRObzk0AQEEaVIhAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgIQMMMAXFORTEZ6Wgr3J/bOYuxQ+Zx2x2navQDB9mXKwYpSED1HT7ayDMB9T83C9mX0uJv5rlreGo38PbVOnvlCBT3mtTriIGAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGAwwwBcU5FMRnpaCvcn9s5i7FD5nHbHadq9AMH2ZcrBilGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGA9R0+2sgzAfU/NwvZl9Lib+a5a3hqN/D21Tp75QgU95rGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAAAABASAwdQAAAAAAABepFNOUoV+rstZknr008uY+q7X5zBvNhyICAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgRzBEAiBlMIZvdUv2Mds8HNz3jMaWV0OJS5eMUmwB6D73o9dJ8QIgYIGTj/bDkxNMzbG+oqxfd6nLJj/3Y0YHM9e/0cgDIr0BAQQiACAwxvSLmUwUufkJl1eSRGYeFIrzN9t4KZ276xYYg0GPUwEFaVIhAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgIQMMMAXFORTEZ6Wgr3J/bOYuxQ+Zx2x2navQDB9mXKwYpSED1HT7ayDMB9T83C9mX0uJv5rlreGo38PbVOnvlCBT3mtTriIGAsXlT7BVn8zcIG+BmZXdNhzvkBg5TDKeShkHR5ONjxJgGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGAwwwBcU5FMRnpaCvcn9s5i7FD5nHbHadq9AMH2ZcrBilGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAACIGA9R0+2sgzAfU/NwvZl9Lib+a5a3hqN/D21Tp75QgU95rGAAAAAAwAACAAAAAgAAAAIAAAAAAAAAAAAABAStAnAAAAAAAACIAIDDG9IuZTBS5+QmXV5JEZh4UivM323gpnbvrFhiDQY9TIgICxeVPsFWfzNwgb4GZld02HO+QGDlMMp5KGQdHk42PEmBHMEQCIE3VNH1oxndaU9LqIX4czMW74XKrA6F4N1hBCDd9hem3AiBQGlUxU2UjPZhp9E82lO76DgkjDhSyLD9F1bzIJI0HhQEBBWlSIQLF5U+wVZ/M3CBvgZmV3TYc75AYOUwynkoZB0eTjY8SYCEDDDAFxTkUxGeloK9yf2zmLsUPmcdsdp2r0AwfZlysGKUhA9R0+2sgzAfU/NwvZl9Lib+a5a3hqN/D21Tp75QgU95rU64iBgLF5U+wVZ/M3CBvgZmV3TYc75AYOUwynkoZB0eTjY8SYBgAAAAAMAAAgAAAAIAAAACAAAAAAAAAAAAiBgMMMAXFORTEZ6Wgr3J/bOYuxQ+Zx2x2navQDB9mXKwYpRgAAAAAMAAAgAAAAIAAAACAAAAAAAAAAAAiBgPUdPtrIMwH1PzcL2ZfS4m/muWt4ajfw9tU6e+UIFPeaxgAAAAAMAAAgAAAAIAAAACAAAAAAAAAAAAAAA==");
+
+            let ret = call_api(
+                "combine_psbt",
+                PsbtCombineInput {
+                    psbts: vec![rsp.psbt, cosigner_psbt.to_string()],
+                },
+            )
+            .unwrap();
+            let combined: PsbtInput = PsbtInput::decode(ret.as_slice()).unwrap();
+            let ret = call_api("finalize_psbt", combined).unwrap();
+            let rsp: BtcForkSignedTxOutput = BtcForkSignedTxOutput::decode(ret.as_slice()).unwrap();
+            assert_eq!(
+                rsp.tx_hash,
+                "2c9b3092282d79a1838c036270fc6ab12844ba7c994b06344bfd1dcc70942d56"
+            );
+
+            remove_created_wallet(&wallet.id);
+        })
+    }
+
+    #[test]
+    pub fn test_btc_fork_descriptor() {
+        run_test(|| {
+            let wallet = import_default_wallet();
+            let param = KeystoreCommonDeriveParam {
+                id: wallet.id.to_string(),
+                password: TEST_PASSWORD.to_string(),
+                derivations: vec![Derivation {
+                    chain_type: "BITCOIN".to_string(),
+                    path: "m/49'/0'/0'/0/0".to_string(),
+                    network: "MAINNET".to_string(),
+                    seg_wit: "P2WPKH".to_string(),
+                    chain_id: "".to_string(),
+                    curve: "".to_string(),
+                }],
+            };
+            let ret = call_api("keystore_common_derive", param).unwrap();
+            let accounts: AccountsResponse = AccountsResponse::decode(ret.as_slice()).unwrap();
+
+            let mut param = BtcForkExportDescriptorParam {
+                id: wallet.id.to_string(),
+                password: TEST_PASSWORD.to_string(),
+                chain_type: "BITCOIN".to_string(),
+                address: accounts.accounts[0].address.to_string(),
+            };
+            let ret = call_api("btc_fork_export_descriptor", param.clone()).unwrap();
+            let output = BtcForkDescriptorOutput::decode(ret.as_slice()).unwrap();
+            assert_eq!(output.descriptor, "sh(wpkh([1468dba9/49'/0'/0']xpub6Boii2KSAfEv7EhbBuopXKB2Gshi8kMpTGWyHuY9BHwYA8qPeu7ZYdnnXCuUdednhwyjyK2Z8gJD2AfawgBHp3Kkf2GjBjzEQAyJ3uJ4SuG/0/*))#xsne6wl7");
+            assert_eq!(output.change_descriptor, "sh(wpkh([1468dba9/49'/0'/0']xpub6Boii2KSAfEv7EhbBuopXKB2Gshi8kMpTGWyHuY9BHwYA8qPeu7ZYdnnXCuUdednhwyjyK2Z8gJD2AfawgBHp3Kkf2GjBjzEQAyJ3uJ4SuG/1/*))#n3a0z32p");
+
+            param.password = "WRONG PASSWORD".to_string();
+            let ret = call_api("btc_fork_export_descriptor", param);
+            assert_eq!(format!("{}", ret.err().unwrap()), "password_incorrect");
+
+            let mut param = BtcForkImportDescriptorParam {
+                id: "".to_string(),
+                password: "".to_string(),
+                chain_type: "BITCOIN".to_string(),
+                network: "MAINNET".to_string(),
+                descriptor: output.descriptor.to_string(),
+            };
+            let ret = call_api("btc_fork_import_descriptor", param.clone()).unwrap();
+            let imported = BtcForkImportDescriptorOutput::decode(ret.as_slice()).unwrap();
+            assert_eq!(imported.descriptor, output.descriptor);
+            assert_eq!(imported.address, "3JmreiUEKn8P3SyLYmZ7C1YCd4r2nFy3Dp");
+            assert!(imported.watch_only);
+            assert_eq!(imported.account_address, "");
+
+            param.id = wallet.id.to_string();
+            param.password = TEST_PASSWORD.to_string();
+            param.descriptor = "wpkh([1468dba9/84h/0h/0h]xpub6CKMszasQeidek6fYD7g5N1mwUK3ouX8YHWs47MZyXh62GxsEQsU57NuN6GTS3Mh3bwykHGa14617A6HQoYFDSM9deJvgjDeEJxBYsfJ1bs/0/*)".to_string();
+            let ret = call_api("btc_fork_import_descriptor", param.clone()).unwrap();
+            let imported = BtcForkImportDescriptorOutput::decode(ret.as_slice()).unwrap();
+            assert_eq!(imported.descriptor, "wpkh([1468dba9/84'/0'/0']xpub6CKMszasQeidek6fYD7g5N1mwUK3ouX8YHWs47MZyXh62GxsEQsU57NuN6GTS3Mh3bwykHGa14617A6HQoYFDSM9deJvgjDeEJxBYsfJ1bs/0/*)#6632xwz9");
+            assert_eq!(
+                imported.address,
+                "bc1q05ec6z8df2vlzkxjxfd2xr3veypzm93wqnazr2"
+            );
+            assert!(!imported.watch_only);
+            assert_eq!(
+                imported.account_address,
+                "bc1q05ec6z8df2vlzkxjxfd2xr3veypzm93wqnazr2"
+            );
+            assert_eq!(imported.path, "m/84'/0'/0'/0/0");
+
+            // the keystore keeps single key accounts only, the multisig ones are watched
+            param.descriptor = "wsh(sortedmulti(2,[1468dba9/48'/0'/0']xpub6CJWFLqLfYKRssptbhvkwbqcQR2eWKJwLNbyxbMagAT75TQDcLxJ7xBkeokJhkTHC7Msh5SL5RRh7C9ekqKLh9xMBQ8dhfaH3ktX8o9MWQQ/0/*,[73c5da0a/48'/0'/0']xpub6CKZtUaK1YHpQbg6CLaGRmsMKLQB1iKzsvmxtyHD6X7gzLqCB2VNZYd1XCxrccQnE8hhDxtYbR1Sakkvisy2J4CcTxWeeGjmkasCoNS9vZm/0/*,[b8688df1/48'/0'/0']xpub6CETL9tkmWBQkYmxxxGAEzVZNHCZLx24pj58FYqe41qhXgyAcabP9iyXaVCJXcZWcVbzttVPdoJpJvYNfBnQeFcunvuxcsKKAwxMw6S5S7s/0/*))#34r4qp9d".to_string();
+            let ret = call_api("btc_fork_import_descriptor", param.clone()).unwrap();
+            let imported = BtcForkImportDescriptorOutput::decode(ret.as_slice()).unwrap();
+            assert_eq!(
+                imported.address,
+                "bc1qxrr0fzuefs2tn7gfjatey3rxrc2g4uehmduzn8dmavtp3q6p3afsa02c5u"
+            );
+            assert!(imported.watch_only);
+            assert_eq!(imported.account_address, "");
+            assert_eq!(imported.path, "");
+
+            // the account of another wallet
+            param.descriptor = "wpkh([73c5da0a/84'/0'/0']xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/0/*)".to_string();
+            let ret = call_api("btc_fork_import_descriptor", param.clone());
+            assert_eq!(
+                format!("{}", ret.err().unwrap()),
+                "descriptor_key_not_in_wallet"
+            );
+
+            param.descriptor = "wpkh([1468dba9/84'/0'/0']xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/0/*)".to_string();
+            let ret = call_api("btc_fork_import_descriptor", param.clone());
+            assert_eq!(format!("{}", ret.err().unwrap()), "descriptor_key_mismatch");
+
+            // the account keys are hardened
+            param.descriptor = "wpkh([1468dba9/84'/0'/0]xpub6CKMszasQeidek6fYD7g5N1mwUK3ouX8YHWs47MZyXh62GxsEQsU57NuN6GTS3Mh3bwykHGa14617A6HQoYFDSM9deJvgjDeEJxBYsfJ1bs/0/*)".to_string();
+            let ret = call_api("btc_fork_import_descriptor", param.clone());
+            assert_eq!(
+                format!("{}", ret.err().unwrap()),
+                "unsupported_descriptor_path"
+            );
+
+            param.descriptor = "wpkh([1468dba9/84'/0'/0']xpub6CKMszasQeidek6fYD7g5N1mwUK3ouX8YHWs47MZyXh62GxsEQsU57NuN6GTS3Mh3bwykHGa14617A6HQoYFDSM9deJvgjDeEJxBYsfJ1bs/0/*)".to_string();
+            param.network = "TESTNET".to_string();
+            let ret = call_api("btc_fork_import_descriptor", param.clone());
+            assert_eq!(
+                format!("{}", ret.err().unwrap()),
+                "descriptor_network_mismatch"
+            );
+            param.network = "MAINNET".to_string();
+
+            param.password = "WRONG PASSWORD".to_string();
+            let ret = call_api("btc_fork_import_descriptor", param);
+            assert_eq!(format!("{}", ret.err().unwrap()), "password_incorrect");
+
+            remove_created_wallet(&wallet.id);
+        })
+    }
+
+    #[test]
+    pub fn test_btc_fork_select_coins() {
+        run_test(|| {
+            let utxo = |vout: i32, amount: i64| Utxo {
+                tx_hash: "a477af6b2667c29670467e4e0728b685ee07b240235771862318e29ddbe58458"
+                    .to_string(),
+                vout,
+                amount,
+                address: "bc1qum864wd9nwsc0u9ytkctz6wzrw6g7zdntm7f4e".to_string(),
+                script_pub_key: "".to_string(),
+                derived_path: "0/0".to_string(),
+                sequence: 0,
+            };
+            let mut input = CoinSelectionInput {
+                chain_type: "BITCOIN".to_string(),
+                network: "MAINNET".to_string(),
+                seg_wit: "SEGWIT".to_string(),
+                to: "bc1pqvrla5hul9cqdtz60lwwn35zdcx363pyxua0trqnz3wx8hvjxzdsdevceu".to_string(),
+                amount: 25000,
+                unspents: vec![utxo(0, 10000), utxo(1, 200000), utxo(2, 20000)],
+                fee_rate: 10,
+                change_address: "".to_string(),
+                op_return: "".to_string(),
+            };
+            let ret = call_api("btc_fork_select_coins", input.clone()).unwrap();
+            let output: CoinSelectionOutput = CoinSelectionOutput::decode(ret.as_slice()).unwrap();
+            assert_eq!(output.unspents, vec![utxo(2, 20000), utxo(0, 10000)]);
+            assert_eq!(output.vsize, 221);
+            assert_eq!(output.fee, 2210);
+            assert_eq!(output.change, 2790);
+
+            input.to = "bc1qum864wd9nwsc0u9ytkctz6wzrw6g7zdntm7f4f".to_string();
+            let ret = call_api("btc_fork_select_coins", input);
+            assert_eq!(format!("{}", ret.err().unwrap()), "address_invalid");
+        })
+    }
+
+    #[test]
+    pub fn test_sign_eth_tx() {
+        run_test(|| {
+            let derivation = Derivation {
+                chain_type: "ETHEREUM".to_string(),
+                path: "m/44'/60'/0'/0/0".to_string(),
+                network: "MAINNET".to_string(),
+                seg_wit: "".to_string(),
+                chain_id: "".to_string(),
+                curve: "".to_string(),
+            };
+
+            let wallet = import_and_derive(derivation);
+
+            let input = EthereumTxIn {
+                nonce: "0".to_string(),
+                to: "132D1eA7EF895b6834D25911656a434d7167091C".to_string(),
+                value: U256::zero().to_string(),
+                gas_price: "1000".to_string(),
+                gas: "21240".to_string(),
+                data: "7f7465737432000000000000000000000000000000000000000000000000000000600057"
+                    .to_string(),
+                network: "MAINNET".to_string(),
+                access_list: "[]".to_string(),
+                max_priority_fee_per_gas: "100000".to_string(),
+                transaction_type: "2".to_string(),
+                chain_id: 0,
+                authorization_list: "".to_string(),
+            };
+
+            let tx = SignParam {
+                id: wallet.id.to_string(),
+                key: Some(Key::Password(TEST_PASSWORD.to_string())),
+                chain_type: "ETHEREUM".to_string(),
+                address: wallet.accounts.first().unwrap().address.to_string(),
+                input: Some(::prost_types::Any {
+                    type_url: "imtoken".to_string(),
+                    value: encode_message(input.clone()).unwrap(),
+                }),
+            };
+
+            let ret = call_api("sign_tx", tx.clone()).unwrap();
+            let output: EthereumTxOut = EthereumTxOut::decode(ret.as_slice()).unwrap();
+            assert_eq!(output.signature, "02f88b0180830186a08203e88252f894132d1ea7ef895b6834d25911656a434d7167091c80a47f7465737432000000000000000000000000000000000000000000000000000000600057c080a0c28d6c8defe19593c03d7a5c8f9c8b3508bfc4c519205dc8f054249a82769600a0250b1c3fbfcf141bce373c6397e201f41a79d8be1d0b7daab4b4fb541aacaf48");
+            assert_eq!(
+                output.tx_hash,
+                "0x97d9aee6d1b41a069bbdb9c48b321462e1b8da0e91454bd867f5f80147153d3f"
+            );
+
+            // an explicit chain id takes precedence over the network name
+            let input = EthereumTxIn {
+                network: "".to_string(),
+                chain_id: 1,
+                ..input
+            };
+            let tx = SignParam {
+                input: Some(::prost_types::Any {
+                    type_url: "imtoken".to_string(),
+                    value: encode_message(input.clone()).unwrap(),
+                }),
+                ..tx
+            };
+            let ret = call_api("sign_tx", tx).unwrap();
+            let chain_id_output: EthereumTxOut = EthereumTxOut::decode(ret.as_slice()).unwrap();
+            assert_eq!(output.signature, chain_id_output.signature);
+
+            let ret = call_api(
+                "eth_decode_tx",
+                EthereumTxDecodeIn {
+                    raw_tx: output.signature.to_string(),
+                },
+            )
+            .unwrap();
+            let decoded: EthereumTxDecodeOut = EthereumTxDecodeOut::decode(ret.as_slice()).unwrap();
+            assert!(decoded.signed);
+            assert_eq!(decoded.from, wallet.accounts.first().unwrap().address);
+            let decoded_tx = decoded.tx.unwrap();
+            assert_eq!(decoded_tx.to.to_lowercase(), input.to.to_lowercase());
+            assert_eq!(
+                EthereumTxIn {
+                    to: input.to.to_string(),
+                    ..decoded_tx
+                },
+                input
+            );
+            remove_created_wallet(&wallet.id);
+        })
+    }
+
+    #[test]
+    pub fn test_eth_sign_message() {
+        run_test(|| {
+            let wallet = import_pk_and_derive(Derivation {
+                chain_type: "ETHEREUM".to_string(),
+                path: "".to_string(),
+                network: "MAINNET".to_string(),
+                seg_wit: "".to_string(),
+                chain_id: "".to_string(),
+                curve: "".to_string(),
+            });
+            let address = wallet.accounts.first().unwrap().address.to_string();
+            assert_eq!(
+                address.to_lowercase(),
+                "0xef678007d18427e6022059dbc264f27507cd1ffc"
+            );
+
+            let input = EthereumMsgIn {
+                value: "Hello World".to_string(),
+                chain_id: 0,
+                signature_type: SignatureType::PersonalSign as i32,
+                is_utf8: true,
+                validator: "".to_string(),
+            };
+            let param = SignParam {
+                id: wallet.id.to_string(),
+                key: Some(Key::Password(TEST_PASSWORD.to_string())),
+                chain_type: "ETHEREUM".to_string(),
+                address: address.to_string(),
+                input: Some(::prost_types::Any {
+                    type_url: "imtoken".to_string(),
+                    value: encode_message(input.clone()).unwrap(),
+                }),
+            };
+            let ret = call_api("eth_sign_msg", param.clone()).unwrap();
+            let output: EthereumMsgOut = EthereumMsgOut::decode(ret.as_slice()).unwrap();
+            // 65 bytes with v at 27/28, as ecrecover and personal_sign verifiers expect
+            assert_eq!(output.signature, "60f839116c5aab1a2284e4a881f564bc477a22457ddeb2ab841fc976bb1ecbc754950c63bd04484c3b5130604a983ba380675138e5c1d7afced85394f8a38c2f1c");
+
+            // the chain id doesn't change v
+            let param = SignParam {
+                input: Some(::prost_types::Any {
+                    type_url: "imtoken".to_string(),
+                    value: encode_message(EthereumMsgIn {
+                        chain_id: 1,
+                        ..input
+                    })
+                    .unwrap(),
+                }),
+                ..param
+            };
+            let ret = call_api("eth_sign_msg", param).unwrap();
+            let output: EthereumMsgOut = EthereumMsgOut::decode(ret.as_slice()).unwrap();
+            assert_eq!(output.signature, "60f839116c5aab1a2284e4a881f564bc477a22457ddeb2ab841fc976bb1ecbc754950c63bd04484c3b5130604a983ba380675138e5c1d7afced85394f8a38c2f1c");
+
+            remove_created_wallet(&wallet.id);
+        })
+    }
+
+    #[test]
+    pub fn test_eth_abi_encode_and_build_token_tx() {
+        run_test(|| {
+            let abi = r#"[
+                {"type":"function","name":"balanceOf","inputs":[{"name":"owner","type":"address"}]},
+                {"type":"function","name":"transfer","inputs":[{"name":"to","type":"address"},{"name":"amount","type":"uint256"}]}
+            ]"#;
+            let ret = call_api(
+                "eth_abi_encode",
+                EthereumAbiEncodeIn {
+                    abi: abi.to_string(),
+                    method: "transfer".to_string(),
+                    args: r#"["0x3de0e4CD2FF24557bD9ed743e038eF18202F2acd", "1000000"]"#
+                        .to_string(),
+                },
+            )
+            .unwrap();
+            let encoded = EthereumAbiEncodeOut::decode(ret.as_slice()).unwrap();
+            assert_eq!(encoded.data, "a9059cbb0000000000000000000000003de0e4cd2ff24557bd9ed743e038ef18202f2acd00000000000000000000000000000000000000000000000000000000000f4240");
+
+            let ret = call_api(
+                "eth_abi_encode",
+                EthereumAbiEncodeIn {
+                    abi: abi.to_string(),
+                    method: "transfer".to_string(),
+                    args: r#"["0x3de0e4CD2FF24557bD9ed743e038eF18202F2acd"]"#.to_string(),
+                },
+            );
+            assert_eq!(format!("{}", ret.err().unwrap()), "abi_invalid_arguments");
+
+            let ret = call_api(
+                "eth_build_token_tx",
+                EthereumTokenTxIn {
+                    tx: Some(EthereumTxIn {
+                        nonce: "8".to_string(),
+                        gas_price: "20000000000".to_string(),
+                        gas: "60000".to_string(),
+                        network: "MAINNET".to_string(),
+                        ..EthereumTxIn::default()
+                    }),
+                    contract: "0xdAC17F958D2ee523a2206206994597C13D831ec7".to_string(),
+                    call: Some(ethereum_token_tx_in::Call::Erc20Transfer(Erc20Transfer {
+                        to: "0x3de0e4CD2FF24557bD9ed743e038eF18202F2acd".to_string(),
+                        amount: "1000000".to_string(),
+                    })),
+                },
+            )
+            .unwrap();
+            let token_tx = EthereumTxIn::decode(ret.as_slice()).unwrap();
+            assert_eq!(token_tx.to, "dac17f958d2ee523a2206206994597c13d831ec7");
+            assert_eq!(token_tx.data, encoded.data);
+
+            let wallet = import_and_derive(Derivation {
+                chain_type: "ETHEREUM".to_string(),
+                path: "m/44'/60'/0'/0/0".to_string(),
+                network: "MAINNET".to_string(),
+                seg_wit: "".to_string(),
+                chain_id: "".to_string(),
+                curve: "".to_string(),
+            });
+            let tx = SignParam {
+                id: wallet.id.to_string(),
+                key: Some(Key::Password(TEST_PASSWORD.to_string())),
+                chain_type: "ETHEREUM".to_string(),
+                address: wallet.accounts.first().unwrap().address.to_string(),
+                input: Some(::prost_types::Any {
+                    type_url: "imtoken".to_string(),
+                    value: encode_message(token_tx).unwrap(),
+                }),
+            };
+            let ret = call_api("sign_tx", tx).unwrap();
+            let output: EthereumTxOut = EthereumTxOut::decode(ret.as_slice()).unwrap();
+
+            let ret = call_api(
+                "eth_decode_tx",
+                EthereumTxDecodeIn {
+                    raw_tx: output.signature,
+                },
+            )
+            .unwrap();
+            let decoded: EthereumTxDecodeOut = EthereumTxDecodeOut::decode(ret.as_slice()).unwrap();
+            let contract_call = decoded.contract_call.unwrap();
+            assert_eq!(contract_call.standard, "ERC20");
+            assert_eq!(contract_call.method, "transfer");
+            assert_eq!(contract_call.arguments[1].value, "1000000");
+            remove_created_wallet(&wallet.id);
+        })
+    }
+
+    #[test]
+    pub fn test_eth_sign_authorization() {
+        run_test(|| {
+            let wallet = import_and_derive(Derivation {
+                chain_type: "ETHEREUM".to_string(),
+                path: "m/44'/60'/0'/0/0".to_string(),
+                network: "MAINNET".to_string(),
+                seg_wit: "".to_string(),
+                chain_id: "".to_string(),
+                curve: "".to_string(),
+            });
+            let address = wallet.accounts.first().unwrap().address.to_string();
+            let sign_param = |input: EthereumAuthorizationIn| SignParam {
+                id: wallet.id.to_string(),
+                key: Some(Key::Password(TEST_PASSWORD.to_string())),
+                chain_type: "ETHEREUM".to_string(),
+                address: address.to_string(),
+                input: Some(::prost_types::Any {
+                    type_url: "imtoken".to_string(),
+                    value: encode_message(input).unwrap(),
+                }),
+            };
+
+            let ret = call_api(
+                "eth_sign_authorization",
+                sign_param(EthereumAuthorizationIn {
+                    chain_id: 1,
+                    address: "0x355972B9007c736515523417c96561F63db4e7bC".to_string(),
+                    nonce: 1,
+                }),
+            )
+            .unwrap();
+            let output = EthereumAuthorizationOut::decode(ret.as_slice()).unwrap();
+            assert_eq!(output.signature, "bdac32bd2e73eff882f50fc11814cba950ca42aef4993efc365c4320823235933b993829ef2c4fa299ff4374df982ca1472d04565ed71480344eb6fbdfe08bd201");
+            assert_eq!(
+                output.authorization,
+                r#"{"chainId":"0x1","address":"0x355972b9007c736515523417c96561f63db4e7bc","nonce":"0x1","yParity":"0x1","r":"0xbdac32bd2e73eff882f50fc11814cba950ca42aef4993efc365c432082323593","s":"0x3b993829ef2c4fa299ff4374df982ca1472d04565ed71480344eb6fbdfe08bd2"}"#
+            );
+
+            // the account delegates its own code, so the authorization nonce is one above the tx nonce
+            let input = EthereumTxIn {
+                nonce: "0".to_string(),
+                to: address[2..].to_string(),
+                value: "0".to_string(),
+                gas_price: "2000000000".to_string(),
+                gas: "100000".to_string(),
+                data: "".to_string(),
+                network: "MAINNET".to_string(),
+                access_list: "[]".to_string(),
+                max_priority_fee_per_gas: "1000000000".to_string(),
+                transaction_type: "4".to_string(),
+                chain_id: 0,
+                authorization_list: format!("[{}]", output.authorization),
+            };
+            let tx = SignParam {
+                input: Some(::prost_types::Any {
+                    type_url: "imtoken".to_string(),
+                    value: encode_message(input.clone()).unwrap(),
+                }),
+                ..sign_param(EthereumAuthorizationIn::default())
+            };
+            let ret = call_api("sign_tx", tx).unwrap();
+            let signed: EthereumTxOut = EthereumTxOut::decode(ret.as_slice()).unwrap();
+            assert!(signed.signature.starts_with("04"));
+
+            let ret = call_api(
+                "eth_decode_tx",
+                EthereumTxDecodeIn {
+                    raw_tx: signed.signature,
+                },
+            )
+            .unwrap();
+            let decoded: EthereumTxDecodeOut = EthereumTxDecodeOut::decode(ret.as_slice()).unwrap();
+            assert_eq!(decoded.from, address);
+            let decoded_tx = decoded.tx.unwrap();
+            assert_eq!(decoded_tx.transaction_type, "4");
+            assert_eq!(decoded_tx.authorization_list, input.authorization_list);
+
+            let ret = call_api(
+                "eth_sign_authorization",
+                sign_param(EthereumAuthorizationIn {
+                    chain_id: 1,
+                    address: "0x1234".to_string(),
+                    nonce: 1,
+                }),
+            );
+            assert_eq!(
+                format!("{}", ret.err().unwrap()),
+                "invalid_authorization_address"
+            );
+            remove_created_wallet(&wallet.id);
+        })
+    }
+
+    #[test]
+    pub fn test_eth_validator_keystore_and_deposit_data() {
+        run_test(|| {
+            let wallet = import_default_wallet();
+            let param = EthereumValidatorKeystoreIn {
+                id: wallet.id.to_string(),
+                password: TEST_PASSWORD.to_string(),
+                index: 0,
+                keystore_password: "testpassword".to_string(),
+                kdf: "pbkdf2".to_string(),
+            };
+            let ret = call_api("eth_export_validator_keystore", param).unwrap();
+            let output = EthereumValidatorKeystoreOut::decode(ret.as_slice()).unwrap();
+            assert_eq!(output.pubkey, "b809eea8f4c1935fa6c0cc6d454a5b297c7856da4df4da96432ea2ef6fa86abf90f8553aaf8c6d605c94244d68d62eb8");
+            assert_eq!(output.path, "m/12381/3600/0/0/0");
+            let keystore: Eip2335Keystore = serde_json::from_str(&output.keystore).unwrap();
+            assert_eq!(
+                hex::encode(keystore.decrypt("testpassword").unwrap()),
+                "6bf145d39e0f1312ce67b27efae17d104bd85ff687a2524213a6f2f7827d933c"
+            );
+
+            let param = EthereumDepositDataIn {
+                id: wallet.id.to_string(),
+                password: TEST_PASSWORD.to_string(),
+                index: 0,
+                withdrawal_credentials: "0x6031564e7b2F5cc33737807b2E58DaFF870B590b".to_string(),
+                amount: 32000000000,
+                network: "MAINNET".to_string(),
+                fork_version: "".to_string(),
+            };
+            let ret = call_api("eth_sign_deposit_data", param.clone()).unwrap();
+            let output = EthereumDepositDataOut::decode(ret.as_slice()).unwrap();
+            assert_eq!(output.signature, "b857b2559d559df9ac634ed5df1b31a47b6d4bbc4693d8890364bd75f215509382095855f031ea9df9b2f0ab6089a2880d4c79982d252861e6ae1f2f9b2beef35bdc6616177269303f9ef8447550efdaf5dc259b528d86767af9f95a5953f86c");
+            assert_eq!(
+                output.deposit_data_root,
+                "6a1f3b9abf718a78cf632dc47e7992372731f721f79a8f481bae10f46153fce0"
+            );
+
+            let ret = call_api(
+                "eth_sign_deposit_data",
+                EthereumDepositDataIn {
+                    password: "WRONG PASSWORD".to_string(),
+                    ..param
+                },
+            );
+            assert_eq!(format!("{}", ret.err().unwrap()), "password_incorrect");
+            remove_created_wallet(&wallet.id);
+        })
+    }
+
+    #[test]
+    pub fn test_siwe_sign() {
+        run_test(|| {
+            let wallet = import_and_derive(Derivation {
+                chain_type: "ETHEREUM".to_string(),
+                path: "m/44'/60'/0'/0/0".to_string(),
+                network: "MAINNET".to_string(),
+                seg_wit: "".to_string(),
+                chain_id: "".to_string(),
+                curve: "".to_string(),
+            });
+            let siwe = EthereumSiweIn {
+                domain: "service.org".to_string(),
+                address: "0x6031564e7b2F5cc33737807b2E58DaFF870B590b".to_string(),
+                statement: "I accept the ServiceOrg Terms of Service: https://service.org/tos"
+                    .to_string(),
+                uri: "https://service.org/login".to_string(),
+                version: "1".to_string(),
+                chain_id: 1,
+                nonce: "32891757".to_string(),
+                issued_at: "2021-09-30T16:25:24.000Z".to_string(),
+                ..EthereumSiweIn::default()
+            };
+            let sign_param = |input: EthereumSiweIn| SignParam {
+                id: wallet.id.to_string(),
+                key: Some(Key::Password(TEST_PASSWORD.to_string())),
+                chain_type: "ETHEREUM".to_string(),
+                address: wallet.accounts.first().unwrap().address.to_string(),
+                input: Some(::prost_types::Any {
+                    type_url: "imtoken".to_string(),
+                    value: encode_message(input).unwrap(),
+                }),
+            };
+            let ret = call_api("siwe_sign", sign_param(siwe.clone())).unwrap();
+            let output = EthereumSiweOut::decode(ret.as_slice()).unwrap();
+            assert_eq!(
+                output.message,
+                "service.org wants you to sign in with your Ethereum account:\n\
+                 0x6031564e7b2F5cc33737807b2E58DaFF870B590b\n\
+                 \n\
+                 I accept the ServiceOrg Terms of Service: https://service.org/tos\n\
+                 \n\
+                 URI: https://service.org/login\n\
+                 Version: 1\n\
+                 Chain ID: 1\n\
+                 Nonce: 32891757\n\
+                 Issued At: 2021-09-30T16:25:24.000Z"
+            );
+            assert_eq!(output.signature, "f7d0376d1c04cc0f7ec0962338a452a460b2f95edb942bd7604c69442a95ab916b821570fd2cd5a288fb559f0b75d985ee48bd682dbbc5c3b3b0078d10c0e7d01c");
+
+            let ret = call_api(
+                "siwe_sign",
+                sign_param(EthereumSiweIn {
+                    address: "0x3de0e4CD2FF24557bD9ed743e038eF18202F2acd".to_string(),
+                    ..siwe
+                }),
+            );
+            assert_eq!(format!("{}", ret.err().unwrap()), "siwe_address_mismatch");
+            remove_created_wallet(&wallet.id);
+        })
+    }
+
+    #[test]
+    pub fn test_eth_sign_user_operation() {
+        run_test(|| {
+            let wallet = import_and_derive(Derivation {
+                chain_type: "ETHEREUM".to_string(),
+                path: "m/44'/60'/0'/0/0".to_string(),
+                network: "MAINNET".to_string(),
+                seg_wit: "".to_string(),
+                chain_id: "".to_string(),
+                curve: "".to_string(),
+            });
+            let user_op = EthereumUserOperationIn {
+                entry_point: "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789".to_string(),
+                chain_id: 1,
+                entry_point_version: EntryPointVersion::EntryPointV06 as i32,
+                sender: "0x3de0e4CD2FF24557bD9ed743e038eF18202F2acd".to_string(),
+                nonce: "0x1".to_string(),
+                init_code: "0x".to_string(),
+                call_data: "0xb61d27f6".to_string(),
+                call_gas_limit: "100000".to_string(),
+                verification_gas_limit: "200000".to_string(),
+                pre_verification_gas: "50000".to_string(),
+                max_fee_per_gas: "2000000000".to_string(),
+                max_priority_fee_per_gas: "1000000000".to_string(),
+                ..EthereumUserOperationIn::default()
+            };
+            let param = SignParam {
+                id: wallet.id.to_string(),
+                key: Some(Key::Password(TEST_PASSWORD.to_string())),
+                chain_type: "ETHEREUM".to_string(),
+                address: wallet.accounts.first().unwrap().address.to_string(),
+                input: Some(::prost_types::Any {
+                    type_url: "imtoken".to_string(),
+                    value: encode_message(user_op.clone()).unwrap(),
+                }),
+            };
+            let ret = call_api("eth_sign_user_operation", param.clone()).unwrap();
+            let output = EthereumUserOperationOut::decode(ret.as_slice()).unwrap();
+            assert_eq!(
+                output.user_op_hash,
+                "0x1f26e7db9d9ca7b4a4a6e3cef7cb51fb5a98bb1ffb69ff3f5ca25f3c9f260353"
+            );
+            assert_eq!(output.signature, "0x7e7d3de19c2f2f4b680a1291f007eb3a3aca1dd3b444c47ace06120ef58314774ab179661594254fe33e66a1f08465debb3eb6f8e22b20fbabf2e7c3ca8acfcc1c");
+
+            let param = SignParam {
+                input: Some(::prost_types::Any {
+                    type_url: "imtoken".to_string(),
+                    value: encode_message(EthereumUserOperationIn {
+                        raw_hash: true,
+                        ..user_op
+                    })
+                    .unwrap(),
+                }),
+                ..param
+            };
+            let ret = call_api("eth_sign_user_operation", param).unwrap();
+            let output = EthereumUserOperationOut::decode(ret.as_slice()).unwrap();
+            assert_eq!(output.signature, "0x9ecee61fb0e6edf7a3a81b2fdeeaf3d0ef6ed5593d0d135bfa11cf4e647cbc6a54bc28b32ac9a40c3a0a0296b07ad7c67cf2c1a4a8fa0d51a3068d6db06f776e1b");
+            remove_created_wallet(&wallet.id);
+        })
+    }
+
+    #[test]
+    pub fn test_eth_sign_typed_data() {
+        run_test(|| {
+            // the private key is keccak256("cow") used by the EIP-712 examples
+            let param = PrivateKeyStoreImportParam {
+                private_key: "c85ef7d79691fe79573b1a7064c19c1a9819ebdbd1faaab1a8ec92344438aaf4"
+                    .to_string(),
+                password: TEST_PASSWORD.to_string(),
+                name: "test_eth_sign_typed_data".to_string(),
+                password_hint: "".to_string(),
+                overwrite: true,
+                encoding: "".to_string(),
+            };
+            let ret = call_api("private_key_store_import", param).unwrap();
+            let wallet: WalletResult = WalletResult::decode(ret.as_slice()).unwrap();
+
+            let param = KeystoreCommonDeriveParam {
+                id: wallet.id.to_string(),
+                password: TEST_PASSWORD.to_string(),
+                derivations: vec![Derivation {
+                    chain_type: "ETHEREUM".to_string(),
+                    path: "m/44'/60'/0'/0/0".to_string(),
+                    network: "MAINNET".to_string(),
+                    seg_wit: "".to_string(),
+                    chain_id: "".to_string(),
+                    curve: "".to_string(),
+                }],
+            };
+            let ret = call_api("keystore_common_derive", param).unwrap();
+            let accounts: AccountsResponse = AccountsResponse::decode(ret.as_slice()).unwrap();
+            assert_eq!(
+                accounts.accounts[0].address.to_lowercase(),
+                "0xcd2a3d9f938e13cd947ec05abc7fe734df8dd826"
+            );
+
+            let typed_data = r#"{
+                "types": {
+                    "EIP712Domain": [
+                        {"name": "name", "type": "string"},
+                        {"name": "version", "type": "string"},
+                        {"name": "chainId", "type": "uint256"},
+                        {"name": "verifyingContract", "type": "address"}
+                    ],
+                    "Group": [
+                        {"name": "name", "type": "string"},
+                        {"name": "members", "type": "Person[]"}
+                    ],
+                    "Mail": [
+                        {"name": "from", "type": "Person"},
+                        {"name": "to", "type": "Person[]"},
+                        {"name": "contents", "type": "string"}
+                    ],
+                    "Person": [
+                        {"name": "name", "type": "string"},
+                        {"name": "wallets", "type": "address[]"}
+                    ]
+                },
+                "domain": {
+                    "chainId": 1,
+                    "name": "Ether Mail",
+                    "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC",
+                    "version": "1"
+                },
+                "primaryType": "Mail",
+                "message": {
+                    "contents": "Hello, Bob!",
+                    "from": {
+                        "name": "Cow",
+                        "wallets": [
+                            "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826",
+                            "0xDeaDbeefdEAdbeefdEadbEEFdeadbeEFdEaDbeeF"
+                        ]
+                    },
+                    "to": [{
+                        "name": "Bob",
+                        "wallets": [
+                            "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB",
+                            "0xB0BdaBea57B0BDABeA57b0bdABEA57b0BDabEa57",
+                            "0xB0B0b0b0b0b0B000000000000000000000000000"
+                        ]
+                    }]
+                }
+            }"#;
+            let param = SignParam {
+                id: wallet.id.to_string(),
+                key: Some(Key::Password(TEST_PASSWORD.to_string())),
+                chain_type: "ETHEREUM".to_string(),
+                address: accounts.accounts[0].address.to_string(),
+                input: Some(::prost_types::Any {
+                    type_url: "imtoken".to_string(),
+                    value: encode_message(EthereumTypedDataIn {
+                        data: typed_data.to_string(),
+                    })
+                    .unwrap(),
+                }),
+            };
+            let ret = call_api("eth_sign_typed_data", param.clone()).unwrap();
+            let output: EthereumTypedDataOut =
+                EthereumTypedDataOut::decode(ret.as_slice()).unwrap();
+            assert_eq!(output.signature, "65cbd956f2fae28a601bebc9b906cea0191744bd4c4247bcd27cd08f8eb6b71c78efdf7a31dc9abee78f492292721f362d296cf86b4538e07b51303b67f749061b");
+
+            let param = SignParam {
+                input: Some(::prost_types::Any {
+                    type_url: "imtoken".to_string(),
+                    value: encode_message(EthereumTypedDataIn {
+                        data: "{}".to_string(),
+                    })
+                    .unwrap(),
+                }),
+                ..param
+            };
+            let ret = call_api("eth_sign_typed_data", param);
+            assert_eq!(format!("{}", ret.err().unwrap()), "invalid_typed_data");
+
+            remove_created_wallet(&wallet.id);
+        })
+    }
+
+    #[test]
+    pub fn test_chain_registry() {
+        run_test(|| {
+            let chain_info = EvmChainInfoParam {
+                network: "SCROLL".to_string(),
+                network_id: 534352,
+                chain_id: 534352,
+            };
+            let coin_info = CoinInfoParam {
+                chain_type: "ETHEREUM".to_string(),
+                path: "m/44'/60'/0'/0/0".to_string(),
+                network: "SCROLL".to_string(),
+                seg_wit: "".to_string(),
+                curve: "".to_string(),
+            };
+            call_api("register_evm_chain_info", chain_info.clone()).unwrap();
+            call_api("register_coin_info", coin_info.clone()).unwrap();
+            let ret = call_api("register_evm_chain_info", chain_info.clone());
+            assert_eq!(
+                format!("{}", ret.err().unwrap()),
+                "chain_info_already_exists"
+            );
+
+            let dash_network = BtcForkNetworkParam {
+                coin: "DASH".to_string(),
+                network: "MAINNET".to_string(),
+                seg_wit: "NONE".to_string(),
+                hrp: "".to_string(),
+                p2pkh_prefix: 0x4c,
+                p2sh_prefix: 0x10,
+                private_prefix: 0xcc,
+                xpub_prefix: "0488b21e".to_string(),
+                xprv_prefix: "0488ade4".to_string(),
+            };
+            let dash_coin_info = CoinInfoParam {
+                chain_type: "DASH".to_string(),
+                path: "m/44'/5'/0'/0/0".to_string(),
+                network: "MAINNET".to_string(),
+                seg_wit: "NONE".to_string(),
+                curve: "SECP256k1".to_string(),
+            };
+            call_api("register_btc_fork_network", dash_network.clone()).unwrap();
+            call_api("register_coin_info", dash_coin_info.clone()).unwrap();
+
+            // the registry survives a reload
+            setup();
+            let ret = call_api(
+                "list_evm_chain_infos",
+                ChainRegistryListParam { custom_only: true },
+            )
+            .unwrap();
+            let chain_infos = EvmChainInfosResult::decode(ret.as_slice()).unwrap();
+            assert_eq!(chain_infos.chain_infos, vec![chain_info.clone()]);
+            let ret = call_api(
+                "list_coin_infos",
+                ChainRegistryListParam { custom_only: true },
+            )
+            .unwrap();
+            let coin_infos = CoinInfosResult::decode(ret.as_slice()).unwrap();
+            assert_eq!(coin_infos.coin_infos.len(), 2);
+            let ret = call_api(
+                "list_btc_fork_networks",
+                ChainRegistryListParam { custom_only: false },
+            )
+            .unwrap();
+            let networks = BtcForkNetworksResult::decode(ret.as_slice()).unwrap();
+            assert_eq!(networks.networks.last().unwrap(), &dash_network);
+
+            let wallet = import_and_derive(Derivation {
+                chain_type: "ETHEREUM".to_string(),
+                path: "m/44'/60'/0'/0/0".to_string(),
+                network: "SCROLL".to_string(),
+                seg_wit: "".to_string(),
+                chain_id: "".to_string(),
+                curve: "".to_string(),
+            });
+            let input = EthereumTxIn {
+                nonce: "0".to_string(),
+                to: "132D1eA7EF895b6834D25911656a434d7167091C".to_string(),
+                value: "1".to_string(),
+                gas_price: "1000".to_string(),
+                gas: "21000".to_string(),
+                data: "".to_string(),
+                network: "SCROLL".to_string(),
+                access_list: "[]".to_string(),
+                max_priority_fee_per_gas: "".to_string(),
+                transaction_type: "".to_string(),
+                chain_id: 0,
+                authorization_list: "".to_string(),
+            };
+            let tx = SignParam {
+                id: wallet.id.to_string(),
+                key: Some(Key::Password(TEST_PASSWORD.to_string())),
+                chain_type: "ETHEREUM".to_string(),
+                address: wallet.accounts.first().unwrap().address.to_string(),
+                input: Some(::prost_types::Any {
+                    type_url: "imtoken".to_string(),
+                    value: encode_message(input).unwrap(),
+                }),
+            };
+            assert!(call_api("sign_tx", tx).is_ok());
+            remove_created_wallet(&wallet.id);
+
+            let wallet = import_and_derive(Derivation {
+                chain_type: "DASH".to_string(),
+                path: "m/44'/5'/0'/0/0".to_string(),
+                network: "MAINNET".to_string(),
+                seg_wit: "NONE".to_string(),
+                chain_id: "".to_string(),
+                curve: "".to_string(),
+            });
+            assert!(wallet.accounts.first().unwrap().address.starts_with('X'));
+            remove_created_wallet(&wallet.id);
+
+            call_api("remove_evm_chain_info", chain_info).unwrap();
+            call_api("remove_coin_info", coin_info).unwrap();
+            call_api("remove_btc_fork_network", dash_network).unwrap();
+            call_api("remove_coin_info", dash_coin_info.clone()).unwrap();
+            let ret = call_api("remove_coin_info", dash_coin_info);
+            assert_eq!(
+                format!("{}", ret.err().unwrap()),
+                "custom_coin_info_not_found"
+            );
+
+            setup();
+            let ret = call_api(
+                "list_coin_infos",
+                ChainRegistryListParam { custom_only: true },
+            )
+            .unwrap();
+            let coin_infos = CoinInfosResult::decode(ret.as_slice()).unwrap();
+            assert!(coin_infos.coin_infos.is_empty());
+        })
+    }
+
+    fn import_solana_pk_store() -> WalletResult {
+        let param: PrivateKeyStoreImportParam = PrivateKeyStoreImportParam {
+            private_key: "f3f7c4290567ce10c0672bef4953cb4d8e416ba7f5b6b5c0a978e76860dae5b7"
+                .to_string(),
+            password: TEST_PASSWORD.to_string(),
+            name: "import_solana_pk_store".to_string(),
+            password_hint: "".to_string(),
+            overwrite: true,
+            encoding: "".to_string(),
+        };
+
+        let ret = private_key_store_import(&encode_message(param).unwrap()).unwrap();
+        WalletResult::decode(ret.as_slice()).unwrap()
+    }
+
+    fn import_solana_pk_store_1() -> WalletResult {
+        let param: PrivateKeyStoreImportParam = PrivateKeyStoreImportParam {
+            private_key: "bfd09d018175b9e8f167c4136d75ca37b7a592bcddd3ccbd7b2d1a093111b9b1"
+                .to_string(),
+            password: TEST_PASSWORD.to_string(),
+            name: "import_solana_pk_store".to_string(),
+            password_hint: "".to_string(),
+            overwrite: true,
+            encoding: "".to_string(),
+        };
+
+        let ret = private_key_store_import(&encode_message(param).unwrap()).unwrap();
+        WalletResult::decode(ret.as_slice()).unwrap()
+    }
+    #[test]
+    pub fn test_sign_solana_tx() {
         run_test(|| {
             let wallet = import_solana_pk_store();
             let derivation = Derivation {
@@ -2973,6 +4821,7 @@ mod tests {
             let ret = call_api("sign_tx", tx).unwrap();
             let output: SolanaTxOut = SolanaTxOut::decode(ret.as_slice()).unwrap();
             assert_eq!(output.tx,"5aYTkY1Hmrch6GYeoV1c4pLZBK5WxKYLMmEnAHbWCbtrmLgzUQQrsDBdbrSpsf2J9PhPMdMsKP4fcNrh8DCxDb2CnZCu3p97Q4qPRswEqVcu8x7i3WM6PRF3Bkc8MuHL3vmrHpXdEzvtHfTydtFKkSz2bCzwVjpDbjDueQHNX1FdXb4aaLyu7QE5GYoKoR4U6V6DLMe3Uymx1QcuZpgLm1jhaqtwLEuQ1NTLB4qJk73wMwvokkfd2DwhSgmBcgu5yMgF7ewwJmZzvsV8QkMDZUhApYGUbpJJdDvvB");
+            assert_eq!(output.tx_hash, "4LupcunTcB46qUFBVEt9Rhv3ougSswgP6rNDF4uVtY6x86hZssB4MfVF74VC5FPiNuSHewKt8QbqKkDELTnb6i1z");
             let export_param = ExportPrivateKeyParam {
                 id: wallet.id.to_string(),
                 password: TEST_PASSWORD.to_string(),
@@ -3142,6 +4991,10 @@ mod tests {
                     68, 5
                 ]
             );
+            assert_eq!(
+                output.tx_hash,
+                "0xffb37b85e0b3170583d8a5cc77fd77e726a0eb30ebe889ac4173257b1400a751"
+            );
             remove_created_wallet(&wallet.id);
         })
     }